@@ -3,8 +3,9 @@ use crate::base::TimestampIden;
 use super::{CommonId, DMC};
 use jd_utils::time::now_utc;
 use modql::field::{SeaField, SeaFields};
+use uuid::Uuid;
 
-pub fn prepare_fields_for_create<MC>(fields: &mut SeaFields, user_id: i64)
+pub fn prepare_fields_for_create<MC>(fields: &mut SeaFields, user_id: Uuid)
 where
   MC: DMC,
 {
@@ -20,7 +21,7 @@ where
   }
 }
 
-pub fn prepare_fields_for_update<MC>(fields: &mut SeaFields, user_id: i64)
+pub fn prepare_fields_for_update<MC>(fields: &mut SeaFields, user_id: Uuid)
 where
   MC: DMC,
 {
@@ -0,0 +1,130 @@
+use std::net::IpAddr;
+
+use axum::http::HeaderMap;
+use ipnet::IpNet;
+use tracing::warn;
+
+/// Reverse proxies whose forwarded client address headers are believed.
+/// Requests reaching the services directly are identified by their socket address only, any
+/// `X-Forwarded-For` they send is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+  networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+  /// Parses comma separated IPs and CIDRs, invalid entries are logged and skipped
+  pub fn parse(list: &str) -> Self {
+    let networks = list
+      .split(',')
+      .map(str::trim)
+      .filter(|entry| !entry.is_empty())
+      .filter_map(|entry| {
+        let network = entry.parse::<IpNet>().or_else(|_| entry.parse::<IpAddr>().map(IpNet::from));
+        if network.is_err() {
+          warn!("⚠️ Ignoring invalid trusted proxy entry: {}", entry);
+        }
+        network.ok()
+      })
+      .collect();
+
+    Self { networks }
+  }
+
+  pub fn is_trusted(&self, ip: IpAddr) -> bool {
+    let ip = ip.to_canonical();
+    self.networks.iter().any(|network| network.contains(&ip))
+  }
+
+  /// Address of the client behind the connection from `peer`.
+  /// `X-Forwarded-For` is walked from the nearest hop and the first address that is not a
+  /// trusted proxy wins, so a client cannot pose as another by sending the header itself.
+  pub fn client_ip(&self, headers: &HeaderMap, peer: IpAddr) -> IpAddr {
+    let peer = peer.to_canonical();
+    if !self.is_trusted(peer) {
+      return peer;
+    }
+
+    let hops: Vec<&str> = headers
+      .get_all("x-forwarded-for")
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .flat_map(|value| value.split(','))
+      .collect();
+
+    if hops.is_empty() {
+      return header_ip(headers, "x-real-ip").unwrap_or(peer);
+    }
+
+    let mut client = peer;
+    for hop in hops.into_iter().rev() {
+      // Anything left of a malformed hop was not written by a proxy we know
+      let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+        break;
+      };
+      client = ip.to_canonical();
+      if !self.is_trusted(client) {
+        break;
+      }
+    }
+    client
+  }
+}
+
+fn header_ip(headers: &HeaderMap, name: &str) -> Option<IpAddr> {
+  let value = headers.get(name)?.to_str().ok()?;
+  value.trim().parse::<IpAddr>().ok().map(|ip| ip.to_canonical())
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::http::HeaderValue;
+
+  use super::*;
+
+  fn ip(value: &str) -> IpAddr {
+    value.parse().unwrap()
+  }
+
+  fn forwarded(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", HeaderValue::from_static(value));
+    headers
+  }
+
+  #[test]
+  fn untrusted_peer_is_the_client() {
+    let proxies = TrustedProxies::parse("10.0.0.0/8");
+    let headers = forwarded("203.0.113.7");
+
+    assert_eq!(proxies.client_ip(&headers, ip("198.51.100.1")), ip("198.51.100.1"));
+    assert_eq!(TrustedProxies::default().client_ip(&headers, ip("10.0.0.2")), ip("10.0.0.2"));
+  }
+
+  #[test]
+  fn nearest_untrusted_hop_is_the_client() {
+    let proxies = TrustedProxies::parse("10.0.0.0/8, 192.0.2.10");
+    // The client prepended a spoofed address before reaching the proxies
+    let headers = forwarded("1.2.3.4, 203.0.113.7, 192.0.2.10");
+
+    assert_eq!(proxies.client_ip(&headers, ip("10.1.2.3")), ip("203.0.113.7"));
+  }
+
+  #[test]
+  fn real_ip_header_is_used_without_forwarded_for() {
+    let proxies = TrustedProxies::parse("10.0.0.1");
+    let mut headers = HeaderMap::new();
+    headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.7"));
+
+    assert_eq!(proxies.client_ip(&headers, ip("10.0.0.1")), ip("203.0.113.7"));
+    assert_eq!(proxies.client_ip(&HeaderMap::new(), ip("10.0.0.1")), ip("10.0.0.1"));
+  }
+
+  #[test]
+  fn mapped_ipv4_peers_match_ipv4_networks() {
+    let proxies = TrustedProxies::parse("10.0.0.0/8, not-an-ip");
+
+    assert!(proxies.is_trusted(ip("::ffff:10.0.0.1")));
+    assert!(!proxies.is_trusted(ip("11.0.0.1")));
+  }
+}
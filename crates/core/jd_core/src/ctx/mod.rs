@@ -4,31 +4,68 @@ mod error;
 
 pub use self::error::{Error, Result};
 
+use uuid::Uuid;
+
 // endregion: --- Modules
 
+/// Role carried by the root context, never assigned to a real user
+pub const ROOT_ROLE: &str = "root";
+
 #[derive(Clone, Debug)]
 pub struct Ctx {
-  user_id: i64,
+  user_id: Uuid,
+  /// Role name as issued by the auth service, e.g. `admin`
+  role: String,
+  session_id: Option<Uuid>,
+  /// Permission names an API key is limited to, `None` for callers acting with their full role
+  scopes: Option<Vec<String>>,
 }
 
 // Constructor.
 impl Ctx {
   pub fn root_ctx() -> Self {
-    Ctx { user_id: 0 }
+    Ctx { user_id: Uuid::nil(), role: ROOT_ROLE.to_string(), session_id: None, scopes: None }
   }
 
-  pub fn new(user_id: i64) -> Result<Self> {
-    if user_id == 0 {
+  pub fn new(user_id: Uuid, role: impl Into<String>, session_id: Option<Uuid>) -> Result<Self> {
+    if user_id.is_nil() {
       Err(Error::CtxCannotNewRootCtx { message: user_id.to_string() })
     } else {
-      Ok(Self { user_id })
+      Ok(Self { user_id, role: role.into(), session_id, scopes: None })
     }
   }
+
+  /// Limits the context to `scopes`, for callers authenticated with an API key
+  pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+    self.scopes = Some(scopes);
+    self
+  }
 }
 
 // Property Accessors.
 impl Ctx {
-  pub fn user_id(&self) -> i64 {
+  pub fn user_id(&self) -> Uuid {
     self.user_id
   }
+
+  pub fn role(&self) -> &str {
+    &self.role
+  }
+
+  pub fn session_id(&self) -> Option<Uuid> {
+    self.session_id
+  }
+
+  pub fn is_root(&self) -> bool {
+    self.user_id.is_nil()
+  }
+
+  pub fn is_scoped(&self) -> bool {
+    self.scopes.is_some()
+  }
+
+  /// Whether the caller may use what `scope` guards, always for unscoped callers
+  pub fn has_scope(&self, scope: &str) -> bool {
+    self.scopes.as_ref().is_none_or(|scopes| scopes.iter().any(|granted| granted == scope))
+  }
 }
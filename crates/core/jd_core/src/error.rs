@@ -76,6 +76,9 @@ pub enum Error {
   #[error("Failed to create Sui client: {0}")]
   CantCreateSuiClient(String),
 
+  #[error("JWT keys error: {0}")]
+  JwtKeys(String),
+
   #[error("RPC error: {0}")]
   RpcError(String),
 }
@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use jd_utils::config::JwtConfig;
+use jsonwebtoken::jwk::{
+  AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+  OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use rsa::pkcs8::{DecodePrivateKey as _, DecodePublicKey as _};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::Deserialize;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::{Error, Result};
+
+/// How long a retired key keeps verifying, it covers the longest token lifetime (the refresh
+/// token's 7 days) so nothing signed before `retire_at` is cut short
+pub const RETIRED_KEY_GRACE: chrono::Duration = chrono::Duration::days(7);
+
+/// Key schedule read from the keys directory
+const SCHEDULE_FILE: &str = "keys.json";
+
+/// When a key may sign. Keys without a window sign as soon as they are present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct KeyWindow {
+  /// Signing starts here, the key is already published so verifiers can cache it
+  pub not_before: Option<DateTime<Utc>>,
+  /// Signing stops here, tokens signed earlier keep verifying until they expire
+  pub retire_at: Option<DateTime<Utc>>,
+}
+
+impl KeyWindow {
+  pub fn signs_at(&self, now: DateTime<Utc>) -> bool {
+    self.not_before.is_none_or(|not_before| not_before <= now)
+      && self.retire_at.is_none_or(|retire_at| now < retire_at)
+  }
+
+  /// A retired key stops verifying once every token it signed has expired
+  pub fn verifies_at(&self, now: DateTime<Utc>) -> bool {
+    self
+      .retire_at
+      .is_none_or(|retire_at| now < retire_at + RETIRED_KEY_GRACE)
+  }
+
+  /// Tokens claiming to be issued after retirement were not signed by us
+  pub fn accepts_issued_at(&self, issued_at: DateTime<Utc>) -> bool {
+    self
+      .retire_at
+      .is_none_or(|retire_at| issued_at <= retire_at)
+  }
+}
+
+/// PEM encoded key as found in the keys directory
+#[derive(Debug, Clone)]
+pub struct KeyMaterial {
+  pub kid: String,
+  pub pem: String,
+  /// Public keys only verify, they are kept around while tokens they signed are still valid
+  pub is_private: bool,
+  pub window: KeyWindow,
+}
+
+struct SigningEntry {
+  kid: Option<String>,
+  encoding_key: EncodingKey,
+  window: KeyWindow,
+}
+
+struct VerificationKey {
+  kid: Option<String>,
+  decoding_key: DecodingKey,
+  window: KeyWindow,
+  /// Published through JWKS, absent for shared secrets
+  jwk: Option<Jwk>,
+}
+
+/// Every key that may sign plus every key still accepted for verification
+pub struct JwtKeySet {
+  algorithm: Algorithm,
+  /// Breaks ties between keys that became active at the same time
+  preferred_kid: Option<String>,
+  signing_keys: Vec<SigningEntry>,
+  verification_keys: Vec<VerificationKey>,
+}
+
+impl fmt::Debug for JwtKeySet {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    // Never print key material
+    f.debug_struct("JwtKeySet")
+      .field("algorithm", &self.algorithm)
+      .field("signing_kids", &self.signing_keys.iter().map(|k| &k.kid).collect::<Vec<_>>())
+      .field(
+        "kids",
+        &self
+          .verification_keys
+          .iter()
+          .map(|k| &k.kid)
+          .collect::<Vec<_>>(),
+      )
+      .finish()
+  }
+}
+
+impl JwtKeySet {
+  /// HS256 with a shared secret, tokens carry no `kid`
+  pub fn hmac(secret: &str) -> Self {
+    Self {
+      algorithm: Algorithm::HS256,
+      preferred_kid: None,
+      signing_keys: vec![SigningEntry {
+        kid: None,
+        encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+        window: KeyWindow::default(),
+      }],
+      verification_keys: vec![VerificationKey {
+        kid: None,
+        decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+        window: KeyWindow::default(),
+        jwk: None,
+      }],
+    }
+  }
+
+  pub fn from_config(config: &JwtConfig, secret: &str) -> Result<Self> {
+    let algorithm = match config.algorithm.as_str() {
+      "HS256" => return Ok(Self::hmac(secret)),
+      "RS256" => Algorithm::RS256,
+      "EdDSA" => Algorithm::EdDSA,
+      other => return Err(Error::JwtKeys(format!("Unsupported JWT algorithm: {}", other))),
+    };
+
+    let keys_dir = config.keys_dir.as_deref().ok_or_else(|| {
+      Error::JwtKeys(format!("JWT.KEYS_DIR is required for {}", config.algorithm))
+    })?;
+
+    let keys = Self::asymmetric(
+      algorithm,
+      config.active_kid.as_deref(),
+      read_keys_dir(Path::new(keys_dir))?,
+    )?;
+    if keys.signing_key_at(Utc::now()).is_none() {
+      return Err(Error::JwtKeys(format!("No JWT key in {} can sign now", keys_dir)));
+    }
+    Ok(keys)
+  }
+
+  pub fn asymmetric(
+    algorithm: Algorithm,
+    preferred_kid: Option<&str>,
+    keys: Vec<KeyMaterial>,
+  ) -> Result<Self> {
+    let mut signing_keys = Vec::new();
+    let mut verification_keys = Vec::with_capacity(keys.len());
+
+    for key in keys {
+      if key.is_private {
+        signing_keys.push(SigningEntry {
+          kid: Some(key.kid.clone()),
+          encoding_key: encoding_key_from_pem(algorithm, &key)?,
+          window: key.window,
+        });
+      }
+      verification_keys.push(verification_key_from_pem(algorithm, &key)?);
+    }
+
+    let is_private = |kid: &&str| {
+      signing_keys
+        .iter()
+        .any(|key| key.kid.as_deref() == Some(*kid))
+    };
+    if let Some(kid) = preferred_kid.filter(|kid| !is_private(kid)) {
+      return Err(Error::JwtKeys(format!("No private key found for active kid {}", kid)));
+    }
+
+    Ok(Self {
+      algorithm,
+      preferred_kid: preferred_kid.map(str::to_string),
+      signing_keys,
+      verification_keys,
+    })
+  }
+
+  pub fn algorithm(&self) -> Algorithm {
+    self.algorithm
+  }
+
+  /// Key to sign with right now, see [`Self::signing_key_at`]
+  pub fn signing_key(&self) -> Option<(Option<&str>, &EncodingKey)> {
+    self.signing_key_at(Utc::now())
+  }
+
+  /// The private key that became active most recently, keys without `not_before` rank last
+  pub fn signing_key_at(&self, now: DateTime<Utc>) -> Option<(Option<&str>, &EncodingKey)> {
+    self
+      .signing_keys
+      .iter()
+      .filter(|key| key.window.signs_at(now))
+      .max_by_key(|key| {
+        let preferred = key.kid.is_some() && key.kid == self.preferred_kid;
+        (key.window.not_before, preferred, key.kid.as_deref())
+      })
+      .map(|key| (key.kid.as_deref(), &key.encoding_key))
+  }
+
+  /// Key matching the token header, see [`Self::decoding_key_at`]
+  pub fn decoding_key(&self, kid: Option<&str>) -> Option<(&DecodingKey, KeyWindow)> {
+    self.decoding_key_at(kid, Utc::now())
+  }
+
+  /// Key matching the token header. Removed keys and keys past their retirement grace find
+  /// none; the caller still checks the token was issued inside the returned window.
+  pub fn decoding_key_at(
+    &self,
+    kid: Option<&str>,
+    now: DateTime<Utc>,
+  ) -> Option<(&DecodingKey, KeyWindow)> {
+    self
+      .verification_keys
+      .iter()
+      .find(|key| key.kid.as_deref() == kid && key.window.verifies_at(now))
+      .map(|key| (&key.decoding_key, key.window))
+  }
+
+  /// Public keys for downstream verifiers, empty when signing with a shared secret
+  pub fn jwks(&self) -> JwkSet {
+    self.jwks_at(Utc::now())
+  }
+
+  /// Keys scheduled for the future are published too, so verifiers know them before use
+  pub fn jwks_at(&self, now: DateTime<Utc>) -> JwkSet {
+    JwkSet {
+      keys: self
+        .verification_keys
+        .iter()
+        .filter(|key| key.window.verifies_at(now))
+        .filter_map(|key| key.jwk.clone())
+        .collect(),
+    }
+  }
+}
+
+/// Key set shared by the whole process, re-read from the keys directory so keys can be
+/// added and retired without a restart
+pub struct JwtKeyStore {
+  config: JwtConfig,
+  secret: String,
+  keys: RwLock<Arc<JwtKeySet>>,
+}
+
+impl fmt::Debug for JwtKeyStore {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("JwtKeyStore")
+      .field("keys", &self.current())
+      .finish()
+  }
+}
+
+impl JwtKeyStore {
+  pub fn load(config: &JwtConfig, secret: &str) -> Result<Self> {
+    let keys = JwtKeySet::from_config(config, secret)?;
+    info!("Loaded JWT keys: {:?}", keys);
+
+    Ok(Self {
+      config: config.clone(),
+      secret: secret.to_string(),
+      keys: RwLock::new(Arc::new(keys)),
+    })
+  }
+
+  pub fn current(&self) -> Arc<JwtKeySet> {
+    self.keys.read().unwrap_or_else(|e| e.into_inner()).clone()
+  }
+
+  /// Re-reads the keys directory. A broken directory keeps the previous keys in place.
+  pub fn reload(&self) -> Result<()> {
+    let keys = Arc::new(JwtKeySet::from_config(&self.config, &self.secret)?);
+    *self.keys.write().unwrap_or_else(|e| e.into_inner()) = keys;
+    Ok(())
+  }
+
+  /// Reloads every `reload_secs` until the store is dropped, shared secrets never change
+  pub fn spawn_reload(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+    self.config.keys_dir.as_ref()?;
+    let store = Arc::downgrade(self);
+    let interval = Duration::from_secs(self.config.reload_secs.max(1));
+
+    Some(tokio::spawn(async move {
+      let mut ticker = tokio::time::interval(interval);
+      // The first tick completes right away, `load` just read the directory
+      ticker.tick().await;
+      loop {
+        ticker.tick().await;
+        let Some(store) = store.upgrade() else {
+          break;
+        };
+        store
+          .reload()
+          .unwrap_or_else(|e| warn!("Keeping previous JWT keys: {}", e));
+      }
+    }))
+  }
+}
+
+/// `<kid>.pem` files are private keys, `<kid>.pub.pem` files public keys, `keys.json`
+/// schedules them
+fn read_keys_dir(dir: &Path) -> Result<Vec<KeyMaterial>> {
+  let entries = fs::read_dir(dir).map_err(|e| {
+    Error::JwtKeys(format!("Failed to read JWT keys from {}: {}", dir.display(), e))
+  })?;
+  let schedule = read_schedule(dir)?;
+
+  let mut keys = Vec::new();
+  for entry in entries {
+    let path = entry
+      .map_err(|e| Error::JwtKeys(format!("Failed to read JWT keys dir: {}", e)))?
+      .path();
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+      continue;
+    };
+
+    let (kid, is_private) = if let Some(kid) = file_name.strip_suffix(".pub.pem") {
+      (kid, false)
+    } else if let Some(kid) = file_name.strip_suffix(".pem") {
+      (kid, true)
+    } else {
+      continue;
+    };
+
+    let pem = fs::read_to_string(&path)
+      .map_err(|e| Error::JwtKeys(format!("Failed to read JWT key {}: {}", path.display(), e)))?;
+    let window = schedule.get(kid).copied().unwrap_or_default();
+
+    keys.push(KeyMaterial { kid: kid.to_string(), pem, is_private, window });
+  }
+
+  // Deterministic JWKS ordering
+  keys.sort_by(|a, b| a.kid.cmp(&b.kid));
+  Ok(keys)
+}
+
+fn read_schedule(dir: &Path) -> Result<HashMap<String, KeyWindow>> {
+  let path = dir.join(SCHEDULE_FILE);
+  if !path.exists() {
+    return Ok(HashMap::new());
+  }
+
+  let invalid = |e: &dyn fmt::Display| {
+    Error::JwtKeys(format!("Invalid JWT key schedule {}: {}", path.display(), e))
+  };
+  let raw = fs::read_to_string(&path).map_err(|e| invalid(&e))?;
+  serde_json::from_str(&raw).map_err(|e| invalid(&e))
+}
+
+fn invalid_key(kid: &str, err: impl fmt::Display) -> Error {
+  Error::JwtKeys(format!("Invalid JWT key {}: {}", kid, err))
+}
+
+fn encoding_key_from_pem(algorithm: Algorithm, key: &KeyMaterial) -> Result<EncodingKey> {
+  let pem = key.pem.as_bytes();
+  match algorithm {
+    Algorithm::RS256 => EncodingKey::from_rsa_pem(pem),
+    _ => EncodingKey::from_ed_pem(pem),
+  }
+  .map_err(|e| invalid_key(&key.kid, e))
+}
+
+fn verification_key_from_pem(algorithm: Algorithm, key: &KeyMaterial) -> Result<VerificationKey> {
+  let params = match algorithm {
+    Algorithm::RS256 => {
+      let public = if key.is_private {
+        RsaPrivateKey::from_pkcs8_pem(&key.pem)
+          .map(|private| private.to_public_key())
+          .map_err(|e| invalid_key(&key.kid, e))?
+      } else {
+        RsaPublicKey::from_public_key_pem(&key.pem).map_err(|e| invalid_key(&key.kid, e))?
+      };
+      rsa_parameters(&public)
+    }
+    _ => {
+      let public = if key.is_private {
+        SigningKey::from_pkcs8_pem(&key.pem)
+          .map(|private| private.verifying_key())
+          .map_err(|e| invalid_key(&key.kid, e))?
+      } else {
+        VerifyingKey::from_public_key_pem(&key.pem).map_err(|e| invalid_key(&key.kid, e))?
+      };
+      AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+        key_type: OctetKeyPairType::OctetKeyPair,
+        curve: EllipticCurve::Ed25519,
+        x: URL_SAFE_NO_PAD.encode(public.as_bytes()),
+      })
+    }
+  };
+
+  let jwk = Jwk {
+    common: CommonParameters {
+      public_key_use: Some(PublicKeyUse::Signature),
+      key_algorithm: Some(match algorithm {
+        Algorithm::RS256 => KeyAlgorithm::RS256,
+        _ => KeyAlgorithm::EdDSA,
+      }),
+      key_id: Some(key.kid.clone()),
+      ..Default::default()
+    },
+    algorithm: params,
+  };
+  let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| invalid_key(&key.kid, e))?;
+
+  Ok(VerificationKey {
+    kid: Some(key.kid.clone()),
+    decoding_key,
+    window: key.window,
+    jwk: Some(jwk),
+  })
+}
+
+fn rsa_parameters(public: &RsaPublicKey) -> AlgorithmParameters {
+  AlgorithmParameters::RSA(RSAKeyParameters {
+    key_type: RSAKeyType::RSA,
+    n: URL_SAFE_NO_PAD.encode(public.n().to_bytes_be()),
+    e: URL_SAFE_NO_PAD.encode(public.e().to_bytes_be()),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use rsa::pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+
+  use super::*;
+
+  fn ed_key(kid: &str, seed: u8, is_private: bool, window: KeyWindow) -> KeyMaterial {
+    let signing = SigningKey::from_bytes(&[seed; 32]);
+    let pem = if is_private {
+      signing.to_pkcs8_pem(LineEnding::LF).unwrap().to_string()
+    } else {
+      signing
+        .verifying_key()
+        .to_public_key_pem(LineEnding::LF)
+        .unwrap()
+    };
+
+    KeyMaterial { kid: kid.to_string(), pem, is_private, window }
+  }
+
+  fn at(value: &str) -> DateTime<Utc> {
+    value.parse().unwrap()
+  }
+
+  fn signing_kid(keys: &JwtKeySet, now: &str) -> Option<String> {
+    keys
+      .signing_key_at(at(now))
+      .and_then(|(kid, _)| kid.map(str::to_string))
+  }
+
+  fn rotation() -> JwtKeySet {
+    let k1 = KeyWindow { not_before: None, retire_at: Some(at("2026-02-01T00:00:00Z")) };
+    let k2 = KeyWindow { not_before: Some(at("2026-01-01T00:00:00Z")), retire_at: None };
+    let keys = vec![ed_key("k1", 1, true, k1), ed_key("k2", 2, true, k2)];
+
+    JwtKeySet::asymmetric(Algorithm::EdDSA, None, keys).unwrap()
+  }
+
+  #[test]
+  fn newest_active_key_signs() {
+    let keys = rotation();
+
+    assert_eq!(signing_kid(&keys, "2025-12-31T00:00:00Z").as_deref(), Some("k1"));
+    assert_eq!(signing_kid(&keys, "2026-01-15T00:00:00Z").as_deref(), Some("k2"));
+    assert_eq!(signing_kid(&keys, "2026-03-01T00:00:00Z").as_deref(), Some("k2"));
+  }
+
+  #[test]
+  fn scheduled_key_is_published_before_it_signs() {
+    let keys = rotation();
+
+    assert_eq!(keys.jwks_at(at("2025-12-31T00:00:00Z")).keys.len(), 2);
+    assert!(
+      keys
+        .decoding_key_at(Some("k2"), at("2025-12-31T00:00:00Z"))
+        .is_some()
+    );
+  }
+
+  #[test]
+  fn retired_key_verifies_until_its_tokens_expire() {
+    let keys = rotation();
+    let retire_at = at("2026-02-01T00:00:00Z");
+
+    let (_, window) = keys
+      .decoding_key_at(Some("k1"), at("2026-02-07T00:00:00Z"))
+      .unwrap();
+    assert!(window.accepts_issued_at(retire_at));
+    assert!(!window.accepts_issued_at(at("2026-02-02T00:00:00Z")));
+
+    let expired = retire_at + RETIRED_KEY_GRACE;
+    assert!(keys.decoding_key_at(Some("k1"), expired).is_none());
+    assert_eq!(keys.jwks_at(expired).keys.len(), 1);
+  }
+
+  #[test]
+  fn active_kid_breaks_ties() {
+    let keys = vec![
+      ed_key("k1", 1, true, KeyWindow::default()),
+      ed_key("k2", 2, true, KeyWindow::default()),
+    ];
+    let keys = JwtKeySet::asymmetric(Algorithm::EdDSA, Some("k1"), keys).unwrap();
+
+    assert_eq!(signing_kid(&keys, "2026-01-01T00:00:00Z").as_deref(), Some("k1"));
+  }
+
+  #[test]
+  fn active_kid_requires_private_key() {
+    let keys = vec![ed_key("k1", 1, false, KeyWindow::default())];
+
+    assert!(JwtKeySet::asymmetric(Algorithm::EdDSA, Some("k1"), keys).is_err());
+  }
+
+  #[test]
+  fn shared_secret_is_not_published() {
+    assert!(JwtKeySet::hmac("test-secret").jwks().keys.is_empty());
+  }
+
+  #[test]
+  fn reload_picks_up_new_keys_and_keeps_old_ones_on_error() {
+    let dir = std::env::temp_dir().join(format!("jwt-keys-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let write = |key: KeyMaterial| {
+      let suffix = if key.is_private { "pem" } else { "pub.pem" };
+      fs::write(dir.join(format!("{}.{}", key.kid, suffix)), key.pem).unwrap();
+    };
+    let config = JwtConfig {
+      algorithm: "EdDSA".to_string(),
+      keys_dir: Some(dir.display().to_string()),
+      ..Default::default()
+    };
+
+    write(ed_key("k1", 1, true, KeyWindow::default()));
+    let store = JwtKeyStore::load(&config, "").unwrap();
+    assert_eq!(store.current().signing_key().unwrap().0, Some("k1"));
+
+    write(ed_key("k2", 2, true, KeyWindow::default()));
+    fs::write(dir.join(SCHEDULE_FILE), r#"{"k2": {"not_before": "2020-01-01T00:00:00Z"}}"#)
+      .unwrap();
+    store.reload().unwrap();
+    assert_eq!(store.current().signing_key().unwrap().0, Some("k2"));
+
+    fs::write(dir.join(SCHEDULE_FILE), "not json").unwrap();
+    assert!(store.reload().is_err());
+    assert_eq!(store.current().signing_key().unwrap().0, Some("k2"));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 use tracing::info;
+pub mod client_ip;
+pub mod jwt_keys;
 pub mod sui;
 
 use jd_storage::{dbx::Dbx, new_db_pool};
@@ -42,6 +44,8 @@ pub struct AppState {
   pub redis: Arc<RedisClient>,
   pub sui_client: Arc<sui::sui_client::SuiClient>,
   pub config: Arc<Config>,
+  pub trusted_proxies: Arc<client_ip::TrustedProxies>,
+  pub jwt_keys: Arc<jwt_keys::JwtKeyStore>,
   // TODO: S3 Service
   // TODO: Email Service
 }
@@ -59,8 +63,15 @@ impl AppState {
         .await
         .map_err(|ex| Error::CantCreateSuiClient(ex.to_string()))?,
     );
+    sui_client.spawn_health_checks();
 
-    Ok(AppState { mm, redis, sui_client, config })
+    let trusted_proxies =
+      Arc::new(client_ip::TrustedProxies::parse(&config.web.trusted_proxies));
+
+    let jwt_keys = Arc::new(jwt_keys::JwtKeyStore::load(&config.jwt, &config.auth_jwt_secret)?);
+    jwt_keys.spawn_reload();
+
+    Ok(AppState { mm, redis, sui_client, config, trusted_proxies, jwt_keys })
   }
 
   // Convenience methods
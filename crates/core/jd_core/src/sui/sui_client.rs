@@ -1,28 +1,408 @@
 use crate::Result;
 use crate::error::Error;
-use jd_utils::config::SuiConfig;
-use sui_sdk::SuiClientBuilder;
+use futures::future::join_all;
+use jd_utils::config::{SuiConfig, SuiHealthCheckConfig, SuiRpcEndpoint};
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use sui_sdk::{
+  SUI_DEVNET_URL, SUI_LOCAL_NETWORK_URL, SUI_MAINNET_URL, SUI_TESTNET_URL, SuiClientBuilder,
+};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeHealth {
+  Healthy,
+  Unreachable,
+  WrongChain,
+  Lagging,
+}
+
+/// Chain identifier and latest checkpoint a node reported
+type Probe = Option<(String, u64)>;
+
+struct SuiNode {
+  url: String,
+  weight: u32,
+  /// Connected on the first probe it answers, a node that is down at startup joins later
+  client: RwLock<Option<sui_sdk::SuiClient>>,
+  health: RwLock<(NodeHealth, Option<u64>)>,
+}
+
+impl SuiNode {
+  fn new(endpoint: SuiRpcEndpoint) -> Self {
+    Self {
+      url: endpoint.url,
+      weight: endpoint.weight,
+      client: RwLock::new(None),
+      health: RwLock::new((NodeHealth::Unreachable, None)),
+    }
+  }
+
+  fn client(&self) -> Option<sui_sdk::SuiClient> {
+    self
+      .client
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+      .clone()
+  }
+
+  fn health(&self) -> (NodeHealth, Option<u64>) {
+    *self.health.read().unwrap_or_else(|e| e.into_inner())
+  }
+
+  async fn probe(&self, timeout: Duration) -> Probe {
+    let probe = async {
+      let client = match self.client() {
+        Some(client) => client,
+        None => {
+          let client = SuiClientBuilder::default().build(&self.url).await?;
+          *self.client.write().unwrap_or_else(|e| e.into_inner()) = Some(client.clone());
+          client
+        }
+      };
+      let read_api = client.read_api();
+      tokio::try_join!(
+        read_api.get_chain_identifier(),
+        read_api.get_latest_checkpoint_sequence_number()
+      )
+    };
+
+    match tokio::time::timeout(timeout, probe).await {
+      Ok(Ok(probe)) => Some(probe),
+      Ok(Err(e)) => {
+        warn!("Sui fullnode {} failed its health check: {}", self.url, e);
+        None
+      }
+      Err(_) => {
+        warn!("Sui fullnode {} timed out on its health check", self.url);
+        None
+      }
+    }
+  }
+}
+
+/// Sui fullnodes behind one client, shared by every reader and the gas station.
+/// Reads go to a healthy node picked by weight. When no node is healthy they go to the most
+/// advanced node still on the chain, so a lagging network degrades instead of failing.
 pub struct SuiClient {
-  pub client: sui_sdk::SuiClient,
+  nodes: Vec<SuiNode>,
+  /// Chain every node has to be on, agreed on by the first nodes that answered
+  chain_id: OnceLock<String>,
+  /// Client of the first node that connected, used until the health checks know better
+  fallback: sui_sdk::SuiClient,
+  health_check: SuiHealthCheckConfig,
 }
 
 impl SuiClient {
+  /// Connects to `SuiConfig::rpc_urls`, or to the public fullnode of `SuiConfig::env` when
+  /// none are set. Fails when no node answers.
   pub async fn new(config: &SuiConfig) -> Result<Self> {
-    let client = match config.env.to_lowercase().as_str() {
-      "mainnet" => SuiClientBuilder::default().build_mainnet().await?,
-      "testnet" => SuiClientBuilder::default().build_testnet().await?,
-      "devnet" => SuiClientBuilder::default().build_devnet().await?,
-      "local" => SuiClientBuilder::default().build_localnet().await?,
-      _ => {
-        return Err(Error::CantCreateSuiClient(format!("Invalid Sui environment: {}", config.env)));
-      }
+    let mut endpoints = config.rpc_endpoints()?;
+    if endpoints.is_empty() {
+      let url = match config.env.to_lowercase().as_str() {
+        "mainnet" => SUI_MAINNET_URL,
+        "testnet" => SUI_TESTNET_URL,
+        "devnet" => SUI_DEVNET_URL,
+        "local" => SUI_LOCAL_NETWORK_URL,
+        _ => {
+          return Err(Error::CantCreateSuiClient(format!(
+            "Invalid Sui environment: {}",
+            config.env
+          )));
+        }
+      };
+      endpoints.push(SuiRpcEndpoint { url: url.to_string(), weight: 1 });
+    }
+
+    let nodes: Vec<SuiNode> = endpoints.into_iter().map(SuiNode::new).collect();
+    let timeout = Duration::from_millis(config.health_check.timeout_ms);
+    let probes = join_all(nodes.iter().map(|node| node.probe(timeout))).await;
+    let fallback = nodes
+      .iter()
+      .find_map(SuiNode::client)
+      .ok_or_else(|| Error::CantCreateSuiClient("No Sui fullnode answered".to_string()))?;
+
+    let client = Self {
+      nodes,
+      chain_id: OnceLock::new(),
+      fallback,
+      health_check: config.health_check.clone(),
     };
+    client.apply(&probes);
+    Ok(client)
+  }
+
+  /// Client of the node the next read should go to
+  pub fn client(&self) -> sui_sdk::SuiClient {
+    let healths: Vec<(NodeHealth, Option<u64>)> = self.nodes.iter().map(SuiNode::health).collect();
+    let weights: Vec<u32> = self.nodes.iter().map(|node| node.weight).collect();
+    let total: u64 = healthy_weights(&healths, &weights)
+      .map(|(_, weight)| u64::from(weight))
+      .sum();
+    let roll = if total > 0 { rand::thread_rng().gen_range(0..total) } else { 0 };
+
+    select_node(&healths, &weights, roll)
+      .and_then(|index| self.nodes[index].client())
+      .unwrap_or_else(|| self.fallback.clone())
+  }
+
+  /// Node a read should go to among those `usable` accepts, with its URL. Falls back to a
+  /// usable node that is not known to be healthy, `None` when no node is usable.
+  pub fn pick(&self, usable: impl Fn(&str) -> bool) -> Option<(&str, sui_sdk::SuiClient)> {
+    let healths: Vec<(NodeHealth, Option<u64>)> = self
+      .nodes
+      .iter()
+      .map(|node| if usable(&node.url) { node.health() } else { (NodeHealth::Unreachable, None) })
+      .collect();
+    let weights: Vec<u32> = self.nodes.iter().map(|node| node.weight).collect();
+    let total: u64 = healthy_weights(&healths, &weights)
+      .map(|(_, weight)| u64::from(weight))
+      .sum();
+    let roll = if total > 0 { rand::thread_rng().gen_range(0..total) } else { 0 };
+
+    select_node(&healths, &weights, roll)
+      .and_then(|index| Some((self.nodes[index].url.as_str(), self.nodes[index].client()?)))
+      .or_else(|| {
+        self
+          .nodes
+          .iter()
+          .filter(|node| usable(&node.url))
+          .find_map(|node| Some((node.url.as_str(), node.client()?)))
+      })
+  }
+
+  /// Client of the node transactions and the reads following them go to, the first healthy
+  /// node in configuration order. It stays the same while that node is healthy, on every
+  /// instance sharing the configuration, so a coin read back after a transaction is never
+  /// older than what the transaction left.
+  pub fn pinned_client(&self) -> sui_sdk::SuiClient {
+    let healths: Vec<(NodeHealth, Option<u64>)> = self.nodes.iter().map(SuiNode::health).collect();
+
+    pinned_node(&healths)
+      .and_then(|index| self.nodes[index].client())
+      .unwrap_or_else(|| self.fallback.clone())
+  }
 
-    Ok(Self { client })
+  /// Chain the nodes agreed on, unknown until enough of them answered
+  pub fn chain_id(&self) -> Option<&str> {
+    self.chain_id.get().map(String::as_str)
   }
 
   pub async fn get_api_version(&self) -> Result<String> {
-    Ok(self.client.api_version().to_string())
+    Ok(self.client().api_version().to_string())
+  }
+
+  /// Probes every node once and updates where reads go
+  pub async fn check_health(&self) {
+    let timeout = Duration::from_millis(self.health_check.timeout_ms);
+    let probes = join_all(self.nodes.iter().map(|node| node.probe(timeout))).await;
+    self.apply(&probes);
+  }
+
+  /// Runs `check_health` every `SuiHealthCheckConfig::interval_secs` until the client is dropped
+  pub fn spawn_health_checks(self: &Arc<Self>) -> JoinHandle<()> {
+    let client = Arc::downgrade(self);
+    let interval = Duration::from_secs(self.health_check.interval_secs.max(1));
+
+    tokio::spawn(async move {
+      let mut ticker = tokio::time::interval(interval);
+      // The first tick completes right away, `new` just probed every node
+      ticker.tick().await;
+      loop {
+        ticker.tick().await;
+        let Some(client) = client.upgrade() else {
+          break;
+        };
+        client.check_health().await;
+      }
+    })
+  }
+
+  fn apply(&self, probes: &[Probe]) {
+    let weights: Vec<u32> = self.nodes.iter().map(|node| node.weight).collect();
+    let Some(chain_id) = agreed_chain(probes, &weights) else {
+      warn!("No Sui fullnode answered the health check");
+      for node in &self.nodes {
+        node.health.write().unwrap_or_else(|e| e.into_inner()).0 = NodeHealth::Unreachable;
+      }
+      return;
+    };
+    let chain_id = self.chain_id.get_or_init(|| {
+      info!("Sui fullnodes are on chain {}", chain_id);
+      chain_id
+    });
+
+    let healths = evaluate(probes, chain_id, self.health_check.max_checkpoint_lag);
+    for ((node, probe), health) in self.nodes.iter().zip(probes).zip(healths) {
+      let mut current = node.health.write().unwrap_or_else(|e| e.into_inner());
+      if current.0 != health {
+        match health {
+          NodeHealth::Healthy => info!("Sui fullnode {} is healthy", node.url),
+          _ => warn!("Sui fullnode {} is {:?}, reads move to other nodes", node.url, health),
+        }
+      }
+      *current = (
+        health,
+        probe
+          .as_ref()
+          .map(|(_, checkpoint)| *checkpoint)
+          .or(current.1),
+      );
+    }
+  }
+}
+
+/// Chain reported by the most weight, every node counts at least once so standbys vote too
+fn agreed_chain(probes: &[Probe], weights: &[u32]) -> Option<String> {
+  let mut votes: HashMap<&str, u64> = HashMap::new();
+  for (probe, weight) in probes.iter().zip(weights) {
+    if let Some((chain_id, _)) = probe {
+      *votes.entry(chain_id.as_str()).or_default() += u64::from(*weight).max(1);
+    }
+  }
+  votes
+    .into_iter()
+    .max_by(|(a_chain, a_votes), (b_chain, b_votes)| {
+      a_votes.cmp(b_votes).then_with(|| b_chain.cmp(a_chain))
+    })
+    .map(|(chain_id, _)| chain_id.to_string())
+}
+
+/// A node is healthy on the agreed chain and at most `max_lag` checkpoints behind the best
+fn evaluate(probes: &[Probe], chain_id: &str, max_lag: u64) -> Vec<NodeHealth> {
+  let best = probes
+    .iter()
+    .flatten()
+    .filter(|(chain, _)| chain == chain_id)
+    .map(|(_, checkpoint)| *checkpoint)
+    .max()
+    .unwrap_or(0);
+
+  probes
+    .iter()
+    .map(|probe| match probe {
+      None => NodeHealth::Unreachable,
+      Some((chain, _)) if chain != chain_id => NodeHealth::WrongChain,
+      Some((_, checkpoint)) if checkpoint.saturating_add(max_lag) < best => NodeHealth::Lagging,
+      Some(_) => NodeHealth::Healthy,
+    })
+    .collect()
+}
+
+fn healthy_weights<'a>(
+  healths: &'a [(NodeHealth, Option<u64>)],
+  weights: &'a [u32],
+) -> impl Iterator<Item = (usize, u32)> + 'a {
+  healths
+    .iter()
+    .zip(weights)
+    .enumerate()
+    .filter(|(_, ((health, _), _))| *health == NodeHealth::Healthy)
+    .map(|(index, (_, weight))| (index, *weight))
+}
+
+/// Node `roll` lands on among the healthy ones, `roll` being below their total weight.
+/// Without weighted healthy nodes a healthy standby is used, then the most advanced lagging one.
+fn select_node(
+  healths: &[(NodeHealth, Option<u64>)],
+  weights: &[u32],
+  mut roll: u64,
+) -> Option<usize> {
+  for (index, weight) in healthy_weights(healths, weights) {
+    if roll < u64::from(weight) {
+      return Some(index);
+    }
+    roll -= u64::from(weight);
+  }
+
+  healthy_weights(healths, weights)
+    .map(|(index, _)| index)
+    .next()
+    .or_else(|| most_advanced_lagging(healths))
+}
+
+/// First healthy node, otherwise the most advanced lagging one
+fn pinned_node(healths: &[(NodeHealth, Option<u64>)]) -> Option<usize> {
+  healths
+    .iter()
+    .position(|(health, _)| *health == NodeHealth::Healthy)
+    .or_else(|| most_advanced_lagging(healths))
+}
+
+fn most_advanced_lagging(healths: &[(NodeHealth, Option<u64>)]) -> Option<usize> {
+  healths
+    .iter()
+    .enumerate()
+    .filter(|(_, (health, _))| *health == NodeHealth::Lagging)
+    .max_by_key(|(_, (_, checkpoint))| *checkpoint)
+    .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn probe(chain_id: &str, checkpoint: u64) -> Probe {
+    Some((chain_id.to_string(), checkpoint))
+  }
+
+  #[test]
+  fn nodes_off_chain_or_behind_are_unhealthy() {
+    let probes = [probe("35834a8a", 1_000), probe("35834a8a", 990), probe("4c78adac", 5_000), None];
+
+    assert_eq!(agreed_chain(&probes, &[1, 1, 1, 1]).as_deref(), Some("35834a8a"));
+    assert_eq!(agreed_chain(&probes, &[1, 1, 5, 1]).as_deref(), Some("4c78adac"));
+    assert_eq!(
+      evaluate(&probes, "35834a8a", 5),
+      [
+        NodeHealth::Healthy,
+        NodeHealth::Lagging,
+        NodeHealth::WrongChain,
+        NodeHealth::Unreachable
+      ]
+    );
+    assert_eq!(evaluate(&probes, "35834a8a", 10)[1], NodeHealth::Healthy);
+  }
+
+  #[test]
+  fn reads_are_spread_by_weight_over_healthy_nodes() {
+    use NodeHealth::*;
+    let healths = [(Healthy, Some(10)), (Unreachable, None), (Healthy, Some(10))];
+    let weights = [3, 5, 1];
+
+    assert_eq!(select_node(&healths, &weights, 0), Some(0));
+    assert_eq!(select_node(&healths, &weights, 2), Some(0));
+    assert_eq!(select_node(&healths, &weights, 3), Some(2));
+  }
+
+  #[test]
+  fn fails_over_to_standbys_then_the_most_advanced_node() {
+    use NodeHealth::*;
+    let weights = [1, 0, 1];
+
+    let standby = [(Lagging, Some(5)), (Healthy, Some(10)), (Unreachable, None)];
+    assert_eq!(select_node(&standby, &weights, 0), Some(1));
+
+    let lagging = [(Lagging, Some(5)), (WrongChain, Some(90)), (Lagging, Some(8))];
+    assert_eq!(select_node(&lagging, &weights, 0), Some(2));
+
+    let down = [(Unreachable, None), (WrongChain, Some(90)), (Unreachable, None)];
+    assert_eq!(select_node(&down, &weights, 0), None);
+  }
+
+  #[test]
+  fn transactions_stay_on_the_first_healthy_node() {
+    use NodeHealth::*;
+
+    let healthy = [(Lagging, Some(5)), (Healthy, Some(10)), (Healthy, Some(12))];
+    assert_eq!(pinned_node(&healthy), Some(1));
+
+    let lagging = [(Lagging, Some(5)), (Unreachable, None), (Lagging, Some(8))];
+    assert_eq!(pinned_node(&lagging), Some(2));
+
+    assert_eq!(pinned_node(&[(Unreachable, None), (WrongChain, Some(90))]), None);
   }
 }
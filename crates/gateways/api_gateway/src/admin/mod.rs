@@ -0,0 +1,110 @@
+use analytics_processor::{
+  AnalyticsQueries,
+  queries::{
+    AppSponsorship, LoginDay, RangeQuery, SponsorshipPoint, SponsorshipSeriesQuery, TopQuery,
+    UserSponsorship,
+  },
+};
+use auth_service::application::handlers::ApiKeyHandler;
+use auth_service::models::{
+  ApiKeyInfo, ApiKeyListQuery, CreateApiKeyRequest, IssuedApiKeyResponse, RevokeApiKeyResponse,
+};
+use axum::{
+  Extension, Router,
+  extract::{Json, Path, Query, State},
+  middleware,
+  routing::{delete, get, post},
+};
+use jd_core::AppState;
+use sui_service::infrastructure::sui_cache::{CacheStats, SuiCache};
+use uuid::Uuid;
+
+use crate::middleware::mw_auth_rbac::{AuthContext, mw_require_auth};
+use crate::middleware::mw_rate_limit::{RateLimitStats, rate_limit_stats};
+use crate::require_admin;
+
+pub fn admin_router(app_state: AppState) -> Router<AppState> {
+  Router::new()
+    .route("/analytics/sponsorships", get(sponsorship_series))
+    .route("/analytics/sponsorships/users", get(sponsorship_by_user))
+    .route("/analytics/sponsorships/apps", get(sponsorship_by_app))
+    .route("/analytics/logins", get(login_daily))
+    .route("/api-keys", get(list_api_keys).post(create_api_key))
+    .route("/api-keys/{key_id}/rotate", post(rotate_api_key))
+    .route("/api-keys/{key_id}", delete(revoke_api_key))
+    .route("/sui/cache", get(sui_cache_stats))
+    .route("/rate-limit", get(rate_limit_status))
+    .route_layer(middleware::from_fn(require_admin!()))
+    .route_layer(middleware::from_fn_with_state(app_state, mw_require_auth))
+}
+
+fn queries(state: &AppState) -> analytics_processor::Result<AnalyticsQueries> {
+  AnalyticsQueries::new(state.mm.dbx().db().clone())
+}
+
+async fn sponsorship_series(
+  State(state): State<AppState>,
+  Query(query): Query<SponsorshipSeriesQuery>,
+) -> analytics_processor::Result<Json<Vec<SponsorshipPoint>>> {
+  Ok(Json(queries(&state)?.sponsorship_series(&query).await?))
+}
+
+async fn sponsorship_by_user(
+  State(state): State<AppState>,
+  Query(query): Query<TopQuery>,
+) -> analytics_processor::Result<Json<Vec<UserSponsorship>>> {
+  Ok(Json(queries(&state)?.sponsorship_by_user(&query).await?))
+}
+
+async fn sponsorship_by_app(
+  State(state): State<AppState>,
+  Query(query): Query<TopQuery>,
+) -> analytics_processor::Result<Json<Vec<AppSponsorship>>> {
+  Ok(Json(queries(&state)?.sponsorship_by_app(&query).await?))
+}
+
+async fn login_daily(
+  State(state): State<AppState>,
+  Query(query): Query<RangeQuery>,
+) -> analytics_processor::Result<Json<Vec<LoginDay>>> {
+  Ok(Json(queries(&state)?.login_daily(&query).await?))
+}
+
+async fn create_api_key(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Json(request): Json<CreateApiKeyRequest>,
+) -> auth_service::Result<Json<IssuedApiKeyResponse>> {
+  ApiKeyHandler::create_api_key(State(state), auth.user_id, Json(request)).await
+}
+
+async fn list_api_keys(
+  State(state): State<AppState>,
+  Query(query): Query<ApiKeyListQuery>,
+) -> auth_service::Result<Json<Vec<ApiKeyInfo>>> {
+  ApiKeyHandler::list_api_keys(State(state), query.owner_id).await
+}
+
+async fn rotate_api_key(
+  State(state): State<AppState>,
+  Path(key_id): Path<Uuid>,
+) -> auth_service::Result<Json<IssuedApiKeyResponse>> {
+  ApiKeyHandler::rotate_api_key(State(state), key_id).await
+}
+
+async fn revoke_api_key(
+  State(state): State<AppState>,
+  Path(key_id): Path<Uuid>,
+) -> auth_service::Result<Json<RevokeApiKeyResponse>> {
+  ApiKeyHandler::revoke_api_key(State(state), key_id).await
+}
+
+/// Hits and misses of the Sui read cache since this instance started
+async fn sui_cache_stats(State(state): State<AppState>) -> Json<CacheStats> {
+  Json(SuiCache::shared(&state).stats())
+}
+
+/// Requests the gateway limiter could not count since this instance started
+async fn rate_limit_status(State(state): State<AppState>) -> Json<RateLimitStats> {
+  Json(rate_limit_stats(&state))
+}
@@ -1,64 +1,196 @@
 use auth_service::{
-  application::handlers::AuthHandler,
-  domain::AuthUser,
-  infrastructure::{NonceRepositoryImpl, SignatureVerifierImpl, UserRepositoryImpl},
+  application::handlers::{
+    AuthHandler, EmailAuthHandler, OAuthHandler, ProviderHandler, SessionHandler,
+  },
+  domain::{AuthUser, ClientInfo},
+  infrastructure::{NonceRepositoryImpl, RefreshTokenRepositoryImpl, UserRepositoryImpl},
   models::{
-    NonceRequest, NonceResponse, RefreshRequest, RefreshResponse, UserInfo, VerifyRequest,
-    VerifyResponse,
+    ConfirmEmailLinkRequest, LinkEmailRequest, LinkEmailResponse, LinkProviderResponse,
+    LinkWalletRequest, LoginRequest, LoginResponse, NonceRequest, NonceResponse,
+    OAuthCallbackQuery, OAuthCallbackResponse, OAuthStartQuery, OAuthStartResponse,
+    ProviderInfo, RefreshRequest, RefreshResponse, RegisterRequest, RevokeSessionsResponse,
+    SessionInfo, UnlinkProviderResponse, UserInfo, VerifyRequest,
   },
 };
 use axum::{
   Router,
-  extract::{Extension, Json, State},
+  extract::{Extension, Json, Path, Query, State},
+  middleware,
   response::Json as ResponseJson,
-  routing::{get, post},
+  routing::{delete, get, post},
 };
 use jd_core::AppState;
+use uuid::Uuid;
+
+use crate::middleware::mw_auth_rbac::{AuthContext, mw_require_auth};
+
+type Handler = AuthHandler<NonceRepositoryImpl, UserRepositoryImpl, RefreshTokenRepositoryImpl>;
+
+pub fn auth_router(app_state: AppState) -> Router<AppState> {
+  let authenticated_routes = Router::new()
+    .route("/sessions", get(list_sessions))
+    .route("/sessions/{session_id}", delete(revoke_session))
+    .route("/logout", post(logout))
+    .route("/logout-all", post(logout_all))
+    .route("/providers", get(list_providers))
+    .route("/providers/wallet", post(link_wallet))
+    .route("/providers/email", post(link_email))
+    .route("/providers/email/confirm", post(confirm_email_link))
+    .route("/providers/oauth/{provider}/start", get(link_oauth_start))
+    .route("/providers/{provider_id}", delete(unlink_provider))
+    .route_layer(middleware::from_fn_with_state(app_state, mw_require_auth));
 
-pub fn auth_router() -> Router<AppState> {
   Router::new()
     .route("/nonce", post(generate_nonce))
     .route("/verify", post(verify_signature))
     .route("/refresh", post(refresh_token))
     .route("/me", get(get_current_user))
+    .route("/register", post(register))
+    .route("/login", post(login))
+    .route("/oauth/{provider}/start", get(oauth_start))
+    .route("/oauth/{provider}/callback", get(oauth_callback))
+    .merge(authenticated_routes)
 }
 
 async fn generate_nonce(
   State(state): State<AppState>,
   Json(request): Json<NonceRequest>,
 ) -> auth_service::Result<ResponseJson<NonceResponse>> {
-  AuthHandler::<NonceRepositoryImpl, UserRepositoryImpl, SignatureVerifierImpl>::generate_nonce(
-    State(state),
-    Json(request),
-  )
-  .await
+  Handler::generate_nonce(State(state), Json(request)).await
 }
 
 async fn verify_signature(
   State(state): State<AppState>,
+  client_info: ClientInfo,
   Json(request): Json<VerifyRequest>,
-) -> auth_service::Result<ResponseJson<VerifyResponse>> {
-  AuthHandler::<NonceRepositoryImpl, UserRepositoryImpl, SignatureVerifierImpl>::verify_signature(
-    State(state),
-    Json(request),
-  )
-  .await
+) -> auth_service::Result<ResponseJson<LoginResponse>> {
+  Handler::verify_signature(State(state), client_info, Json(request)).await
 }
 
 async fn refresh_token(
   State(state): State<AppState>,
   Json(request): Json<RefreshRequest>,
 ) -> auth_service::Result<ResponseJson<RefreshResponse>> {
-  AuthHandler::<NonceRepositoryImpl, UserRepositoryImpl, SignatureVerifierImpl>::refresh_token(
-    State(state),
-    Json(request),
-  )
-  .await
+  Handler::refresh_token(State(state), Json(request)).await
+}
+
+pub(crate) async fn jwks(
+  State(state): State<AppState>,
+) -> auth_service::Result<impl axum::response::IntoResponse> {
+  Handler::jwks(State(state)).await
 }
 
 async fn get_current_user(Extension(user): Extension<AuthUser>) -> ResponseJson<UserInfo> {
-  AuthHandler::<NonceRepositoryImpl, UserRepositoryImpl, SignatureVerifierImpl>::get_current_user(
-    Extension(user),
-  )
-  .await
+  Handler::get_current_user(Extension(user)).await
+}
+
+async fn register(
+  State(state): State<AppState>,
+  client_info: ClientInfo,
+  Json(request): Json<RegisterRequest>,
+) -> auth_service::Result<ResponseJson<LoginResponse>> {
+  EmailAuthHandler::register(State(state), client_info, Json(request)).await
+}
+
+async fn login(
+  State(state): State<AppState>,
+  client_info: ClientInfo,
+  Json(request): Json<LoginRequest>,
+) -> auth_service::Result<ResponseJson<LoginResponse>> {
+  EmailAuthHandler::login(State(state), client_info, Json(request)).await
+}
+
+async fn oauth_start(
+  State(state): State<AppState>,
+  Path(provider): Path<String>,
+  Query(query): Query<OAuthStartQuery>,
+) -> auth_service::Result<ResponseJson<OAuthStartResponse>> {
+  OAuthHandler::start(State(state), Path(provider), Query(query)).await
+}
+
+async fn oauth_callback(
+  State(state): State<AppState>,
+  Path(provider): Path<String>,
+  client_info: ClientInfo,
+  Query(query): Query<OAuthCallbackQuery>,
+) -> auth_service::Result<ResponseJson<OAuthCallbackResponse>> {
+  OAuthHandler::callback(State(state), Path(provider), client_info, Query(query)).await
+}
+
+async fn list_sessions(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+) -> auth_service::Result<ResponseJson<Vec<SessionInfo>>> {
+  SessionHandler::list_sessions(State(state), auth.user_id, auth.session_id).await
+}
+
+async fn revoke_session(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Path(session_id): Path<Uuid>,
+) -> auth_service::Result<ResponseJson<RevokeSessionsResponse>> {
+  SessionHandler::revoke_session(State(state), auth.user_id, session_id).await
+}
+
+async fn logout(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+) -> auth_service::Result<ResponseJson<RevokeSessionsResponse>> {
+  let session_id = auth.session_id.ok_or_else(auth_service::Error::session_not_found)?;
+  SessionHandler::logout(State(state), auth.user_id, session_id).await
+}
+
+async fn logout_all(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+) -> auth_service::Result<ResponseJson<RevokeSessionsResponse>> {
+  SessionHandler::logout_all(State(state), auth.user_id).await
+}
+
+async fn list_providers(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+) -> auth_service::Result<ResponseJson<Vec<ProviderInfo>>> {
+  ProviderHandler::list_providers(State(state), auth.user_id).await
+}
+
+async fn link_wallet(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Json(request): Json<LinkWalletRequest>,
+) -> auth_service::Result<ResponseJson<LinkProviderResponse>> {
+  ProviderHandler::link_wallet(State(state), auth.user_id, Json(request)).await
+}
+
+async fn link_email(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Json(request): Json<LinkEmailRequest>,
+) -> auth_service::Result<ResponseJson<LinkEmailResponse>> {
+  ProviderHandler::link_email(State(state), auth.user_id, Json(request)).await
+}
+
+async fn confirm_email_link(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Json(request): Json<ConfirmEmailLinkRequest>,
+) -> auth_service::Result<ResponseJson<LinkProviderResponse>> {
+  ProviderHandler::confirm_email_link(State(state), auth.user_id, Json(request)).await
+}
+
+async fn link_oauth_start(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Path(provider): Path<String>,
+  Query(query): Query<OAuthStartQuery>,
+) -> auth_service::Result<ResponseJson<OAuthStartResponse>> {
+  ProviderHandler::link_oauth_start(State(state), auth.user_id, Path(provider), Query(query)).await
+}
+
+async fn unlink_provider(
+  State(state): State<AppState>,
+  Extension(auth): Extension<AuthContext>,
+  Path(provider_id): Path<Uuid>,
+) -> auth_service::Result<ResponseJson<UnlinkProviderResponse>> {
+  ProviderHandler::unlink_provider(State(state), auth.user_id, provider_id).await
 }
@@ -48,7 +48,7 @@ pub enum Error {
   InsufficientPermissions { resource: String },
 
   #[error("Rate limit exceeded for client '{client_id}': {limit} requests per {window}")]
-  RateLimitExceeded { client_id: String, limit: u32, window: String },
+  RateLimitExceeded { client_id: String, limit: u32, window: String, retry_after: u32 },
 
   // -- Service Communication
   #[error("Downstream service '{service}' unavailable")]
@@ -165,11 +165,14 @@ impl Clone for Error {
       Self::InsufficientPermissions { resource } => {
         Self::InsufficientPermissions { resource: resource.clone() }
       }
-      Self::RateLimitExceeded { client_id, limit, window } => Self::RateLimitExceeded {
-        client_id: client_id.clone(),
-        limit: *limit,
-        window: window.clone(),
-      },
+      Self::RateLimitExceeded { client_id, limit, window, retry_after } => {
+        Self::RateLimitExceeded {
+          client_id: client_id.clone(),
+          limit: *limit,
+          window: window.clone(),
+          retry_after: *retry_after,
+        }
+      }
       Self::ServiceUnavailable { service } => Self::ServiceUnavailable { service: service.clone() },
       Self::ServiceTimeout { service, timeout_ms } => {
         Self::ServiceTimeout { service: service.clone(), timeout_ms: *timeout_ms }
@@ -287,8 +290,19 @@ impl Error {
     Self::InsufficientPermissions { resource: resource.into() }
   }
 
-  pub fn rate_limited(client_id: impl Into<String>, limit: u32, window: impl Into<String>) -> Self {
-    Self::RateLimitExceeded { client_id: client_id.into(), limit, window: window.into() }
+  /// `retry_after` is the number of seconds until the client's next request fits the window
+  pub fn rate_limited(
+    client_id: impl Into<String>,
+    limit: u32,
+    window: impl Into<String>,
+    retry_after: u32,
+  ) -> Self {
+    Self::RateLimitExceeded {
+      client_id: client_id.into(),
+      limit,
+      window: window.into(),
+      retry_after,
+    }
   }
 
   pub fn service_unavailable(service: impl Into<String>) -> Self {
@@ -432,7 +446,7 @@ impl Error {
 
   pub fn retry_after_seconds(&self) -> Option<u32> {
     match self {
-      Self::RateLimitExceeded { .. } => Some(60),
+      Self::RateLimitExceeded { retry_after, .. } => Some(*retry_after),
       Self::ServiceUnavailable { .. } => Some(30),
       Self::CircuitBreakerOpen { .. } => Some(120),
       Self::DatabasePoolExhausted => Some(5),
@@ -446,6 +460,14 @@ impl Error {
     request_context: &RequestContext,
   ) -> (StatusCode, ClientError) {
     let (status_code, error_code, message, details) = match self {
+      // The token could not be checked, retrying may succeed
+      Self::CtxExt(middleware::mw_auth::CtxExtError::ModelAccessError(_)) => (
+        StatusCode::SERVICE_UNAVAILABLE,
+        "AUTHENTICATION_UNAVAILABLE",
+        "Authentication is temporarily unavailable".to_string(),
+        None,
+      ),
+
       // Authentication Errors (401)
       Self::CtxExt(_) => (
         StatusCode::UNAUTHORIZED,
@@ -514,14 +536,15 @@ impl Error {
       ),
 
       // Rate Limiting (429)
-      Self::RateLimitExceeded { client_id, limit, window } => (
+      Self::RateLimitExceeded { client_id, limit, window, retry_after } => (
         StatusCode::TOO_MANY_REQUESTS,
         "RATE_LIMIT_EXCEEDED",
         "Rate limit exceeded".to_string(),
         Some(serde_json::json!({
             "client_id": client_id,
             "limit": limit,
-            "window": window
+            "window": window,
+            "retry_after": retry_after
         })),
       ),
 
@@ -833,6 +856,29 @@ impl From<reqwest::Error> for Error {
   }
 }
 
+/// Fullnode failures surface as service errors, `sui/<method>` names the tripped circuit
+impl From<sui_service::Error> for Error {
+  fn from(err: sui_service::Error) -> Self {
+    match err {
+      sui_service::Error::InvalidRequest(message) => Self::invalid_request(message),
+      sui_service::Error::Timeout { endpoint, timeout_ms } => {
+        Self::service_timeout(format!("sui/{endpoint}"), timeout_ms)
+      }
+      sui_service::Error::CircuitOpen { endpoint } => {
+        Self::circuit_breaker_open(format!("sui/{endpoint}"))
+      }
+      sui_service::Error::SuiClient(reason) => {
+        warn!("Sui node call failed: {}", reason);
+        Self::service_unavailable("sui")
+      }
+      sui_service::Error::PolicyViolation { rule, .. } => {
+        Self::SecurityPolicyViolation { policy: rule.to_string() }
+      }
+      err => Self::service_error("sui", 500, Some(err.to_string())),
+    }
+  }
+}
+
 impl From<redis::RedisError> for Error {
   fn from(err: redis::RedisError) -> Self {
     Self::RedisConnectionFailed { source: Box::new(err) }
@@ -1,8 +1,9 @@
 use auth::auth_router;
-use axum::Router;
+use axum::{Router, routing::get};
 use jd_core::AppState;
 use users::user_router;
 
+mod admin;
 mod auth;
 mod error;
 mod log;
@@ -14,15 +15,16 @@ mod users;
 pub type Result<T> = std::result::Result<T, error::Error>;
 
 pub fn v1_routes(app_state: AppState) -> Router {
-  let mm = app_state.mm.as_ref().clone();
   Router::new()
     .nest(
       "/api/v1",
       Router::new()
         .nest("/users", user_router())
-        .nest("/sui", sui::sui_router())
-        .nest("/auth", auth_router()),
+        .nest("/sui", sui::sui_router(app_state.clone()))
+        .nest("/auth", auth_router(app_state.clone()))
+        .nest("/admin", admin::admin_router(app_state.clone())),
     )
-    .nest("/api", routes_rpc::routes(mm))
+    .nest("/api", routes_rpc::routes())
+    .route("/.well-known/jwks.json", get(auth::jwks))
     .with_state(app_state)
 }
@@ -9,6 +9,7 @@ use serde_with::skip_serializing_none;
 use std::collections::HashMap;
 use time::Duration;
 use tracing::info;
+use uuid::Uuid;
 
 // List of sensitive fields that should be masked
 const SENSITIVE_FIELDS: &[&str] = &[
@@ -221,7 +222,7 @@ struct RequestContext {
   query: Option<Value>,
   headers: Option<Value>,
   body: Option<Value>,
-  user_id: Option<i64>,
+  user_id: Option<Uuid>,
 }
 
 #[skip_serializing_none]
@@ -1,5 +1,7 @@
+pub mod mw_api_key;
 pub mod mw_auth;
 pub mod mw_auth_rbac;
+pub mod mw_rate_limit;
 pub mod mw_request_context;
 pub mod mw_res_map;
 pub mod mw_res_timestamp;
@@ -0,0 +1,87 @@
+use auth_service::application::use_cases::AuthenticateApiKeyUseCase;
+use auth_service::infrastructure::{ApiKeyRepositoryImpl, PermissionRepositoryImpl};
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{HeaderName, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use jd_core::{AppState, ctx::Ctx};
+use tracing::{debug, error};
+
+use crate::Result;
+use crate::error::Error;
+use crate::middleware::mw_auth::insert_ctx;
+use crate::middleware::mw_auth_rbac::AuthContext;
+use crate::middleware::mw_rate_limit::ApiKeyClient;
+
+pub const X_API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Authenticates server-to-server callers sending `X-Api-Key`. The key's owner becomes the
+/// caller, with an `AuthContext` and a `Ctx` limited to the key's scopes. Requests without the
+/// header pass untouched, an invalid key is rejected with `API_KEY_INVALID`.
+/// Runs outside the rate limiter so requests are counted against the key.
+pub async fn mw_api_key_resolve(
+  State(app_state): State<AppState>,
+  mut req: Request<Body>,
+  next: Next,
+) -> Result<Response> {
+  let Some(key) = req.headers().get(X_API_KEY) else {
+    return Ok(next.run(req).await);
+  };
+  let key = key
+    .to_str()
+    .map_err(|_| Error::api_key_failed("API key is not valid text"))?;
+
+  let use_case = AuthenticateApiKeyUseCase::new(
+    ApiKeyRepositoryImpl::new(app_state.clone()),
+    PermissionRepositoryImpl::new(app_state.clone()),
+  );
+  let authorized = use_case.execute(key).await.map_err(api_key_error)?;
+
+  let client = ApiKeyClient {
+    key_id: authorized.api_key.key_id,
+    rate_limit: authorized
+      .api_key
+      .rate_limit
+      .and_then(|limit| u32::try_from(limit).ok()),
+  };
+  let auth_context = AuthContext::from(authorized);
+  // Permissions were narrowed to the key's scopes that the owner still holds
+  let scopes = auth_context.permissions.iter().map(|p| p.permission_name.clone()).collect();
+  let ctx = Ctx::new(auth_context.user_id, auth_context.role.to_string(), None)
+    .map_err(|e| Error::api_key_failed(e.to_string()))?
+    .with_scopes(scopes);
+
+  insert_ctx(req.extensions_mut(), ctx);
+  req.extensions_mut().insert(client);
+  req.extensions_mut().insert(auth_context);
+
+  Ok(next.run(req).await)
+}
+
+/// Key and account problems are the caller's fault, storage failures are ours
+fn api_key_error(err: auth_service::Error) -> Error {
+  if err.is_infrastructure() {
+    error!("Failed to authenticate API key: {}", err);
+    return Error::service_unavailable("auth");
+  }
+
+  debug!("Rejected API key: {}", err);
+  Error::api_key_failed(err.error)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn storage_failures_are_not_blamed_on_the_key() {
+    let err = api_key_error(auth_service::Error::database_error("pool closed"));
+    assert!(matches!(err, Error::ServiceUnavailable { .. }));
+
+    match api_key_error(auth_service::Error::api_key_revoked()) {
+      Error::ApiKeyAuthFailed { reason } => assert_eq!(reason, "API key has been revoked"),
+      other => panic!("expected an API key failure, got {:?}", other),
+    }
+  }
+}
@@ -1,43 +1,88 @@
 use crate::Result;
 use crate::error::Error;
+use auth_service::application::use_cases::AuthorizeRequestUseCase;
+use auth_service::domain::JwtManager;
+use auth_service::infrastructure::{PermissionRepositoryImpl, SessionRepositoryImpl};
 use axum::body::Body;
 use axum::extract::{FromRequestParts, State};
-use axum::http::Request;
+use axum::http::{Extensions, Request};
+use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
 use axum::middleware::Next;
 use axum::response::Response;
 use jd_core::{AppState, ctx::Ctx};
 use serde::Serialize;
 use tower_cookies::Cookies;
+use tracing::{debug, error};
+
+/// Cookie holding the access token for browser clients that cannot set headers
+pub const AUTH_TOKEN_COOKIE: &str = "auth-token";
 
-#[allow(dead_code)] // For now, until we have the rpc.
 pub async fn mw_ctx_require(ctx: Result<CtxW>, req: Request<Body>, next: Next) -> Result<Response> {
   ctx?;
 
   Ok(next.run(req).await)
 }
 
-#[allow(unused_variables, unused_mut)] // For now, until we have the rpc.
+/// Resolves the caller from the bearer token, or the auth cookie when no header is sent.
+/// Never rejects, handlers that need a user extract `CtxW` and get the resolution error.
 pub async fn mw_ctx_resolve(
   State(app_state): State<AppState>,
   cookies: Cookies,
   mut req: Request<Body>,
   next: Next,
 ) -> Result<Response> {
-  let ctx_ext_result = ctx_resolve(app_state, &cookies).await;
+  let ctx_ext_result = ctx_resolve(&app_state, &req, &cookies).await;
 
-  // Add context to request extensions
-  if let Ok(ctx_w) = ctx_ext_result {
-    req.extensions_mut().insert(ctx_w.0);
+  if let Ok(CtxW(ctx)) = &ctx_ext_result {
+    // Plain `Ctx` for the rpc_router handlers, which extract it directly
+    req.extensions_mut().insert(ctx.clone());
   }
+  req.extensions_mut().insert(ctx_ext_result);
 
   Ok(next.run(req).await)
 }
 
-async fn ctx_resolve(_app_state: AppState, _cookies: &Cookies) -> CtxExtResult {
-  Ctx::new(0i64)
+/// Sets the caller for both the `CtxW` extractor and handlers that take a plain `Ctx`,
+/// replacing what `mw_ctx_resolve` found
+pub(crate) fn insert_ctx(extensions: &mut Extensions, ctx: Ctx) {
+  extensions.insert(ctx.clone());
+  extensions.insert::<CtxExtResult>(Ok(CtxW(ctx)));
+}
+
+async fn ctx_resolve(app_state: &AppState, req: &Request<Body>, cookies: &Cookies) -> CtxExtResult {
+  let token = match req.headers().get(AUTHORIZATION) {
+    Some(header) => header
+      .to_str()
+      .ok()
+      .and_then(|value| value.strip_prefix("Bearer "))
+      .ok_or(CtxExtError::TokenWrongFormat)?
+      .to_string(),
+    None => cookies
+      .get(AUTH_TOKEN_COOKIE)
+      .map(|cookie| cookie.value().to_string())
+      .ok_or(CtxExtError::TokenNotFound)?,
+  };
+
+  let use_case = AuthorizeRequestUseCase::new(
+    PermissionRepositoryImpl::new(app_state.clone()),
+    SessionRepositoryImpl::new(app_state.clone()),
+    JwtManager::from_state(app_state),
+  );
+
+  let authorized = use_case.execute(&token).await.map_err(|e| {
+    let ext_error = CtxExtError::from(&e);
+    if matches!(ext_error, CtxExtError::ModelAccessError(_)) {
+      error!("Failed to resolve ctx: {}", e);
+    } else {
+      debug!("Rejected ctx token: {}", e);
+    }
+    ext_error
+  })?;
+
+  Ctx::new(authorized.user.user_id, authorized.user.role.to_string(), authorized.session_id)
     .map(CtxW)
-    .map_err(|_| CtxExtError::CtxCreateFail("error".to_string()))
+    .map_err(|e| CtxExtError::CtxCreateFail(e.to_string()))
 }
 
 // region:    --- Ctx Extractor
@@ -63,10 +108,12 @@ type CtxExtResult = core::result::Result<CtxW, CtxExtError>;
 
 #[derive(Clone, Serialize, Debug)]
 pub enum CtxExtError {
-  TokenNotInCookie,
+  TokenNotFound,
   TokenWrongFormat,
 
   UserNotFound,
+  UserDisabled,
+  SessionRevoked,
   ModelAccessError(String),
   FailValidate,
   CannotSetTokenCookie,
@@ -75,12 +122,27 @@ pub enum CtxExtError {
   CtxCreateFail(String),
 }
 
+/// Token and account problems are the caller's fault, storage failures are ours
+impl From<&auth_service::Error> for CtxExtError {
+  fn from(err: &auth_service::Error) -> Self {
+    match err.code.as_str() {
+      "USER_NOT_FOUND" => Self::UserNotFound,
+      "ACCOUNT_DISABLED" => Self::UserDisabled,
+      "SESSION_REVOKED" => Self::SessionRevoked,
+      _ if err.is_infrastructure() => Self::ModelAccessError(err.error.clone()),
+      _ => Self::FailValidate,
+    }
+  }
+}
+
 impl std::fmt::Display for CtxExtError {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
-      Self::TokenNotInCookie => write!(f, "Token not found in cookie"),
+      Self::TokenNotFound => write!(f, "Token not found in Authorization header or cookie"),
       Self::TokenWrongFormat => write!(f, "Token has wrong format"),
       Self::UserNotFound => write!(f, "User not found"),
+      Self::UserDisabled => write!(f, "User account is disabled"),
+      Self::SessionRevoked => write!(f, "Session has been revoked"),
       Self::ModelAccessError(msg) => write!(f, "Model access error: {}", msg),
       Self::FailValidate => write!(f, "Validation failed"),
       Self::CannotSetTokenCookie => write!(f, "Cannot set token cookie"),
@@ -92,3 +154,31 @@ impl std::fmt::Display for CtxExtError {
 
 impl std::error::Error for CtxExtError {}
 // endregion: --- Ctx Extractor Result/Error
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn storage_failures_are_not_blamed_on_the_token() {
+    let err = CtxExtError::from(&auth_service::Error::database_error("pool closed"));
+
+    assert!(matches!(err, CtxExtError::ModelAccessError(_)));
+  }
+
+  #[test]
+  fn token_problems_map_to_client_errors() {
+    assert!(matches!(
+      CtxExtError::from(&auth_service::Error::session_revoked()),
+      CtxExtError::SessionRevoked
+    ));
+    assert!(matches!(
+      CtxExtError::from(&auth_service::Error::account_disabled()),
+      CtxExtError::UserDisabled
+    ));
+    assert!(matches!(
+      CtxExtError::from(&auth_service::Error::invalid_token()),
+      CtxExtError::FailValidate
+    ));
+  }
+}
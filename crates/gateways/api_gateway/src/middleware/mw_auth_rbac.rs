@@ -5,10 +5,14 @@ use axum::{
     response::Response,
 };
 use serde::{Deserialize, Serialize};
-// use std::collections::HashMap; // For future use
+use tracing::{debug, error};
 
-use jd_core::ctx::Ctx;
-use auth_service::domain::{UserRole, UserPermission};
+use auth_service::application::use_cases::{
+    AuthorizeRequestUseCase, AuthorizedApiKey, AuthorizedUser,
+};
+use auth_service::domain::{JwtManager, UserRole, UserPermission};
+use auth_service::infrastructure::{PermissionRepositoryImpl, SessionRepositoryImpl};
+use jd_core::AppState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthContext {
@@ -19,6 +23,31 @@ pub struct AuthContext {
     pub permissions: Vec<UserPermission>,
     pub is_active: bool,
     pub session_id: Option<uuid::Uuid>,
+    /// Set when the caller authenticated with an API key instead of a bearer token
+    pub api_key_id: Option<uuid::Uuid>,
+}
+
+impl From<AuthorizedUser> for AuthContext {
+    fn from(authorized: AuthorizedUser) -> Self {
+        let AuthorizedUser { user, permissions, session_id } = authorized;
+        Self {
+            user_id: user.user_id,
+            username: user.username,
+            email: user.email,
+            role: user.role,
+            permissions,
+            is_active: user.is_active,
+            session_id,
+            api_key_id: None,
+        }
+    }
+}
+
+impl From<AuthorizedApiKey> for AuthContext {
+    fn from(authorized: AuthorizedApiKey) -> Self {
+        let api_key_id = authorized.api_key.key_id;
+        Self { api_key_id: Some(api_key_id), ..Self::from(authorized.user) }
+    }
 }
 
 impl AuthContext {
@@ -34,26 +63,44 @@ impl AuthContext {
             .any(|p| p.resource == resource && p.action == action)
     }
 
+    /// API keys never pass staff checks, whatever the owner's role. Staff routes, key
+    /// management included, need a signed in moderator or administrator.
     pub fn has_role(&self, required_role: UserRole) -> bool {
+        if required_role.is_staff() && self.is_api_key() {
+            return false;
+        }
         self.role.can_access(required_role)
     }
 
     pub fn is_admin(&self) -> bool {
-        self.role.is_admin()
+        self.role.is_admin() && !self.is_api_key()
     }
 
     pub fn is_staff(&self) -> bool {
-        self.role.is_staff()
+        self.role.is_staff() && !self.is_api_key()
+    }
+
+    pub fn is_api_key(&self) -> bool {
+        self.api_key_id.is_some()
+    }
+
+    /// API keys only reach what their scopes name, signed in users are not limited by scopes
+    pub fn has_scope(&self, scope: &str) -> bool {
+        !self.is_api_key() || self.has_permission(scope)
     }
 }
 
 pub async fn mw_require_auth(
-    State(ctx): State<Ctx>,
+    State(state): State<AppState>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let auth_context = extract_auth_context(&ctx, &req).await?;
-    
+    let auth_context = match req.extensions().get::<AuthContext>() {
+        // Already resolved from an `X-Api-Key` header
+        Some(auth_context) => auth_context.clone(),
+        None => extract_auth_context(&state, &req).await?,
+    };
+
     if !auth_context.is_active {
         return Err(StatusCode::UNAUTHORIZED);
     }
@@ -99,6 +146,24 @@ pub fn require_permission(permission: &'static str) -> impl Fn(Request, Next) ->
     }
 }
 
+/// Lets API keys through only with `scope`, see `AuthContext::has_scope`
+pub fn require_scope(scope: &'static str) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, StatusCode>> + Send>> + Clone {
+    move |req: Request, next: Next| {
+        Box::pin(async move {
+            let auth_context = req
+                .extensions()
+                .get::<AuthContext>()
+                .ok_or(StatusCode::UNAUTHORIZED)?;
+
+            if !auth_context.has_scope(scope) {
+                return Err(StatusCode::FORBIDDEN);
+            }
+
+            Ok(next.run(req).await)
+        })
+    }
+}
+
 pub fn require_resource_access(resource: &'static str, action: &'static str) -> impl Fn(Request, Next) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, StatusCode>> + Send>> + Clone {
     move |req: Request, next: Next| {
         Box::pin(async move {
@@ -116,7 +181,7 @@ pub fn require_resource_access(resource: &'static str, action: &'static str) ->
     }
 }
 
-async fn extract_auth_context(ctx: &Ctx, req: &Request) -> Result<AuthContext, StatusCode> {
+async fn extract_auth_context(state: &AppState, req: &Request) -> Result<AuthContext, StatusCode> {
     // Extract JWT token from Authorization header
     let auth_header = req
         .headers()
@@ -130,24 +195,36 @@ async fn extract_auth_context(ctx: &Ctx, req: &Request) -> Result<AuthContext, S
 
     let token = &auth_header[7..]; // Remove "Bearer " prefix
 
-    // Validate JWT and extract user info
-    // This would use your JWT validation logic from auth_service
-    validate_jwt_and_get_context(ctx, token).await
+    validate_jwt_and_get_context(state, token).await
 }
 
-async fn validate_jwt_and_get_context(_ctx: &Ctx, _token: &str) -> Result<AuthContext, StatusCode> {
-    // This is a placeholder - you would implement the actual JWT validation
-    // and database lookup here using your auth_service
-    
-    // Example implementation outline:
-    // 1. Validate JWT signature and claims
-    // 2. Extract user_id from JWT
-    // 3. Query database for user and permissions
-    // 4. Build AuthContext
-    
-    // For now, return a mock context for testing
-    // TODO: Implement actual JWT validation
-    Err(StatusCode::UNAUTHORIZED)
+async fn validate_jwt_and_get_context(state: &AppState, token: &str) -> Result<AuthContext, StatusCode> {
+    let use_case = AuthorizeRequestUseCase::new(
+        PermissionRepositoryImpl::new(state.clone()),
+        SessionRepositoryImpl::new(state.clone()),
+        JwtManager::from_state(state),
+    );
+
+    let authorized = use_case.execute(token).await.map_err(|e| {
+        let status = auth_error_status(&e);
+        if status == StatusCode::INTERNAL_SERVER_ERROR {
+            error!("Failed to resolve auth context: {}", e);
+        } else {
+            debug!("Rejected bearer token: {}", e);
+        }
+        status
+    })?;
+
+    Ok(AuthContext::from(authorized))
+}
+
+/// Token and account problems are the caller's fault, storage failures are ours
+fn auth_error_status(err: &auth_service::Error) -> StatusCode {
+    if err.is_infrastructure() {
+        StatusCode::INTERNAL_SERVER_ERROR
+    } else {
+        StatusCode::UNAUTHORIZED
+    }
 }
 
 // Convenience macros for common role checks
@@ -204,11 +281,54 @@ fn protected_routes() -> Router {
         .layer(middleware::from_fn(require_permission("users.read.own")))
         .route("/content-write", get(content_write_handler))
         .layer(middleware::from_fn(require_resource_access("content", "write")))
-        .layer(middleware::from_fn_with_state(ctx.clone(), mw_require_auth))
+        .layer(middleware::from_fn_with_state(app_state.clone(), mw_require_auth))
 }
 
 async fn admin_handler(req: Request) -> impl IntoResponse {
     let auth = req.require_auth_context().unwrap();
     format!("Hello Admin {}!", auth.username)
 }
-*/
\ No newline at end of file
+*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(role: UserRole, api_key_id: Option<uuid::Uuid>) -> AuthContext {
+        AuthContext {
+            user_id: uuid::Uuid::new_v4(),
+            username: "partner".to_string(),
+            email: None,
+            role,
+            permissions: vec![],
+            is_active: true,
+            session_id: None,
+            api_key_id,
+        }
+    }
+
+    #[test]
+    fn api_keys_never_pass_staff_checks() {
+        let key = context(UserRole::Admin, Some(uuid::Uuid::new_v4()));
+
+        assert!(!key.has_role(UserRole::Admin));
+        assert!(!key.has_role(UserRole::Moderator));
+        assert!(key.has_role(UserRole::Vip));
+        assert!(!key.is_admin());
+
+        assert!(context(UserRole::Admin, None).has_role(UserRole::Admin));
+    }
+
+    #[test]
+    fn api_keys_are_limited_to_their_scopes() {
+        let mut key = context(UserRole::Vip, Some(uuid::Uuid::new_v4()));
+        key.permissions = vec![UserPermission {
+            permission_name: "sui.read".to_string(),
+            resource: "sui".to_string(),
+            action: "read".to_string(),
+        }];
+
+        assert!(key.has_scope("sui.read"));
+        assert!(!key.has_scope("sui.sponsor"));
+        assert!(context(UserRole::Normal, None).has_scope("sui.sponsor"));
+    }
+}
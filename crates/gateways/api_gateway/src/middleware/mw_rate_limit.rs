@@ -0,0 +1,240 @@
+use axum::{
+  extract::{ConnectInfo, Request, State},
+  http::{HeaderMap, HeaderName, HeaderValue},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use jd_core::{AppState, client_ip::TrustedProxies, ctx::Ctx};
+use serde::Serialize;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::middleware::mw_request_context::extract_client_ip;
+
+pub const X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+pub const X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+pub const X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Sliding window log, one sorted set entry per counted request scored by its time in ms of
+/// Redis `TIME`. KEYS: client window. ARGV: window ms, limit, request id.
+/// Returns whether the request was counted, the requests left and the ms until the oldest
+/// counted request leaves the window.
+const SLIDING_WINDOW: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window, limit = tonumber(ARGV[1]), tonumber(ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+local count = redis.call('ZCARD', KEYS[1])
+local allowed = 0
+if count < limit then
+  redis.call('ZADD', KEYS[1], now, ARGV[3])
+  redis.call('PEXPIRE', KEYS[1], window)
+  count = count + 1
+  allowed = 1
+end
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+local reset = window
+if oldest[2] then
+  reset = tonumber(oldest[2]) + window - now
+end
+return { allowed, limit - count, reset }
+"#;
+
+/// Requests the limiter could not count since the process started, Redis being unreachable
+static REDIS_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Client counted by its API key instead of its user or IP address. Inserted by the API key
+/// authentication, which runs ahead of the limiter.
+#[derive(Debug, Clone)]
+pub struct ApiKeyClient {
+  pub key_id: Uuid,
+  /// Replaces the limit of the route group and role when set
+  pub rate_limit: Option<u32>,
+}
+
+/// Limiter health on this instance
+#[derive(Debug, Serialize)]
+pub struct RateLimitStats {
+  pub fail_open: bool,
+  /// Requests that could not be counted since the instance started
+  pub redis_failures: u64,
+}
+
+struct Client {
+  id: String,
+  role: Option<String>,
+  limit: Option<u32>,
+}
+
+struct Window {
+  allowed: bool,
+  remaining: u32,
+  reset_ms: u64,
+}
+
+impl Window {
+  fn reset_secs(&self) -> u32 {
+    self.reset_ms.div_ceil(1000).max(1) as u32
+  }
+}
+
+/// Counts the request against the limit of its route group and rejects it with
+/// `RATE_LIMIT_EXCEEDED` once the window is full. Every response carries the `X-RateLimit-*`
+/// headers. While Redis is unreachable requests pass uncounted, or are rejected with `503`
+/// when `fail_open` is off.
+pub async fn mw_rate_limit(
+  State(app_state): State<AppState>,
+  req: Request,
+  next: Next,
+) -> Response {
+  let config = &app_state.config.rate_limit;
+  if !config.enabled {
+    return next.run(req).await;
+  }
+
+  let (group, rule) = config.rule(route_group(req.uri().path()));
+  let client = client(&req, &app_state.trusted_proxies);
+  let limit = client.limit.unwrap_or_else(|| rule.limit_for(client.role.as_deref()));
+  let key = format!("rate_limit:gw:{}:{}", group, client.id);
+
+  let window = match count_request(&app_state, &key, limit, rule.window_secs).await {
+    Ok(window) => window,
+    Err(e) => {
+      let failures = REDIS_FAILURES.fetch_add(1, Ordering::Relaxed) + 1;
+      warn!(failures, fail_open = config.fail_open, "Rate limiting failed, Redis down: {}", e);
+      if !config.fail_open {
+        return Error::service_unavailable("rate_limiter").into_response();
+      }
+      return next.run(req).await;
+    }
+  };
+
+  let mut res = if window.allowed {
+    next.run(req).await
+  } else {
+    let window_label = format!("{}s", rule.window_secs);
+    Error::rate_limited(client.id, limit, window_label, window.reset_secs()).into_response()
+  };
+  set_rate_limit_headers(res.headers_mut(), limit, &window);
+  res
+}
+
+async fn count_request(
+  app_state: &AppState,
+  key: &str,
+  limit: u32,
+  window_secs: u64,
+) -> redis::RedisResult<Window> {
+  let mut conn = app_state.redis.get_multiplexed_async_connection().await?;
+  let (allowed, remaining, reset_ms): (u8, i64, i64) = redis::Script::new(SLIDING_WINDOW)
+    .key(key)
+    .arg(window_secs.saturating_mul(1000))
+    .arg(limit)
+    .arg(Uuid::new_v4().to_string())
+    .invoke_async(&mut conn)
+    .await?;
+
+  Ok(Window {
+    allowed: allowed == 1,
+    remaining: remaining.max(0) as u32,
+    reset_ms: reset_ms.max(0) as u64,
+  })
+}
+
+pub fn rate_limit_stats(app_state: &AppState) -> RateLimitStats {
+  RateLimitStats {
+    fail_open: app_state.config.rate_limit.fail_open,
+    redis_failures: REDIS_FAILURES.load(Ordering::Relaxed),
+  }
+}
+
+/// The validated API key, else the signed in user, else the IP address, with the role of a
+/// signed in caller. A client-chosen `X-Api-Key` alone never selects the window, neither do
+/// forwarded headers from peers outside the trusted proxies.
+fn client(req: &Request, trusted_proxies: &TrustedProxies) -> Client {
+  let ctx = req.extensions().get::<Ctx>();
+  let role = ctx.map(|ctx| ctx.role().to_string());
+
+  if let Some(api_key) = req.extensions().get::<ApiKeyClient>() {
+    return Client { id: format!("key:{}", api_key.key_id), role, limit: api_key.rate_limit };
+  }
+  if let Some(ctx) = ctx {
+    return Client { id: format!("user:{}", ctx.user_id()), role, limit: None };
+  }
+
+  let ip = req
+    .extensions()
+    .get::<ConnectInfo<SocketAddr>>()
+    .map(|ConnectInfo(addr)| extract_client_ip(trusted_proxies, req.headers(), *addr))
+    .unwrap_or_else(|| "unknown".to_string());
+  Client { id: format!("ip:{}", ip), role: None, limit: None }
+}
+
+/// First path segment after `/api/v1`, or after `/api` for the JSON-RPC endpoint
+fn route_group(path: &str) -> &str {
+  path
+    .strip_prefix("/api/v1/")
+    .or_else(|| path.strip_prefix("/api/"))
+    .and_then(|rest| rest.split('/').next())
+    .unwrap_or_default()
+}
+
+fn set_rate_limit_headers(headers: &mut HeaderMap, limit: u32, window: &Window) {
+  headers.insert(X_RATELIMIT_LIMIT, HeaderValue::from(limit));
+  headers.insert(X_RATELIMIT_REMAINING, HeaderValue::from(window.remaining));
+  headers.insert(X_RATELIMIT_RESET, HeaderValue::from(window.reset_secs()));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use jd_utils::config::{RateLimitConfig, RateLimitRule};
+  use std::collections::HashMap;
+
+  #[test]
+  fn route_groups_follow_the_api_prefix() {
+    assert_eq!(route_group("/api/v1/auth/login"), "auth");
+    assert_eq!(route_group("/api/v1/sui/sponsor/reserve"), "sui");
+    assert_eq!(route_group("/api/rpc"), "rpc");
+    assert_eq!(route_group("/.well-known/jwks.json"), "");
+  }
+
+  #[test]
+  fn ungrouped_routes_share_the_default_rule() {
+    let config = RateLimitConfig {
+      enabled: true,
+      fail_open: true,
+      default: RateLimitRule::default(),
+      groups: HashMap::from([(
+        "sui".to_string(),
+        RateLimitRule {
+          requests: 20,
+          window_secs: 60,
+          roles: HashMap::from([("vip".to_string(), 200)]),
+        },
+      )]),
+    };
+
+    let (group, rule) = config.rule("sui");
+    assert_eq!(group, "sui");
+    assert_eq!(rule.limit_for(Some("vip")), 200);
+    assert_eq!(rule.limit_for(Some("normal")), 20);
+    assert_eq!(rule.limit_for(None), 20);
+
+    let (group, rule) = config.rule("users");
+    assert_eq!(group, "default");
+    assert_eq!(rule.limit_for(Some("vip")), 300);
+  }
+
+  #[test]
+  fn reset_rounds_up_to_whole_seconds() {
+    let window = |reset_ms| Window { allowed: false, remaining: 0, reset_ms };
+
+    assert_eq!(window(0).reset_secs(), 1);
+    assert_eq!(window(1_001).reset_secs(), 2);
+    assert_eq!(window(60_000).reset_secs(), 60);
+  }
+}
@@ -1,23 +1,25 @@
 use axum::{
-  extract::{ConnectInfo, Request},
+  extract::{ConnectInfo, Request, State},
   http::HeaderMap,
   middleware::Next,
   response::Response,
 };
+use jd_core::{AppState, client_ip::TrustedProxies};
 use std::net::SocketAddr;
 use tracing::{info, instrument};
 
 use crate::error::RequestContext;
 
 /// Middleware to extract and setup RequestContext for the entire request lifecycle
-#[instrument(skip(req, next), fields(request_id, trace_id))]
+#[instrument(skip(app_state, req, next), fields(request_id, trace_id))]
 pub async fn mw_request_context(
+  State(app_state): State<AppState>,
   ConnectInfo(addr): ConnectInfo<SocketAddr>,
   mut req: Request,
   next: Next,
 ) -> Response {
   // Extract client IP
-  let client_ip = extract_client_ip(req.headers(), addr);
+  let client_ip = extract_client_ip(&app_state.trusted_proxies, req.headers(), addr);
 
   // Create RequestContext from headers and client info
   let mut context = RequestContext::from_headers(req.headers());
@@ -48,35 +50,14 @@ pub async fn mw_request_context(
   context.run_with_context(next.run(req)).await
 }
 
-/// Extract client IP from various headers with fallback to socket address
-fn extract_client_ip(headers: &HeaderMap, socket_addr: SocketAddr) -> String {
-  // Try various headers in order of preference
-  let possible_headers = [
-    "x-forwarded-for",     // Most common proxy header
-    "x-real-ip",           // Nginx
-    "cf-connecting-ip",    // Cloudflare
-    "x-cluster-client-ip", // GCP Load Balancer
-    "x-forwarded",         // General forwarded header
-    "forwarded-for",       // Alternative
-    "forwarded",           // RFC 7239
-  ];
-
-  for header_name in possible_headers {
-    if let Some(header_value) = headers.get(header_name) {
-      if let Ok(header_str) = header_value.to_str() {
-        // Handle comma-separated IPs (take the first one)
-        if let Some(first_ip) = header_str.split(',').next() {
-          let trimmed_ip = first_ip.trim();
-          if !trimmed_ip.is_empty() && trimmed_ip != "unknown" {
-            return trimmed_ip.to_string();
-          }
-        }
-      }
-    }
-  }
-
-  // Fallback to socket address
-  socket_addr.ip().to_string()
+/// Client IP behind the connection. Forwarded headers are only believed when the socket
+/// address is a trusted proxy, see `TrustedProxies`.
+pub fn extract_client_ip(
+  trusted_proxies: &TrustedProxies,
+  headers: &HeaderMap,
+  socket_addr: SocketAddr,
+) -> String {
+  trusted_proxies.client_ip(headers, socket_addr.ip()).to_string()
 }
 
 /// Helper to get RequestContext from request extensions
@@ -92,23 +73,35 @@ mod tests {
 
   #[test]
   fn test_extract_client_ip_from_headers() {
+    let trusted_proxies = TrustedProxies::parse("192.168.1.0/24, 70.41.3.18");
     let mut headers = HeaderMap::new();
     let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)), 8080);
 
     // Test x-forwarded-for header
     headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.195"));
-    assert_eq!(extract_client_ip(&headers, socket_addr), "203.0.113.195");
+    assert_eq!(extract_client_ip(&trusted_proxies, &headers, socket_addr), "203.0.113.195");
 
-    // Test comma-separated IPs (should take first)
+    // Test comma-separated IPs (the nearest hop that is not a trusted proxy)
     headers.insert(
       "x-forwarded-for",
-      HeaderValue::from_static("203.0.113.195, 70.41.3.18, 150.172.238.178"),
+      HeaderValue::from_static("203.0.113.195, 150.172.238.178, 70.41.3.18"),
     );
-    assert_eq!(extract_client_ip(&headers, socket_addr), "203.0.113.195");
+    assert_eq!(extract_client_ip(&trusted_proxies, &headers, socket_addr), "150.172.238.178");
 
     // Test fallback to socket address
     headers.clear();
-    assert_eq!(extract_client_ip(&headers, socket_addr), "192.168.1.1");
+    assert_eq!(extract_client_ip(&trusted_proxies, &headers, socket_addr), "192.168.1.1");
+  }
+
+  #[test]
+  fn test_extract_client_ip_ignores_headers_from_untrusted_peers() {
+    let mut headers = HeaderMap::new();
+    let socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7)), 8080);
+    headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.195"));
+    headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.195"));
+
+    let trusted_proxies = TrustedProxies::parse("192.168.1.0/24");
+    assert_eq!(extract_client_ip(&trusted_proxies, &headers, socket_addr), "198.51.100.7");
   }
 
   #[test]
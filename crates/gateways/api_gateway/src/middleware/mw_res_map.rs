@@ -6,7 +6,7 @@ use crate::{
 use axum::body::to_bytes;
 use axum::{
   Json,
-  http::{Method, StatusCode, Uri},
+  http::{HeaderName, Method, StatusCode, Uri, header::RETRY_AFTER},
   response::{IntoResponse, Response},
 };
 use jd_utils::time::{format_time, now_utc};
@@ -16,6 +16,7 @@ use uuid::Uuid;
 
 use super::{mw_auth::CtxW, mw_res_timestamp::ReqStamp};
 use crate::error::RequestContext;
+use crate::routes_rpc::RpcExchange;
 
 /// Standard response structure for all API responses
 #[derive(Debug)]
@@ -183,6 +184,12 @@ pub async fn mw_map_response(
   req_stamp: ReqStamp,
   res: Response,
 ) -> Response {
+  // JSON-RPC bodies follow their own spec, errors included, and are not wrapped
+  if res.extensions().get::<RpcExchange>().is_some() {
+    info!("RPC request completed: {} - {} - Status: {}", req_method, uri, res.status());
+    return res;
+  }
+
   let ctx = ctx.map(|ctx| ctx.0).ok();
   let ReqStamp { uuid, .. } = req_stamp;
 
@@ -214,8 +221,20 @@ pub async fn mw_map_response(
   // Log the request details
   log_request_response(uri, req_method, req_stamp, ctx, request_body, &processed, web_error).await;
 
-  // Return the processed response
-  (processed.status_code, Json(processed.body)).into_response()
+  // Return the processed response, keeping the headers clients act on
+  let mut response = (processed.status_code, Json(processed.body)).into_response();
+  for (name, value) in parts
+    .headers
+    .iter()
+    .filter(|(name, _)| is_client_header(name))
+  {
+    response.headers_mut().insert(name, value.clone());
+  }
+  response
+}
+
+fn is_client_header(name: &HeaderName) -> bool {
+  *name == RETRY_AFTER || name.as_str().starts_with("x-ratelimit-")
 }
 
 #[cfg(test)]
@@ -99,3 +99,17 @@ impl ResponseMetadata {
     self
   }
 }
+
+/// One page of a cursor paginated listing
+#[derive(Debug, Serialize)]
+pub struct CursorPage<T> {
+  pub data: Vec<T>,
+  pub pagination: PaginationMetadata,
+}
+
+impl<T> CursorPage<T> {
+  /// Forward only listing, there is no previous cursor
+  pub fn new(data: Vec<T>, next_cursor: Option<String>, has_more: bool) -> Self {
+    Self { data, pagination: PaginationMetadata::new_cursor(next_cursor, None, has_more) }
+  }
+}
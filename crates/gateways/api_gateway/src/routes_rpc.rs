@@ -1,53 +1,46 @@
+use std::sync::Arc;
+
+use crate::middleware::mw_auth::CtxW;
+use crate::sui::sui_rpc;
 use crate::users::user_rpc;
-use axum::response::IntoResponse;
-use axum::Router;
-use axum::{extract::State, routing::post, Json};
-use jd_core::ctx::Ctx;
-use jd_core::{AppState, ModelManager};
-use serde_json::{json, Value};
+use axum::body::Bytes;
+use axum::extract::{Extension, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use jd_core::AppState;
+use jd_rpc_core::RpcRegistry;
+
+/// Marks JSON-RPC responses so `mw_res_map` passes them through unwrapped
+#[derive(Debug, Clone, Copy)]
+pub struct RpcExchange;
 
-/// Simple RPC handler that routes to the appropriate function
+/// JSON-RPC 2.0 endpoint, single and batch requests.
+/// Calls run as the authenticated caller, requests without a valid token are rejected.
 pub async fn rpc_handler(
   State(app_state): State<AppState>,
-  Json(rpc_req): Json<Value>,
-) -> impl IntoResponse {
-  // Create a default context for now
-  let ctx = Ctx::new(0).unwrap_or_else(|_| Ctx::root_ctx());
-  // Extract method and params from the request
-  let method = rpc_req.get("method").and_then(|v| v.as_str()).unwrap_or("");
-
-  let params = rpc_req.get("params").cloned().unwrap_or(json!({}));
-
-  let id = rpc_req.get("id").cloned();
-
-  // Route to the appropriate handler
-  let result = match method {
-    m if m.starts_with("user.") => {
-      let user_method = &m[5..]; // Remove "user." prefix
-      user_rpc::handle_user_rpc(user_method, params, ctx, app_state.clone()).await
-    }
-    _ => Err(jd_core::Error::RpcError(format!("Unknown method: {}", method))),
+  Extension(registry): Extension<Arc<RpcRegistry<AppState>>>,
+  CtxW(ctx): CtxW,
+  body: Bytes,
+) -> Response {
+  let mut res = match registry.handle(app_state, ctx, &body).await {
+    Some(payload) => Json(payload).into_response(),
+    None => StatusCode::NO_CONTENT.into_response(),
   };
+  res.extensions_mut().insert(RpcExchange);
+
+  res
+}
 
-  // Build the response
-  match result {
-    Ok(data) => Json(json!({
-        "jsonrpc": "2.0",
-        "result": data,
-        "id": id
-    })),
-    Err(e) => Json(json!({
-        "jsonrpc": "2.0",
-        "error": {
-            "code": -32603,
-            "message": e.to_string()
-        },
-        "id": id
-    })),
-  }
+/// Every method exposed over RPC
+pub fn rpc_registry() -> RpcRegistry<AppState> {
+  RpcRegistry::new().merge(user_rpc::user_rpc()).merge(sui_rpc::sui_rpc())
 }
 
 /// Build the Axum router for '/api/rpc'
-pub fn routes(_mm: ModelManager) -> Router<AppState> {
-  Router::new().route("/rpc", post(rpc_handler))
+pub fn routes() -> Router<AppState> {
+  Router::new()
+    .route("/rpc", post(rpc_handler))
+    .layer(Extension(Arc::new(rpc_registry())))
 }
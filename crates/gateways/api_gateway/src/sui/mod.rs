@@ -1,14 +1,62 @@
 use axum::{Router, routing::post};
+mod read_routes;
 mod sponsor_routes;
+pub mod sui_rpc;
 use jd_core::AppState;
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::Page;
 use sui_service::application::handlers::sui_handler::SuiHandler;
+use sui_service::application::use_cases::NetworkUseCases;
+use sui_service::infrastructure::cached_sui_repository::CachedSuiRepository;
 use sui_service::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use sui_service::infrastructure::resilient_sui_repository::ResilientSuiRepository;
 
-type Handler = SuiHandler<EnhancedSuiRepository>;
+use crate::middleware::pagination::CursorPage;
 
-pub fn sui_router() -> Router<AppState> {
+type Handler = SuiHandler<ResilientSuiRepository<EnhancedSuiRepository>>;
+
+/// Largest page the Sui fullnode serves in one call
+const MAX_PAGE_LIMIT: usize = 50;
+
+pub fn sui_router(app_state: AppState) -> Router<AppState> {
   Router::new()
     // Coin operations  
     .route("/fetch-coin", post(Handler::fetch_coin))
-    .merge(sponsor_routes::sponsor_router())
+    .merge(read_routes::read_router())
+    .merge(sponsor_routes::sponsor_router(app_state))
+}
+
+/// Cursor and page size of a listing without filters
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+  pub cursor: Option<String>,
+  pub limit: Option<usize>,
+}
+
+fn page_limit(limit: Option<usize>) -> Option<usize> {
+  limit.map(|limit| limit.clamp(1, MAX_PAGE_LIMIT))
+}
+
+fn cursor_page<T, C: ToString>(page: Page<T, C>) -> CursorPage<T> {
+  CursorPage::new(page.data, page.next_cursor.map(|c| c.to_string()), page.has_next_page)
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkInfo {
+  pub chain_id: String,
+  pub latest_checkpoint: u64,
+  pub total_transactions: u64,
+  pub reference_gas_price: u64,
+}
+
+async fn network_info(state: AppState) -> sui_service::Result<NetworkInfo> {
+  let network = NetworkUseCases::new(CachedSuiRepository::new(state));
+  let (chain_id, latest_checkpoint, total_transactions, reference_gas_price) = tokio::try_join!(
+    network.get_chain_id(),
+    network.get_latest_checkpoint(),
+    network.get_total_transactions(),
+    network.get_gas_price(),
+  )?;
+
+  Ok(NetworkInfo { chain_id, latest_checkpoint, total_transactions, reference_gas_price })
 }
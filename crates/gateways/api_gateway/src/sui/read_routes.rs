@@ -0,0 +1,189 @@
+use axum::{
+  Json, Router,
+  extract::{Path, Query, State},
+  routing::get,
+};
+use jd_core::AppState;
+use serde::Deserialize;
+use sui_sdk::rpc_types::{
+  Balance, Coin, DynamicFieldInfo, SuiCoinMetadata, SuiEvent, SuiObjectResponse,
+  SuiTransactionBlockResponse,
+};
+use sui_service::application::use_cases::{
+  CoinUseCases, EventUseCases, ObjectUseCases, TransactionUseCases,
+};
+use sui_service::infrastructure::cached_sui_repository::CachedSuiRepository;
+use sui_service::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use sui_service::infrastructure::resilient_sui_repository::ResilientSuiRepository;
+
+use super::{NetworkInfo, PageQuery, cursor_page, network_info, page_limit};
+use crate::Result;
+use crate::middleware::pagination::CursorPage;
+
+type Repository = CachedSuiRepository<ResilientSuiRepository<EnhancedSuiRepository>>;
+
+// Chain reads - addresses, objects, transactions and network info
+pub fn read_router() -> Router<AppState> {
+  Router::new()
+    .route("/addresses/{address}/balances", get(get_balances))
+    .route("/addresses/{address}/balance", get(get_balance))
+    .route("/addresses/{address}/coins", get(get_coins))
+    .route("/addresses/{address}/objects", get(get_owned_objects))
+    .route("/coins/metadata", get(get_coin_metadata))
+    .route("/objects/{object_id}", get(get_object))
+    .route("/objects/{object_id}/dynamic-fields", get(get_dynamic_fields))
+    .route("/transactions/{digest}", get(get_transaction))
+    .route("/transactions/{digest}/events", get(get_transaction_events))
+    .route("/network", get(get_network_info))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CoinTypeQuery {
+  pub coin_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoinMetadataQuery {
+  pub coin_type: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CoinsQuery {
+  pub coin_type: Option<String>,
+  pub cursor: Option<String>,
+  pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ObjectsQuery {
+  pub object_type: Option<String>,
+  pub cursor: Option<String>,
+  pub limit: Option<usize>,
+}
+
+// region:    --- Shared with the RPC methods
+
+pub(super) async fn balance(
+  state: AppState,
+  address: &str,
+  query: CoinTypeQuery,
+) -> sui_service::Result<Balance> {
+  let coins = CoinUseCases::new(Repository::new(state));
+  match query.coin_type {
+    Some(coin_type) => coins.get_balance_by_type(address, &coin_type).await,
+    None => coins.get_sui_balance(address).await,
+  }
+}
+
+pub(super) async fn coins(
+  state: AppState,
+  address: &str,
+  query: CoinsQuery,
+) -> sui_service::Result<CursorPage<Coin>> {
+  let coins = CoinUseCases::new(Repository::new(state));
+  let limit = page_limit(query.limit);
+  let page = match query.coin_type {
+    Some(coin_type) => coins.get_coins_by_type(address, &coin_type, query.cursor, limit).await?,
+    None => coins.get_all_coins(address, query.cursor, limit).await?,
+  };
+  Ok(cursor_page(page))
+}
+
+pub(super) async fn owned_objects(
+  state: AppState,
+  address: &str,
+  query: ObjectsQuery,
+) -> sui_service::Result<CursorPage<SuiObjectResponse>> {
+  let objects = ObjectUseCases::new(Repository::new(state));
+  let limit = page_limit(query.limit);
+  let page = objects.get_owned_objects(address, query.object_type, query.cursor, limit).await?;
+  Ok(cursor_page(page))
+}
+
+pub(super) async fn dynamic_fields(
+  state: AppState,
+  object_id: &str,
+  query: PageQuery,
+) -> sui_service::Result<CursorPage<DynamicFieldInfo>> {
+  let objects = ObjectUseCases::new(Repository::new(state));
+  let page = objects.get_dynamic_fields(object_id, query.cursor, page_limit(query.limit)).await?;
+  Ok(cursor_page(page))
+}
+
+// endregion: --- Shared with the RPC methods
+
+async fn get_balances(
+  State(state): State<AppState>,
+  Path(address): Path<String>,
+) -> Result<Json<Vec<Balance>>> {
+  let coins = CoinUseCases::new(Repository::new(state));
+  Ok(Json(coins.get_all_balances(&address).await?))
+}
+
+async fn get_balance(
+  State(state): State<AppState>,
+  Path(address): Path<String>,
+  Query(query): Query<CoinTypeQuery>,
+) -> Result<Json<Balance>> {
+  Ok(Json(balance(state, &address, query).await?))
+}
+
+async fn get_coins(
+  State(state): State<AppState>,
+  Path(address): Path<String>,
+  Query(query): Query<CoinsQuery>,
+) -> Result<Json<CursorPage<Coin>>> {
+  Ok(Json(coins(state, &address, query).await?))
+}
+
+async fn get_owned_objects(
+  State(state): State<AppState>,
+  Path(address): Path<String>,
+  Query(query): Query<ObjectsQuery>,
+) -> Result<Json<CursorPage<SuiObjectResponse>>> {
+  Ok(Json(owned_objects(state, &address, query).await?))
+}
+
+async fn get_coin_metadata(
+  State(state): State<AppState>,
+  Query(query): Query<CoinMetadataQuery>,
+) -> Result<Json<Option<SuiCoinMetadata>>> {
+  let coins = CoinUseCases::new(Repository::new(state));
+  Ok(Json(coins.get_coin_metadata(&query.coin_type).await?))
+}
+
+async fn get_object(
+  State(state): State<AppState>,
+  Path(object_id): Path<String>,
+) -> Result<Json<SuiObjectResponse>> {
+  let objects = ObjectUseCases::new(Repository::new(state));
+  Ok(Json(objects.get_object_details(&object_id).await?))
+}
+
+async fn get_dynamic_fields(
+  State(state): State<AppState>,
+  Path(object_id): Path<String>,
+  Query(query): Query<PageQuery>,
+) -> Result<Json<CursorPage<DynamicFieldInfo>>> {
+  Ok(Json(dynamic_fields(state, &object_id, query).await?))
+}
+
+async fn get_transaction(
+  State(state): State<AppState>,
+  Path(digest): Path<String>,
+) -> Result<Json<SuiTransactionBlockResponse>> {
+  let transactions = TransactionUseCases::new(Repository::new(state));
+  Ok(Json(transactions.get_transaction_details(&digest).await?))
+}
+
+async fn get_transaction_events(
+  State(state): State<AppState>,
+  Path(digest): Path<String>,
+) -> Result<Json<Vec<SuiEvent>>> {
+  let events = EventUseCases::new(Repository::new(state));
+  Ok(Json(events.get_transaction_events(&digest).await?))
+}
+
+async fn get_network_info(State(state): State<AppState>) -> Result<Json<NetworkInfo>> {
+  Ok(Json(network_info(state).await?))
+}
@@ -1,14 +1,67 @@
+use std::sync::Arc;
+
 use axum::{
-  Router,
-  routing::get,
+  Extension, Json, Router, middleware,
+  extract::State,
+  routing::{get, post},
 };
 use jd_core::AppState;
+use sui_service::application::use_cases::SuiUseCases;
+use sui_service::infrastructure::sui_repository_impl::SuiRepositoryImpl;
+use sui_service::models::{
+  ExecuteSponsorRequest, ReserveSponsorRequest, ReserveSponsorResponse, SponsorCaller,
+  SponsorResponse,
+};
+use tokio::sync::OnceCell;
 
 use super::Handler;
+use crate::middleware::mw_auth_rbac::{AuthContext, mw_require_auth, require_scope};
+
+type Sponsor = SuiUseCases<SuiRepositoryImpl>;
+
+/// Sponsor shared by all requests. Built on first use since it loads the gas pool from the
+/// network and needs the sponsor key, which deployments without sponsorship don't configure.
+type SponsorCell = Arc<OnceCell<Sponsor>>;
+
+pub fn sponsor_router(app_state: AppState) -> Router<AppState> {
+  let sponsored_routes = Router::new()
+    .route("/sponsor/reserve", post(reserve_sponsorship))
+    .route("/sponsor/execute", post(execute_sponsorship))
+    // API keys sponsor only with the `sui.sponsor` scope
+    .route_layer(middleware::from_fn(require_scope("sui.sponsor")))
+    .route_layer(middleware::from_fn_with_state(app_state, mw_require_auth))
+    .layer(Extension(SponsorCell::default()));
 
-// Sponsor operations - simplified to working methods only
-pub fn sponsor_router() -> Router<AppState> {
   Router::new()
     .route("/health", get(Handler::health_check))
     .route("/test-connection", get(Handler::test_connection))
+    .merge(sponsored_routes)
+}
+
+async fn sponsor(state: AppState, cell: &SponsorCell) -> sui_service::Result<&Sponsor> {
+  cell
+    .get_or_try_init(|| async move {
+      SuiRepositoryImpl::from_config(state).await.map(Sponsor::new)
+    })
+    .await
+}
+
+async fn reserve_sponsorship(
+  State(state): State<AppState>,
+  Extension(cell): Extension<SponsorCell>,
+  Extension(auth): Extension<AuthContext>,
+  Json(request): Json<ReserveSponsorRequest>,
+) -> sui_service::Result<Json<ReserveSponsorResponse>> {
+  let sponsor = sponsor(state, &cell).await?;
+  let caller = SponsorCaller { user_id: auth.user_id, role: auth.role };
+  Ok(Json(sponsor.reserve_sponsorship(request, caller).await?))
+}
+
+async fn execute_sponsorship(
+  State(state): State<AppState>,
+  Extension(cell): Extension<SponsorCell>,
+  Json(request): Json<ExecuteSponsorRequest>,
+) -> sui_service::Result<Json<SponsorResponse>> {
+  let sponsor = sponsor(state, &cell).await?;
+  Ok(Json(sponsor.execute_sponsorship(request).await?))
 }
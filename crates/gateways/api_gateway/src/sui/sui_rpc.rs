@@ -0,0 +1,162 @@
+use jd_core::AppState;
+use jd_core::ctx::Ctx;
+use jd_rpc_core::{RpcError, RpcRegistry};
+use serde::Deserialize;
+use sui_sdk::rpc_types::{
+  Balance, Coin, DynamicFieldInfo, SuiCoinMetadata, SuiEvent, SuiObjectResponse,
+  SuiTransactionBlockResponse,
+};
+use sui_service::application::use_cases::{
+  CoinUseCases, EventUseCases, ObjectUseCases, TransactionUseCases,
+};
+use sui_service::infrastructure::cached_sui_repository::CachedSuiRepository;
+use sui_service::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use sui_service::infrastructure::resilient_sui_repository::ResilientSuiRepository;
+use tracing::error;
+
+use super::read_routes::{self, CoinTypeQuery, CoinsQuery, ObjectsQuery};
+use super::{NetworkInfo, PageQuery, network_info};
+use crate::middleware::pagination::CursorPage;
+
+type Repository = CachedSuiRepository<ResilientSuiRepository<EnhancedSuiRepository>>;
+type RpcResult<T> = Result<T, RpcError>;
+
+const SUI_READ: &str = "sui.read";
+
+/// `sui.*` methods of the JSON-RPC endpoint, mirroring the `/api/v1/sui` read routes.
+/// API keys need the `sui.read` scope.
+pub fn sui_rpc() -> RpcRegistry<AppState> {
+  RpcRegistry::new()
+    .register_scoped("sui.get_balances", SUI_READ, get_balances)
+    .register_scoped("sui.get_balance", SUI_READ, get_balance)
+    .register_scoped("sui.get_coins", SUI_READ, get_coins)
+    .register_scoped("sui.get_owned_objects", SUI_READ, get_owned_objects)
+    .register_scoped("sui.get_coin_metadata", SUI_READ, get_coin_metadata)
+    .register_scoped("sui.get_object", SUI_READ, get_object)
+    .register_scoped("sui.get_dynamic_fields", SUI_READ, get_dynamic_fields)
+    .register_scoped("sui.get_transaction", SUI_READ, get_transaction)
+    .register_scoped("sui.get_transaction_events", SUI_READ, get_transaction_events)
+    .register_scoped("sui.get_network_info", SUI_READ, get_network_info)
+}
+
+#[derive(Deserialize)]
+pub struct ParamsAddress<Q> {
+  pub address: String,
+  #[serde(flatten)]
+  pub query: Q,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsObject<Q> {
+  pub object_id: String,
+  #[serde(flatten)]
+  pub query: Q,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsDigest {
+  pub digest: String,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsCoinType {
+  pub coin_type: String,
+}
+
+async fn get_balances(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsAddress<()>,
+) -> RpcResult<Vec<Balance>> {
+  let coins = CoinUseCases::new(Repository::new(state));
+  coins.get_all_balances(&params.address).await.map_err(rpc_error)
+}
+
+async fn get_balance(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsAddress<CoinTypeQuery>,
+) -> RpcResult<Balance> {
+  read_routes::balance(state, &params.address, params.query).await.map_err(rpc_error)
+}
+
+async fn get_coins(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsAddress<CoinsQuery>,
+) -> RpcResult<CursorPage<Coin>> {
+  read_routes::coins(state, &params.address, params.query).await.map_err(rpc_error)
+}
+
+async fn get_owned_objects(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsAddress<ObjectsQuery>,
+) -> RpcResult<CursorPage<SuiObjectResponse>> {
+  read_routes::owned_objects(state, &params.address, params.query).await.map_err(rpc_error)
+}
+
+async fn get_coin_metadata(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsCoinType,
+) -> RpcResult<Option<SuiCoinMetadata>> {
+  let coins = CoinUseCases::new(Repository::new(state));
+  coins.get_coin_metadata(&params.coin_type).await.map_err(rpc_error)
+}
+
+async fn get_object(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsObject<()>,
+) -> RpcResult<SuiObjectResponse> {
+  let objects = ObjectUseCases::new(Repository::new(state));
+  objects.get_object_details(&params.object_id).await.map_err(rpc_error)
+}
+
+async fn get_dynamic_fields(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsObject<PageQuery>,
+) -> RpcResult<CursorPage<DynamicFieldInfo>> {
+  read_routes::dynamic_fields(state, &params.object_id, params.query).await.map_err(rpc_error)
+}
+
+async fn get_transaction(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsDigest,
+) -> RpcResult<SuiTransactionBlockResponse> {
+  let transactions = TransactionUseCases::new(Repository::new(state));
+  transactions.get_transaction_details(&params.digest).await.map_err(rpc_error)
+}
+
+async fn get_transaction_events(
+  state: AppState,
+  _ctx: Ctx,
+  params: ParamsDigest,
+) -> RpcResult<Vec<SuiEvent>> {
+  let events = EventUseCases::new(Repository::new(state));
+  events.get_transaction_events(&params.digest).await.map_err(rpc_error)
+}
+
+async fn get_network_info(state: AppState, _ctx: Ctx, _params: ()) -> RpcResult<NetworkInfo> {
+  network_info(state).await.map_err(rpc_error)
+}
+
+/// Malformed addresses, digests and cursors are the caller's fault, node failures are not
+fn rpc_error(err: sui_service::Error) -> RpcError {
+  match err {
+    sui_service::Error::InvalidRequest(reason) => RpcError::invalid_params(reason),
+    sui_service::Error::ImplementationPending(reason) => {
+      RpcError::new(RpcError::SERVER_ERROR, reason)
+    }
+    err @ (sui_service::Error::Timeout { .. } | sui_service::Error::CircuitOpen { .. }) => {
+      RpcError::new(RpcError::SERVER_ERROR, err.to_string())
+    }
+    err => {
+      error!("Sui RPC failed: {err}");
+      RpcError::internal()
+    }
+  }
+}
@@ -1,110 +1,122 @@
-use jd_contracts::user::dtos::requests::{
-  create_user_request::CreateUserRequest, user_filter::UserFilter,
+use jd_contracts::user::dtos::{
+  records::user_record::UserRecord,
+  requests::{create_user_request::CreateUserRequest, user_filter::UserFilter},
 };
+use jd_core::AppState;
+use jd_core::base::rpc::{DataRpcResult, ParamsForCreate};
 use jd_core::ctx::Ctx;
-use jd_core::{AppState, Result};
-use serde_json::{Value, json};
+use jd_rpc_core::{RpcError, RpcRegistry};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::error;
 use user_service::{
   application::use_cases::{CreateUserUseCase, GetUserUseCase},
   infrastructure::database::user_repository_impl::UserRepositoryImpl,
 };
 
-// Note: For proper RPC integration, we should pass AppState instead of ModelManager
-// For now, we'll work with what we have, but this is a design limitation
+type RpcResult<T> = Result<DataRpcResult<T>, RpcError>;
 
-// Real RPC handler using user_service
-pub async fn handle_user_rpc(
-  method: &str,
-  params: Value,
+const USERS_READ: &str = "users.read.all";
+const USERS_WRITE: &str = "users.write.all";
+
+/// `user.*` methods of the JSON-RPC endpoint. API keys need the `users.read.all` scope,
+/// `users.write.all` to create users.
+pub fn user_rpc() -> RpcRegistry<AppState> {
+  RpcRegistry::new()
+    .register_scoped("user.get_user_by_username", USERS_READ, get_user_by_username)
+    .register_scoped("user.get_user_by_email", USERS_READ, get_user_by_email)
+    .register_scoped("user.get_user_by_filter", USERS_READ, get_user_by_filter)
+    .register_scoped("user.get_user_by_active_status", USERS_READ, get_user_by_active_status)
+    .register_scoped("user.create_user", USERS_WRITE, create_user)
+}
+
+#[derive(Deserialize)]
+pub struct ParamsUsername {
+  pub username: String,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsEmail {
+  pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsFilter {
+  pub filter: UserFilter,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsActiveStatus {
+  pub is_active: bool,
+}
+
+async fn get_user_by_username(
+  app_state: AppState,
+  _ctx: Ctx,
+  params: ParamsUsername,
+) -> RpcResult<UserRecord> {
+  let use_case = GetUserUseCase::new(UserRepositoryImpl::new(app_state));
+  let user = use_case.execute_by_username(params.username).await.map_err(rpc_error)?;
+  Ok(user.into())
+}
+
+async fn get_user_by_email(
+  app_state: AppState,
   _ctx: Ctx,
+  params: ParamsEmail,
+) -> RpcResult<UserRecord> {
+  let use_case = GetUserUseCase::new(UserRepositoryImpl::new(app_state));
+  let user = use_case.execute_by_email(params.email).await.map_err(rpc_error)?;
+  Ok(user.into())
+}
+
+async fn get_user_by_filter(
+  app_state: AppState,
+  _ctx: Ctx,
+  params: ParamsFilter,
+) -> RpcResult<UserRecord> {
+  let use_case = GetUserUseCase::new(UserRepositoryImpl::new(app_state));
+  let user = use_case.execute_by_wow(params.filter).await.map_err(rpc_error)?;
+  Ok(user.into())
+}
+
+async fn get_user_by_active_status(
+  app_state: AppState,
+  _ctx: Ctx,
+  params: ParamsActiveStatus,
+) -> RpcResult<UserRecord> {
+  let use_case = GetUserUseCase::new(UserRepositoryImpl::new(app_state));
+  let user = use_case.execute_by_is_active(params.is_active).await.map_err(rpc_error)?;
+  Ok(user.into())
+}
+
+async fn create_user(
   app_state: AppState,
-) -> Result<Value> {
-  match method {
-    "get_user_by_username" => {
-      let username = params
-        .get("username")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| jd_core::Error::RpcError("Missing username parameter".to_string()))?;
-
-      let repository = UserRepositoryImpl::new(app_state.clone());
-      let use_case = GetUserUseCase::new(repository);
-
-      let user = use_case
-        .execute_by_username(username.to_string())
-        .await
-        .map_err(|e| jd_core::Error::RpcError(format!("Failed to get user: {}", e)))?;
-
-      Ok(json!({ "data": user }))
-    }
-    "get_user_by_email" => {
-      let email = params
-        .get("email")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| jd_core::Error::RpcError("Missing email parameter".to_string()))?;
-
-      let repository = UserRepositoryImpl::new(app_state.clone());
-      let use_case = GetUserUseCase::new(repository);
-
-      let user = use_case
-        .execute_by_email(email.to_string())
-        .await
-        .map_err(|e| jd_core::Error::RpcError(format!("Failed to get user: {}", e)))?;
-
-      Ok(json!({ "data": user }))
-    }
-    "get_user_by_filter" => {
-      let filter_data = params
-        .get("filter")
-        .ok_or_else(|| jd_core::Error::RpcError("Missing filter parameter".to_string()))?;
-
-      let filter: UserFilter = serde_json::from_value(filter_data.clone())
-        .map_err(|e| jd_core::Error::RpcError(format!("Invalid filter format: {}", e)))?;
-
-      let repository = UserRepositoryImpl::new(app_state.clone());
-      let use_case = GetUserUseCase::new(repository);
-
-      let user = use_case
-        .execute_by_wow(filter)
-        .await
-        .map_err(|e| jd_core::Error::RpcError(format!("Failed to get user: {}", e)))?;
-
-      Ok(json!({ "data": user }))
-    }
-    "create_user" => {
-      let data: CreateUserRequest = serde_json::from_value(
-        params
-          .get("data")
-          .ok_or_else(|| jd_core::Error::RpcError("Missing data parameter".to_string()))?
-          .clone(),
-      )
-      .map_err(|e| jd_core::Error::RpcError(format!("Invalid data format: {}", e)))?;
-
-      let repository = UserRepositoryImpl::new(app_state.clone());
-      let use_case = CreateUserUseCase::new(repository);
-
-      let user = use_case
-        .execute(data)
-        .await
-        .map_err(|e| jd_core::Error::RpcError(format!("Failed to create user: {}", e)))?;
-
-      Ok(json!({ "data": user }))
-    }
-    "get_user_by_active_status" => {
-      let is_active = params
-        .get("is_active")
-        .and_then(|v| v.as_bool())
-        .ok_or_else(|| jd_core::Error::RpcError("Missing is_active parameter".to_string()))?;
-
-      let repository = UserRepositoryImpl::new(app_state.clone());
-      let use_case = GetUserUseCase::new(repository);
-
-      let user = use_case
-        .execute_by_is_active(is_active)
-        .await
-        .map_err(|e| jd_core::Error::RpcError(format!("Failed to get user: {}", e)))?;
-
-      Ok(json!({ "data": user }))
-    }
-    _ => Err(jd_core::Error::RpcError(format!("Unknown method: {}", method))),
+  _ctx: Ctx,
+  params: ParamsForCreate<CreateUserRequest>,
+) -> RpcResult<UserRecord> {
+  let use_case = CreateUserUseCase::new(UserRepositoryImpl::new(app_state));
+  let user = use_case.execute(params.data).await.map_err(rpc_error)?;
+  Ok(user.into())
+}
+
+/// Client errors keep their message and code, server errors are logged and hidden
+fn rpc_error(err: user_service::Error) -> RpcError {
+  use user_service::Error as E;
+
+  if err.is_server_error() {
+    error!("User RPC failed: {err}");
+    return RpcError::internal();
   }
+
+  let (_, client_error) = err.client_status_and_error(None);
+  let code = match &err {
+    E::BadRequest { .. } | E::ValidationFailed { .. } => RpcError::INVALID_PARAMS,
+    E::EntityNotFound { .. } => RpcError::NOT_FOUND,
+    E::Conflict { .. } => RpcError::CONFLICT,
+    _ => RpcError::SERVER_ERROR,
+  };
+
+  RpcError::new(code, client_error.message)
+    .with_data(json!({ "type": client_error.error_code, "details": client_error.details }))
 }
@@ -1,6 +1,10 @@
 use api_gateway::{
   middleware::{
-    mw_auth::mw_ctx_resolve, mw_request_context::mw_request_context, mw_res_map, mw_res_timestamp,
+    mw_api_key::{X_API_KEY, mw_api_key_resolve},
+    mw_auth::mw_ctx_resolve,
+    mw_rate_limit::{X_RATELIMIT_LIMIT, X_RATELIMIT_REMAINING, X_RATELIMIT_RESET, mw_rate_limit},
+    mw_request_context::mw_request_context,
+    mw_res_map, mw_res_timestamp,
   },
   v1_routes,
 };
@@ -8,6 +12,7 @@ use api_gateway::{
 use axum::{http::StatusCode, middleware, response::IntoResponse, Json, Router};
 use dotenv::dotenv;
 use jd_core::AppState;
+use jd_messaging::{OutboxRelay, RedisStreamsPublisher, RelayConfig};
 use serde_json::json;
 use tower_cookies::CookieManagerLayer;
 use tower_http::cors::CorsLayer;
@@ -19,7 +24,7 @@ use jd_utils::{
   time::{format_time, now_utc},
 };
 
-use axum::http::{HeaderName, HeaderValue, Method};
+use axum::http::{HeaderName, HeaderValue, Method, header::RETRY_AFTER};
 
 mod error;
 
@@ -33,13 +38,27 @@ async fn main() -> error::Result<()> {
 
   let cfg = config::Config::from_env().expect("Loading env failed");
 
+  let messaging = &app_state.config.messaging;
+  let publisher = RedisStreamsPublisher::new(
+    app_state.redis.clone(),
+    messaging.stream_key.clone(),
+    messaging.stream_max_len,
+  );
+  OutboxRelay::new(app_state.mm.dbx().db().clone(), publisher, RelayConfig::from(messaging))
+    .expect("Failed to create outbox relay")
+    .spawn();
+
   let app = Router::new()
     .merge(v1_routes(app_state.clone()))
+    // Inside the response mapping so rejections get the standard error body
+    .layer(middleware::from_fn_with_state(app_state.clone(), mw_rate_limit))
+    // Ahead of the limiter so API key callers are counted per key
+    .layer(middleware::from_fn_with_state(app_state.clone(), mw_api_key_resolve))
     .layer(middleware::map_response(mw_res_map::mw_map_response))
     .layer(middleware::from_fn_with_state(app_state.clone(), mw_ctx_resolve))
     .layer(CookieManagerLayer::new())
     .layer(middleware::from_fn(mw_res_timestamp::mw_req_stamp_resolver))
-    .layer(middleware::from_fn(mw_request_context))
+    .layer(middleware::from_fn_with_state(app_state.clone(), mw_request_context))
     .layer(
       CorsLayer::new()
         .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
@@ -51,7 +70,9 @@ async fn main() -> error::Result<()> {
           HeaderName::from_static("x-requested-with"),
           HeaderName::from_static("x-request-id"),
           HeaderName::from_static("x-trace-id"),
+          X_API_KEY,
         ])
+        .expose_headers([RETRY_AFTER, X_RATELIMIT_LIMIT, X_RATELIMIT_REMAINING, X_RATELIMIT_RESET])
         .allow_credentials(true),
     )
     .fallback(fallback_handler);
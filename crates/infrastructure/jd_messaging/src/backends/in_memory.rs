@@ -0,0 +1,223 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use tokio::sync::Notify;
+
+use crate::Result;
+use crate::event::EventEnvelope;
+use crate::traits::{Delivery, EventPublisher, EventSubscriber};
+
+/// Process local bus for tests. Every subscriber sees every published event.
+#[derive(Clone, Default)]
+pub struct InMemoryEventBus {
+  inner: Arc<BusInner>,
+}
+
+#[derive(Default)]
+struct BusInner {
+  log: Mutex<Vec<EventEnvelope>>,
+  notify: Notify,
+}
+
+impl InMemoryEventBus {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Snapshot of everything published so far
+  pub fn published(&self) -> Vec<EventEnvelope> {
+    self.inner.log.lock().unwrap().clone()
+  }
+
+  /// Subscriber reading from the start of the log
+  pub fn subscriber(&self) -> InMemorySubscriber {
+    InMemorySubscriber {
+      inner: self.inner.clone(),
+      state: Mutex::default(),
+      poll_timeout: Duration::from_millis(50),
+      redeliver_after: Duration::from_secs(1),
+    }
+  }
+}
+
+#[async_trait]
+impl EventPublisher for InMemoryEventBus {
+  async fn publish(&self, envelope: &EventEnvelope) -> Result<()> {
+    self.inner.log.lock().unwrap().push(envelope.clone());
+    self.inner.notify.notify_waiters();
+    Ok(())
+  }
+}
+
+pub struct InMemorySubscriber {
+  inner: Arc<BusInner>,
+  state: Mutex<SubscriberState>,
+  poll_timeout: Duration,
+  redeliver_after: Duration,
+}
+
+#[derive(Default)]
+struct SubscriberState {
+  cursor: usize,
+  /// Unacknowledged deliveries by log index, with when they were last handed out
+  pending: HashMap<usize, Instant>,
+}
+
+impl InMemorySubscriber {
+  /// How long a delivery stays unacknowledged before it is handed out again
+  pub fn with_redeliver_after(mut self, redeliver_after: Duration) -> Self {
+    self.redeliver_after = redeliver_after;
+    self
+  }
+
+  /// Number of deliveries handed out but not acknowledged yet
+  pub fn pending(&self) -> usize {
+    self.state.lock().unwrap().pending.len()
+  }
+
+  fn take(&self, max: usize) -> Vec<Delivery> {
+    let log = self.inner.log.lock().unwrap();
+    let mut state = self.state.lock().unwrap();
+    let now = Instant::now();
+
+    let mut indexes: Vec<usize> = state
+      .pending
+      .iter()
+      .filter(|(_, handed_out)| now.duration_since(**handed_out) >= self.redeliver_after)
+      .map(|(index, _)| *index)
+      .collect();
+    indexes.sort_unstable();
+    indexes.truncate(max);
+
+    let end = log.len().min(state.cursor + max - indexes.len());
+    indexes.extend(state.cursor..end);
+    state.cursor = end;
+
+    indexes
+      .into_iter()
+      .map(|index| {
+        state.pending.insert(index, now);
+        Delivery { delivery_id: index.to_string(), envelope: log[index].clone() }
+      })
+      .collect()
+  }
+}
+
+#[async_trait]
+impl EventSubscriber for InMemorySubscriber {
+  async fn receive(&self, max: usize) -> Result<Vec<Delivery>> {
+    // Register interest before looking so a publish in between is not missed
+    let notified = self.inner.notify.notified();
+
+    let deliveries = self.take(max);
+    if !deliveries.is_empty() {
+      return Ok(deliveries);
+    }
+
+    let _ = tokio::time::timeout(self.poll_timeout, notified).await;
+    Ok(self.take(max))
+  }
+
+  async fn ack(&self, delivery: &Delivery) -> Result<()> {
+    if let Ok(index) = delivery.delivery_id.parse::<usize>() {
+      self.state.lock().unwrap().pending.remove(&index);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::event::DomainEvent;
+  use serde::Serialize;
+
+  #[derive(Serialize)]
+  struct Pinged {
+    id: u32,
+  }
+
+  impl DomainEvent for Pinged {
+    const EVENT_TYPE: &'static str = "test.pinged";
+
+    fn aggregate_id(&self) -> String {
+      self.id.to_string()
+    }
+  }
+
+  #[tokio::test]
+  async fn subscriber_receives_published_events_in_order() {
+    let bus = InMemoryEventBus::new();
+    let subscriber = bus.subscriber();
+
+    for id in 0..3 {
+      bus.publish(&EventEnvelope::new(&Pinged { id }).unwrap()).await.unwrap();
+    }
+
+    let first = subscriber.receive(2).await.unwrap();
+    let rest = subscriber.receive(10).await.unwrap();
+
+    let ids: Vec<_> = first.iter().chain(&rest).map(|d| d.envelope.aggregate_id.clone()).collect();
+    assert_eq!(ids, ["0", "1", "2"]);
+    assert_eq!(bus.published().len(), 3);
+  }
+
+  #[tokio::test]
+  async fn ack_clears_pending_delivery() {
+    let bus = InMemoryEventBus::new();
+    let subscriber = bus.subscriber();
+    bus.publish(&EventEnvelope::new(&Pinged { id: 1 }).unwrap()).await.unwrap();
+
+    let deliveries = subscriber.receive(10).await.unwrap();
+    assert_eq!(subscriber.pending(), 1);
+
+    subscriber.ack(&deliveries[0]).await.unwrap();
+    assert_eq!(subscriber.pending(), 0);
+  }
+
+  #[tokio::test]
+  async fn unacknowledged_delivery_is_handed_out_again() {
+    let bus = InMemoryEventBus::new();
+    let subscriber = bus.subscriber().with_redeliver_after(Duration::from_millis(200));
+    bus.publish(&EventEnvelope::new(&Pinged { id: 1 }).unwrap()).await.unwrap();
+
+    let first = subscriber.receive(10).await.unwrap();
+    assert!(subscriber.receive(10).await.unwrap().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let again = subscriber.receive(10).await.unwrap();
+    assert_eq!(again.len(), 1);
+    assert_eq!(again[0].delivery_id, first[0].delivery_id);
+
+    subscriber.ack(&again[0]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(subscriber.receive(10).await.unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn receive_wakes_up_on_publish() {
+    let bus = InMemoryEventBus::new();
+    let mut subscriber = bus.subscriber();
+    subscriber.poll_timeout = Duration::from_secs(5);
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+      tokio::time::sleep(Duration::from_millis(10)).await;
+      publisher.publish(&EventEnvelope::new(&Pinged { id: 9 }).unwrap()).await.unwrap();
+    });
+
+    let deliveries = subscriber.receive(10).await.unwrap();
+    assert_eq!(deliveries.len(), 1);
+  }
+
+  #[tokio::test]
+  async fn receive_returns_empty_when_idle() {
+    let bus = InMemoryEventBus::new();
+
+    assert!(bus.subscriber().receive(10).await.unwrap().is_empty());
+  }
+}
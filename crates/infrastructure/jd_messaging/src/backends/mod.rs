@@ -0,0 +1,5 @@
+mod in_memory;
+mod redis_streams;
+
+pub use in_memory::{InMemoryEventBus, InMemorySubscriber};
+pub use redis_streams::{RedisStreamsPublisher, RedisStreamsSubscriber, unique_consumer_name};
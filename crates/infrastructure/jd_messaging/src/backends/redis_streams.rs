@@ -0,0 +1,268 @@
+use std::{
+  sync::{
+    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+  },
+  time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use redis::{
+  AsyncCommands,
+  streams::{
+    StreamAutoClaimOptions, StreamAutoClaimReply, StreamId, StreamMaxlen, StreamReadOptions,
+    StreamReadReply,
+  },
+};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::Result;
+use crate::event::EventEnvelope;
+use crate::traits::{Delivery, EventPublisher, EventSubscriber};
+
+/// Field of the stream entry holding the JSON encoded envelope
+const EVENT_FIELD: &str = "event";
+
+/// Appends events to a Redis stream, trimmed to roughly `max_len` entries
+#[derive(Clone)]
+pub struct RedisStreamsPublisher {
+  client: Arc<redis::Client>,
+  stream_key: String,
+  max_len: usize,
+}
+
+impl RedisStreamsPublisher {
+  pub fn new(client: Arc<redis::Client>, stream_key: impl Into<String>, max_len: usize) -> Self {
+    Self { client, stream_key: stream_key.into(), max_len }
+  }
+}
+
+#[async_trait]
+impl EventPublisher for RedisStreamsPublisher {
+  async fn publish(&self, envelope: &EventEnvelope) -> Result<()> {
+    let mut conn = self.client.get_multiplexed_async_connection().await?;
+    let json = serde_json::to_string(envelope)?;
+
+    let _: String = conn
+      .xadd_maxlen(&self.stream_key, StreamMaxlen::Approx(self.max_len), "*", &[(EVENT_FIELD, json)])
+      .await?;
+    Ok(())
+  }
+}
+
+/// Consumer name unique to this process, `<prefix>-<host>-<random>`. Entries a previous
+/// process left unacknowledged are claimed by the survivors once idle, the name need not
+/// survive restarts.
+pub fn unique_consumer_name(prefix: &str) -> String {
+  let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "local".to_string());
+  let suffix = Uuid::new_v4().simple().to_string();
+  format!("{prefix}-{host}-{}", &suffix[..8])
+}
+
+/// Reads a Redis stream as a member of a consumer group.
+///
+/// On the first call the consumer replays entries it received but never acknowledged before a
+/// restart, then switches to new entries. Every `min_idle` it also claims entries that stayed
+/// unacknowledged that long, whether a handler failed on them here or their consumer died.
+/// Entries that cannot be decoded are logged and acknowledged so they do not block the group.
+pub struct RedisStreamsSubscriber {
+  client: Arc<redis::Client>,
+  stream_key: String,
+  group: String,
+  consumer: String,
+  block: Duration,
+  min_idle: Duration,
+  cursors: Mutex<Cursors>,
+  group_ready: AtomicBool,
+}
+
+struct Cursors {
+  /// Id after which own unacknowledged entries are replayed, `None` once the backlog is done
+  replay: Option<String>,
+  /// Where the running `XAUTOCLAIM` sweep continues
+  claim: String,
+  next_claim: Instant,
+}
+
+impl RedisStreamsSubscriber {
+  pub fn new(
+    client: Arc<redis::Client>,
+    stream_key: impl Into<String>,
+    group: impl Into<String>,
+    consumer: impl Into<String>,
+  ) -> Self {
+    Self {
+      client,
+      stream_key: stream_key.into(),
+      group: group.into(),
+      consumer: consumer.into(),
+      block: Duration::from_secs(1),
+      min_idle: Duration::from_secs(30),
+      cursors: Mutex::new(Cursors {
+        replay: Some("0".to_string()),
+        claim: "0-0".to_string(),
+        next_claim: Instant::now(),
+      }),
+      group_ready: AtomicBool::new(false),
+    }
+  }
+
+  /// How long `receive` waits for new entries before returning empty
+  pub fn with_block(mut self, block: Duration) -> Self {
+    self.block = block;
+    self
+  }
+
+  /// How long an entry stays unacknowledged before it is handed out again
+  pub fn with_min_idle(mut self, min_idle: Duration) -> Self {
+    self.min_idle = min_idle;
+    self
+  }
+
+  async fn ensure_group(&self, conn: &mut redis::aio::MultiplexedConnection) -> Result<()> {
+    if self.group_ready.load(Ordering::Acquire) {
+      return Ok(());
+    }
+
+    let created: redis::RedisResult<()> =
+      conn.xgroup_create_mkstream(&self.stream_key, &self.group, "0").await;
+    match created {
+      Ok(()) => {}
+      // The group already exists, another consumer created it first
+      Err(err) if err.code() == Some("BUSYGROUP") => {}
+      Err(err) => return Err(err.into()),
+    }
+
+    self.group_ready.store(true, Ordering::Release);
+    Ok(())
+  }
+
+  async fn read(
+    &self,
+    conn: &mut redis::aio::MultiplexedConnection,
+    id: &str,
+    max: usize,
+  ) -> Result<Vec<Delivery>> {
+    let mut options = StreamReadOptions::default().group(&self.group, &self.consumer).count(max);
+    if id == ">" {
+      options = options.block(self.block.as_millis() as usize);
+    }
+
+    let reply: Option<StreamReadReply> =
+      conn.xread_options(&[&self.stream_key], &[id], &options).await?;
+
+    let entries = reply.into_iter().flat_map(|r| r.keys).flat_map(|k| k.ids).collect();
+    self.decode(conn, entries).await
+  }
+
+  /// Takes over entries idle for `min_idle`, one page of the sweep per call
+  async fn claim_idle(
+    &self,
+    conn: &mut redis::aio::MultiplexedConnection,
+    start: &str,
+    max: usize,
+  ) -> Result<Vec<Delivery>> {
+    let reply: StreamAutoClaimReply = conn
+      .xautoclaim_options(
+        &self.stream_key,
+        &self.group,
+        &self.consumer,
+        self.min_idle.as_millis() as u64,
+        start,
+        StreamAutoClaimOptions::default().count(max),
+      )
+      .await?;
+
+    {
+      let mut cursors = self.cursors.lock().unwrap();
+      // "0-0" ends the sweep, the next one starts after another idle period
+      if reply.next_stream_id == "0-0" {
+        cursors.next_claim = Instant::now() + self.min_idle;
+      }
+      cursors.claim = reply.next_stream_id;
+    }
+
+    if !reply.claimed.is_empty() {
+      warn!(stream = %self.stream_key, count = reply.claimed.len(), "Claimed idle events");
+    }
+    self.decode(conn, reply.claimed).await
+  }
+
+  async fn decode(
+    &self,
+    conn: &mut redis::aio::MultiplexedConnection,
+    entries: Vec<StreamId>,
+  ) -> Result<Vec<Delivery>> {
+    let mut deliveries = Vec::new();
+    for entry in entries {
+      let decoded = entry
+        .get::<String>(EVENT_FIELD)
+        .ok_or_else(|| format!("missing '{EVENT_FIELD}' field"))
+        .and_then(|json| {
+          serde_json::from_str::<EventEnvelope>(&json).map_err(|err| err.to_string())
+        });
+
+      match decoded {
+        Ok(envelope) => deliveries.push(Delivery { delivery_id: entry.id, envelope }),
+        Err(reason) => {
+          error!(stream = %self.stream_key, id = %entry.id, "Dropping undecodable event: {reason}");
+          let _: i64 = conn.xack(&self.stream_key, &self.group, &[&entry.id]).await?;
+        }
+      }
+    }
+
+    Ok(deliveries)
+  }
+}
+
+#[async_trait]
+impl EventSubscriber for RedisStreamsSubscriber {
+  async fn receive(&self, max: usize) -> Result<Vec<Delivery>> {
+    let mut conn = self.client.get_multiplexed_async_connection().await?;
+    self.ensure_group(&mut conn).await?;
+
+    let (replay, claim) = {
+      let cursors = self.cursors.lock().unwrap();
+      let claim = (Instant::now() >= cursors.next_claim).then(|| cursors.claim.clone());
+      (cursors.replay.clone(), claim)
+    };
+
+    if let Some(cursor) = replay {
+      let pending = self.read(&mut conn, &cursor, max).await?;
+      self.cursors.lock().unwrap().replay = pending.last().map(|d| d.delivery_id.clone());
+      if !pending.is_empty() {
+        return Ok(pending);
+      }
+    }
+
+    if let Some(start) = claim {
+      let claimed = self.claim_idle(&mut conn, &start, max).await?;
+      if !claimed.is_empty() {
+        return Ok(claimed);
+      }
+    }
+
+    self.read(&mut conn, ">", max).await
+  }
+
+  async fn ack(&self, delivery: &Delivery) -> Result<()> {
+    let mut conn = self.client.get_multiplexed_async_connection().await?;
+    let _: i64 = conn.xack(&self.stream_key, &self.group, &[&delivery.delivery_id]).await?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn consumer_names_are_unique_per_process() {
+    let first = unique_consumer_name("notification_processor");
+    let second = unique_consumer_name("notification_processor");
+
+    assert!(first.starts_with("notification_processor-"));
+    assert_ne!(first, second);
+  }
+}
@@ -0,0 +1,46 @@
+use serde::Serialize;
+use serde_with::{DisplayFromStr, serde_as};
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(Debug, Serialize, thiserror::Error)]
+#[serde(tag = "type", content = "data")]
+pub enum Error {
+  // -- Event Errors
+  #[error("Event '{event_type}' could not be decoded: {reason}")]
+  EventDecode { event_type: String, reason: String },
+
+  // -- External Dependencies
+  #[error("Event serialization error: {0}")]
+  Serde(
+    #[from]
+    #[serde_as(as = "DisplayFromStr")]
+    serde_json::Error,
+  ),
+
+  #[error("Redis error: {0}")]
+  Redis(
+    #[from]
+    #[serde_as(as = "DisplayFromStr")]
+    redis::RedisError,
+  ),
+
+  #[error(transparent)]
+  Dbx(#[from] jd_storage::dbx::Error),
+}
+
+impl Error {
+  /// Connection problems clear up on their own, anything else fails the same way again
+  pub fn is_transient(&self) -> bool {
+    match self {
+      Self::Redis(err) => {
+        err.is_io_error()
+          || err.is_connection_dropped()
+          || err.is_connection_refusal()
+          || err.is_timeout()
+      }
+      _ => false,
+    }
+  }
+}
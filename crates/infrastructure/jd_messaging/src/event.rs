@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+/// A fact about the domain that other services may react to
+pub trait DomainEvent: Serialize {
+  /// Stable name subscribers dispatch on, e.g. `user.created`
+  const EVENT_TYPE: &'static str;
+
+  /// Identifier of the entity the event is about
+  fn aggregate_id(&self) -> String;
+}
+
+/// Serialized event as stored in the outbox and carried by every backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EventEnvelope {
+  pub event_id: Uuid,
+  pub event_type: String,
+  pub aggregate_id: String,
+  pub payload: serde_json::Value,
+  #[serde(with = "time::serde::rfc3339")]
+  pub occurred_at: OffsetDateTime,
+}
+
+impl EventEnvelope {
+  pub fn new<E: DomainEvent>(event: &E) -> Result<Self> {
+    Ok(Self {
+      event_id: Uuid::new_v4(),
+      event_type: E::EVENT_TYPE.to_string(),
+      aggregate_id: event.aggregate_id(),
+      payload: serde_json::to_value(event)?,
+      occurred_at: OffsetDateTime::now_utc(),
+    })
+  }
+
+  pub fn is<E: DomainEvent>(&self) -> bool {
+    self.event_type == E::EVENT_TYPE
+  }
+
+  /// Decodes the payload, `None` when the envelope carries another event type
+  pub fn decode<E: DomainEvent + DeserializeOwned>(&self) -> Result<Option<E>> {
+    if !self.is::<E>() {
+      return Ok(None);
+    }
+
+    serde_json::from_value(self.payload.clone())
+      .map(Some)
+      .map_err(|e| Error::EventDecode { event_type: self.event_type.clone(), reason: e.to_string() })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[derive(Debug, PartialEq, Serialize, Deserialize)]
+  struct OrderPlaced {
+    order_id: u32,
+  }
+
+  impl DomainEvent for OrderPlaced {
+    const EVENT_TYPE: &'static str = "order.placed";
+
+    fn aggregate_id(&self) -> String {
+      self.order_id.to_string()
+    }
+  }
+
+  #[derive(Debug, Serialize, Deserialize)]
+  struct OrderShipped {
+    order_id: u32,
+  }
+
+  impl DomainEvent for OrderShipped {
+    const EVENT_TYPE: &'static str = "order.shipped";
+
+    fn aggregate_id(&self) -> String {
+      self.order_id.to_string()
+    }
+  }
+
+  #[test]
+  fn envelope_round_trips_its_event() {
+    let envelope = EventEnvelope::new(&OrderPlaced { order_id: 7 }).unwrap();
+
+    assert_eq!(envelope.event_type, "order.placed");
+    assert_eq!(envelope.aggregate_id, "7");
+    assert_eq!(envelope.decode::<OrderPlaced>().unwrap(), Some(OrderPlaced { order_id: 7 }));
+  }
+
+  #[test]
+  fn decode_skips_other_event_types() {
+    let envelope = EventEnvelope::new(&OrderPlaced { order_id: 7 }).unwrap();
+
+    assert!(envelope.decode::<OrderShipped>().unwrap().is_none());
+  }
+
+  #[test]
+  fn envelope_survives_json_transport() {
+    let envelope = EventEnvelope::new(&OrderPlaced { order_id: 7 }).unwrap();
+
+    let json = serde_json::to_string(&envelope).unwrap();
+    let restored: EventEnvelope = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.event_id, envelope.event_id);
+    assert_eq!(restored.payload, envelope.payload);
+  }
+}
@@ -1,14 +1,16 @@
-pub fn add(left: u64, right: u64) -> u64 {
-  left + right
-}
+// -->>> Region:: START  --->>>  Public Modules
+pub mod backends;
+pub mod event;
+pub mod outbox;
+pub mod relay;
+pub mod traits;
+// <<<-- Region:: END    <<<---  Public Modules
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+mod error;
 
-  #[test]
-  fn it_works() {
-    let result = add(2, 2);
-    assert_eq!(result, 4);
-  }
-}
+pub use backends::*;
+pub use error::{Error, Result};
+pub use event::{DomainEvent, EventEnvelope};
+pub use outbox::Outbox;
+pub use relay::{OutboxRelay, RelayConfig};
+pub use traits::{Delivery, EventPublisher, EventSubscriber};
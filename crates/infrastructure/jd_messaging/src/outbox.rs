@@ -0,0 +1,32 @@
+use jd_storage::dbx::Dbx;
+
+use crate::Result;
+use crate::event::{DomainEvent, EventEnvelope};
+
+/// Writes events to `messaging.outbox`, from where `OutboxRelay` publishes them
+pub struct Outbox;
+
+impl Outbox {
+  /// Records the event on the caller's `dbx`. Call it inside the transaction that makes the
+  /// domain change, the event is then only relayed if that transaction commits.
+  pub async fn enqueue<E: DomainEvent>(dbx: &Dbx, event: &E) -> Result<EventEnvelope> {
+    let envelope = EventEnvelope::new(event)?;
+    Self::enqueue_envelope(dbx, &envelope).await?;
+    Ok(envelope)
+  }
+
+  pub async fn enqueue_envelope(dbx: &Dbx, envelope: &EventEnvelope) -> Result<()> {
+    let query = sqlx::query(
+      "INSERT INTO messaging.outbox (event_id, event_type, aggregate_id, payload, occurred_at) \
+       VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(envelope.event_id)
+    .bind(&envelope.event_type)
+    .bind(&envelope.aggregate_id)
+    .bind(&envelope.payload)
+    .bind(envelope.occurred_at);
+
+    dbx.execute(query).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use jd_storage::{Db, dbx::Dbx};
+use jd_utils::{config::MessagingConfig, with_transaction};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, warn};
+
+use crate::event::EventEnvelope;
+use crate::traits::EventPublisher;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone)]
+pub struct RelayConfig {
+  /// Maximum number of outbox rows published per transaction
+  pub batch_size: i64,
+  /// Pause between polls once the outbox has been drained
+  pub poll_interval: Duration,
+  /// Failed publishes after which an event moves to `messaging.outbox_dead_letter`. Connection
+  /// failures never dead-letter, they would empty the outbox during a Redis outage.
+  pub max_attempts: i32,
+}
+
+impl Default for RelayConfig {
+  fn default() -> Self {
+    Self { batch_size: 100, poll_interval: Duration::from_millis(500), max_attempts: 10 }
+  }
+}
+
+impl From<&MessagingConfig> for RelayConfig {
+  fn from(config: &MessagingConfig) -> Self {
+    Self {
+      batch_size: config.relay_batch_size,
+      poll_interval: Duration::from_millis(config.relay_interval_ms),
+      max_attempts: config.relay_max_attempts,
+    }
+  }
+}
+
+/// Moves committed events from `messaging.outbox` to an `EventPublisher`.
+///
+/// Rows are locked with `FOR UPDATE SKIP LOCKED` so several relays can run side by side, and
+/// deleted once published. A failed publish keeps the row and stops the batch, events of one
+/// relay are therefore published in the order they were written. An event that keeps failing
+/// for `max_attempts` is set aside as dead letter so it no longer holds back the rest.
+pub struct OutboxRelay<P> {
+  dbx: Dbx,
+  publisher: P,
+  config: RelayConfig,
+}
+
+impl<P: EventPublisher + 'static> OutboxRelay<P> {
+  pub fn new(db: Db, publisher: P, config: RelayConfig) -> Result<Self> {
+    Ok(Self { dbx: Dbx::new(db, true)?, publisher, config })
+  }
+
+  /// Publishes one batch and returns the number of events sent
+  pub async fn drain_once(&self) -> Result<usize> {
+    let dbx = &self.dbx;
+    let publisher = &self.publisher;
+    let batch_size = self.config.batch_size;
+    let max_attempts = self.config.max_attempts;
+
+    with_transaction!(dbx, {
+      let pending = dbx
+        .fetch_all(sqlx::query_as::<_, EventEnvelope>(
+          "SELECT event_id, event_type, aggregate_id, payload, occurred_at \
+           FROM messaging.outbox ORDER BY created_at, event_id LIMIT $1 FOR UPDATE SKIP LOCKED",
+        )
+        .bind(batch_size))
+        .await?;
+
+      let mut published = 0;
+      for envelope in &pending {
+        if let Err(err) = publisher.publish(envelope).await {
+          warn!(event_id = %envelope.event_id, "Failed to publish outbox event: {err}");
+          let (attempts,) = dbx
+            .fetch_one(
+              sqlx::query_as::<_, (i32,)>(
+                "UPDATE messaging.outbox SET attempts = attempts + 1, last_error = $2 \
+                 WHERE event_id = $1 RETURNING attempts",
+              )
+              .bind(envelope.event_id)
+              .bind(err.to_string()),
+            )
+            .await?;
+
+          if attempts < max_attempts || err.is_transient() {
+            break;
+          }
+
+          error!(event_id = %envelope.event_id, attempts, "Dead-lettering outbox event: {err}");
+          dbx
+            .execute(
+              sqlx::query(
+                "WITH dead AS (DELETE FROM messaging.outbox WHERE event_id = $1 RETURNING *) \
+                 INSERT INTO messaging.outbox_dead_letter \
+                   (event_id, event_type, aggregate_id, payload, occurred_at, attempts, last_error) \
+                 SELECT event_id, event_type, aggregate_id, payload, occurred_at, attempts, \
+                   last_error FROM dead",
+              )
+              .bind(envelope.event_id),
+            )
+            .await?;
+          continue;
+        }
+
+        dbx
+          .execute(
+            sqlx::query("DELETE FROM messaging.outbox WHERE event_id = $1").bind(envelope.event_id),
+          )
+          .await?;
+        published += 1;
+      }
+
+      Ok::<_, Error>(published)
+    })
+  }
+
+  /// Drains the outbox until the task is aborted
+  pub async fn run(self) {
+    loop {
+      match self.drain_once().await {
+        Ok(count) if count as i64 >= self.config.batch_size => continue,
+        Ok(count) => {
+          if count > 0 {
+            debug!("Relayed {count} outbox events");
+          }
+        }
+        Err(err) => error!("Outbox relay failed: {err}"),
+      }
+
+      tokio::time::sleep(self.config.poll_interval).await;
+    }
+  }
+
+  pub fn spawn(self) -> JoinHandle<()> {
+    tokio::spawn(self.run())
+  }
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+
+use crate::Result;
+use crate::event::EventEnvelope;
+
+/// Hands events to a backend. The outbox relay is the only caller in production, services
+/// write to the outbox instead so events and domain changes commit together.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+  async fn publish(&self, envelope: &EventEnvelope) -> Result<()>;
+}
+
+/// Event handed out by a subscriber, acknowledged once it has been handled
+#[derive(Debug, Clone)]
+pub struct Delivery {
+  /// Backend specific id used to acknowledge the delivery
+  pub delivery_id: String,
+  pub envelope: EventEnvelope,
+}
+
+/// Reads events at least once. A delivery that is not acknowledged is handed out again once it
+/// has been idle for a while, possibly to another subscriber of the group, handlers must
+/// therefore be idempotent.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+  /// Waits briefly for new events and returns at most `max` of them, possibly none
+  async fn receive(&self, max: usize) -> Result<Vec<Delivery>>;
+  async fn ack(&self, delivery: &Delivery) -> Result<()>;
+}
@@ -0,0 +1,46 @@
+use axum::{
+  Json,
+  http::StatusCode,
+  response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+use serde_with::serde_as;
+use tracing::error;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(Debug, Serialize, thiserror::Error)]
+#[serde(tag = "type", content = "data")]
+pub enum Error {
+  // -- Query Errors
+  #[error("Invalid analytics query: {0}")]
+  InvalidQuery(String),
+
+  // -- External Dependencies
+  #[error(transparent)]
+  Messaging(#[from] jd_messaging::Error),
+
+  #[error(transparent)]
+  Dbx(#[from] jd_storage::dbx::Error),
+}
+
+impl IntoResponse for Error {
+  fn into_response(self) -> Response {
+    let (status, code) = match &self {
+      Self::InvalidQuery(_) => (StatusCode::BAD_REQUEST, "INVALID_ANALYTICS_QUERY"),
+      Self::Messaging(_) | Self::Dbx(_) => {
+        error!("Analytics query failed: {self}");
+        (StatusCode::INTERNAL_SERVER_ERROR, "ANALYTICS_UNAVAILABLE")
+      }
+    };
+
+    let message = match &self {
+      Self::InvalidQuery(_) => self.to_string(),
+      _ => "Analytics are temporarily unavailable".to_string(),
+    };
+
+    (status, Json(json!({ "error": message, "code": code }))).into_response()
+  }
+}
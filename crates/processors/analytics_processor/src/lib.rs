@@ -1,14 +1,14 @@
-pub fn add(left: u64, right: u64) -> u64 {
-  left + right
-}
+// -->>> Region:: START  --->>>  Public Modules
+pub mod processor;
+pub mod queries;
+pub mod repository;
+pub mod rollup;
+// <<<-- Region:: END    <<<---  Public Modules
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+mod error;
 
-  #[test]
-  fn it_works() {
-    let result = add(2, 2);
-    assert_eq!(result, 4);
-  }
-}
+pub use error::{Error, Result};
+pub use processor::AnalyticsProcessor;
+pub use queries::AnalyticsQueries;
+pub use repository::{RollupRepository, RollupRepositoryImpl};
+pub use rollup::RollupUpdate;
@@ -0,0 +1,44 @@
+use std::{sync::Arc, time::Duration};
+
+use analytics_processor::{AnalyticsProcessor, RollupRepositoryImpl};
+use dotenv::dotenv;
+use jd_messaging::{RedisStreamsSubscriber, unique_consumer_name};
+use jd_tracing::tracing_init;
+use jd_utils::config::Config;
+use tracing::info;
+
+#[tokio::main]
+async fn main() {
+  dotenv().ok();
+
+  let _ = tracing_init();
+
+  let config = Config::from_env().expect("Loading env failed");
+  let settings = &config.analytics;
+
+  let db = jd_storage::new_db_pool().await.expect("Failed to connect to Postgres");
+  let redis = Arc::new(redis::Client::open(config.redis.addr.clone()).expect("Invalid Redis URL"));
+
+  let consumer_name = settings
+    .consumer_name
+    .clone()
+    .unwrap_or_else(|| unique_consumer_name(&settings.consumer_group));
+  let subscriber = RedisStreamsSubscriber::new(
+    redis,
+    config.messaging.stream_key.clone(),
+    settings.consumer_group.clone(),
+    consumer_name,
+  )
+  .with_min_idle(Duration::from_millis(config.messaging.claim_min_idle_ms));
+  let repository = RollupRepositoryImpl::new(db).expect("Failed to create repository");
+
+  let processor =
+    AnalyticsProcessor::new(subscriber, repository).with_batch_size(settings.batch_size);
+
+  info!("Analytics processor consuming '{}'", config.messaging.stream_key);
+
+  tokio::select! {
+    _ = processor.run() => {}
+    _ = tokio::signal::ctrl_c() => info!("Analytics processor shutting down"),
+  }
+}
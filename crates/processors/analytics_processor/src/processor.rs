@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use jd_messaging::{EventEnvelope, EventSubscriber};
+use tracing::{debug, error, warn};
+
+use crate::Result;
+use crate::repository::RollupRepository;
+use crate::rollup::RollupUpdate;
+
+/// Folds sponsorship and login events into the analytics rollups.
+///
+/// Events are acknowledged once their update is committed; redelivered events are skipped by
+/// the repository. Events that cannot be decoded are logged and acknowledged, database errors
+/// leave the event unacknowledged so it is applied on the next delivery while the rest of its
+/// batch goes on.
+pub struct AnalyticsProcessor<S, R> {
+  subscriber: S,
+  repository: R,
+  batch_size: usize,
+  retry_delay: Duration,
+}
+
+impl<S: EventSubscriber, R: RollupRepository> AnalyticsProcessor<S, R> {
+  pub fn new(subscriber: S, repository: R) -> Self {
+    Self { subscriber, repository, batch_size: 100, retry_delay: Duration::from_secs(1) }
+  }
+
+  pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+    self.batch_size = batch_size.max(1);
+    self
+  }
+
+  /// Handles one batch and returns the number of events acknowledged
+  pub async fn process_batch(&self) -> Result<usize> {
+    let deliveries = self.subscriber.receive(self.batch_size).await?;
+
+    let mut acknowledged = 0;
+    for delivery in &deliveries {
+      let event_id = delivery.envelope.event_id;
+      if let Err(err) = self.handle(&delivery.envelope).await {
+        warn!(%event_id, "Analytics event left for redelivery: {err}");
+        continue;
+      }
+      match self.subscriber.ack(delivery).await {
+        Ok(()) => acknowledged += 1,
+        Err(err) => warn!(%event_id, "Failed to acknowledge analytics event: {err}"),
+      }
+    }
+
+    Ok(acknowledged)
+  }
+
+  async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+    match RollupUpdate::from_envelope(envelope) {
+      Ok(Some(update)) => {
+        if !self.repository.apply(envelope.event_id, &update).await? {
+          debug!(event_id = %envelope.event_id, "Event already in the rollups");
+        }
+      }
+      Ok(None) => {}
+      Err(err) => warn!(event_id = %envelope.event_id, "Skipping undecodable event: {err}"),
+    }
+    Ok(())
+  }
+
+  pub async fn run(&self) {
+    loop {
+      if let Err(err) = self.process_batch().await {
+        error!("Analytics batch failed: {err}");
+        tokio::time::sleep(self.retry_delay).await;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+  use std::sync::{Arc, Mutex};
+
+  use async_trait::async_trait;
+  use jd_contracts::user::dtos::events::{NewDeviceLoginEvent, UserLoggedInEvent};
+  use jd_messaging::{EventPublisher, InMemoryEventBus, InMemorySubscriber};
+  use jd_utils::time::now_utc;
+  use uuid::Uuid;
+
+  use super::*;
+
+  const REDELIVER_AFTER: Duration = Duration::from_millis(100);
+
+  #[derive(Default)]
+  struct FakeRepository {
+    /// Applies that fail before the database comes back
+    failures: Mutex<u32>,
+    applied: Mutex<HashSet<Uuid>>,
+    updates: Mutex<Vec<RollupUpdate>>,
+  }
+
+  #[async_trait]
+  impl RollupRepository for Arc<FakeRepository> {
+    async fn apply(&self, event_id: Uuid, update: &RollupUpdate) -> Result<bool> {
+      let mut failures = self.failures.lock().unwrap();
+      if *failures > 0 {
+        *failures -= 1;
+        return Err(jd_storage::dbx::Error::from(sqlx::Error::PoolTimedOut).into());
+      }
+      drop(failures);
+      if !self.applied.lock().unwrap().insert(event_id) {
+        return Ok(false);
+      }
+      self.updates.lock().unwrap().push(update.clone());
+      Ok(true)
+    }
+  }
+
+  fn login() -> EventEnvelope {
+    let event = UserLoggedInEvent {
+      user_id: Uuid::new_v4(),
+      session_id: Uuid::new_v4(),
+      provider_type: "google".to_string(),
+      logged_in_at: now_utc(),
+    };
+    EventEnvelope::new(&event).unwrap()
+  }
+
+  async fn setup(
+    envelopes: &[EventEnvelope],
+  ) -> (AnalyticsProcessor<InMemorySubscriber, Arc<FakeRepository>>, Arc<FakeRepository>) {
+    let bus = InMemoryEventBus::new();
+    for envelope in envelopes {
+      bus.publish(envelope).await.unwrap();
+    }
+
+    let repository = Arc::new(FakeRepository::default());
+    let subscriber = bus.subscriber().with_redeliver_after(REDELIVER_AFTER);
+    (AnalyticsProcessor::new(subscriber, repository.clone()), repository)
+  }
+
+  #[tokio::test]
+  async fn applies_tracked_events_and_skips_others() {
+    let other = NewDeviceLoginEvent {
+      user_id: Uuid::new_v4(),
+      session_id: Uuid::new_v4(),
+      ip_address: None,
+      user_agent: "Firefox".to_string(),
+      logged_in_at: now_utc(),
+    };
+    let (processor, repository) = setup(&[login(), EventEnvelope::new(&other).unwrap()]).await;
+
+    assert_eq!(processor.process_batch().await.unwrap(), 2);
+
+    assert_eq!(repository.updates.lock().unwrap().len(), 1);
+    assert_eq!(processor.subscriber.pending(), 0);
+  }
+
+  #[tokio::test]
+  async fn redelivered_events_are_applied_once() {
+    let envelope = login();
+    let (processor, repository) = setup(&[envelope.clone(), envelope]).await;
+
+    processor.process_batch().await.unwrap();
+
+    assert_eq!(repository.updates.lock().unwrap().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn undecodable_events_are_acknowledged() {
+    let mut envelope = login();
+    envelope.payload = serde_json::json!({ "user_id": "not-a-uuid" });
+    let (processor, repository) = setup(&[envelope]).await;
+
+    assert_eq!(processor.process_batch().await.unwrap(), 1);
+
+    assert!(repository.updates.lock().unwrap().is_empty());
+    assert_eq!(processor.subscriber.pending(), 0);
+  }
+
+  #[tokio::test]
+  async fn failed_event_is_redelivered_without_holding_back_the_batch() {
+    let (processor, repository) = setup(&[login(), login()]).await;
+    *repository.failures.lock().unwrap() = 1;
+
+    // The first event hits the database outage, the second one is still applied
+    assert_eq!(processor.process_batch().await.unwrap(), 1);
+    assert_eq!(repository.updates.lock().unwrap().len(), 1);
+    assert_eq!(processor.subscriber.pending(), 1);
+
+    tokio::time::sleep(REDELIVER_AFTER).await;
+    assert_eq!(processor.process_batch().await.unwrap(), 1);
+
+    assert_eq!(repository.updates.lock().unwrap().len(), 2);
+    assert_eq!(processor.subscriber.pending(), 0);
+  }
+}
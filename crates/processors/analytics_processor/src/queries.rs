@@ -0,0 +1,240 @@
+use jd_storage::{Db, dbx::Dbx};
+use jd_utils::time::{now_utc, parse_utc};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use crate::{Error, Result};
+
+const DEFAULT_RANGE_DAYS: i64 = 7;
+const MAX_RANGE_DAYS: i64 = 366;
+const MAX_LIMIT: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+  Hour,
+  #[default]
+  Day,
+}
+
+/// Query string of the sponsorship series, bounds are RFC 3339 and default to the last 7 days
+#[derive(Debug, Default, Deserialize)]
+pub struct SponsorshipSeriesQuery {
+  pub from: Option<String>,
+  pub to: Option<String>,
+  #[serde(default)]
+  pub granularity: Granularity,
+  pub user_address: Option<String>,
+  pub app_id: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TopQuery {
+  pub from: Option<String>,
+  pub to: Option<String>,
+  pub limit: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RangeQuery {
+  pub from: Option<String>,
+  pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SponsorshipPoint {
+  #[serde(with = "time::serde::rfc3339")]
+  pub bucket: OffsetDateTime,
+  pub success_count: i64,
+  pub failure_count: i64,
+  pub gas_sponsored: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UserSponsorship {
+  pub user_address: String,
+  pub success_count: i64,
+  pub failure_count: i64,
+  pub gas_sponsored: i64,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub last_transaction_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AppSponsorship {
+  pub app_id: String,
+  pub success_count: i64,
+  pub failure_count: i64,
+  pub gas_sponsored: i64,
+  pub active_users: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct LoginDay {
+  #[serde(with = "time::serde::rfc3339")]
+  pub bucket: OffsetDateTime,
+  pub provider_type: String,
+  pub login_count: i64,
+  pub active_users: i64,
+}
+
+/// Read side of the analytics rollups
+pub struct AnalyticsQueries {
+  dbx: Dbx,
+}
+
+impl AnalyticsQueries {
+  pub fn new(db: Db) -> Result<Self> {
+    Ok(Self { dbx: Dbx::new(db, false)? })
+  }
+
+  pub async fn sponsorship_series(
+    &self,
+    query: &SponsorshipSeriesQuery,
+  ) -> Result<Vec<SponsorshipPoint>> {
+    let (from, to) = resolve_range(query.from.as_deref(), query.to.as_deref())?;
+    let (table, bucket, lower) = match query.granularity {
+      Granularity::Hour => ("sponsorship_hourly", "bucket", "date_trunc('hour', $1)"),
+      Granularity::Day => (
+        "sponsorship_daily",
+        "(bucket::timestamp AT TIME ZONE 'UTC')",
+        "($1 AT TIME ZONE 'UTC')::date",
+      ),
+    };
+
+    let sql = format!(
+      "SELECT {bucket} AS bucket, \
+       SUM(success_count)::BIGINT AS success_count, \
+       SUM(failure_count)::BIGINT AS failure_count, \
+       SUM(gas_sponsored)::BIGINT AS gas_sponsored \
+       FROM analytics.{table} \
+       WHERE bucket >= {lower} AND {bucket} < $2 \
+       AND ($3::TEXT IS NULL OR user_address = $3) \
+       AND ($4::TEXT IS NULL OR app_id = $4) \
+       GROUP BY 1 ORDER BY 1"
+    );
+    let query = sqlx::query_as(&sql)
+      .bind(from)
+      .bind(to)
+      .bind(query.user_address.as_deref())
+      .bind(query.app_id.as_deref());
+
+    Ok(self.dbx.fetch_all(query).await?)
+  }
+
+  /// Users ranked by sponsored gas
+  pub async fn sponsorship_by_user(&self, query: &TopQuery) -> Result<Vec<UserSponsorship>> {
+    let (from, to) = resolve_range(query.from.as_deref(), query.to.as_deref())?;
+    let limit = resolve_limit(query.limit)?;
+
+    let query = sqlx::query_as(
+      "SELECT user_address, \
+       SUM(success_count)::BIGINT AS success_count, \
+       SUM(failure_count)::BIGINT AS failure_count, \
+       SUM(gas_sponsored)::BIGINT AS gas_sponsored, \
+       MAX(last_transaction_at) AS last_transaction_at \
+       FROM analytics.sponsorship_daily \
+       WHERE bucket >= ($1 AT TIME ZONE 'UTC')::date \
+       AND (bucket::timestamp AT TIME ZONE 'UTC') < $2 \
+       GROUP BY user_address ORDER BY gas_sponsored DESC, user_address LIMIT $3",
+    )
+    .bind(from)
+    .bind(to)
+    .bind(limit);
+
+    Ok(self.dbx.fetch_all(query).await?)
+  }
+
+  /// Apps ranked by sponsored gas
+  pub async fn sponsorship_by_app(&self, query: &TopQuery) -> Result<Vec<AppSponsorship>> {
+    let (from, to) = resolve_range(query.from.as_deref(), query.to.as_deref())?;
+    let limit = resolve_limit(query.limit)?;
+
+    let query = sqlx::query_as(
+      "SELECT app_id, \
+       SUM(success_count)::BIGINT AS success_count, \
+       SUM(failure_count)::BIGINT AS failure_count, \
+       SUM(gas_sponsored)::BIGINT AS gas_sponsored, \
+       COUNT(DISTINCT user_address) AS active_users \
+       FROM analytics.sponsorship_daily \
+       WHERE bucket >= ($1 AT TIME ZONE 'UTC')::date \
+       AND (bucket::timestamp AT TIME ZONE 'UTC') < $2 \
+       GROUP BY app_id ORDER BY gas_sponsored DESC, app_id LIMIT $3",
+    )
+    .bind(from)
+    .bind(to)
+    .bind(limit);
+
+    Ok(self.dbx.fetch_all(query).await?)
+  }
+
+  /// Logins and daily active users per auth provider
+  pub async fn login_daily(&self, query: &RangeQuery) -> Result<Vec<LoginDay>> {
+    let (from, to) = resolve_range(query.from.as_deref(), query.to.as_deref())?;
+
+    let query = sqlx::query_as(
+      "SELECT (bucket::timestamp AT TIME ZONE 'UTC') AS bucket, provider_type, \
+       login_count, active_users \
+       FROM analytics.login_daily \
+       WHERE bucket >= ($1 AT TIME ZONE 'UTC')::date \
+       AND (bucket::timestamp AT TIME ZONE 'UTC') < $2 \
+       ORDER BY bucket, provider_type",
+    )
+    .bind(from)
+    .bind(to);
+
+    Ok(self.dbx.fetch_all(query).await?)
+  }
+}
+
+fn resolve_range(from: Option<&str>, to: Option<&str>) -> Result<(OffsetDateTime, OffsetDateTime)> {
+  let parse = |value: &str| {
+    parse_utc(value).map_err(|_| Error::InvalidQuery(format!("'{value}' is not an RFC 3339 time")))
+  };
+
+  let to = to.map(parse).transpose()?.unwrap_or_else(now_utc);
+  let from = from.map(parse).transpose()?.unwrap_or(to - Duration::days(DEFAULT_RANGE_DAYS));
+
+  if from >= to {
+    return Err(Error::InvalidQuery("'from' must be before 'to'".to_string()));
+  }
+  if to - from > Duration::days(MAX_RANGE_DAYS) {
+    return Err(Error::InvalidQuery(format!("range exceeds {MAX_RANGE_DAYS} days")));
+  }
+
+  Ok((from, to))
+}
+
+fn resolve_limit(limit: Option<i64>) -> Result<i64> {
+  match limit.unwrap_or(50) {
+    limit @ 1..=MAX_LIMIT => Ok(limit),
+    _ => Err(Error::InvalidQuery(format!("limit must be between 1 and {MAX_LIMIT}"))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn range_defaults_to_last_week() {
+    let (from, to) = resolve_range(None, Some("2026-03-08T00:00:00Z")).unwrap();
+
+    assert_eq!(to - from, Duration::days(DEFAULT_RANGE_DAYS));
+  }
+
+  #[test]
+  fn rejects_inverted_or_unparsable_ranges() {
+    let inverted = resolve_range(Some("2026-03-08T00:00:00Z"), Some("2026-03-01T00:00:00Z"));
+
+    assert!(matches!(inverted, Err(Error::InvalidQuery(_))));
+    assert!(matches!(resolve_range(Some("yesterday"), None), Err(Error::InvalidQuery(_))));
+  }
+
+  #[test]
+  fn limit_is_bounded() {
+    assert_eq!(resolve_limit(None).unwrap(), 50);
+    assert!(resolve_limit(Some(0)).is_err());
+    assert!(resolve_limit(Some(MAX_LIMIT + 1)).is_err());
+  }
+}
@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use jd_storage::{Db, dbx::Dbx};
+use jd_utils::with_transaction;
+use uuid::Uuid;
+
+use crate::rollup::{RollupUpdate, day_bucket, hour_bucket};
+use crate::{Error, Result};
+
+#[async_trait]
+pub trait RollupRepository: Send + Sync {
+  /// Applies the update unless the event was applied before, returns whether it was applied
+  async fn apply(&self, event_id: Uuid, update: &RollupUpdate) -> Result<bool>;
+}
+
+pub struct RollupRepositoryImpl {
+  dbx: Dbx,
+}
+
+impl RollupRepositoryImpl {
+  pub fn new(db: Db) -> Result<Self> {
+    Ok(Self { dbx: Dbx::new(db, true)? })
+  }
+
+  async fn apply_sponsorship(&self, update: &RollupUpdate) -> Result<()> {
+    let RollupUpdate::Sponsorship { at, user_address, app_id, succeeded, gas_sponsored } = update
+    else {
+      return Ok(());
+    };
+    let (success, failure) = if *succeeded { (1_i64, 0_i64) } else { (0, 1) };
+    let last_transaction_at = succeeded.then_some(*at);
+
+    for table in ["sponsorship_hourly", "sponsorship_daily"] {
+      let sql = format!(
+        "INSERT INTO analytics.{table} \
+         (bucket, user_address, app_id, success_count, failure_count, gas_sponsored, \
+          last_transaction_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7) \
+         ON CONFLICT (bucket, user_address, app_id) DO UPDATE SET \
+         success_count = {table}.success_count + EXCLUDED.success_count, \
+         failure_count = {table}.failure_count + EXCLUDED.failure_count, \
+         gas_sponsored = {table}.gas_sponsored + EXCLUDED.gas_sponsored, \
+         last_transaction_at = GREATEST({table}.last_transaction_at, EXCLUDED.last_transaction_at)"
+      );
+      let query = sqlx::query(&sql);
+      let query = if table == "sponsorship_hourly" {
+        query.bind(hour_bucket(*at))
+      } else {
+        query.bind(day_bucket(*at))
+      };
+      let query = query
+        .bind(user_address)
+        .bind(app_id)
+        .bind(success)
+        .bind(failure)
+        .bind(gas_sponsored)
+        .bind(last_transaction_at);
+
+      self.dbx.execute(query).await?;
+    }
+
+    Ok(())
+  }
+
+  async fn apply_login(&self, update: &RollupUpdate) -> Result<()> {
+    let RollupUpdate::Login { at, provider_type, user_id } = update else {
+      return Ok(());
+    };
+    let bucket = day_bucket(*at);
+
+    let first_today = self
+      .dbx
+      .execute(
+        sqlx::query(
+          "INSERT INTO analytics.login_daily_users (bucket, provider_type, user_id) \
+           VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        )
+        .bind(bucket)
+        .bind(provider_type)
+        .bind(user_id),
+      )
+      .await?
+      == 1;
+
+    let query = sqlx::query(
+      "INSERT INTO analytics.login_daily (bucket, provider_type, login_count, active_users) \
+       VALUES ($1, $2, 1, $3) \
+       ON CONFLICT (bucket, provider_type) DO UPDATE SET \
+       login_count = login_daily.login_count + 1, \
+       active_users = login_daily.active_users + EXCLUDED.active_users",
+    )
+    .bind(bucket)
+    .bind(provider_type)
+    .bind(i64::from(first_today));
+
+    self.dbx.execute(query).await?;
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl RollupRepository for RollupRepositoryImpl {
+  async fn apply(&self, event_id: Uuid, update: &RollupUpdate) -> Result<bool> {
+    let dbx = &self.dbx;
+
+    with_transaction!(dbx, {
+      let first_time = dbx
+        .execute(
+          sqlx::query(
+            "INSERT INTO analytics.processed_events (event_id) VALUES ($1) ON CONFLICT DO NOTHING",
+          )
+          .bind(event_id),
+        )
+        .await?
+        == 1;
+      if !first_time {
+        return Ok(false);
+      }
+
+      match update {
+        RollupUpdate::Sponsorship { .. } => self.apply_sponsorship(update).await?,
+        RollupUpdate::Login { .. } => self.apply_login(update).await?,
+      }
+      Ok::<_, Error>(true)
+    })
+  }
+}
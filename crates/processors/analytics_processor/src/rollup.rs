@@ -0,0 +1,152 @@
+use jd_contracts::{
+  sui::dtos::events::{SponsoredTransactionExecutedEvent, SponsoredTransactionFailedEvent},
+  user::dtos::events::UserLoggedInEvent,
+};
+use jd_messaging::EventEnvelope;
+use time::{Date, OffsetDateTime, Time, UtcOffset};
+use uuid::Uuid;
+
+use crate::Result;
+
+/// Rollup key for sponsorships whose client did not name an app
+pub const UNSPECIFIED_APP: &str = "unspecified";
+
+/// Change one event makes to the rollup tables
+#[derive(Debug, Clone, PartialEq)]
+pub enum RollupUpdate {
+  Sponsorship {
+    at: OffsetDateTime,
+    user_address: String,
+    app_id: String,
+    succeeded: bool,
+    /// Gas used in MIST, zero for failures since nothing was spent
+    gas_sponsored: i64,
+  },
+  Login {
+    at: OffsetDateTime,
+    provider_type: String,
+    user_id: Uuid,
+  },
+}
+
+impl RollupUpdate {
+  /// Maps an event to its update, `None` for events that are not tracked
+  pub fn from_envelope(envelope: &EventEnvelope) -> Result<Option<Self>> {
+    if let Some(event) = envelope.decode::<SponsoredTransactionExecutedEvent>()? {
+      return Ok(Some(Self::Sponsorship {
+        at: event.executed_at,
+        user_address: event.user_address,
+        app_id: event.app_id.unwrap_or_else(|| UNSPECIFIED_APP.to_string()),
+        succeeded: true,
+        // Older events only carry the budget, the most the sponsor could have paid
+        gas_sponsored: i64::try_from(event.gas_used.unwrap_or(event.gas_budget))
+          .unwrap_or(i64::MAX),
+      }));
+    }
+
+    if let Some(event) = envelope.decode::<SponsoredTransactionFailedEvent>()? {
+      return Ok(Some(Self::Sponsorship {
+        at: event.failed_at,
+        user_address: event.user_address,
+        app_id: event.app_id.unwrap_or_else(|| UNSPECIFIED_APP.to_string()),
+        succeeded: false,
+        gas_sponsored: 0,
+      }));
+    }
+
+    if let Some(event) = envelope.decode::<UserLoggedInEvent>()? {
+      return Ok(Some(Self::Login {
+        at: event.logged_in_at,
+        provider_type: event.provider_type,
+        user_id: event.user_id,
+      }));
+    }
+
+    Ok(None)
+  }
+}
+
+/// Start of the UTC hour `at` falls in
+pub fn hour_bucket(at: OffsetDateTime) -> OffsetDateTime {
+  let at = at.to_offset(UtcOffset::UTC);
+  at.replace_time(Time::from_hms(at.hour(), 0, 0).expect("hour of a valid time"))
+}
+
+/// UTC day `at` falls in
+pub fn day_bucket(at: OffsetDateTime) -> Date {
+  at.to_offset(UtcOffset::UTC).date()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use time::macros::{date, datetime};
+
+  #[test]
+  fn buckets_are_utc() {
+    let at = datetime!(2026-03-01 00:45:10 +02:00);
+
+    assert_eq!(hour_bucket(at), datetime!(2026-02-28 22:00:00 UTC));
+    assert_eq!(day_bucket(at), date!(2026 - 02 - 28));
+  }
+
+  #[test]
+  fn executed_sponsorship_counts_gas_used_not_budget() {
+    let event = SponsoredTransactionExecutedEvent {
+      user_address: "0xabc".to_string(),
+      app_id: Some("game".to_string()),
+      gas_budget: 5_000,
+      gas_used: Some(1_200),
+      tx_digest: "digest".to_string(),
+      executed_at: OffsetDateTime::now_utc(),
+    };
+    let envelope = EventEnvelope::new(&event).unwrap();
+
+    let update = RollupUpdate::from_envelope(&envelope).unwrap().unwrap();
+
+    let RollupUpdate::Sponsorship { succeeded, gas_sponsored, .. } = update else {
+      panic!("expected a sponsorship update");
+    };
+    assert!(succeeded);
+    assert_eq!(gas_sponsored, 1_200);
+  }
+
+  #[test]
+  fn failed_sponsorship_counts_no_gas() {
+    let event = SponsoredTransactionFailedEvent {
+      user_address: "0xabc".to_string(),
+      app_id: None,
+      gas_budget: 5_000,
+      reason: "pool empty".to_string(),
+      failed_at: OffsetDateTime::now_utc(),
+    };
+    let envelope = EventEnvelope::new(&event).unwrap();
+
+    let update = RollupUpdate::from_envelope(&envelope).unwrap().unwrap();
+
+    let RollupUpdate::Sponsorship { succeeded, gas_sponsored, app_id, .. } = update else {
+      panic!("expected a sponsorship update");
+    };
+    assert!(!succeeded);
+    assert_eq!(gas_sponsored, 0);
+    assert_eq!(app_id, UNSPECIFIED_APP);
+  }
+
+  #[test]
+  fn login_keeps_provider_type() {
+    let event = UserLoggedInEvent {
+      user_id: Uuid::new_v4(),
+      session_id: Uuid::new_v4(),
+      provider_type: "wallet".to_string(),
+      logged_in_at: OffsetDateTime::now_utc(),
+    };
+    let envelope = EventEnvelope::new(&event).unwrap();
+
+    let update = RollupUpdate::from_envelope(&envelope).unwrap().unwrap();
+
+    assert!(matches!(
+      update,
+      RollupUpdate::Login { provider_type, .. } if provider_type == "wallet"
+    ));
+  }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+use jd_storage::{Db, dbx::Dbx};
+
+use super::NotificationChannel;
+use crate::notification::{ChannelKind, Notification, Recipient};
+use crate::{Error, Result};
+
+/// Writes to `unified_auth.notification_inbox`, a repeated event is stored once
+pub struct InboxChannel {
+  dbx: Dbx,
+}
+
+impl InboxChannel {
+  pub fn new(db: Db) -> Result<Self> {
+    Ok(Self { dbx: Dbx::new(db, false)? })
+  }
+}
+
+#[async_trait]
+impl NotificationChannel for InboxChannel {
+  fn kind(&self) -> ChannelKind {
+    ChannelKind::InApp
+  }
+
+  fn can_reach(&self, recipient: &Recipient) -> bool {
+    recipient.user_id.is_some()
+  }
+
+  async fn deliver(&self, recipient: &Recipient, notification: &Notification) -> Result<()> {
+    let user_id = recipient.user_id.ok_or_else(|| Error::delivery(self.kind(), "no user id"))?;
+
+    let query = sqlx::query(
+      "INSERT INTO unified_auth.notification_inbox (user_id, event_id, event_type, title, body) \
+       VALUES ($1, $2, $3, $4, $5) ON CONFLICT (event_id, user_id) DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(notification.event_id)
+    .bind(&notification.event_type)
+    .bind(&notification.title)
+    .bind(&notification.body);
+
+    self.dbx.execute(query).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,21 @@
+use async_trait::async_trait;
+
+use crate::Result;
+use crate::notification::{ChannelKind, Notification, Recipient};
+
+mod inbox;
+mod smtp;
+mod webhook;
+
+pub use inbox::InboxChannel;
+pub use smtp::SmtpChannel;
+pub use webhook::WebhookChannel;
+
+/// Delivers rendered notifications. Deliveries may be repeated after a failure or a restart.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+  fn kind(&self) -> ChannelKind;
+  /// Whether the recipient has an address on this channel
+  fn can_reach(&self, recipient: &Recipient) -> bool;
+  async fn deliver(&self, recipient: &Recipient, notification: &Notification) -> Result<()>;
+}
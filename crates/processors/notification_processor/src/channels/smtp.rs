@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use jd_utils::config::SmtpSettings;
+use lettre::{
+  AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+  message::{Mailbox, header::ContentType},
+  transport::smtp::authentication::Credentials,
+};
+
+use super::NotificationChannel;
+use crate::notification::{ChannelKind, Notification, Recipient};
+use crate::{Error, Result};
+
+/// Sends plain text mail through an SMTP relay
+pub struct SmtpChannel {
+  transport: AsyncSmtpTransport<Tokio1Executor>,
+  from: Mailbox,
+}
+
+impl SmtpChannel {
+  pub fn new(settings: &SmtpSettings) -> Result<Self> {
+    let transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+      .map_err(|e| Error::delivery(ChannelKind::Email, e))?
+      .port(settings.port)
+      .credentials(Credentials::new(settings.username.clone(), settings.password.clone()))
+      .build();
+    let from = settings.from.parse().map_err(|e| Error::delivery(ChannelKind::Email, e))?;
+
+    Ok(Self { transport, from })
+  }
+}
+
+#[async_trait]
+impl NotificationChannel for SmtpChannel {
+  fn kind(&self) -> ChannelKind {
+    ChannelKind::Email
+  }
+
+  fn can_reach(&self, recipient: &Recipient) -> bool {
+    recipient.email.is_some()
+  }
+
+  async fn deliver(&self, recipient: &Recipient, notification: &Notification) -> Result<()> {
+    let to: Mailbox = recipient
+      .email
+      .as_deref()
+      .ok_or_else(|| Error::delivery(self.kind(), "no email address"))?
+      .parse()
+      .map_err(|e| Error::delivery(self.kind(), e))?;
+
+    let message = Message::builder()
+      .from(self.from.clone())
+      .to(to)
+      .subject(&notification.title)
+      .header(ContentType::TEXT_PLAIN)
+      .body(notification.body.clone())
+      .map_err(|e| Error::delivery(self.kind(), e))?;
+
+    self.transport.send(message).await.map_err(|e| Error::delivery(self.kind(), e))?;
+    Ok(())
+  }
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::NotificationChannel;
+use crate::notification::{ChannelKind, Notification, Recipient};
+use crate::{Error, Result};
+
+/// Posts every notification as JSON to one configured URL, e.g. a chat or CRM integration
+pub struct WebhookChannel {
+  client: reqwest::Client,
+  url: String,
+}
+
+impl WebhookChannel {
+  pub fn new(url: impl Into<String>) -> Result<Self> {
+    let client = reqwest::Client::builder()
+      .timeout(Duration::from_secs(10))
+      .build()
+      .map_err(|e| Error::delivery(ChannelKind::Webhook, e))?;
+
+    Ok(Self { client, url: url.into() })
+  }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+  fn kind(&self) -> ChannelKind {
+    ChannelKind::Webhook
+  }
+
+  fn can_reach(&self, _recipient: &Recipient) -> bool {
+    true
+  }
+
+  async fn deliver(&self, recipient: &Recipient, notification: &Notification) -> Result<()> {
+    let body = json!({
+      "event_id": notification.event_id,
+      "event_type": notification.event_type,
+      "user_id": recipient.user_id,
+      "title": notification.title,
+      "body": notification.body,
+    });
+
+    self
+      .client
+      .post(&self.url)
+      // Lets the receiver drop repeated deliveries
+      .header("Idempotency-Key", notification.event_id.to_string())
+      .json(&body)
+      .send()
+      .await
+      .and_then(reqwest::Response::error_for_status)
+      .map_err(|e| Error::delivery(self.kind(), e))?;
+    Ok(())
+  }
+}
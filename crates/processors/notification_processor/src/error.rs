@@ -0,0 +1,39 @@
+use serde::Serialize;
+use serde_with::{DisplayFromStr, serde_as};
+
+use crate::notification::ChannelKind;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[serde_as]
+#[derive(Debug, Serialize, thiserror::Error)]
+#[serde(tag = "type", content = "data")]
+pub enum Error {
+  // -- Rendering
+  #[error("Template variable '{0}' is missing")]
+  MissingTemplateVar(String),
+
+  // -- Delivery
+  #[error("Delivery through {channel} failed: {reason}")]
+  Delivery { channel: String, reason: String },
+
+  // -- External Dependencies
+  #[error(transparent)]
+  Messaging(#[from] jd_messaging::Error),
+
+  #[error("Serialization error: {0}")]
+  Serde(
+    #[from]
+    #[serde_as(as = "DisplayFromStr")]
+    serde_json::Error,
+  ),
+
+  #[error(transparent)]
+  Dbx(#[from] jd_storage::dbx::Error),
+}
+
+impl Error {
+  pub fn delivery(channel: ChannelKind, reason: impl ToString) -> Self {
+    Self::Delivery { channel: channel.to_string(), reason: reason.to_string() }
+  }
+}
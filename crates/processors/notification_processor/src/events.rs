@@ -0,0 +1,185 @@
+use jd_contracts::{
+  sui::dtos::events::SponsoredTransactionFailedEvent,
+  user::dtos::events::{
+    NewDeviceLoginEvent, UserCreatedEvent, UserDirectory, UserEmailChangedEvent,
+  },
+};
+use jd_messaging::EventEnvelope;
+use jd_utils::time::format_time;
+
+use crate::Result;
+use crate::notification::RecipientRef;
+use crate::templates::{self, Template};
+
+/// An event the processor notifies about, ready to be rendered
+#[derive(Debug, Clone)]
+pub struct PreparedNotification {
+  pub recipient: RecipientRef,
+  /// Used instead of the address on file, e.g. to alert the previous address of an email change
+  pub email: Option<String>,
+  pub template: Template,
+  pub vars: Vec<(&'static str, String)>,
+}
+
+/// Maps an event to its notification, `None` for events nobody is notified about
+pub fn prepare(envelope: &EventEnvelope) -> Result<Option<PreparedNotification>> {
+  if let Some(event) = envelope.decode::<UserCreatedEvent>()? {
+    let recipient = match event.directory {
+      UserDirectory::Profile => RecipientRef::ProfileUser(event.user_id),
+      UserDirectory::UnifiedAuth => RecipientRef::UserId(event.user_id),
+    };
+    // Wallet sign-ups have no address, they are named by their username
+    let account = event.email.clone().unwrap_or(event.username);
+    return Ok(Some(PreparedNotification {
+      recipient,
+      email: event.email,
+      template: templates::USER_CREATED,
+      vars: vec![("account", account), ("created_at", format_time(event.created_at))],
+    }));
+  }
+
+  if let Some(event) = envelope.decode::<UserEmailChangedEvent>()? {
+    let old_email = event.old_email.clone().unwrap_or_else(|| "no address".to_string());
+    return Ok(Some(PreparedNotification {
+      recipient: RecipientRef::UserId(event.user_id),
+      // The previous owner of the account is the one who has to hear about it
+      email: event.old_email.or_else(|| Some(event.new_email.clone())),
+      template: templates::EMAIL_CHANGED,
+      vars: vec![
+        ("old_email", old_email),
+        ("new_email", event.new_email),
+        ("changed_at", format_time(event.changed_at)),
+      ],
+    }));
+  }
+
+  if let Some(event) = envelope.decode::<NewDeviceLoginEvent>()? {
+    return Ok(Some(PreparedNotification {
+      recipient: RecipientRef::UserId(event.user_id),
+      email: None,
+      template: templates::NEW_DEVICE_LOGIN,
+      vars: vec![
+        ("user_agent", event.user_agent),
+        ("ip_address", event.ip_address.unwrap_or_else(|| "unknown address".to_string())),
+        ("logged_in_at", format_time(event.logged_in_at)),
+        ("session_id", event.session_id.to_string()),
+      ],
+    }));
+  }
+
+  if let Some(event) = envelope.decode::<SponsoredTransactionFailedEvent>()? {
+    return Ok(Some(PreparedNotification {
+      recipient: RecipientRef::Wallet(event.user_address.clone()),
+      email: None,
+      template: templates::SPONSORED_TRANSACTION_FAILED,
+      vars: vec![
+        ("user_address", event.user_address),
+        ("gas_budget", event.gas_budget.to_string()),
+        ("reason", event.reason),
+      ],
+    }));
+  }
+
+  Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use jd_utils::time::now_utc;
+  use uuid::Uuid;
+
+  #[test]
+  fn email_change_alerts_previous_address() {
+    let event = UserEmailChangedEvent {
+      user_id: Uuid::new_v4(),
+      old_email: Some("old@example.com".to_string()),
+      new_email: "new@example.com".to_string(),
+      changed_at: now_utc(),
+    };
+    let envelope = EventEnvelope::new(&event).unwrap();
+
+    let prepared = prepare(&envelope).unwrap().unwrap();
+
+    assert_eq!(prepared.email.as_deref(), Some("old@example.com"));
+    let (_, body) = prepared.template.render(&prepared.vars).unwrap();
+    assert!(body.contains("old@example.com") && body.contains("new@example.com"));
+  }
+
+  #[test]
+  fn every_prepared_template_renders() {
+    let user_id = Uuid::new_v4();
+    let envelopes = [
+      EventEnvelope::new(&UserCreatedEvent {
+        user_id,
+        directory: UserDirectory::UnifiedAuth,
+        email: None,
+        username: "wallet_abababab".to_string(),
+        created_at: now_utc(),
+      }),
+      EventEnvelope::new(&NewDeviceLoginEvent {
+        user_id,
+        session_id: Uuid::new_v4(),
+        ip_address: None,
+        user_agent: "curl/8.0".to_string(),
+        logged_in_at: now_utc(),
+      }),
+      EventEnvelope::new(&SponsoredTransactionFailedEvent {
+        user_address: "0x1".to_string(),
+        app_id: None,
+        gas_budget: 10,
+        reason: "pool empty".to_string(),
+        failed_at: now_utc(),
+      }),
+    ];
+
+    for envelope in envelopes {
+      let prepared = prepare(&envelope.unwrap()).unwrap().unwrap();
+      prepared.template.render(&prepared.vars).unwrap();
+    }
+  }
+
+  #[test]
+  fn user_created_is_resolved_in_the_table_of_its_producer() {
+    let user_id = Uuid::new_v4();
+    let created = |directory| {
+      EventEnvelope::new(&UserCreatedEvent {
+        user_id,
+        directory,
+        email: Some("a@example.com".to_string()),
+        username: "ada".to_string(),
+        created_at: now_utc(),
+      })
+      .unwrap()
+    };
+
+    let profile = prepare(&created(UserDirectory::Profile)).unwrap().unwrap();
+    let unified = prepare(&created(UserDirectory::UnifiedAuth)).unwrap().unwrap();
+
+    assert_eq!(profile.recipient, RecipientRef::ProfileUser(user_id));
+    assert_eq!(unified.recipient, RecipientRef::UserId(user_id));
+  }
+
+  #[test]
+  fn user_created_before_the_directory_field_is_a_profile_user() {
+    let user_id = Uuid::new_v4();
+    let mut envelope = EventEnvelope::new(&UserCreatedEvent {
+      user_id,
+      directory: UserDirectory::Profile,
+      email: Some("a@example.com".to_string()),
+      username: String::new(),
+      created_at: now_utc(),
+    })
+    .unwrap();
+    envelope.payload = serde_json::json!({
+      "user_id": user_id,
+      "email": "a@example.com",
+      "created_at": "2025-01-01T00:00:00Z",
+    });
+
+    let prepared = prepare(&envelope).unwrap().unwrap();
+
+    assert_eq!(prepared.recipient, RecipientRef::ProfileUser(user_id));
+    assert_eq!(prepared.email.as_deref(), Some("a@example.com"));
+  }
+}
@@ -1,14 +1,15 @@
-pub fn add(left: u64, right: u64) -> u64 {
-  left + right
-}
+// -->>> Region:: START  --->>>  Public Modules
+pub mod channels;
+pub mod events;
+pub mod notification;
+pub mod processor;
+pub mod repository;
+pub mod templates;
+// <<<-- Region:: END    <<<---  Public Modules
 
-#[cfg(test)]
-mod tests {
-  use super::*;
+mod error;
 
-  #[test]
-  fn it_works() {
-    let result = add(2, 2);
-    assert_eq!(result, 4);
-  }
-}
+pub use error::{Error, Result};
+pub use notification::{ChannelKind, Notification, Recipient, RecipientRef};
+pub use processor::{NotificationProcessor, RetryPolicy};
+pub use repository::{DeadLetter, NotificationRepository, NotificationRepositoryImpl};
@@ -0,0 +1,57 @@
+use std::{sync::Arc, time::Duration};
+
+use dotenv::dotenv;
+use jd_messaging::{RedisStreamsSubscriber, unique_consumer_name};
+use jd_tracing::tracing_init;
+use jd_utils::config::Config;
+use notification_processor::{
+  NotificationProcessor, NotificationRepositoryImpl, RetryPolicy,
+  channels::{InboxChannel, SmtpChannel, WebhookChannel},
+};
+use tracing::info;
+
+#[tokio::main]
+async fn main() {
+  dotenv().ok();
+
+  let _ = tracing_init();
+
+  let config = Config::from_env().expect("Loading env failed");
+  let settings = &config.notifications;
+
+  let db = jd_storage::new_db_pool().await.expect("Failed to connect to Postgres");
+  let redis = Arc::new(redis::Client::open(config.redis.addr.clone()).expect("Invalid Redis URL"));
+
+  let consumer_name = settings
+    .consumer_name
+    .clone()
+    .unwrap_or_else(|| unique_consumer_name(&settings.consumer_group));
+  let subscriber = RedisStreamsSubscriber::new(
+    redis,
+    config.messaging.stream_key.clone(),
+    settings.consumer_group.clone(),
+    consumer_name,
+  )
+  .with_min_idle(Duration::from_millis(config.messaging.claim_min_idle_ms));
+  let repository =
+    NotificationRepositoryImpl::new(db.clone()).expect("Failed to create repository");
+
+  let mut processor = NotificationProcessor::new(subscriber, repository, &settings.consumer_group)
+    .with_channel(InboxChannel::new(db).expect("Failed to create inbox channel"))
+    .with_retry(RetryPolicy::from(settings))
+    .with_batch_size(settings.batch_size);
+
+  if let Some(smtp) = &settings.smtp {
+    processor = processor.with_channel(SmtpChannel::new(smtp).expect("Invalid SMTP settings"));
+  }
+  if let Some(url) = &settings.webhook_url {
+    processor = processor.with_channel(WebhookChannel::new(url).expect("Invalid webhook settings"));
+  }
+
+  info!("Notification processor consuming '{}'", config.messaging.stream_key);
+
+  tokio::select! {
+    _ = processor.run() => {}
+    _ = tokio::signal::ctrl_c() => info!("Notification processor shutting down"),
+  }
+}
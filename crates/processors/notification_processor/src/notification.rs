@@ -0,0 +1,54 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+/// How a notification reaches the user, stored as `channel` in the preference table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelKind {
+  Email,
+  Webhook,
+  InApp,
+}
+
+impl ChannelKind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Email => "email",
+      Self::Webhook => "webhook",
+      Self::InApp => "in_app",
+    }
+  }
+}
+
+impl fmt::Display for ChannelKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// How an event identifies the user it is about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecipientRef {
+  /// A user of `unified_auth.users`
+  UserId(Uuid),
+  /// A user of `profile.users`, only reachable by email
+  ProfileUser(Uuid),
+  Wallet(String),
+}
+
+/// Addresses a notification can be delivered to. `user_id` is only set for users of
+/// `unified_auth`, the in-app inbox and preferences need it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recipient {
+  pub user_id: Option<Uuid>,
+  pub email: Option<String>,
+}
+
+/// Rendered message for one event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+  pub event_id: Uuid,
+  pub event_type: String,
+  pub title: String,
+  pub body: String,
+}
@@ -0,0 +1,385 @@
+use std::time::Duration;
+
+use jd_messaging::{EventEnvelope, EventSubscriber};
+use jd_utils::config::NotificationConfig;
+use tracing::{error, warn};
+
+use crate::Result;
+use crate::channels::NotificationChannel;
+use crate::events::prepare;
+use crate::notification::{Notification, Recipient};
+use crate::repository::{DeadLetter, NotificationRepository};
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+  /// Attempts per channel, the first one included
+  pub max_attempts: u32,
+  /// Delay before the second attempt, doubled for every further one
+  pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+  fn delay(&self, attempt: u32) -> Duration {
+    self.base_delay.saturating_mul(1 << attempt.saturating_sub(1).min(10))
+  }
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self { max_attempts: 5, base_delay: Duration::from_millis(500) }
+  }
+}
+
+impl From<&NotificationConfig> for RetryPolicy {
+  fn from(config: &NotificationConfig) -> Self {
+    Self {
+      max_attempts: config.max_attempts.max(1),
+      base_delay: Duration::from_millis(config.retry_base_ms),
+    }
+  }
+}
+
+/// Turns domain events into notifications and delivers them through every channel the
+/// recipient can be reached on and has not opted out of.
+///
+/// An event is acknowledged once every channel delivered it or gave up on it; deliveries that
+/// exhausted their retries, and events that cannot be rendered, go to the dead-letter table.
+/// Infrastructure errors leave the event unacknowledged so it is handed out again later, the
+/// rest of its batch is still handled.
+pub struct NotificationProcessor<S, R> {
+  subscriber: S,
+  repository: R,
+  channels: Vec<Box<dyn NotificationChannel>>,
+  retry: RetryPolicy,
+  consumer: String,
+  batch_size: usize,
+}
+
+impl<S: EventSubscriber, R: NotificationRepository> NotificationProcessor<S, R> {
+  pub fn new(subscriber: S, repository: R, consumer: impl Into<String>) -> Self {
+    Self {
+      subscriber,
+      repository,
+      channels: Vec::new(),
+      retry: RetryPolicy::default(),
+      consumer: consumer.into(),
+      batch_size: 32,
+    }
+  }
+
+  pub fn with_channel(mut self, channel: impl NotificationChannel + 'static) -> Self {
+    self.channels.push(Box::new(channel));
+    self
+  }
+
+  pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+    self.retry = retry;
+    self
+  }
+
+  pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+    self.batch_size = batch_size.max(1);
+    self
+  }
+
+  /// Handles one batch and returns the number of events acknowledged
+  pub async fn process_batch(&self) -> Result<usize> {
+    let deliveries = self.subscriber.receive(self.batch_size).await?;
+
+    let mut acknowledged = 0;
+    for delivery in &deliveries {
+      let event_id = delivery.envelope.event_id;
+      if let Err(err) = self.handle(&delivery.envelope).await {
+        warn!(%event_id, "Notification left for redelivery: {err}");
+        continue;
+      }
+      match self.subscriber.ack(delivery).await {
+        Ok(()) => acknowledged += 1,
+        Err(err) => warn!(%event_id, "Failed to acknowledge notification event: {err}"),
+      }
+    }
+
+    Ok(acknowledged)
+  }
+
+  pub async fn run(&self) {
+    loop {
+      if let Err(err) = self.process_batch().await {
+        error!("Notification batch failed: {err}");
+        tokio::time::sleep(self.retry.base_delay).await;
+      }
+    }
+  }
+
+  pub async fn handle(&self, envelope: &EventEnvelope) -> Result<()> {
+    let prepared = match prepare(envelope) {
+      Ok(Some(prepared)) => prepared,
+      Ok(None) => return Ok(()),
+      Err(err) => return self.dead_letter(envelope, None, 1, err.to_string()).await,
+    };
+
+    let (title, body) = match prepared.template.render(&prepared.vars) {
+      Ok(rendered) => rendered,
+      Err(err) => return self.dead_letter(envelope, None, 1, err.to_string()).await,
+    };
+    let notification = Notification {
+      event_id: envelope.event_id,
+      event_type: envelope.event_type.clone(),
+      title,
+      body,
+    };
+
+    let mut recipient =
+      self.repository.find_recipient(&prepared.recipient).await?.unwrap_or_default();
+    if prepared.email.is_some() {
+      recipient.email = prepared.email;
+    }
+
+    for channel in &self.channels {
+      if !channel.can_reach(&recipient) {
+        continue;
+      }
+      let enabled = match recipient.user_id {
+        Some(user_id) => {
+          self.repository.is_enabled(user_id, &envelope.event_type, channel.kind()).await?
+        }
+        None => true,
+      };
+      if !enabled {
+        continue;
+      }
+
+      self.deliver(channel.as_ref(), &recipient, &notification, envelope).await?;
+    }
+
+    Ok(())
+  }
+
+  async fn deliver(
+    &self,
+    channel: &dyn NotificationChannel,
+    recipient: &Recipient,
+    notification: &Notification,
+    envelope: &EventEnvelope,
+  ) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+      match channel.deliver(recipient, notification).await {
+        Ok(()) => return Ok(()),
+        Err(err) if attempt >= self.retry.max_attempts => {
+          error!(
+            event_id = %envelope.event_id,
+            channel = %channel.kind(),
+            "Giving up on notification: {err}"
+          );
+          return self.dead_letter(envelope, Some(channel), attempt, err.to_string()).await;
+        }
+        Err(err) => {
+          warn!(
+            event_id = %envelope.event_id,
+            channel = %channel.kind(),
+            attempt,
+            "Notification delivery failed: {err}"
+          );
+          tokio::time::sleep(self.retry.delay(attempt)).await;
+          attempt += 1;
+        }
+      }
+    }
+  }
+
+  async fn dead_letter(
+    &self,
+    envelope: &EventEnvelope,
+    channel: Option<&dyn NotificationChannel>,
+    attempts: u32,
+    last_error: String,
+  ) -> Result<()> {
+    self
+      .repository
+      .dead_letter(DeadLetter {
+        consumer: self.consumer.clone(),
+        channel: channel.map(|channel| channel.kind()),
+        envelope: envelope.clone(),
+        attempts,
+        last_error,
+      })
+      .await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+  use std::sync::{Arc, Mutex};
+
+  use async_trait::async_trait;
+  use jd_contracts::user::dtos::events::NewDeviceLoginEvent;
+  use jd_messaging::{EventPublisher, InMemoryEventBus, InMemorySubscriber};
+  use jd_utils::time::now_utc;
+  use uuid::Uuid;
+
+  use super::*;
+  use crate::notification::{ChannelKind, RecipientRef};
+
+  type TestProcessor = NotificationProcessor<InMemorySubscriber, Arc<FakeRepository>>;
+
+  #[derive(Default)]
+  struct FakeRepository {
+    opted_out: HashSet<ChannelKind>,
+    /// Recipient lookups that fail before the database comes back
+    lookup_failures: Mutex<u32>,
+    dead_letters: Mutex<Vec<DeadLetter>>,
+  }
+
+  #[async_trait]
+  impl NotificationRepository for Arc<FakeRepository> {
+    async fn find_recipient(&self, recipient: &RecipientRef) -> Result<Option<Recipient>> {
+      let mut lookup_failures = self.lookup_failures.lock().unwrap();
+      if *lookup_failures > 0 {
+        *lookup_failures -= 1;
+        return Err(jd_storage::dbx::Error::from(sqlx::Error::PoolTimedOut).into());
+      }
+      let RecipientRef::UserId(user_id) = recipient else { return Ok(None) };
+      Ok(Some(Recipient { user_id: Some(*user_id), email: Some("a@example.com".to_string()) }))
+    }
+
+    async fn is_enabled(&self, _: Uuid, _: &str, channel: ChannelKind) -> Result<bool> {
+      Ok(!self.opted_out.contains(&channel))
+    }
+
+    async fn dead_letter(&self, dead_letter: DeadLetter) -> Result<()> {
+      self.dead_letters.lock().unwrap().push(dead_letter);
+      Ok(())
+    }
+  }
+
+  /// Fails the first `failures` attempts, then records deliveries
+  #[derive(Default)]
+  struct FakeChannel {
+    failures: Mutex<u32>,
+    delivered: Mutex<Vec<Notification>>,
+  }
+
+  #[async_trait]
+  impl NotificationChannel for Arc<FakeChannel> {
+    fn kind(&self) -> ChannelKind {
+      ChannelKind::Email
+    }
+
+    fn can_reach(&self, recipient: &Recipient) -> bool {
+      recipient.email.is_some()
+    }
+
+    async fn deliver(&self, _: &Recipient, notification: &Notification) -> Result<()> {
+      let mut failures = self.failures.lock().unwrap();
+      if *failures > 0 {
+        *failures -= 1;
+        return Err(crate::Error::delivery(ChannelKind::Email, "relay down"));
+      }
+      self.delivered.lock().unwrap().push(notification.clone());
+      Ok(())
+    }
+  }
+
+  const REDELIVER_AFTER: Duration = Duration::from_millis(100);
+
+  async fn setup(
+    repository: FakeRepository,
+    failures: u32,
+  ) -> (TestProcessor, Arc<FakeRepository>, Arc<FakeChannel>) {
+    setup_with_events(repository, failures, 1).await
+  }
+
+  async fn setup_with_events(
+    repository: FakeRepository,
+    failures: u32,
+    events: usize,
+  ) -> (TestProcessor, Arc<FakeRepository>, Arc<FakeChannel>) {
+    let bus = InMemoryEventBus::new();
+    for _ in 0..events {
+      let event = NewDeviceLoginEvent {
+        user_id: Uuid::new_v4(),
+        session_id: Uuid::new_v4(),
+        ip_address: Some("203.0.113.7".to_string()),
+        user_agent: "Firefox".to_string(),
+        logged_in_at: now_utc(),
+      };
+      bus.publish(&EventEnvelope::new(&event).unwrap()).await.unwrap();
+    }
+
+    let repository = Arc::new(repository);
+    let channel = Arc::new(FakeChannel { failures: Mutex::new(failures), ..Default::default() });
+    let subscriber = bus.subscriber().with_redeliver_after(REDELIVER_AFTER);
+    let processor = NotificationProcessor::new(subscriber, repository.clone(), "test")
+      .with_channel(channel.clone())
+      .with_retry(RetryPolicy { max_attempts: 3, base_delay: Duration::ZERO });
+
+    (processor, repository, channel)
+  }
+
+  #[tokio::test]
+  async fn delivers_and_acknowledges() {
+    let (processor, _, channel) = setup(FakeRepository::default(), 0).await;
+
+    assert_eq!(processor.process_batch().await.unwrap(), 1);
+
+    let delivered = channel.delivered.lock().unwrap();
+    assert_eq!(delivered.len(), 1);
+    assert!(delivered[0].body.contains("Firefox"));
+    assert_eq!(processor.subscriber.pending(), 0);
+  }
+
+  #[tokio::test]
+  async fn retries_transient_failures() {
+    let (processor, repository, channel) = setup(FakeRepository::default(), 2).await;
+
+    processor.process_batch().await.unwrap();
+
+    assert_eq!(channel.delivered.lock().unwrap().len(), 1);
+    assert!(repository.dead_letters.lock().unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn dead_letters_after_last_attempt() {
+    let (processor, repository, channel) = setup(FakeRepository::default(), 3).await;
+
+    processor.process_batch().await.unwrap();
+
+    assert!(channel.delivered.lock().unwrap().is_empty());
+    let dead_letters = repository.dead_letters.lock().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].attempts, 3);
+    assert_eq!(dead_letters[0].channel, Some(ChannelKind::Email));
+    assert_eq!(processor.subscriber.pending(), 0);
+  }
+
+  #[tokio::test]
+  async fn skips_channels_the_user_opted_out_of() {
+    let repository =
+      FakeRepository { opted_out: HashSet::from([ChannelKind::Email]), ..Default::default() };
+    let (processor, _, channel) = setup(repository, 0).await;
+
+    processor.process_batch().await.unwrap();
+
+    assert!(channel.delivered.lock().unwrap().is_empty());
+  }
+
+  #[tokio::test]
+  async fn failed_event_is_redelivered_without_holding_back_the_batch() {
+    let repository = FakeRepository { lookup_failures: Mutex::new(1), ..Default::default() };
+    let (processor, repository, channel) = setup_with_events(repository, 0, 2).await;
+
+    // The first event hits the database outage, the second one still goes out
+    assert_eq!(processor.process_batch().await.unwrap(), 1);
+    assert_eq!(channel.delivered.lock().unwrap().len(), 1);
+    assert_eq!(processor.subscriber.pending(), 1);
+
+    tokio::time::sleep(REDELIVER_AFTER).await;
+    assert_eq!(processor.process_batch().await.unwrap(), 1);
+
+    assert_eq!(channel.delivered.lock().unwrap().len(), 2);
+    assert_eq!(processor.subscriber.pending(), 0);
+    assert!(repository.dead_letters.lock().unwrap().is_empty());
+  }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use jd_messaging::EventEnvelope;
+use jd_storage::{Db, dbx::Dbx};
+use uuid::Uuid;
+
+use crate::Result;
+use crate::notification::{ChannelKind, Recipient, RecipientRef};
+
+/// Delivery the processor gave up on
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+  pub consumer: String,
+  /// `None` when the event could not be turned into a notification at all
+  pub channel: Option<ChannelKind>,
+  pub envelope: EventEnvelope,
+  pub attempts: u32,
+  pub last_error: String,
+}
+
+#[async_trait]
+pub trait NotificationRepository: Send + Sync {
+  async fn find_recipient(&self, recipient: &RecipientRef) -> Result<Option<Recipient>>;
+  /// Channels are enabled unless the user opted out for this event type or for `*`
+  async fn is_enabled(&self, user_id: Uuid, event_type: &str, channel: ChannelKind)
+  -> Result<bool>;
+  async fn dead_letter(&self, dead_letter: DeadLetter) -> Result<()>;
+}
+
+pub struct NotificationRepositoryImpl {
+  dbx: Dbx,
+}
+
+impl NotificationRepositoryImpl {
+  pub fn new(db: Db) -> Result<Self> {
+    Ok(Self { dbx: Dbx::new(db, false)? })
+  }
+}
+
+#[async_trait]
+impl NotificationRepository for NotificationRepositoryImpl {
+  async fn find_recipient(&self, recipient: &RecipientRef) -> Result<Option<Recipient>> {
+    let query = match recipient {
+      RecipientRef::UserId(user_id) => sqlx::query_as::<_, (Uuid, Option<String>)>(
+        "SELECT user_id, email FROM unified_auth.users WHERE user_id = $1 AND deleted_at IS NULL",
+      )
+      .bind(*user_id),
+      // Inbox and preferences belong to `unified_auth` users, these only have an address
+      RecipientRef::ProfileUser(user_id) => {
+        let query = sqlx::query_as::<_, (String,)>(
+          "SELECT email FROM profile.users WHERE user_id = $1 AND is_active",
+        )
+        .bind(*user_id);
+        let found = self.dbx.fetch_optional(query).await?;
+        return Ok(found.map(|(email,)| Recipient { user_id: None, email: Some(email) }));
+      }
+      RecipientRef::Wallet(address) => sqlx::query_as::<_, (Uuid, Option<String>)>(
+        "SELECT u.user_id, u.email FROM unified_auth.user_auth_providers p \
+         JOIN unified_auth.users u ON u.user_id = p.user_id \
+         WHERE p.wallet_address = $1 AND p.status = 'active' AND u.deleted_at IS NULL",
+      )
+      .bind(address.clone()),
+    };
+
+    let found = self.dbx.fetch_optional(query).await?;
+    Ok(found.map(|(user_id, email)| Recipient { user_id: Some(user_id), email }))
+  }
+
+  async fn is_enabled(
+    &self,
+    user_id: Uuid,
+    event_type: &str,
+    channel: ChannelKind,
+  ) -> Result<bool> {
+    // A row for the event type wins over the `*` row
+    let query = sqlx::query_as::<_, (bool,)>(
+      "SELECT enabled FROM unified_auth.notification_preferences \
+       WHERE user_id = $1 AND channel = $2 AND event_type IN ($3, '*') \
+       ORDER BY event_type = '*' LIMIT 1",
+    )
+    .bind(user_id)
+    .bind(channel.as_str())
+    .bind(event_type);
+
+    let row = self.dbx.fetch_optional(query).await?;
+    Ok(row.is_none_or(|(enabled,)| enabled))
+  }
+
+  async fn dead_letter(&self, dead_letter: DeadLetter) -> Result<()> {
+    let query = sqlx::query(
+      "INSERT INTO messaging.dead_letters \
+       (consumer, channel, event_id, event_type, payload, attempts, last_error) \
+       VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(dead_letter.consumer)
+    .bind(dead_letter.channel.map(|channel| channel.as_str()))
+    .bind(dead_letter.envelope.event_id)
+    .bind(&dead_letter.envelope.event_type)
+    .bind(serde_json::to_value(&dead_letter.envelope)?)
+    .bind(dead_letter.attempts as i32)
+    .bind(dead_letter.last_error);
+
+    self.dbx.execute(query).await?;
+    Ok(())
+  }
+}
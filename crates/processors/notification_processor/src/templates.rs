@@ -0,0 +1,91 @@
+use crate::{Error, Result};
+
+/// Title and body with `{{name}}` placeholders
+#[derive(Debug, Clone, Copy)]
+pub struct Template {
+  pub title: &'static str,
+  pub body: &'static str,
+}
+
+pub const USER_CREATED: Template = Template {
+  title: "Welcome to JD",
+  body: "Your account {{account}} was created on {{created_at}}.",
+};
+
+pub const EMAIL_CHANGED: Template = Template {
+  title: "Your email address was changed",
+  body: "The email address of your account was changed from {{old_email}} to {{new_email}} on \
+         {{changed_at}}. If you did not make this change, secure your account right away.",
+};
+
+pub const NEW_DEVICE_LOGIN: Template = Template {
+  title: "New sign-in to your account",
+  body: "Your account was signed in to from {{user_agent}} ({{ip_address}}) on {{logged_in_at}}. \
+         If this was not you, revoke session {{session_id}} from your account settings.",
+};
+
+pub const SPONSORED_TRANSACTION_FAILED: Template = Template {
+  title: "Sponsored transaction failed",
+  body: "A transaction from {{user_address}} with a gas budget of {{gas_budget}} MIST could not \
+         be sponsored: {{reason}}",
+};
+
+impl Template {
+  pub fn render(&self, vars: &[(&str, String)]) -> Result<(String, String)> {
+    Ok((render(self.title, vars)?, render(self.body, vars)?))
+  }
+}
+
+/// Replaces every `{{name}}` with its value, a placeholder without a value is an error
+pub fn render(template: &str, vars: &[(&str, String)]) -> Result<String> {
+  let mut out = String::with_capacity(template.len());
+  let mut rest = template;
+
+  while let Some(start) = rest.find("{{") {
+    out.push_str(&rest[..start]);
+    let after = &rest[start + 2..];
+    let Some(end) = after.find("}}") else {
+      // Not a placeholder, keep the braces as written
+      out.push_str(&rest[start..]);
+      return Ok(out);
+    };
+
+    let name = after[..end].trim();
+    let value = vars
+      .iter()
+      .find(|(key, _)| *key == name)
+      .map(|(_, value)| value)
+      .ok_or_else(|| Error::MissingTemplateVar(name.to_string()))?;
+    out.push_str(value);
+    rest = &after[end + 2..];
+  }
+
+  out.push_str(rest);
+  Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn render_substitutes_every_placeholder() {
+    let vars = [("name", "Ada".to_string()), ("count", "3".to_string())];
+
+    let rendered = render("Hi {{name}}, {{ count }} new, bye {{name}}", &vars).unwrap();
+
+    assert_eq!(rendered, "Hi Ada, 3 new, bye Ada");
+  }
+
+  #[test]
+  fn render_rejects_missing_variable() {
+    let err = render("Hi {{name}}", &[]).unwrap_err();
+
+    assert!(matches!(err, Error::MissingTemplateVar(name) if name == "name"));
+  }
+
+  #[test]
+  fn render_keeps_unterminated_braces() {
+    assert_eq!(render("a {{b", &[]).unwrap(), "a {{b");
+  }
+}
@@ -0,0 +1,76 @@
+use axum::{
+  extract::{Json, State},
+  response::Json as ResponseJson,
+};
+use jd_core::AppState;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::application::use_cases::ManageApiKeysUseCase;
+use crate::domain::NewApiKey;
+use crate::error::{Error, Result};
+use crate::infrastructure::{ApiKeyRepositoryImpl, PermissionRepositoryImpl};
+use crate::models::{ApiKeyInfo, CreateApiKeyRequest, IssuedApiKeyResponse, RevokeApiKeyResponse};
+
+type UseCase = ManageApiKeysUseCase<ApiKeyRepositoryImpl, PermissionRepositoryImpl>;
+
+/// API key administration. The gateway checks that the caller is an administrator and
+/// passes the caller's `user_id` in.
+pub struct ApiKeyHandler;
+
+impl ApiKeyHandler {
+  fn use_case(state: AppState) -> UseCase {
+    ManageApiKeysUseCase::new(
+      ApiKeyRepositoryImpl::new(state.clone()),
+      PermissionRepositoryImpl::new(state),
+    )
+  }
+
+  pub async fn create_api_key(
+    State(state): State<AppState>,
+    admin_id: Uuid,
+    Json(request): Json<CreateApiKeyRequest>,
+  ) -> Result<ResponseJson<IssuedApiKeyResponse>> {
+    request
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let new_key = NewApiKey {
+      owner_id: request.owner_id,
+      name: request.name,
+      scopes: request.scopes,
+      rate_limit: request.rate_limit,
+      expires_at: request.expires_at,
+    };
+    let issued = Self::use_case(state).create(admin_id, new_key).await?;
+
+    Ok(ResponseJson(IssuedApiKeyResponse::from(issued)))
+  }
+
+  pub async fn list_api_keys(
+    State(state): State<AppState>,
+    owner_id: Option<Uuid>,
+  ) -> Result<ResponseJson<Vec<ApiKeyInfo>>> {
+    let api_keys = Self::use_case(state).list(owner_id).await?;
+
+    Ok(ResponseJson(api_keys.into_iter().map(ApiKeyInfo::from).collect()))
+  }
+
+  pub async fn rotate_api_key(
+    State(state): State<AppState>,
+    key_id: Uuid,
+  ) -> Result<ResponseJson<IssuedApiKeyResponse>> {
+    let issued = Self::use_case(state).rotate(key_id).await?;
+
+    Ok(ResponseJson(IssuedApiKeyResponse::from(issued)))
+  }
+
+  pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    key_id: Uuid,
+  ) -> Result<ResponseJson<RevokeApiKeyResponse>> {
+    Self::use_case(state).revoke(key_id).await?;
+
+    Ok(ResponseJson(RevokeApiKeyResponse { success: true }))
+  }
+}
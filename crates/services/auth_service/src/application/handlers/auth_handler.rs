@@ -1,37 +1,45 @@
 use axum::{
   extract::{Extension, Json, State},
-  http::HeaderMap,
-  response::Json as ResponseJson,
+  http::{HeaderMap, header},
+  response::{IntoResponse, Json as ResponseJson},
 };
 use validator::Validate;
 
 use crate::application::use_cases::{
-  GenerateNonceUseCase, RefreshTokenUseCase, ValidateTokenUseCase, VerifySignatureUseCase,
+  GenerateNonceUseCase, RefreshTokenUseCase, UnifiedAuthService, ValidateTokenUseCase,
+};
+use crate::domain::{
+  AuthUser, ClientInfo, JwtManager, NonceRepository, RefreshTokenRepository, UserRepository,
 };
-use crate::domain::{AuthUser, NonceRepository, SignatureVerifier, UserRepository};
 use crate::error::{Error, Result};
-use crate::infrastructure::{NonceRepositoryImpl, SignatureVerifierImpl, UserRepositoryImpl};
+use crate::infrastructure::{
+  NonceRepositoryImpl, OAuthClient, RefreshTokenRepositoryImpl, SessionRepositoryImpl,
+  UserRepositoryImpl,
+};
 use crate::models::{
-  NonceRequest, NonceResponse, RefreshRequest, RefreshResponse, UserInfo, VerifyRequest,
-  VerifyResponse,
+  LoginResponse, NonceRequest, NonceResponse, RefreshRequest, RefreshResponse, UserInfo,
+  VerifyRequest,
 };
 use jd_core::AppState;
 
-pub struct AuthHandler<N: NonceRepository, U: UserRepository, S: SignatureVerifier> {
+pub struct AuthHandler<N: NonceRepository, U: UserRepository, R: RefreshTokenRepository> {
   pub generate_nonce: GenerateNonceUseCase<N>,
-  pub verify_signature: VerifySignatureUseCase<N, U, S>,
-  pub refresh_token: RefreshTokenUseCase,
+  pub refresh_token: RefreshTokenUseCase<R, SessionRepositoryImpl>,
   pub validate_token: ValidateTokenUseCase<U>,
 }
 
-impl<N: NonceRepository, U: UserRepository, S: SignatureVerifier> AuthHandler<N, U, S> {
+impl<N, U, R> AuthHandler<N, U, R>
+where
+  N: NonceRepository,
+  U: UserRepository,
+  R: RefreshTokenRepository,
+{
   pub fn new(
     generate_nonce: GenerateNonceUseCase<N>,
-    verify_signature: VerifySignatureUseCase<N, U, S>,
-    refresh_token: RefreshTokenUseCase,
+    refresh_token: RefreshTokenUseCase<R, SessionRepositoryImpl>,
     validate_token: ValidateTokenUseCase<U>,
   ) -> Self {
-    Self { generate_nonce, verify_signature, refresh_token, validate_token }
+    Self { generate_nonce, refresh_token, validate_token }
   }
 
   pub async fn generate_nonce(
@@ -52,29 +60,24 @@ impl<N: NonceRepository, U: UserRepository, S: SignatureVerifier> AuthHandler<N,
     Ok(ResponseJson(response))
   }
 
+  /// Wallet sign-in, backed by `UnifiedAuthService` so the tokens name the unified user and
+  /// its session like email and OAuth logins
   pub async fn verify_signature(
     State(state): State<AppState>,
+    client_info: ClientInfo,
     Json(request): Json<VerifyRequest>,
-  ) -> Result<ResponseJson<VerifyResponse>> {
+  ) -> Result<ResponseJson<LoginResponse>> {
     request
       .validate()
       .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
 
-    let nonce_repo = NonceRepositoryImpl::new(state.clone());
-    let user_repo = UserRepositoryImpl::new(state.clone());
-    let signature_verifier = SignatureVerifierImpl::new();
-    let jwt_secret = state.config.auth_jwt_secret.clone();
-
-    let use_case =
-      VerifySignatureUseCase::new(nonce_repo, user_repo, signature_verifier, jwt_secret);
-
-    let (user, tokens) = use_case
-      .execute(&request.address, &request.signature, &request.public_key)
+    let service = UnifiedAuthService::from_state(&state, OAuthClient::new())?
+      .with_client_info(client_info);
+    let result = service
+      .login_with_wallet(request.address, request.public_key, request.signature)
       .await?;
 
-    let response = VerifyResponse { success: true, user: UserInfo::from(user), tokens };
-
-    Ok(ResponseJson(response))
+    Ok(ResponseJson(LoginResponse::from(result)))
   }
 
   pub async fn refresh_token(
@@ -85,11 +88,14 @@ impl<N: NonceRepository, U: UserRepository, S: SignatureVerifier> AuthHandler<N,
       .validate()
       .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
 
-    let jwt_secret = state.config.auth_jwt_secret.clone();
-    let use_case = RefreshTokenUseCase::new(jwt_secret);
-    let access_token = use_case.execute(&request.refresh_token).await?;
+    let refresh_repo = RefreshTokenRepositoryImpl::new(state.clone());
+    let session_repo = SessionRepositoryImpl::new(state.clone());
+    let jwt_manager = JwtManager::from_state(&state);
+    let use_case = RefreshTokenUseCase::new(refresh_repo, session_repo, jwt_manager);
+    let tokens = use_case.execute(&request.refresh_token).await?;
 
-    let response = RefreshResponse { access_token };
+    let response =
+      RefreshResponse { access_token: tokens.access_token, refresh_token: tokens.refresh_token };
 
     Ok(ResponseJson(response))
   }
@@ -105,8 +111,8 @@ impl<N: NonceRepository, U: UserRepository, S: SignatureVerifier> AuthHandler<N,
       .ok_or_else(Error::missing_auth_header)?;
 
     let user_repo = UserRepositoryImpl::new(state.clone());
-    let jwt_secret = state.config.auth_jwt_secret.clone();
-    let use_case = ValidateTokenUseCase::new(user_repo, jwt_secret);
+    let jwt_manager = JwtManager::from_state(&state);
+    let use_case = ValidateTokenUseCase::new(user_repo, jwt_manager);
 
     let token = ValidateTokenUseCase::<UserRepositoryImpl>::extract_token_from_header(auth_header)?;
     let user = use_case.execute(token).await?;
@@ -116,6 +122,13 @@ impl<N: NonceRepository, U: UserRepository, S: SignatureVerifier> AuthHandler<N,
     Ok(request)
   }
 
+  /// Public verification keys, downstream services cache them for a few minutes
+  pub async fn jwks(State(state): State<AppState>) -> Result<impl IntoResponse> {
+    let jwt_manager = JwtManager::from_state(&state);
+
+    Ok(([(header::CACHE_CONTROL, "public, max-age=300")], ResponseJson(jwt_manager.jwks())))
+  }
+
   pub async fn get_current_user(Extension(user): Extension<AuthUser>) -> ResponseJson<UserInfo> {
     ResponseJson(UserInfo::from(user))
   }
@@ -0,0 +1,50 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, FromRef, FromRequestParts};
+use axum::http::{HeaderMap, request::Parts};
+use jd_core::AppState;
+use serde_json::json;
+
+use crate::domain::ClientInfo;
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+  AppState: FromRef<S>,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    // Forwarded headers are only believed from trusted proxies, see `TrustedProxies`
+    let state = AppState::from_ref(state);
+    let ip_address = parts
+      .extensions
+      .get::<ConnectInfo<SocketAddr>>()
+      .map(|ConnectInfo(addr)| state.trusted_proxies.client_ip(&parts.headers, addr.ip()))
+      .map(|ip| ip.to_string());
+
+    Ok(ClientInfo {
+      ip_address,
+      user_agent: header_str(&parts.headers, "user-agent").map(str::to_string),
+      device_info: device_info(&parts.headers),
+    })
+  }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+  headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Client hints and an optional app supplied device id
+fn device_info(headers: &HeaderMap) -> Option<serde_json::Value> {
+  let platform = header_str(headers, "sec-ch-ua-platform").map(|v| v.trim_matches('"'));
+  let mobile = header_str(headers, "sec-ch-ua-mobile").map(|v| v == "?1");
+  let device_id = header_str(headers, "x-device-id");
+
+  if platform.is_none() && mobile.is_none() && device_id.is_none() {
+    return None;
+  }
+
+  Some(json!({ "platform": platform, "mobile": mobile, "device_id": device_id }))
+}
@@ -0,0 +1,57 @@
+use axum::{
+  extract::{Json, State},
+  response::Json as ResponseJson,
+};
+use validator::Validate;
+
+use crate::application::use_cases::UnifiedAuthService;
+use crate::domain::ClientInfo;
+use crate::error::{Error, Result};
+use crate::infrastructure::OAuthClient;
+use crate::models::{LoginRequest, LoginResponse, RegisterRequest};
+use jd_core::AppState;
+
+/// Email/password registration and login backed by `UnifiedAuthService`
+pub struct EmailAuthHandler;
+
+impl EmailAuthHandler {
+  pub async fn register(
+    State(state): State<AppState>,
+    client_info: ClientInfo,
+    Json(request): Json<RegisterRequest>,
+  ) -> Result<ResponseJson<LoginResponse>> {
+    request
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let service = UnifiedAuthService::from_state(&state, OAuthClient::new())?
+      .with_client_info(client_info);
+    let result = service
+      .register_with_email(
+        request.email.trim().to_lowercase(),
+        request.username,
+        request.password,
+        request.display_name,
+      )
+      .await?;
+
+    Ok(ResponseJson(LoginResponse::from(result)))
+  }
+
+  pub async fn login(
+    State(state): State<AppState>,
+    client_info: ClientInfo,
+    Json(request): Json<LoginRequest>,
+  ) -> Result<ResponseJson<LoginResponse>> {
+    request
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let service = UnifiedAuthService::from_state(&state, OAuthClient::new())?
+      .with_client_info(client_info);
+    let result =
+      service.login_with_email(request.email.trim().to_lowercase(), request.password).await?;
+
+    Ok(ResponseJson(LoginResponse::from(result)))
+  }
+}
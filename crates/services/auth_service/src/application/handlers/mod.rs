@@ -1,3 +1,14 @@
+pub mod api_key_handler;
 pub mod auth_handler;
+pub mod client_info;
+pub mod email_auth_handler;
+pub mod oauth_handler;
+pub mod provider_handler;
+pub mod session_handler;
 
+pub use api_key_handler::ApiKeyHandler;
 pub use auth_handler::AuthHandler;
+pub use email_auth_handler::EmailAuthHandler;
+pub use oauth_handler::OAuthHandler;
+pub use provider_handler::ProviderHandler;
+pub use session_handler::SessionHandler;
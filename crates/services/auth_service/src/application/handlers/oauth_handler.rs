@@ -0,0 +1,81 @@
+use axum::{
+  extract::{Path, Query, State},
+  response::Json as ResponseJson,
+};
+use validator::Validate;
+
+use crate::application::use_cases::{OAuthCallbackResult, UnifiedAuthService};
+use crate::domain::{AuthProviderType, ClientInfo};
+use crate::error::{Error, Result};
+use crate::infrastructure::OAuthClient;
+use crate::models::{
+  LinkProviderResponse, LoginResponse, OAuthCallbackQuery, OAuthCallbackResponse,
+  OAuthLinkResponse, OAuthLoginResponse, OAuthStartQuery, OAuthStartResponse,
+};
+use jd_core::AppState;
+
+/// Google/GitHub login backed by `UnifiedAuthService`. The callback also completes
+/// authorizations started by `ProviderHandler::link_oauth_start`.
+pub struct OAuthHandler;
+
+impl OAuthHandler {
+  pub async fn start(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthStartQuery>,
+  ) -> Result<ResponseJson<OAuthStartResponse>> {
+    query
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let provider_type = Self::provider_type(&provider)?;
+    let service =
+      UnifiedAuthService::from_state(&state, OAuthClient::from_settings(&state.config.oauth))?;
+    let authorization_url = service.initiate_oauth_login(provider_type, query.redirect_url).await?;
+
+    Ok(ResponseJson(OAuthStartResponse { authorization_url }))
+  }
+
+  pub async fn callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client_info: ClientInfo,
+    Query(query): Query<OAuthCallbackQuery>,
+  ) -> Result<ResponseJson<OAuthCallbackResponse>> {
+    let provider_type = Self::provider_type(&provider)?;
+
+    if let Some(error) = query.error {
+      let reason = query.error_description.unwrap_or(error);
+      return Err(Error::oauth_error(format!("Authorization was not granted: {}", reason)));
+    }
+
+    let (code, oauth_state) = match (query.code, query.state) {
+      (Some(code), Some(oauth_state)) => (code, oauth_state),
+      (_, None) => return Err(Error::invalid_oauth_state()),
+      (None, _) => return Err(Error::invalid_request_data("code is required")),
+    };
+
+    let service =
+      UnifiedAuthService::from_state(&state, OAuthClient::from_settings(&state.config.oauth))?
+        .with_client_info(client_info);
+    let (result, redirect_url) =
+      service.complete_oauth_callback(provider_type, code, oauth_state).await?;
+
+    let response = match result {
+      OAuthCallbackResult::Login(login) => OAuthCallbackResponse::Login(OAuthLoginResponse {
+        login: LoginResponse::from(login),
+        redirect_url,
+      }),
+      OAuthCallbackResult::Linked(link) => OAuthCallbackResponse::Linked(OAuthLinkResponse {
+        link: LinkProviderResponse::from(link),
+        redirect_url,
+      }),
+    };
+
+    Ok(ResponseJson(response))
+  }
+
+  pub(crate) fn provider_type(provider: &str) -> Result<AuthProviderType> {
+    AuthProviderType::from_oauth_name(provider).ok_or_else(Error::unsupported_oauth_provider)
+  }
+}
@@ -0,0 +1,117 @@
+use axum::{
+  extract::{Json, Path, Query, State},
+  response::Json as ResponseJson,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::application::handlers::OAuthHandler;
+use crate::application::use_cases::{LinkEmailUseCase, LinkWalletUseCase, UnifiedAuthService};
+use crate::domain::EMAIL_LINK_TTL_SECS;
+use crate::error::{Error, Result};
+use crate::infrastructure::{
+  AuthProviderRepositoryImpl, NonceRepositoryImpl, OAuthClient, SignatureVerifierImpl,
+};
+use crate::models::{
+  ConfirmEmailLinkRequest, LinkEmailRequest, LinkEmailResponse, LinkProviderResponse,
+  LinkWalletRequest, OAuthStartQuery, OAuthStartResponse, ProviderInfo, UnlinkProviderResponse,
+};
+use jd_core::AppState;
+
+/// Listing, linking and unlinking of an authenticated user's sign-in methods. The gateway
+/// resolves `user_id` from the bearer token and passes it in.
+pub struct ProviderHandler;
+
+impl ProviderHandler {
+  pub async fn list_providers(
+    State(state): State<AppState>,
+    user_id: Uuid,
+  ) -> Result<ResponseJson<Vec<ProviderInfo>>> {
+    let service = UnifiedAuthService::from_state(&state, OAuthClient::new())?;
+    let providers = service.get_user_providers(user_id).await?;
+
+    Ok(ResponseJson(providers.into_iter().map(ProviderInfo::from).collect()))
+  }
+
+  pub async fn link_wallet(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    Json(request): Json<LinkWalletRequest>,
+  ) -> Result<ResponseJson<LinkProviderResponse>> {
+    request
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let use_case = LinkWalletUseCase::new(
+      AuthProviderRepositoryImpl::new(state.clone()),
+      NonceRepositoryImpl::new(state),
+      SignatureVerifierImpl::new(),
+    );
+    let result = use_case
+      .execute(user_id, &request.address, &request.signature, &request.public_key)
+      .await?;
+
+    Ok(ResponseJson(LinkProviderResponse::from(result)))
+  }
+
+  /// Returns the provider's consent URL; its callback links instead of logging in
+  pub async fn link_oauth_start(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthStartQuery>,
+  ) -> Result<ResponseJson<OAuthStartResponse>> {
+    query
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let provider_type = OAuthHandler::provider_type(&provider)?;
+    let service =
+      UnifiedAuthService::from_state(&state, OAuthClient::from_settings(&state.config.oauth))?;
+    let authorization_url =
+      service.initiate_oauth_link(user_id, provider_type, query.redirect_url).await?;
+
+    Ok(ResponseJson(OAuthStartResponse { authorization_url }))
+  }
+
+  pub async fn link_email(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    Json(request): Json<LinkEmailRequest>,
+  ) -> Result<ResponseJson<LinkEmailResponse>> {
+    request
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let use_case = LinkEmailUseCase::from_state(&state)?;
+    use_case.request(user_id, request.email.trim().to_lowercase(), request.password).await?;
+
+    Ok(ResponseJson(LinkEmailResponse { success: true, expires_in: EMAIL_LINK_TTL_SECS }))
+  }
+
+  pub async fn confirm_email_link(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    Json(request): Json<ConfirmEmailLinkRequest>,
+  ) -> Result<ResponseJson<LinkProviderResponse>> {
+    request
+      .validate()
+      .map_err(|e| Error::invalid_request_data(&format!("Validation failed: {}", e)))?;
+
+    let use_case = LinkEmailUseCase::from_state(&state)?;
+    let result = use_case.confirm(user_id, &request.token).await?;
+
+    Ok(ResponseJson(LinkProviderResponse::from(result)))
+  }
+
+  pub async fn unlink_provider(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    provider_id: Uuid,
+  ) -> Result<ResponseJson<UnlinkProviderResponse>> {
+    let service = UnifiedAuthService::from_state(&state, OAuthClient::new())?;
+    service.remove_auth_provider(user_id, provider_id).await?;
+
+    Ok(ResponseJson(UnlinkProviderResponse { success: true }))
+  }
+}
@@ -0,0 +1,66 @@
+use axum::{extract::State, response::Json as ResponseJson};
+use uuid::Uuid;
+
+use crate::application::use_cases::ManageSessionsUseCase;
+use crate::domain::revoke_reason;
+use crate::error::Result;
+use crate::infrastructure::SessionRepositoryImpl;
+use crate::models::{RevokeSessionsResponse, SessionInfo};
+use jd_core::AppState;
+
+/// Session management for an already authenticated user. The gateway resolves
+/// `user_id` and the current session from the bearer token and passes them in.
+pub struct SessionHandler;
+
+impl SessionHandler {
+  pub async fn list_sessions(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    current_session: Option<Uuid>,
+  ) -> Result<ResponseJson<Vec<SessionInfo>>> {
+    let use_case = ManageSessionsUseCase::new(SessionRepositoryImpl::new(state));
+    let sessions = use_case.list_active(user_id).await?;
+
+    let sessions = sessions
+      .into_iter()
+      .map(|session| {
+        let is_current = Some(session.session_id) == current_session;
+        SessionInfo::from_session(session, is_current)
+      })
+      .collect();
+
+    Ok(ResponseJson(sessions))
+  }
+
+  pub async fn revoke_session(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    session_id: Uuid,
+  ) -> Result<ResponseJson<RevokeSessionsResponse>> {
+    let use_case = ManageSessionsUseCase::new(SessionRepositoryImpl::new(state));
+    use_case.revoke(user_id, session_id, revoke_reason::USER_REVOKED).await?;
+
+    Ok(ResponseJson(RevokeSessionsResponse { success: true, revoked: 1 }))
+  }
+
+  pub async fn logout(
+    State(state): State<AppState>,
+    user_id: Uuid,
+    current_session: Uuid,
+  ) -> Result<ResponseJson<RevokeSessionsResponse>> {
+    let use_case = ManageSessionsUseCase::new(SessionRepositoryImpl::new(state));
+    use_case.revoke(user_id, current_session, revoke_reason::LOGOUT).await?;
+
+    Ok(ResponseJson(RevokeSessionsResponse { success: true, revoked: 1 }))
+  }
+
+  pub async fn logout_all(
+    State(state): State<AppState>,
+    user_id: Uuid,
+  ) -> Result<ResponseJson<RevokeSessionsResponse>> {
+    let use_case = ManageSessionsUseCase::new(SessionRepositoryImpl::new(state));
+    let revoked = use_case.revoke_all(user_id).await?;
+
+    Ok(ResponseJson(RevokeSessionsResponse { success: true, revoked }))
+  }
+}
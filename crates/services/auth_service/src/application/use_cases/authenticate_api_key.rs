@@ -0,0 +1,65 @@
+use tracing::{error, warn};
+
+use crate::application::use_cases::AuthorizedUser;
+use crate::application::use_cases::authorize_request::resolve_permissions;
+use crate::domain::{API_KEY_PREFIX, ApiKey, ApiKeyRepository, PermissionRepository, hash_api_key};
+use crate::error::{Error, Result};
+
+/// Key that passed authentication, acting as its owner with the permissions of its scopes
+#[derive(Debug, Clone)]
+pub struct AuthorizedApiKey {
+  pub api_key: ApiKey,
+  pub user: AuthorizedUser,
+}
+
+pub struct AuthenticateApiKeyUseCase<K: ApiKeyRepository, P: PermissionRepository> {
+  api_key_repo: K,
+  permission_repo: P,
+}
+
+impl<K: ApiKeyRepository, P: PermissionRepository> AuthenticateApiKeyUseCase<K, P> {
+  pub fn new(api_key_repo: K, permission_repo: P) -> Self {
+    Self { api_key_repo, permission_repo }
+  }
+
+  pub async fn execute(&self, key: &str) -> Result<AuthorizedApiKey> {
+    if !key.starts_with(API_KEY_PREFIX) {
+      return Err(Error::invalid_api_key());
+    }
+
+    let api_key = self
+      .api_key_repo
+      .find_by_hash(&hash_api_key(key))
+      .await?
+      .ok_or_else(Error::invalid_api_key)?;
+
+    if api_key.is_revoked() {
+      return Err(Error::api_key_revoked());
+    }
+    if api_key.is_expired() {
+      return Err(Error::api_key_expired());
+    }
+
+    let user = self
+      .permission_repo
+      .get_user_access(api_key.owner_id)
+      .await?
+      .ok_or_else(|| {
+        error!("❌ Owner {} of API key {} not found", api_key.owner_id, api_key.prefix);
+        Error::invalid_api_key()
+      })?;
+
+    if !user.is_active {
+      return Err(Error::account_disabled());
+    }
+
+    // Scopes are capped by the role at request time, a demoted owner takes its keys along
+    let permissions = api_key.grant(resolve_permissions(&self.permission_repo, &user).await?);
+
+    if let Err(e) = self.api_key_repo.touch(api_key.key_id).await {
+      warn!("⚠️ Failed to update last use of API key {}: {}", api_key.prefix, e);
+    }
+
+    Ok(AuthorizedApiKey { api_key, user: AuthorizedUser { user, permissions, session_id: None } })
+  }
+}
@@ -0,0 +1,280 @@
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::domain::{
+  Claims, JwtManager, PermissionRepository, SessionRepository, UserAccess, UserPermission,
+};
+use crate::error::{Error, Result};
+
+/// Everything the gateway needs to build an authorization context
+#[derive(Debug, Clone)]
+pub struct AuthorizedUser {
+  pub user: UserAccess,
+  pub permissions: Vec<UserPermission>,
+  pub session_id: Option<Uuid>,
+}
+
+pub struct AuthorizeRequestUseCase<P: PermissionRepository, S: SessionRepository> {
+  permission_repo: P,
+  session_repo: S,
+  jwt_manager: JwtManager,
+}
+
+impl<P: PermissionRepository, S: SessionRepository> AuthorizeRequestUseCase<P, S> {
+  pub fn new(permission_repo: P, session_repo: S, jwt_manager: JwtManager) -> Self {
+    Self { permission_repo, session_repo, jwt_manager }
+  }
+
+  pub async fn execute(&self, token: &str) -> Result<AuthorizedUser> {
+    let claims = self.jwt_manager.validate_token(token)?;
+
+    if claims.token_type != "access" {
+      error!("❌ Invalid token type: {} (expected access)", claims.token_type);
+      return Err(Error::invalid_token());
+    }
+
+    // Only tokens issued for a unified auth user carry a subject
+    let user_id = claims.user_id().ok_or_else(Error::invalid_token)?;
+
+    self.ensure_session_active(&claims, user_id).await?;
+
+    let user = self.permission_repo.get_user_access(user_id).await?.ok_or_else(|| {
+      error!("❌ User not found for token subject: {}", user_id);
+      Error::invalid_token()
+    })?;
+
+    if !user.is_active {
+      return Err(Error::account_disabled());
+    }
+
+    let permissions = resolve_permissions(&self.permission_repo, &user).await?;
+
+    Ok(AuthorizedUser { user, permissions, session_id: claims.session_id() })
+  }
+
+  /// A signed token is only honoured while its session is neither revoked nor expired
+  async fn ensure_session_active(&self, claims: &Claims, user_id: Uuid) -> Result<()> {
+    let session_id = claims.session_id().ok_or_else(Error::invalid_token)?;
+
+    let session = self.session_repo.find_by_id(session_id).await?.ok_or_else(|| {
+      warn!("⚠️ No session recorded for id {}", session_id);
+      Error::session_revoked()
+    })?;
+
+    if session.user_id != user_id || !session.is_active() {
+      return Err(Error::session_revoked());
+    }
+
+    // Skipped while recent so authorizing a request is usually read only
+    if session.needs_touch() {
+      self.session_repo.touch(session.session_id).await.unwrap_or_else(|e| {
+        warn!("⚠️ Failed to update activity of session {}: {}", session.session_id, e);
+      });
+    }
+
+    Ok(())
+  }
+}
+
+/// Reads the permission set from the cache, falling back to Postgres.
+/// Cache failures are logged and never fail the request.
+pub(crate) async fn resolve_permissions<P: PermissionRepository>(
+  permission_repo: &P,
+  user: &UserAccess,
+) -> Result<Vec<UserPermission>> {
+  match permission_repo.get_cached_permissions(user.user_id, user.role).await {
+    Ok(Some(permissions)) => return Ok(permissions),
+    Ok(None) => {}
+    Err(e) => warn!("⚠️ Permission cache read failed for {}: {}", user.user_id, e),
+  }
+
+  let permissions = permission_repo.get_role_permissions(user.role).await?;
+
+  if let Err(e) = permission_repo.cache_permissions(user.user_id, user.role, &permissions).await {
+    warn!("⚠️ Permission cache write failed for {}: {}", user.user_id, e);
+  }
+
+  Ok(permissions)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  use async_trait::async_trait;
+  use time::OffsetDateTime;
+
+  use super::*;
+  use crate::domain::{UserRole, UserSession, UserSessionForCreate, revoke_reason};
+
+  struct Permissions;
+
+  #[async_trait]
+  impl PermissionRepository for Permissions {
+    async fn get_user_access(&self, user_id: Uuid) -> Result<Option<UserAccess>> {
+      Ok(Some(UserAccess {
+        user_id,
+        username: "ada".to_string(),
+        email: None,
+        role: UserRole::Normal,
+        is_active: true,
+      }))
+    }
+
+    async fn get_role_permissions(&self, _role: UserRole) -> Result<Vec<UserPermission>> {
+      Ok(Vec::new())
+    }
+
+    async fn update_user_role(&self, _user_id: Uuid, _role: UserRole) -> Result<()> {
+      Ok(())
+    }
+
+    async fn get_cached_permissions(
+      &self,
+      _user_id: Uuid,
+      _role: UserRole,
+    ) -> Result<Option<Vec<UserPermission>>> {
+      Ok(None)
+    }
+
+    async fn cache_permissions(
+      &self,
+      _user_id: Uuid,
+      _role: UserRole,
+      _permissions: &[UserPermission],
+    ) -> Result<()> {
+      Ok(())
+    }
+
+    async fn invalidate_permissions(&self, _user_id: Uuid) -> Result<()> {
+      Ok(())
+    }
+  }
+
+  /// Holds a single session and counts activity writes
+  #[derive(Default)]
+  struct Sessions {
+    session: Mutex<Option<UserSession>>,
+    touches: AtomicUsize,
+  }
+
+  #[async_trait]
+  impl SessionRepository for Sessions {
+    async fn create(&self, new: UserSessionForCreate) -> Result<UserSession> {
+      let created = session(new.user_id, new.session_id, time::Duration::ZERO);
+      *self.session.lock().unwrap() = Some(created.clone());
+      Ok(created)
+    }
+
+    async fn find_by_id(&self, session_id: Uuid) -> Result<Option<UserSession>> {
+      let session = self.session.lock().unwrap().clone();
+      Ok(session.filter(|s| s.session_id == session_id))
+    }
+
+    async fn list_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserSession>> {
+      let session = self.session.lock().unwrap().clone();
+      Ok(session.filter(|s| s.user_id == user_id && s.is_active()).into_iter().collect())
+    }
+
+    async fn is_unknown_client(&self, _user_id: Uuid, _user_agent: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn touch(&self, _session_id: Uuid) -> Result<()> {
+      self.touches.fetch_add(1, Ordering::SeqCst);
+      Ok(())
+    }
+
+    async fn revoke(&self, _user_id: Uuid, session_id: Uuid, reason: &str) -> Result<bool> {
+      let mut session = self.session.lock().unwrap();
+      let Some(session) = session.as_mut().filter(|s| s.session_id == session_id) else {
+        return Ok(false);
+      };
+      session.is_revoked = true;
+      session.revoked_reason = Some(reason.to_string());
+      Ok(true)
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid, reason: &str) -> Result<u64> {
+      let mut revoked = 0;
+      for session in self.list_active_for_user(user_id).await? {
+        revoked += u64::from(self.revoke(user_id, session.session_id, reason).await?);
+      }
+      Ok(revoked)
+    }
+  }
+
+  fn session(user_id: Uuid, session_id: Uuid, idle: time::Duration) -> UserSession {
+    let now = OffsetDateTime::now_utc();
+    UserSession {
+      session_id,
+      user_id,
+      provider_id: None,
+      jwt_token_id: session_id.to_string(),
+      device_info: None,
+      ip_address: None,
+      user_agent: None,
+      created_at: now - idle,
+      expires_at: now + time::Duration::days(7),
+      last_activity: now - idle,
+      is_revoked: false,
+      revoked_at: None,
+      revoked_reason: None,
+    }
+  }
+
+  /// Use case with one recorded session and an access token issued for it
+  fn setup(idle: time::Duration) -> (AuthorizeRequestUseCase<Permissions, Sessions>, String, Uuid) {
+    let jwt_manager = JwtManager::new("test-secret".to_string());
+    let (user_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let tokens = jwt_manager.generate_tokens_for_user(user_id, session_id, "", "").unwrap();
+
+    let sessions = Sessions::default();
+    *sessions.session.lock().unwrap() = Some(session(user_id, session_id, idle));
+
+    (AuthorizeRequestUseCase::new(Permissions, sessions, jwt_manager), tokens.access_token, user_id)
+  }
+
+  #[tokio::test]
+  async fn active_session_is_authorized() {
+    let (use_case, token, user_id) = setup(time::Duration::ZERO);
+
+    let authorized = use_case.execute(&token).await.unwrap();
+
+    assert_eq!(authorized.user.user_id, user_id);
+  }
+
+  #[tokio::test]
+  async fn revoked_session_rejects_its_access_token() {
+    let (use_case, token, user_id) = setup(time::Duration::ZERO);
+    let authorized = use_case.execute(&token).await.unwrap();
+
+    let session_id = authorized.session_id.unwrap();
+    use_case.session_repo.revoke(user_id, session_id, revoke_reason::LOGOUT).await.unwrap();
+
+    let err = use_case.execute(&token).await.unwrap_err();
+    assert_eq!(err.code, "SESSION_REVOKED");
+  }
+
+  #[tokio::test]
+  async fn unknown_session_rejects_the_token() {
+    let (use_case, token, _) = setup(time::Duration::ZERO);
+    *use_case.session_repo.session.lock().unwrap() = None;
+
+    let err = use_case.execute(&token).await.unwrap_err();
+
+    assert_eq!(err.code, "SESSION_REVOKED");
+  }
+
+  #[tokio::test]
+  async fn activity_is_only_written_once_stale() {
+    let (use_case, token, _) = setup(time::Duration::ZERO);
+    use_case.execute(&token).await.unwrap();
+    assert_eq!(use_case.session_repo.touches.load(Ordering::SeqCst), 0);
+
+    let (use_case, token, _) = setup(time::Duration::minutes(10));
+    use_case.execute(&token).await.unwrap();
+    assert_eq!(use_case.session_repo.touches.load(Ordering::SeqCst), 1);
+  }
+}
@@ -0,0 +1,25 @@
+use tracing::info;
+use uuid::Uuid;
+
+use crate::domain::{PermissionRepository, UserRole};
+use crate::error::Result;
+
+pub struct ChangeUserRoleUseCase<P: PermissionRepository> {
+  permission_repo: P,
+}
+
+impl<P: PermissionRepository> ChangeUserRoleUseCase<P> {
+  pub fn new(permission_repo: P) -> Self {
+    Self { permission_repo }
+  }
+
+  pub async fn execute(&self, user_id: Uuid, role: UserRole) -> Result<()> {
+    self.permission_repo.update_user_role(user_id, role).await?;
+
+    // Drop the cached permission set so the next request resolves the new role
+    self.permission_repo.invalidate_permissions(user_id).await?;
+
+    info!("✅ Role of user {} changed to {}", user_id, role);
+    Ok(())
+  }
+}
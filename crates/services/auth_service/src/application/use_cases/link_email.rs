@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use jd_contracts::user::dtos::events::UserEmailChangedEvent;
+use jd_core::{AppState, ModelManager};
+use jd_messaging::Outbox;
+use uuid::Uuid;
+
+use crate::application::use_cases::link_provider::link_provider;
+use crate::application::use_cases::unified_auth::AuthProviderResult;
+use crate::domain::{
+  AuthProviderRepository, AuthProviderType, EmailLinkRepository, PasswordManager,
+  PendingEmailLink, UnifiedUserRepository, UserAuthProvider, VerificationMailer,
+};
+use crate::error::{Error, Result};
+use crate::infrastructure::{
+  AuthProviderRepositoryImpl, EmailLinkRepositoryImpl, LogMailer, UnifiedUserRepositoryImpl,
+};
+
+/// Adds an email/password provider to an existing user. The provider is only created once the
+/// address is confirmed with the token mailed to it.
+pub struct LinkEmailUseCase<
+  U: UnifiedUserRepository,
+  P: AuthProviderRepository,
+  E: EmailLinkRepository,
+  M: VerificationMailer,
+> {
+  user_repo: U,
+  provider_repo: P,
+  email_link_repo: E,
+  mailer: M,
+  // Shared with both repositories so confirming runs in one transaction
+  mm: Arc<ModelManager>,
+  password_manager: PasswordManager,
+}
+
+impl
+  LinkEmailUseCase<
+    UnifiedUserRepositoryImpl,
+    AuthProviderRepositoryImpl,
+    EmailLinkRepositoryImpl,
+    LogMailer,
+  >
+{
+  /// Create one per request, the transaction state must not leak across callers
+  pub fn from_state(state: &AppState) -> Result<Self> {
+    let mm = Arc::new(state.mm.new_with_txn().map_err(|e| Error::database_error(&format!("{e}")))?);
+    let txn_state = AppState { mm: mm.clone(), ..state.clone() };
+
+    Ok(Self::new(
+      UnifiedUserRepositoryImpl::new(txn_state.clone()),
+      AuthProviderRepositoryImpl::new(txn_state),
+      EmailLinkRepositoryImpl::new(state.clone()),
+      LogMailer::new(),
+      mm,
+      PasswordManager::new(&state.config.password)?,
+    ))
+  }
+}
+
+impl<U, P, E, M> LinkEmailUseCase<U, P, E, M>
+where
+  U: UnifiedUserRepository,
+  P: AuthProviderRepository,
+  E: EmailLinkRepository,
+  M: VerificationMailer,
+{
+  pub fn new(
+    user_repo: U,
+    provider_repo: P,
+    email_link_repo: E,
+    mailer: M,
+    mm: Arc<ModelManager>,
+    password_manager: PasswordManager,
+  ) -> Self {
+    Self { user_repo, provider_repo, email_link_repo, mailer, mm, password_manager }
+  }
+
+  /// Checks the address and password up front and mails a verification token
+  pub async fn request(&self, user_id: Uuid, email: String, password: String) -> Result<()> {
+    let user = self.user_repo.find_by_id(user_id).await?.ok_or_else(Error::user_not_found)?;
+
+    let current = self.provider_repo.find_for_user(user_id, AuthProviderType::Email).await?;
+    if current.filter(UserAuthProvider::is_active).is_some() {
+      return Err(Error::provider_already_linked());
+    }
+
+    // The login email is the account's primary email, it cannot differ from it
+    if user.email.as_deref().is_some_and(|primary| primary != email) {
+      return Err(Error::invalid_request_data("email must match the account email"));
+    }
+    let owner = self.user_repo.find_by_email(&email).await?;
+    if owner.is_some_and(|owner| owner.user_id != user_id) {
+      return Err(Error::email_already_exists());
+    }
+
+    let email_local_part = email.split('@').next().unwrap_or_default();
+    self.password_manager.validate_strength(&password, &[user.username.as_str(), email_local_part])?;
+    let password_hash = self.password_manager.hash(&password).await?;
+
+    let (link, token) = PendingEmailLink::new(user_id, email, password_hash);
+    self.email_link_repo.store(&token, &link).await?;
+    self.mailer.send_email_link_token(&link.email, &token).await
+  }
+
+  /// Creates the email provider and marks the address as the user's verified email
+  pub async fn confirm(&self, user_id: Uuid, token: &str) -> Result<AuthProviderResult> {
+    let link = self
+      .email_link_repo
+      .take(token)
+      .await?
+      .filter(|link| link.user_id == user_id)
+      .ok_or_else(Error::invalid_verification_token)?;
+
+    let provider = UserAuthProvider::new_email_provider(user_id, link.email.clone(), link.password_hash);
+
+    let dbx = self.mm.dbx();
+    let email = &link.email;
+    jd_utils::with_transaction!(dbx, {
+      let previous =
+        self.user_repo.find_by_id(user_id).await?.ok_or_else(Error::user_not_found)?;
+      let result = link_provider(&self.provider_repo, provider).await?;
+      let user = self.user_repo.set_verified_email(user_id, email).await?;
+
+      if previous.email.as_deref() != Some(email.as_str()) {
+        let event = UserEmailChangedEvent {
+          user_id,
+          old_email: previous.email,
+          new_email: email.clone(),
+          changed_at: user.updated_at,
+        };
+        Outbox::enqueue(dbx, &event).await?;
+      }
+      Ok::<_, Error>(result)
+    })
+  }
+}
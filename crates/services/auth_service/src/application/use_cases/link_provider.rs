@@ -0,0 +1,38 @@
+use crate::application::use_cases::unified_auth::AuthProviderResult;
+use crate::domain::{
+  AuthProviderRepository, ProviderStatus, UserAuthProvider, UserAuthProviderForCreate,
+};
+use crate::error::{Error, Result};
+
+/// Links a verified identity to `provider.user_id`.
+///
+/// A user has at most one active provider per type and an identity belongs to at most one
+/// user. Identities that were unlinked earlier are reactivated instead of inserted again since
+/// the external id stays unique across revoked rows.
+pub(crate) async fn link_provider<P: AuthProviderRepository>(
+  provider_repo: &P,
+  provider: UserAuthProviderForCreate,
+) -> Result<AuthProviderResult> {
+  let current = provider_repo.find_for_user(provider.user_id, provider.provider_type).await?;
+  if current.filter(UserAuthProvider::is_active).is_some() {
+    return Err(Error::provider_already_linked());
+  }
+
+  let existing =
+    provider_repo.find_by_external_id(provider.provider_type, &provider.provider_user_id).await?;
+
+  match existing {
+    // Suspended identities stay with their account as well
+    Some(existing) if existing.status != ProviderStatus::Revoked => {
+      Err(Error::provider_already_linked())
+    }
+    Some(existing) => {
+      let provider = provider_repo.reactivate(existing.provider_id, provider).await?;
+      Ok(AuthProviderResult { provider, is_new_provider: false })
+    }
+    None => {
+      let provider = provider_repo.create(provider).await?;
+      Ok(AuthProviderResult { provider, is_new_provider: true })
+    }
+  }
+}
@@ -0,0 +1,41 @@
+use uuid::Uuid;
+
+use crate::application::use_cases::link_provider::link_provider;
+use crate::application::use_cases::unified_auth::AuthProviderResult;
+use crate::application::use_cases::wallet_proof::verify_wallet_proof;
+use crate::domain::{AuthProviderRepository, NonceRepository, SignatureVerifier, UserAuthProvider};
+use crate::error::Result;
+
+/// Links a Sui wallet to an authenticated user. Ownership is proven the same way as wallet
+/// login: the wallet signs the nonce issued by `/auth/nonce` for its address.
+pub struct LinkWalletUseCase<P: AuthProviderRepository, N: NonceRepository, V: SignatureVerifier> {
+  provider_repo: P,
+  nonce_repo: N,
+  signature_verifier: V,
+}
+
+impl<P, N, V> LinkWalletUseCase<P, N, V>
+where
+  P: AuthProviderRepository,
+  N: NonceRepository,
+  V: SignatureVerifier,
+{
+  pub fn new(provider_repo: P, nonce_repo: N, signature_verifier: V) -> Self {
+    Self { provider_repo, nonce_repo, signature_verifier }
+  }
+
+  pub async fn execute(
+    &self,
+    user_id: Uuid,
+    address: &str,
+    signature: &str,
+    public_key: &str,
+  ) -> Result<AuthProviderResult> {
+    verify_wallet_proof(&self.nonce_repo, &self.signature_verifier, address, signature, public_key)
+      .await?;
+
+    let provider =
+      UserAuthProvider::new_wallet_provider(user_id, address.to_string(), public_key.to_string());
+    link_provider(&self.provider_repo, provider).await
+  }
+}
@@ -0,0 +1,110 @@
+use time::OffsetDateTime;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::domain::{
+  ApiKey, ApiKeyForCreate, ApiKeyRepository, ApiKeySecret, IssuedApiKey, NewApiKey,
+  PermissionRepository,
+};
+use crate::error::{Error, Result};
+
+pub struct ManageApiKeysUseCase<K: ApiKeyRepository, P: PermissionRepository> {
+  api_key_repo: K,
+  permission_repo: P,
+}
+
+impl<K: ApiKeyRepository, P: PermissionRepository> ManageApiKeysUseCase<K, P> {
+  pub fn new(api_key_repo: K, permission_repo: P) -> Self {
+    Self { api_key_repo, permission_repo }
+  }
+
+  /// Issues a key for `owner_id`. Scopes have to name permissions the owner's role holds.
+  pub async fn create(&self, created_by: Uuid, new_key: NewApiKey) -> Result<IssuedApiKey> {
+    let NewApiKey { owner_id, name, mut scopes, rate_limit, expires_at } = new_key;
+    scopes.sort();
+    scopes.dedup();
+
+    if expires_at.is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc()) {
+      return Err(Error::invalid_request_data("expires_at must be in the future"));
+    }
+
+    let unknown = self.api_key_repo.unknown_scopes(&scopes).await?;
+    if !unknown.is_empty() {
+      return Err(Error::invalid_api_key_scopes(&format!("unknown {}", unknown.join(", "))));
+    }
+
+    let owner = self
+      .permission_repo
+      .get_user_access(owner_id)
+      .await?
+      .ok_or_else(Error::user_not_found)?;
+    if !owner.is_active {
+      return Err(Error::invalid_request_data("owner account is disabled"));
+    }
+
+    let granted = self
+      .permission_repo
+      .get_role_permissions(owner.role)
+      .await?;
+    let denied: Vec<&str> = scopes
+      .iter()
+      .filter(|scope| {
+        !granted
+          .iter()
+          .any(|permission| permission.permission_name == **scope)
+      })
+      .map(String::as_str)
+      .collect();
+    if !denied.is_empty() {
+      return Err(Error::invalid_api_key_scopes(&format!(
+        "role {} lacks {}",
+        owner.role,
+        denied.join(", ")
+      )));
+    }
+
+    let secret = ApiKeySecret::generate();
+    let key = secret.key.clone();
+    let api_key = self
+      .api_key_repo
+      .create(ApiKeyForCreate {
+        owner_id,
+        name,
+        secret,
+        scopes,
+        rate_limit,
+        created_by,
+        expires_at,
+      })
+      .await?;
+
+    info!("🔑 API key {} issued for user {} by {}", api_key.prefix, owner_id, created_by);
+    Ok(IssuedApiKey { api_key, key })
+  }
+
+  pub async fn list(&self, owner_id: Option<Uuid>) -> Result<Vec<ApiKey>> {
+    self.api_key_repo.list(owner_id).await
+  }
+
+  /// Swaps the secret and keeps scopes, limits and expiry
+  pub async fn rotate(&self, key_id: Uuid) -> Result<IssuedApiKey> {
+    let secret = ApiKeySecret::generate();
+    let api_key = self
+      .api_key_repo
+      .rotate(key_id, &secret)
+      .await?
+      .ok_or_else(Error::api_key_not_found)?;
+
+    info!("🔑 API key {} rotated, now {}", key_id, api_key.prefix);
+    Ok(IssuedApiKey { api_key, key: secret.key })
+  }
+
+  pub async fn revoke(&self, key_id: Uuid) -> Result<()> {
+    if !self.api_key_repo.revoke(key_id).await? {
+      return Err(Error::api_key_not_found());
+    }
+
+    info!("🔒 API key {} revoked", key_id);
+    Ok(())
+  }
+}
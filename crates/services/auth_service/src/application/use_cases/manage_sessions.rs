@@ -0,0 +1,36 @@
+use tracing::info;
+use uuid::Uuid;
+
+use crate::domain::{SessionRepository, UserSession, revoke_reason};
+use crate::error::{Error, Result};
+
+pub struct ManageSessionsUseCase<S: SessionRepository> {
+  session_repo: S,
+}
+
+impl<S: SessionRepository> ManageSessionsUseCase<S> {
+  pub fn new(session_repo: S) -> Self {
+    Self { session_repo }
+  }
+
+  pub async fn list_active(&self, user_id: Uuid) -> Result<Vec<UserSession>> {
+    self.session_repo.list_active_for_user(user_id).await
+  }
+
+  pub async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str) -> Result<()> {
+    if !self.session_repo.revoke(user_id, session_id, reason).await? {
+      return Err(Error::session_not_found());
+    }
+
+    info!("🔒 Session {} of user {} revoked ({})", session_id, user_id, reason);
+    Ok(())
+  }
+
+  pub async fn revoke_all(&self, user_id: Uuid) -> Result<u64> {
+    let revoked =
+      self.session_repo.revoke_all_for_user(user_id, revoke_reason::LOGOUT_ALL).await?;
+
+    info!("🔒 Revoked {} sessions of user {}", revoked, user_id);
+    Ok(revoked)
+  }
+}
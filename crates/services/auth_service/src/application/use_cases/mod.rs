@@ -1,11 +1,25 @@
+pub mod authenticate_api_key;
+pub mod authorize_request;
+pub mod change_user_role;
 pub mod generate_nonce;
+pub mod link_email;
+pub(crate) mod link_provider;
+pub mod link_wallet;
+pub mod manage_api_keys;
+pub mod manage_sessions;
 pub mod refresh_token;
 pub mod validate_token;
-pub mod verify_signature;
 pub mod unified_auth;
+pub(crate) mod wallet_proof;
 
+pub use authenticate_api_key::{AuthenticateApiKeyUseCase, AuthorizedApiKey};
+pub use authorize_request::{AuthorizeRequestUseCase, AuthorizedUser};
+pub use change_user_role::ChangeUserRoleUseCase;
 pub use generate_nonce::GenerateNonceUseCase;
+pub use link_email::LinkEmailUseCase;
+pub use link_wallet::LinkWalletUseCase;
+pub use manage_api_keys::ManageApiKeysUseCase;
+pub use manage_sessions::ManageSessionsUseCase;
 pub use refresh_token::RefreshTokenUseCase;
 pub use validate_token::ValidateTokenUseCase;
-pub use verify_signature::VerifySignatureUseCase;
-pub use unified_auth::UnifiedAuthService;
+pub use unified_auth::{OAuthCallbackResult, UnifiedAuthService};
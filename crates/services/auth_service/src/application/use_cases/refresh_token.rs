@@ -1,24 +1,242 @@
-use tracing::info;
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
-use crate::domain::JwtManager;
-use crate::error::Result;
+use crate::domain::{
+  Claims, JwtManager, RefreshRotation, RefreshTokenRepository, SessionRepository, TokenPair,
+  revoke_reason,
+};
+use crate::error::{Error, Result};
 
-pub struct RefreshTokenUseCase {
+pub struct RefreshTokenUseCase<R: RefreshTokenRepository, S: SessionRepository> {
+  refresh_repo: R,
+  session_repo: S,
   jwt_manager: JwtManager,
 }
 
-impl RefreshTokenUseCase {
-  pub fn new(jwt_secret: String) -> Self {
-    Self { jwt_manager: JwtManager::new(jwt_secret) }
+impl<R: RefreshTokenRepository, S: SessionRepository> RefreshTokenUseCase<R, S> {
+  pub fn new(refresh_repo: R, session_repo: S, jwt_manager: JwtManager) -> Self {
+    Self { refresh_repo, session_repo, jwt_manager }
   }
 
-  pub async fn execute(&self, refresh_token: &str) -> Result<String> {
-    info!("🔄 Refreshing access token");
+  /// Exchanges a refresh token for a new pair. The presented token is invalidated;
+  /// presenting it again revokes the whole family and its session.
+  /// Rotation stops working when the session expires, its end is fixed at login.
+  pub async fn execute(&self, refresh_token: &str) -> Result<TokenPair> {
+    info!("🔄 Rotating refresh token");
 
-    // Validate refresh token and generate new access token
-    let access_token = self.jwt_manager.refresh_access_token(refresh_token)?;
+    let claims = self.jwt_manager.validate_token(refresh_token)?;
 
-    info!("✅ Access token refreshed successfully");
-    Ok(access_token)
+    if claims.token_type != "refresh" {
+      error!("❌ Invalid token type: {} (expected refresh)", claims.token_type);
+      return Err(Error::invalid_token());
+    }
+
+    // Tokens issued before rotation existed carry no family, they require a new login
+    let session_id = claims.session_id().ok_or_else(Error::invalid_token)?;
+
+    self.ensure_session_active(&claims, session_id).await?;
+
+    let tokens = self.jwt_manager.rotate_tokens(&claims)?;
+
+    match self.refresh_repo.rotate(session_id, refresh_token, &tokens.refresh_token).await? {
+      RefreshRotation::Rotated => {
+        info!("✅ Refresh token rotated for session {}", session_id);
+        Ok(tokens)
+      }
+      RefreshRotation::Unknown => {
+        warn!("⚠️ Refresh token family {} is expired or revoked", session_id);
+        Err(Error::session_revoked())
+      }
+      RefreshRotation::Reused => {
+        error!("🚨 Refresh token reuse detected, revoking session {}", session_id);
+        self.revoke_family(&claims, session_id).await?;
+        Err(Error::refresh_token_reused())
+      }
+    }
+  }
+
+  /// Unified auth tokens are only refreshed while their session row is active
+  async fn ensure_session_active(&self, claims: &Claims, session_id: Uuid) -> Result<()> {
+    let Some(user_id) = claims.user_id() else {
+      return Ok(());
+    };
+
+    let session = self.session_repo.find_by_id(session_id).await?;
+
+    match session {
+      Some(session) if session.user_id == user_id && session.is_active() => Ok(()),
+      _ => Err(Error::session_revoked()),
+    }
+  }
+
+  async fn revoke_family(&self, claims: &Claims, session_id: Uuid) -> Result<()> {
+    self.refresh_repo.revoke_family(session_id).await?;
+
+    if let Some(user_id) = claims.user_id() {
+      self.session_repo.revoke(user_id, session_id, revoke_reason::REFRESH_TOKEN_REUSE).await?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use std::sync::Mutex;
+
+  use async_trait::async_trait;
+  use time::OffsetDateTime;
+
+  use super::*;
+  use crate::domain::{UserSession, UserSessionForCreate};
+
+  #[derive(Default)]
+  struct Families(Mutex<HashMap<Uuid, String>>);
+
+  #[async_trait]
+  impl RefreshTokenRepository for Families {
+    async fn register(&self, family_id: Uuid, refresh_token: &str) -> Result<()> {
+      self.0.lock().unwrap().insert(family_id, refresh_token.to_string());
+      Ok(())
+    }
+
+    async fn rotate(&self, family_id: Uuid, presented: &str, next: &str) -> Result<RefreshRotation> {
+      let mut families = self.0.lock().unwrap();
+      Ok(match families.get_mut(&family_id) {
+        Some(current) if current == presented => {
+          *current = next.to_string();
+          RefreshRotation::Rotated
+        }
+        Some(_) => RefreshRotation::Reused,
+        None => RefreshRotation::Unknown,
+      })
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> Result<()> {
+      self.0.lock().unwrap().remove(&family_id);
+      Ok(())
+    }
+  }
+
+  struct Sessions(Mutex<UserSession>);
+
+  #[async_trait]
+  impl SessionRepository for Sessions {
+    async fn create(&self, new: UserSessionForCreate) -> Result<UserSession> {
+      let created = session(new.user_id, new.session_id, new.expires_at);
+      *self.0.lock().unwrap() = created.clone();
+      Ok(created)
+    }
+
+    async fn find_by_id(&self, session_id: Uuid) -> Result<Option<UserSession>> {
+      let session = self.0.lock().unwrap().clone();
+      Ok(Some(session).filter(|s| s.session_id == session_id))
+    }
+
+    async fn list_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserSession>> {
+      let session = self.0.lock().unwrap().clone();
+      Ok(Some(session).filter(|s| s.user_id == user_id && s.is_active()).into_iter().collect())
+    }
+
+    async fn is_unknown_client(&self, _user_id: Uuid, _user_agent: &str) -> Result<bool> {
+      Ok(false)
+    }
+
+    async fn touch(&self, _session_id: Uuid) -> Result<()> {
+      Ok(())
+    }
+
+    async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str) -> Result<bool> {
+      let mut session = self.0.lock().unwrap();
+      if session.user_id != user_id || session.session_id != session_id || session.is_revoked {
+        return Ok(false);
+      }
+      session.is_revoked = true;
+      session.revoked_reason = Some(reason.to_string());
+      Ok(true)
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid, reason: &str) -> Result<u64> {
+      let session_id = self.0.lock().unwrap().session_id;
+      Ok(u64::from(self.revoke(user_id, session_id, reason).await?))
+    }
+  }
+
+  fn session(user_id: Uuid, session_id: Uuid, expires_at: OffsetDateTime) -> UserSession {
+    let now = OffsetDateTime::now_utc();
+    UserSession {
+      session_id,
+      user_id,
+      provider_id: None,
+      jwt_token_id: session_id.to_string(),
+      device_info: None,
+      ip_address: None,
+      user_agent: None,
+      created_at: now,
+      expires_at,
+      last_activity: now,
+      is_revoked: false,
+      revoked_at: None,
+      revoked_reason: None,
+    }
+  }
+
+  /// Use case with a session ending at `expires_at` and the refresh token issued at its login
+  async fn login(expires_at: OffsetDateTime) -> (RefreshTokenUseCase<Families, Sessions>, String) {
+    let jwt_manager = JwtManager::new("test-secret".to_string());
+    let (user_id, session_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let tokens = jwt_manager.generate_tokens_for_user(user_id, session_id, "", "").unwrap();
+
+    let families = Families::default();
+    families.register(session_id, &tokens.refresh_token).await.unwrap();
+
+    let use_case = RefreshTokenUseCase::new(families, Sessions(Mutex::new(session(user_id, session_id, expires_at))), jwt_manager);
+    (use_case, tokens.refresh_token)
+  }
+
+  fn in_a_week() -> OffsetDateTime {
+    OffsetDateTime::now_utc() + time::Duration::days(7)
+  }
+
+  #[tokio::test]
+  async fn rotated_token_replaces_the_presented_one() {
+    let (use_case, refresh_token) = login(in_a_week()).await;
+
+    let rotated = use_case.execute(&refresh_token).await.unwrap();
+    let again = use_case.execute(&rotated.refresh_token).await.unwrap();
+
+    assert_ne!(again.refresh_token, rotated.refresh_token);
+  }
+
+  #[tokio::test]
+  async fn reused_token_revokes_family_and_session() {
+    let (use_case, refresh_token) = login(in_a_week()).await;
+    let rotated = use_case.execute(&refresh_token).await.unwrap();
+
+    let err = use_case.execute(&refresh_token).await.unwrap_err();
+    assert_eq!(err.code, "REFRESH_TOKEN_REUSED");
+
+    let session = use_case.session_repo.0.lock().unwrap().clone();
+    assert!(session.is_revoked);
+    assert_eq!(session.revoked_reason.as_deref(), Some(revoke_reason::REFRESH_TOKEN_REUSE));
+    assert!(use_case.refresh_repo.0.lock().unwrap().is_empty());
+
+    // The legitimate holder of the latest token is logged out as well
+    let err = use_case.execute(&rotated.refresh_token).await.unwrap_err();
+    assert_eq!(err.code, "SESSION_REVOKED");
+  }
+
+  #[tokio::test]
+  async fn rotation_does_not_outlive_the_session() {
+    let (use_case, refresh_token) = login(in_a_week()).await;
+    let rotated = use_case.execute(&refresh_token).await.unwrap();
+
+    // The refresh token itself is still valid for a week, the session it belongs to is not
+    use_case.session_repo.0.lock().unwrap().expires_at =
+      OffsetDateTime::now_utc() - time::Duration::seconds(1);
+
+    let err = use_case.execute(&rotated.refresh_token).await.unwrap_err();
+    assert_eq!(err.code, "SESSION_REVOKED");
   }
 }
@@ -1,24 +1,62 @@
-use uuid::Uuid;
+use std::sync::Arc;
+
+use jd_contracts::user::dtos::events::{
+    NewDeviceLoginEvent, UserCreatedEvent, UserDirectory, UserLoggedInEvent,
+};
+use jd_core::AppState;
+use jd_messaging::EventEnvelope;
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 use crate::domain::{
-    UnifiedAuthUser, UnifiedAuthUserForCreate, UnifiedAuthUserForUpdate,
+    AuthProviderRepository, UnifiedAuthUser, UnifiedAuthUserForCreate, UnifiedUserRepository,
     UserAuthProvider, UserAuthProviderForCreate,
-    AuthProviderType, ProviderStatus, UserRole, OAuthTokenResponse
+    AuthProviderType, ProviderStatus, UserRole, OAuthTokenResponse, JwtManager, PasswordManager,
+    PasswordVerification, TokenPair, ClientInfo, SessionRepository, UserSessionForCreate,
+    RefreshTokenRepository, OAuthState, OAuthStateRepository, UnitOfWork, REFRESH_TOKEN_TTL_SECS,
+    NonceRepository, SignatureVerifier
+};
+use crate::infrastructure::database::{
+    AuthProviderRepositoryImpl, NonceRepositoryImpl, OAuthStateRepositoryImpl,
+    RefreshTokenRepositoryImpl, SessionRepositoryImpl, SignatureVerifierImpl,
+    UnifiedUserRepositoryImpl, UnitOfWorkImpl,
 };
+use crate::application::use_cases::link_provider::link_provider;
+use crate::application::use_cases::wallet_proof::verify_wallet_proof;
 use crate::infrastructure::oauth::{OAuthClient, OAuthUserInfo};
 use crate::error::{Result, Error};
 
-pub struct UnifiedAuthService {
+pub struct UnifiedAuthService<
+    U: UnifiedUserRepository,
+    P: AuthProviderRepository,
+    S: SessionRepository,
+    R: RefreshTokenRepository,
+    O: OAuthStateRepository,
+    T: UnitOfWork,
+    N: NonceRepository,
+    V: SignatureVerifier,
+> {
     oauth_client: OAuthClient,
-    // Would also have database repositories here
+    user_repo: U,
+    provider_repo: P,
+    session_repo: S,
+    refresh_repo: R,
+    oauth_state_repo: O,
+    // Transaction of the user, provider and session repositories, multi-step flows run in it
+    uow: T,
+    // Wallet login proofs, see `verify_wallet_proof`
+    nonce_repo: N,
+    signature_verifier: V,
+    jwt_manager: JwtManager,
+    password_manager: PasswordManager,
+    // Recorded on every session started through this service
+    client_info: ClientInfo,
 }
 
 #[derive(Debug, Clone)]
 pub struct LoginResult {
     pub user: UnifiedAuthUser,
-    pub jwt_token: String,
-    pub refresh_token: Option<String>,
+    pub tokens: TokenPair,
     pub is_new_user: bool,
 }
 
@@ -28,13 +66,99 @@ pub struct AuthProviderResult {
     pub is_new_provider: bool,
 }
 
-impl UnifiedAuthService {
-    pub fn new(oauth_client: OAuthClient) -> Self {
+/// Outcome of an OAuth callback, depending on how the authorization was started
+#[derive(Debug, Clone)]
+pub enum OAuthCallbackResult {
+    Login(LoginResult),
+    Linked(AuthProviderResult),
+}
+
+impl
+    UnifiedAuthService<
+        UnifiedUserRepositoryImpl,
+        AuthProviderRepositoryImpl,
+        SessionRepositoryImpl,
+        RefreshTokenRepositoryImpl,
+        OAuthStateRepositoryImpl,
+        UnitOfWorkImpl,
+        NonceRepositoryImpl,
+        SignatureVerifierImpl,
+    >
+{
+    /// Builds a service whose repositories share a dedicated transactional `Dbx`.
+    /// Create one per request, the transaction state must not leak across callers.
+    pub fn from_state(state: &AppState, oauth_client: OAuthClient) -> Result<Self> {
+        let mm = Arc::new(
+            state
+                .mm
+                .new_with_txn()
+                .map_err(|e| Error::database_error(&format!("{e}")))?,
+        );
+        let txn_state = AppState { mm: mm.clone(), ..state.clone() };
+
+        Ok(Self::new(
+            oauth_client,
+            UnifiedUserRepositoryImpl::new(txn_state.clone()),
+            AuthProviderRepositoryImpl::new(txn_state.clone()),
+            SessionRepositoryImpl::new(txn_state),
+            RefreshTokenRepositoryImpl::new(state.clone()),
+            OAuthStateRepositoryImpl::new(state.clone()),
+            UnitOfWorkImpl::new(mm),
+            NonceRepositoryImpl::new(state.clone()),
+            SignatureVerifierImpl::new(),
+            JwtManager::from_state(state),
+            PasswordManager::new(&state.config.password)?,
+        ))
+    }
+}
+
+impl<U, P, S, R, O, T, N, V> UnifiedAuthService<U, P, S, R, O, T, N, V>
+where
+    U: UnifiedUserRepository,
+    P: AuthProviderRepository,
+    S: SessionRepository,
+    R: RefreshTokenRepository,
+    O: OAuthStateRepository,
+    T: UnitOfWork,
+    N: NonceRepository,
+    V: SignatureVerifier,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        oauth_client: OAuthClient,
+        user_repo: U,
+        provider_repo: P,
+        session_repo: S,
+        refresh_repo: R,
+        oauth_state_repo: O,
+        uow: T,
+        nonce_repo: N,
+        signature_verifier: V,
+        jwt_manager: JwtManager,
+        password_manager: PasswordManager,
+    ) -> Self {
         Self {
             oauth_client,
+            user_repo,
+            provider_repo,
+            session_repo,
+            refresh_repo,
+            oauth_state_repo,
+            uow,
+            nonce_repo,
+            signature_verifier,
+            jwt_manager,
+            password_manager,
+            client_info: ClientInfo::default(),
         }
     }
 
+    /// Attach the caller's IP, user agent and device to sessions started by this service
+    pub fn with_client_info(mut self, client_info: ClientInfo) -> Self {
+        self.client_info = client_info;
+        self
+    }
+
     // OAuth Authentication Flow
     pub async fn initiate_oauth_login(
         &self,
@@ -45,84 +169,192 @@ impl UnifiedAuthService {
             .get_provider(provider_type)
             .ok_or(Error::unsupported_oauth_provider())?;
 
-        let state = OAuthClient::generate_state(provider_type, redirect_url);
-        let auth_url = provider.get_authorization_url(&state);
+        if let Some(redirect_url) = redirect_url.as_deref() {
+            self.oauth_client.validate_redirect_url(redirect_url)?;
+        }
+
+        let oauth_state = OAuthState::new(provider_type, redirect_url);
+        self.oauth_state_repo.store(&oauth_state).await?;
+
+        let auth_url =
+            provider.get_authorization_url(&oauth_state.state, &oauth_state.code_challenge());
+
+        Ok(auth_url)
+    }
+
+    /// Starts an authorization whose callback attaches the identity to `user_id` instead of
+    /// logging in
+    pub async fn initiate_oauth_link(
+        &self,
+        user_id: Uuid,
+        provider_type: AuthProviderType,
+        redirect_url: Option<String>,
+    ) -> Result<String> {
+        let provider = self.oauth_client
+            .get_provider(provider_type)
+            .ok_or(Error::unsupported_oauth_provider())?;
+
+        if let Some(redirect_url) = redirect_url.as_deref() {
+            self.oauth_client.validate_redirect_url(redirect_url)?;
+        }
+
+        // Fail before the user goes through consent when the link cannot succeed
+        let current = self.provider_repo.find_for_user(user_id, provider_type).await?;
+        if current.filter(UserAuthProvider::is_active).is_some() {
+            return Err(Error::provider_already_linked());
+        }
+
+        let oauth_state = OAuthState::for_link(provider_type, user_id, redirect_url);
+        self.oauth_state_repo.store(&oauth_state).await?;
+
+        let auth_url =
+            provider.get_authorization_url(&oauth_state.state, &oauth_state.code_challenge());
 
         Ok(auth_url)
     }
 
-    pub async fn complete_oauth_login(
+    /// Completes a callback and returns the login, or the linked provider when the
+    /// authorization was started in link mode, along with its redirect URL
+    pub async fn complete_oauth_callback(
         &self,
         provider_type: AuthProviderType,
         code: String,
         state: String,
-    ) -> Result<LoginResult> {
-        // Handle OAuth callback
-        let (token_response, user_info, _oauth_state) = self
+    ) -> Result<(OAuthCallbackResult, Option<String>)> {
+        // Consumed before the code exchange so a replayed callback fails even if the exchange does
+        let oauth_state = self
+            .oauth_state_repo
+            .take(&state)
+            .await?
+            .ok_or_else(Error::invalid_oauth_state)?;
+
+        let (token_response, user_info) = self
             .oauth_client
-            .handle_oauth_callback(provider_type, code, state)
+            .handle_oauth_callback(provider_type, code, &oauth_state)
             .await?;
 
-        // Check if user already exists with this provider
-        if let Ok(existing_provider) = self.find_auth_provider_by_external_id(
+        let result = match oauth_state.link_user_id {
+            Some(user_id) => OAuthCallbackResult::Linked(
+                self.link_oauth_identity(user_id, provider_type, &token_response, &user_info).await?,
+            ),
+            None => OAuthCallbackResult::Login(
+                self.login_with_oauth_identity(provider_type, token_response, user_info).await?,
+            ),
+        };
+
+        Ok((result, oauth_state.redirect_url))
+    }
+
+    async fn link_oauth_identity(
+        &self,
+        user_id: Uuid,
+        provider_type: AuthProviderType,
+        token_response: &OAuthTokenResponse,
+        user_info: &OAuthUserInfo,
+    ) -> Result<AuthProviderResult> {
+        let user = self.get_user_by_id(user_id).await?;
+        if !user.is_active {
+            return Err(Error::account_disabled());
+        }
+
+        let provider = UserAuthProvider::new_oauth_provider(
+            user_id,
             provider_type,
-            &user_info.id,
-        ).await {
+            user_info.id.clone(),
+            user_info.email.clone(),
+            token_response.clone(),
+            Some(user_info.raw_data.clone()),
+        );
+
+        self.add_auth_provider_to_user(provider).await
+    }
+
+    async fn login_with_oauth_identity(
+        &self,
+        provider_type: AuthProviderType,
+        token_response: OAuthTokenResponse,
+        user_info: OAuthUserInfo,
+    ) -> Result<LoginResult> {
+        // Unlinked identities sign in as if they had never been linked, a suspended one
+        // still belongs to its account and is refused
+        let existing_provider = self
+            .provider_repo
+            .find_by_external_id(provider_type, &user_info.id)
+            .await?
+            .filter(|provider| provider.status != ProviderStatus::Revoked);
+
+        if let Some(existing_provider) = existing_provider {
+            if !existing_provider.is_active() {
+                return Err(Error::account_disabled());
+            }
+
+            let user = self.get_user_by_id(existing_provider.user_id).await?;
+            if !user.is_active {
+                return Err(Error::account_disabled());
+            }
+
             // Update existing provider with new token
-            let updated_provider = self.update_oauth_provider_tokens(
-                existing_provider.provider_id,
-                &token_response,
-            ).await?;
+            let updated_provider = self
+                .provider_repo
+                .update_oauth_tokens(existing_provider.provider_id, &token_response)
+                .await?;
 
-            // Get the user and update login info
-            let mut user = self.get_user_by_id(updated_provider.user_id).await?;
-            user = self.update_user_login(user.user_id).await?;
+            let user = self.user_repo.record_login(user.user_id).await?;
 
-            let jwt_token = self.generate_jwt_for_user(&user).await?;
+            let tokens = self.start_session(&user, &updated_provider).await?;
 
             return Ok(LoginResult {
                 user,
-                jwt_token,
-                refresh_token: token_response.refresh_token,
+                tokens,
                 is_new_user: false,
             });
         }
 
         // Check if user exists with the same email from another provider
-        if let Ok(existing_user) = self.find_user_by_email(&user_info.email).await {
-            // Link this OAuth provider to existing user
-            let _new_provider = self.create_oauth_provider_for_user(
-                existing_user.user_id,
-                provider_type,
-                &user_info,
-                &token_response,
-            ).await?;
-
-            // Update user login info
-            let user = self.update_user_login(existing_user.user_id).await?;
-            let jwt_token = self.generate_jwt_for_user(&user).await?;
+        if let Some(existing_user) = self.user_repo.find_by_email(&user_info.email).await? {
+            // Anyone can claim an address the provider never checked, linking on it would
+            // hand them the account
+            if user_info.verified_email != Some(true) {
+                return Err(Error::oauth_email_not_verified());
+            }
+            if !existing_user.is_active {
+                return Err(Error::account_disabled());
+            }
+
+            // Link this OAuth provider to existing user and update login info atomically
+            let (user_info, token) = (&user_info, &token_response);
+            let (user, provider) = jd_utils::with_transaction!(self.uow, {
+                let provider = self.create_oauth_provider_for_user(
+                    existing_user.user_id,
+                    provider_type,
+                    user_info,
+                    token,
+                ).await?;
+
+                let user = self.user_repo.record_login(existing_user.user_id).await?;
+                Ok::<_, Error>((user, provider))
+            })?;
+            let tokens = self.start_session(&user, &provider).await?;
 
             return Ok(LoginResult {
                 user,
-                jwt_token,
-                refresh_token: token_response.refresh_token,
+                tokens,
                 is_new_user: false,
             });
         }
 
         // Create new user with OAuth provider
-        let new_user = self.create_user_with_oauth_provider(
+        let (new_user, provider) = self.create_user_with_oauth_provider(
             provider_type,
             &user_info,
             &token_response,
         ).await?;
 
-        let jwt_token = self.generate_jwt_for_user(&new_user).await?;
+        let tokens = self.start_session(&new_user, &provider).await?;
 
         Ok(LoginResult {
             user: new_user,
-            jwt_token,
-            refresh_token: token_response.refresh_token,
+            tokens,
             is_new_user: true,
         })
     }
@@ -135,18 +367,23 @@ impl UnifiedAuthService {
         password: String,
         display_name: Option<String>,
     ) -> Result<LoginResult> {
+        // Validated first, a weak password must not reveal whether the email is registered
+        let email_local_part = email.split('@').next().unwrap_or_default();
+        self.password_manager
+            .validate_strength(&password, &[username.as_str(), email_local_part])?;
+
         // Check if email already exists
-        if self.find_user_by_email(&email).await.is_ok() {
+        if self.user_repo.find_by_email(&email).await?.is_some() {
             return Err(Error::email_already_exists());
         }
 
         // Check if username already exists
-        if self.find_user_by_username(&username).await.is_ok() {
+        if self.user_repo.find_by_username(&username).await?.is_some() {
             return Err(Error::username_already_exists());
         }
 
         // Hash password
-        let password_hash = self.hash_password(&password)?;
+        let password_hash = self.password_manager.hash(&password).await?;
 
         // Create user
         let user_create = UnifiedAuthUserForCreate {
@@ -158,23 +395,15 @@ impl UnifiedAuthService {
             is_email_verified: Some(false),
         };
 
-        let user = self.create_user(user_create).await?;
-
-        // Create email auth provider
-        let provider_create = UserAuthProvider::new_email_provider(
-            user.user_id,
-            email,
-            password_hash,
-        );
-
-        self.create_auth_provider(provider_create).await?;
+        let (user, provider) = self.create_user_with_provider(user_create, |user_id| {
+            UserAuthProvider::new_email_provider(user_id, email, password_hash)
+        }).await?;
 
-        let jwt_token = self.generate_jwt_for_user(&user).await?;
+        let tokens = self.start_session(&user, &provider).await?;
 
         Ok(LoginResult {
             user,
-            jwt_token,
-            refresh_token: None,
+            tokens,
             is_new_user: true,
         })
     }
@@ -185,61 +414,93 @@ impl UnifiedAuthService {
         password: String,
     ) -> Result<LoginResult> {
         // Find user by email
-        let user = self.find_user_by_email(&email).await?;
+        let Some(user) = self.user_repo.find_by_email(&email).await? else {
+            // Unknown emails take as long as wrong passwords, timing must not reveal accounts
+            self.password_manager.verify_dummy(&password).await;
+            return Err(Error::invalid_credentials());
+        };
 
         if !user.is_active {
             return Err(Error::account_disabled());
         }
 
         // Find email auth provider
-        let provider = self.find_email_provider_for_user(user.user_id).await?;
+        let provider = self
+            .provider_repo
+            .find_for_user(user.user_id, AuthProviderType::Email)
+            .await?;
+
+        // Accounts without a password, e.g. OAuth only, fail like unknown emails
+        let Some((provider, password_hash)) = provider
+            .and_then(|p| p.password_hash.clone().map(|hash| (p, hash)))
+        else {
+            self.password_manager.verify_dummy(&password).await;
+            return Err(Error::invalid_credentials());
+        };
 
         if !matches!(provider.status, ProviderStatus::Active) {
             return Err(Error::account_disabled());
         }
 
         // Verify password
-        let password_hash = provider.password_hash
-            .ok_or(Error::invalid_credentials())?;
+        let needs_rehash = match self.password_manager.verify(&password, &password_hash).await? {
+            PasswordVerification::Valid { needs_rehash } => needs_rehash,
+            PasswordVerification::Invalid => return Err(Error::invalid_credentials()),
+        };
 
-        if !self.verify_password(&password, &password_hash)? {
-            return Err(Error::invalid_credentials());
+        // Upgrade hashes produced with outdated cost parameters while we have the plaintext
+        if needs_rehash {
+            let new_hash = self.password_manager.hash(&password).await?;
+            self.provider_repo.update_password_hash(provider.provider_id, &new_hash).await?;
         }
 
         // Update login info
-        let user = self.update_user_login(user.user_id).await?;
-        let jwt_token = self.generate_jwt_for_user(&user).await?;
+        let user = self.user_repo.record_login(user.user_id).await?;
+        let tokens = self.start_session(&user, &provider).await?;
 
         Ok(LoginResult {
             user,
-            jwt_token,
-            refresh_token: None,
+            tokens,
             is_new_user: false,
         })
     }
 
     // Wallet Authentication
+    /// Signs in with a signature over the nonce issued for `wallet_address`, creating the user
+    /// on first login
     pub async fn login_with_wallet(
         &self,
         wallet_address: String,
         public_key: String,
         signature: String,
-        nonce: String,
     ) -> Result<LoginResult> {
-        // Verify wallet signature
-        self.verify_wallet_signature(&wallet_address, &public_key, &signature, &nonce).await?;
+        verify_wallet_proof(
+            &self.nonce_repo,
+            &self.signature_verifier,
+            &wallet_address,
+            &signature,
+            &public_key,
+        )
+        .await?;
 
         // Check if user exists with this wallet
-        if let Ok(existing_provider) = self.find_wallet_provider_by_address(&wallet_address).await {
-            let mut user = self.get_user_by_id(existing_provider.user_id).await?;
-            user = self.update_user_login(user.user_id).await?;
+        if let Some(existing_provider) = self.provider_repo.find_by_wallet_address(&wallet_address).await? {
+            if !existing_provider.is_active() {
+                return Err(Error::account_disabled());
+            }
+
+            let user = self.get_user_by_id(existing_provider.user_id).await?;
+            if !user.is_active {
+                return Err(Error::account_disabled());
+            }
+
+            let user = self.user_repo.record_login(user.user_id).await?;
 
-            let jwt_token = self.generate_jwt_for_user(&user).await?;
+            let tokens = self.start_session(&user, &existing_provider).await?;
 
             return Ok(LoginResult {
                 user,
-                jwt_token,
-                refresh_token: None,
+                tokens,
                 is_new_user: false,
             });
         }
@@ -255,45 +516,28 @@ impl UnifiedAuthService {
             is_email_verified: Some(false),
         };
 
-        let user = self.create_user(user_create).await?;
+        let (user, provider) = self.create_user_with_provider(user_create, |user_id| {
+            UserAuthProvider::new_wallet_provider(user_id, wallet_address, public_key)
+        }).await?;
 
-        // Create wallet auth provider
-        let provider_create = UserAuthProvider::new_wallet_provider(
-            user.user_id,
-            wallet_address,
-            public_key,
-        );
-
-        self.create_auth_provider(provider_create).await?;
-
-        let jwt_token = self.generate_jwt_for_user(&user).await?;
+        let tokens = self.start_session(&user, &provider).await?;
 
         Ok(LoginResult {
             user,
-            jwt_token,
-            refresh_token: None,
+            tokens,
             is_new_user: true,
         })
     }
 
     // Provider Management
+    /// Links an identity whose ownership the caller has already verified
     pub async fn add_auth_provider_to_user(
         &self,
-        _user_id: Uuid,
-        _provider_type: AuthProviderType,
-        // Provider-specific data would be passed here
+        provider: UserAuthProviderForCreate,
     ) -> Result<AuthProviderResult> {
-        // Check if user already has this provider type
-        if self.user_has_provider_type(_user_id, _provider_type).await? {
-            return Err(Error::invalid_request_data("Provider already linked"));
-        }
-
-        // Implementation would depend on provider type
-        // For OAuth, would need to initiate OAuth flow
-        // For email, would need email and password
-        // For wallet, would need wallet verification
-
-        todo!("Implement provider-specific linking logic")
+        jd_utils::with_transaction!(self.uow, {
+            link_provider(&self.provider_repo, provider).await
+        })
     }
 
     pub async fn remove_auth_provider(
@@ -301,65 +545,65 @@ impl UnifiedAuthService {
         user_id: Uuid,
         provider_id: Uuid,
     ) -> Result<()> {
-        // Check that user has other active providers before removing
-        let provider_count = self.count_active_providers_for_user(user_id).await?;
-        
-        if provider_count <= 1 {
-            return Err(Error::invalid_request_data("Cannot remove last authentication provider"));
-        }
+        jd_utils::with_transaction!(self.uow, {
+            // Locked so two concurrent unlinks cannot both see a second provider
+            let providers = self.provider_repo.lock_active_for_user(user_id).await?;
 
-        self.deactivate_auth_provider(provider_id).await?;
-        Ok(())
-    }
+            // Only the user's own active providers can be removed
+            if !providers.iter().any(|p| p.provider_id == provider_id) {
+                return Err(Error::provider_not_found());
+            }
 
-    pub async fn get_user_providers(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
-        self.list_providers_for_user(user_id).await
-    }
+            if providers.len() <= 1 {
+                return Err(Error::last_auth_provider());
+            }
 
-    // Helper methods (these would be implemented using your database repositories)
-    async fn find_auth_provider_by_external_id(
-        &self,
-        _provider_type: AuthProviderType,
-        _external_id: &str,
-    ) -> Result<UserAuthProvider> {
-        // Implementation would query the database
-        todo!("Implement database query")
+            self.provider_repo.set_status(provider_id, ProviderStatus::Revoked).await?;
+            Ok::<_, Error>(())
+        })
     }
 
-    async fn find_user_by_email(&self, _email: &str) -> Result<UnifiedAuthUser> {
-        todo!("Implement database query")
+    /// Providers the user can still sign in with or has suspended, unlinked ones are omitted
+    pub async fn get_user_providers(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
+        let providers = self.provider_repo.list_for_user(user_id).await?;
+        Ok(providers.into_iter().filter(|p| p.status != ProviderStatus::Revoked).collect())
     }
 
-    async fn find_user_by_username(&self, _username: &str) -> Result<UnifiedAuthUser> {
-        todo!("Implement database query")
+    // Helper methods
+    async fn get_user_by_id(&self, user_id: Uuid) -> Result<UnifiedAuthUser> {
+        self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(Error::user_not_found)
     }
 
-    async fn get_user_by_id(&self, _user_id: Uuid) -> Result<UnifiedAuthUser> {
-        todo!("Implement database query")
+    /// Creates the user and its first auth provider in a single transaction
+    async fn create_user_with_provider(
+        &self,
+        user_create: UnifiedAuthUserForCreate,
+        provider_for: impl FnOnce(Uuid) -> UserAuthProviderForCreate + Send,
+    ) -> Result<(UnifiedAuthUser, UserAuthProvider)> {
+        jd_utils::with_transaction!(self.uow, {
+            let user = self.create_user(user_create).await?;
+            let provider = self.provider_repo.create(provider_for(user.user_id)).await?;
+            Ok::<_, Error>((user, provider))
+        })
     }
 
-    async fn create_user(&self, _user: UnifiedAuthUserForCreate) -> Result<UnifiedAuthUser> {
-        todo!("Implement database insert")
-    }
+    /// Inserts the user and enqueues its `UserCreatedEvent`, callers run it in their transaction
+    async fn create_user(&self, user_create: UnifiedAuthUserForCreate) -> Result<UnifiedAuthUser> {
+        let user = self.user_repo.create(user_create).await?;
 
-    async fn update_user_login(&self, _user_id: Uuid) -> Result<UnifiedAuthUser> {
-        let _update = UnifiedAuthUserForUpdate {
-            email: None,
-            username: None,
-            display_name: None,
-            role: None,
-            is_active: None,
-            is_email_verified: None,
-            is_profile_complete: None,
-            last_login: Some(OffsetDateTime::now_utc()),
-            login_count: None, // Would increment in database
+        let created = UserCreatedEvent {
+            user_id: user.user_id,
+            directory: UserDirectory::UnifiedAuth,
+            email: user.email.clone(),
+            username: user.username.clone(),
+            created_at: user.created_at,
         };
+        self.uow.enqueue(EventEnvelope::new(&created)?).await?;
 
-        todo!("Implement database update")
-    }
-
-    async fn create_auth_provider(&self, _provider: UserAuthProviderForCreate) -> Result<UserAuthProvider> {
-        todo!("Implement database insert")
+        Ok(user)
     }
 
     async fn create_oauth_provider_for_user(
@@ -378,7 +622,8 @@ impl UnifiedAuthService {
             Some(user_info.raw_data.clone()),
         );
 
-        self.create_auth_provider(provider_create).await
+        // Reuses the row of an identity unlinked earlier, its external id stays unique
+        link_provider(&self.provider_repo, provider_create).await.map(|linked| linked.provider)
     }
 
     async fn create_user_with_oauth_provider(
@@ -386,7 +631,7 @@ impl UnifiedAuthService {
         provider_type: AuthProviderType,
         user_info: &OAuthUserInfo,
         token_response: &OAuthTokenResponse,
-    ) -> Result<UnifiedAuthUser> {
+    ) -> Result<(UnifiedAuthUser, UserAuthProvider)> {
         // Generate unique username from email or name
         let username = self.generate_unique_username(&user_info.email, user_info.name.as_deref()).await?;
 
@@ -396,20 +641,22 @@ impl UnifiedAuthService {
             display_name: user_info.name.clone(),
             role: Some(UserRole::Normal),
             is_active: Some(true),
-            is_email_verified: Some(user_info.verified_email.unwrap_or(true)),
+            is_email_verified: Some(user_info.verified_email == Some(true)),
         };
 
-        let user = self.create_user(user_create).await?;
+        jd_utils::with_transaction!(self.uow, {
+            let user = self.create_user(user_create).await?;
 
-        // Create OAuth provider
-        self.create_oauth_provider_for_user(
-            user.user_id,
-            provider_type,
-            user_info,
-            token_response,
-        ).await?;
+            // Create OAuth provider
+            let provider = self.create_oauth_provider_for_user(
+                user.user_id,
+                provider_type,
+                user_info,
+                token_response,
+            ).await?;
 
-        Ok(user)
+            Ok::<_, Error>((user, provider))
+        })
     }
 
     async fn generate_unique_username(&self, email: &str, name: Option<&str>) -> Result<String> {
@@ -425,7 +672,7 @@ impl UnifiedAuthService {
         let mut username = base_username.clone();
         let mut counter = 1;
 
-        while self.find_user_by_username(&username).await.is_ok() {
+        while self.user_repo.find_by_username(&username).await?.is_some() {
             username = format!("{}{}", base_username, counter);
             counter += 1;
         }
@@ -437,62 +684,894 @@ impl UnifiedAuthService {
         format!("wallet_{}", &wallet_address[2..10]) // Use first 8 chars after 0x
     }
 
-    async fn generate_jwt_for_user(&self, _user: &UnifiedAuthUser) -> Result<String> {
-        // Implementation would use your JWT generation logic
-        todo!("Implement JWT generation")
+    /// Issues a token pair and records it as a session; the session id is the tokens' `sid`
+    /// and also identifies the refresh token family
+    async fn start_session(
+        &self,
+        user: &UnifiedAuthUser,
+        provider: &UserAuthProvider,
+    ) -> Result<TokenPair> {
+        let session_id = Uuid::new_v4();
+
+        // Wallet claims stay empty, the subject identifies the user
+        let tokens = self.jwt_manager.generate_tokens_for_user(user.user_id, session_id, "", "")?;
+
+        let new_device_agent = match self.client_info.user_agent.as_deref() {
+            Some(agent) if self.session_repo.is_unknown_client(user.user_id, agent).await? => {
+                Some(agent.to_string())
+            }
+            _ => None,
+        };
+
+        jd_utils::with_transaction!(self.uow, {
+            let session = self.session_repo
+                .create(UserSessionForCreate {
+                    session_id,
+                    user_id: user.user_id,
+                    provider_id: Some(provider.provider_id),
+                    jwt_token_id: session_id.to_string(),
+                    client: self.client_info.clone(),
+                    expires_at: OffsetDateTime::now_utc() + time::Duration::seconds(REFRESH_TOKEN_TTL_SECS),
+                })
+                .await?;
+
+            let logged_in = UserLoggedInEvent {
+                user_id: user.user_id,
+                session_id,
+                provider_type: provider.provider_type.to_string(),
+                logged_in_at: session.created_at,
+            };
+            self.uow.enqueue(EventEnvelope::new(&logged_in)?).await?;
+
+            if let Some(user_agent) = new_device_agent {
+                let event = NewDeviceLoginEvent {
+                    user_id: user.user_id,
+                    session_id,
+                    ip_address: session.ip_address,
+                    user_agent,
+                    logged_in_at: session.created_at,
+                };
+                self.uow.enqueue(EventEnvelope::new(&event)?).await?;
+            }
+            Ok::<_, Error>(())
+        })?;
+
+        self.refresh_repo.register(session_id, &tokens.refresh_token).await?;
+
+        Ok(tokens)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use jd_utils::config::PasswordConfig;
+
+    use super::*;
+    use crate::domain::{Nonce, RefreshRotation, UserSession};
+    use crate::infrastructure::oauth::stub_server::{CODE_VERIFIER, StubOAuthServer, VALID_CODE};
+
+    const PASSWORD: &str = "Correct-Horse-42";
+
+    /// Rows written by the mock repositories, copied on begin and restored on rollback
+    #[derive(Clone, Default)]
+    struct Tables {
+        users: Vec<UnifiedAuthUser>,
+        providers: Vec<UserAuthProvider>,
+        sessions: Vec<UserSession>,
+        outbox: Vec<EventEnvelope>,
     }
 
-    fn hash_password(&self, _password: &str) -> Result<String> {
-        // Implementation would use bcrypt or argon2
-        todo!("Implement password hashing")
+    #[derive(Default)]
+    struct MockState {
+        tables: Tables,
+        savepoint: Option<Tables>,
+        depth: usize,
+        refresh_families: HashMap<Uuid, String>,
+        oauth_states: HashMap<String, OAuthState>,
+        nonces: HashMap<String, Nonce>,
+        fail_provider_create: bool,
     }
 
-    fn verify_password(&self, _password: &str, _hash: &str) -> Result<bool> {
-        // Implementation would use bcrypt or argon2
-        todo!("Implement password verification")
+    /// In-memory stand-in for every repository of the service and their shared transaction
+    #[derive(Clone, Default)]
+    struct MockDb(Arc<Mutex<MockState>>);
+
+    impl MockDb {
+        fn tables(&self) -> Tables {
+            self.0.lock().unwrap().tables.clone()
+        }
+
+        fn fail_provider_create(&self) {
+            self.0.lock().unwrap().fail_provider_create = true;
+        }
+
+        fn with_tables<T>(&self, f: impl FnOnce(&mut Tables) -> T) -> T {
+            f(&mut self.0.lock().unwrap().tables)
+        }
+
+        fn update_provider(
+            &self,
+            provider_id: Uuid,
+            f: impl FnOnce(&mut UserAuthProvider),
+        ) -> Result<UserAuthProvider> {
+            self.with_tables(|t| {
+                let provider = t
+                    .providers
+                    .iter_mut()
+                    .find(|p| p.provider_id == provider_id)
+                    .ok_or_else(Error::provider_not_found)?;
+                f(provider);
+                Ok(provider.clone())
+            })
+        }
     }
 
-    async fn verify_wallet_signature(
-        &self,
-        _wallet_address: &str,
-        _public_key: &str,
-        _signature: &str,
-        _nonce: &str,
-    ) -> Result<()> {
-        // Implementation would verify the cryptographic signature
-        todo!("Implement signature verification")
+    #[async_trait]
+    impl UnitOfWork for MockDb {
+        async fn begin_txn(&self) -> Result<()> {
+            let mut state = self.0.lock().unwrap();
+            if state.depth == 0 {
+                state.savepoint = Some(state.tables.clone());
+            }
+            state.depth += 1;
+            Ok(())
+        }
+
+        async fn commit_txn(&self) -> Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.depth -= 1;
+            if state.depth == 0 {
+                state.savepoint = None;
+            }
+            Ok(())
+        }
+
+        async fn rollback_txn(&self) -> Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.depth = 0;
+            if let Some(tables) = state.savepoint.take() {
+                state.tables = tables;
+            }
+            Ok(())
+        }
+
+        async fn enqueue(&self, envelope: EventEnvelope) -> Result<()> {
+            self.with_tables(|t| t.outbox.push(envelope));
+            Ok(())
+        }
     }
 
-    // Additional helper methods...
-    async fn update_oauth_provider_tokens(
-        &self,
-        _provider_id: Uuid,
-        _token_response: &OAuthTokenResponse,
-    ) -> Result<UserAuthProvider> {
-        todo!("Implement token update")
+    #[async_trait]
+    impl UnifiedUserRepository for MockDb {
+        async fn find_by_id(&self, user_id: Uuid) -> Result<Option<UnifiedAuthUser>> {
+            Ok(self.with_tables(|t| t.users.iter().find(|u| u.user_id == user_id).cloned()))
+        }
+
+        async fn find_by_email(&self, email: &str) -> Result<Option<UnifiedAuthUser>> {
+            Ok(self.with_tables(|t| {
+                t.users.iter().find(|u| u.email.as_deref() == Some(email)).cloned()
+            }))
+        }
+
+        async fn find_by_username(&self, username: &str) -> Result<Option<UnifiedAuthUser>> {
+            Ok(self.with_tables(|t| t.users.iter().find(|u| u.username == username).cloned()))
+        }
+
+        async fn create(&self, user: UnifiedAuthUserForCreate) -> Result<UnifiedAuthUser> {
+            let now = OffsetDateTime::now_utc();
+            let user = UnifiedAuthUser {
+                user_id: Uuid::new_v4(),
+                email: user.email,
+                username: user.username,
+                display_name: user.display_name,
+                role: user.role.unwrap_or(UserRole::Normal),
+                is_active: user.is_active.unwrap_or(true),
+                is_email_verified: user.is_email_verified.unwrap_or(false),
+                is_profile_complete: false,
+                created_at: now,
+                updated_at: now,
+                last_login: None,
+                login_count: 0,
+                deleted_at: None,
+            };
+            self.with_tables(|t| t.users.push(user.clone()));
+            Ok(user)
+        }
+
+        async fn record_login(&self, user_id: Uuid) -> Result<UnifiedAuthUser> {
+            self.with_tables(|t| {
+                let user = t
+                    .users
+                    .iter_mut()
+                    .find(|u| u.user_id == user_id)
+                    .ok_or_else(Error::user_not_found)?;
+                user.login_count += 1;
+                user.last_login = Some(OffsetDateTime::now_utc());
+                Ok(user.clone())
+            })
+        }
+
+        async fn set_verified_email(&self, user_id: Uuid, email: &str) -> Result<UnifiedAuthUser> {
+            self.with_tables(|t| {
+                let user = t
+                    .users
+                    .iter_mut()
+                    .find(|u| u.user_id == user_id)
+                    .ok_or_else(Error::user_not_found)?;
+                user.email = Some(email.to_string());
+                user.is_email_verified = true;
+                Ok(user.clone())
+            })
+        }
+    }
+
+    #[async_trait]
+    impl AuthProviderRepository for MockDb {
+        async fn find_by_external_id(
+            &self,
+            provider_type: AuthProviderType,
+            provider_user_id: &str,
+        ) -> Result<Option<UserAuthProvider>> {
+            Ok(self.with_tables(|t| {
+                t.providers
+                    .iter()
+                    .find(|p| {
+                        p.provider_type == provider_type && p.provider_user_id == provider_user_id
+                    })
+                    .cloned()
+            }))
+        }
+
+        async fn find_by_wallet_address(
+            &self,
+            wallet_address: &str,
+        ) -> Result<Option<UserAuthProvider>> {
+            Ok(self.with_tables(|t| {
+                t.providers
+                    .iter()
+                    .find(|p| p.wallet_address.as_deref() == Some(wallet_address))
+                    .cloned()
+            }))
+        }
+
+        async fn find_for_user(
+            &self,
+            user_id: Uuid,
+            provider_type: AuthProviderType,
+        ) -> Result<Option<UserAuthProvider>> {
+            let providers: Vec<_> = self
+                .list_for_user(user_id)
+                .await?
+                .into_iter()
+                .filter(|p| p.provider_type == provider_type)
+                .collect();
+            let active = providers.iter().find(|p| p.is_active()).cloned();
+            Ok(active.or_else(|| providers.into_iter().next()))
+        }
+
+        async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
+            Ok(self.with_tables(|t| {
+                t.providers.iter().filter(|p| p.user_id == user_id).cloned().collect()
+            }))
+        }
+
+        async fn lock_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
+            let providers = self.list_for_user(user_id).await?;
+            Ok(providers.into_iter().filter(UserAuthProvider::is_active).collect())
+        }
+
+        async fn create(&self, provider: UserAuthProviderForCreate) -> Result<UserAuthProvider> {
+            if self.0.lock().unwrap().fail_provider_create {
+                return Err(Error::database_error("insert into user_auth_providers failed"));
+            }
+
+            let now = OffsetDateTime::now_utc();
+            let provider = UserAuthProvider {
+                provider_id: Uuid::new_v4(),
+                user_id: provider.user_id,
+                provider_type: provider.provider_type,
+                provider_user_id: provider.provider_user_id,
+                provider_email: provider.provider_email,
+                password_hash: provider.password_hash,
+                wallet_address: provider.wallet_address,
+                public_key: provider.public_key,
+                oauth_access_token: provider.oauth_access_token,
+                oauth_refresh_token: provider.oauth_refresh_token,
+                oauth_token_expires_at: provider.oauth_token_expires_at,
+                provider_metadata: provider.provider_metadata,
+                status: ProviderStatus::Active,
+                created_at: now,
+                updated_at: now,
+                last_used_at: now,
+            };
+            self.with_tables(|t| t.providers.push(provider.clone()));
+            Ok(provider)
+        }
+
+        async fn reactivate(
+            &self,
+            provider_id: Uuid,
+            provider: UserAuthProviderForCreate,
+        ) -> Result<UserAuthProvider> {
+            self.update_provider(provider_id, |p| {
+                p.user_id = provider.user_id;
+                p.provider_email = provider.provider_email;
+                p.password_hash = provider.password_hash;
+                p.oauth_access_token = provider.oauth_access_token;
+                p.oauth_refresh_token = provider.oauth_refresh_token;
+                p.status = ProviderStatus::Active;
+            })
+        }
+
+        async fn update_oauth_tokens(
+            &self,
+            provider_id: Uuid,
+            token_response: &OAuthTokenResponse,
+        ) -> Result<UserAuthProvider> {
+            self.update_provider(provider_id, |p| {
+                p.oauth_access_token = Some(token_response.access_token.clone());
+                p.oauth_refresh_token = token_response.refresh_token.clone();
+            })
+        }
+
+        async fn update_password_hash(&self, provider_id: Uuid, password_hash: &str) -> Result<()> {
+            self.update_provider(provider_id, |p| {
+                p.password_hash = Some(password_hash.to_string());
+            })?;
+            Ok(())
+        }
+
+        async fn set_status(&self, provider_id: Uuid, status: ProviderStatus) -> Result<()> {
+            self.update_provider(provider_id, |p| p.status = status)?;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl SessionRepository for MockDb {
+        async fn create(&self, session: UserSessionForCreate) -> Result<UserSession> {
+            let now = OffsetDateTime::now_utc();
+            let session = UserSession {
+                session_id: session.session_id,
+                user_id: session.user_id,
+                provider_id: session.provider_id,
+                jwt_token_id: session.jwt_token_id,
+                device_info: session.client.device_info,
+                ip_address: session.client.ip_address,
+                user_agent: session.client.user_agent,
+                created_at: now,
+                expires_at: session.expires_at,
+                last_activity: now,
+                is_revoked: false,
+                revoked_at: None,
+                revoked_reason: None,
+            };
+            self.with_tables(|t| t.sessions.push(session.clone()));
+            Ok(session)
+        }
+
+        async fn find_by_id(&self, session_id: Uuid) -> Result<Option<UserSession>> {
+            Ok(self.with_tables(|t| {
+                t.sessions.iter().find(|s| s.session_id == session_id).cloned()
+            }))
+        }
+
+        async fn list_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserSession>> {
+            Ok(self.with_tables(|t| {
+                t.sessions
+                    .iter()
+                    .filter(|s| s.user_id == user_id && s.is_active())
+                    .cloned()
+                    .collect()
+            }))
+        }
+
+        async fn is_unknown_client(&self, _user_id: Uuid, _user_agent: &str) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn touch(&self, _session_id: Uuid) -> Result<()> {
+            Ok(())
+        }
+
+        async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str) -> Result<bool> {
+            Ok(self.with_tables(|t| {
+                let session = t.sessions.iter_mut().find(|s| {
+                    s.user_id == user_id && s.session_id == session_id && !s.is_revoked
+                });
+                session
+                    .map(|s| {
+                        s.is_revoked = true;
+                        s.revoked_at = Some(OffsetDateTime::now_utc());
+                        s.revoked_reason = Some(reason.to_string());
+                    })
+                    .is_some()
+            }))
+        }
+
+        async fn revoke_all_for_user(&self, user_id: Uuid, reason: &str) -> Result<u64> {
+            let sessions = self.list_active_for_user(user_id).await?;
+            for session in &sessions {
+                self.revoke(user_id, session.session_id, reason).await?;
+            }
+            Ok(sessions.len() as u64)
+        }
     }
 
-    async fn find_email_provider_for_user(&self, _user_id: Uuid) -> Result<UserAuthProvider> {
-        todo!("Implement database query")
+    #[async_trait]
+    impl RefreshTokenRepository for MockDb {
+        async fn register(&self, family_id: Uuid, refresh_token: &str) -> Result<()> {
+            let mut state = self.0.lock().unwrap();
+            state.refresh_families.insert(family_id, refresh_token.to_string());
+            Ok(())
+        }
+
+        async fn rotate(
+            &self,
+            family_id: Uuid,
+            presented: &str,
+            next: &str,
+        ) -> Result<RefreshRotation> {
+            let mut state = self.0.lock().unwrap();
+            Ok(match state.refresh_families.get_mut(&family_id) {
+                Some(current) if current == presented => {
+                    *current = next.to_string();
+                    RefreshRotation::Rotated
+                }
+                Some(_) => RefreshRotation::Reused,
+                None => RefreshRotation::Unknown,
+            })
+        }
+
+        async fn revoke_family(&self, family_id: Uuid) -> Result<()> {
+            self.0.lock().unwrap().refresh_families.remove(&family_id);
+            Ok(())
+        }
     }
 
-    async fn find_wallet_provider_by_address(&self, _address: &str) -> Result<UserAuthProvider> {
-        todo!("Implement database query")
+    #[async_trait]
+    impl OAuthStateRepository for MockDb {
+        async fn store(&self, state: &OAuthState) -> Result<()> {
+            let mut inner = self.0.lock().unwrap();
+            inner.oauth_states.insert(state.state.clone(), state.clone());
+            Ok(())
+        }
+
+        async fn take(&self, state: &str) -> Result<Option<OAuthState>> {
+            Ok(self.0.lock().unwrap().oauth_states.remove(state))
+        }
     }
 
-    async fn user_has_provider_type(&self, _user_id: Uuid, _provider_type: AuthProviderType) -> Result<bool> {
-        todo!("Implement database query")
+    #[async_trait]
+    impl NonceRepository for MockDb {
+        async fn store_nonce(&self, nonce: &Nonce) -> Result<()> {
+            let mut inner = self.0.lock().unwrap();
+            inner.nonces.insert(nonce.address.clone(), nonce.clone());
+            Ok(())
+        }
+
+        async fn get_nonce(&self, address: &str) -> Result<Option<Nonce>> {
+            Ok(self.0.lock().unwrap().nonces.get(address).cloned())
+        }
+
+        async fn remove_nonce(&self, address: &str) -> Result<()> {
+            self.0.lock().unwrap().nonces.remove(address);
+            Ok(())
+        }
+
+        async fn take_nonce(&self, address: &str) -> Result<Option<Nonce>> {
+            Ok(self.0.lock().unwrap().nonces.remove(address))
+        }
     }
 
-    async fn count_active_providers_for_user(&self, _user_id: Uuid) -> Result<usize> {
-        todo!("Implement database query")
+    /// Accepts exactly the signatures produced by `sign`
+    struct MockVerifier;
+
+    #[async_trait]
+    impl SignatureVerifier for MockVerifier {
+        async fn verify_signature(
+            &self,
+            message: &str,
+            signature: &str,
+            _public_key: &str,
+            _address: &str,
+        ) -> Result<bool> {
+            Ok(signature == sign(message))
+        }
     }
 
-    async fn deactivate_auth_provider(&self, _provider_id: Uuid) -> Result<()> {
-        todo!("Implement database update")
+    fn sign(message: &str) -> String {
+        format!("signed:{message}")
     }
 
-    async fn list_providers_for_user(&self, _user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
-        todo!("Implement database query")
+    type TestService =
+        UnifiedAuthService<MockDb, MockDb, MockDb, MockDb, MockDb, MockDb, MockDb, MockVerifier>;
+
+    fn service(db: &MockDb) -> TestService {
+        service_with_oauth(db, OAuthClient::new())
     }
-}
\ No newline at end of file
+
+    fn service_with_oauth(db: &MockDb, oauth_client: OAuthClient) -> TestService {
+        // Cheap parameters to keep the tests fast
+        let config = PasswordConfig { memory_kib: 1024, iterations: 1, parallelism: 1, min_length: 10 };
+
+        UnifiedAuthService::new(
+            oauth_client,
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            db.clone(),
+            MockVerifier,
+            JwtManager::new("test-secret".to_string()),
+            PasswordManager::new(&config).unwrap(),
+        )
+    }
+
+    async fn register(service: &TestService, email: &str, username: &str) -> Result<LoginResult> {
+        service
+            .register_with_email(email.to_string(), username.to_string(), PASSWORD.to_string(), None)
+            .await
+    }
+
+    fn google_user(email: &str, verified_email: Option<bool>) -> OAuthUserInfo {
+        OAuthUserInfo {
+            id: format!("google-{}", email),
+            email: email.to_string(),
+            name: None,
+            picture: None,
+            verified_email,
+            raw_data: serde_json::Value::Null,
+        }
+    }
+
+    async fn oauth_login(service: &TestService, user_info: OAuthUserInfo) -> Result<LoginResult> {
+        let token = OAuthTokenResponse {
+            access_token: "access".to_string(),
+            refresh_token: None,
+            expires_in: None,
+            token_type: "Bearer".to_string(),
+            scope: None,
+        };
+        service.login_with_oauth_identity(AuthProviderType::Google, token, user_info).await
+    }
+
+    /// Issues a nonce for `address` and returns the wallet's signature over it
+    async fn signed_nonce(db: &MockDb, address: &str) -> String {
+        let nonce = Nonce::generate(address.to_string());
+        db.store_nonce(&nonce).await.unwrap();
+        sign(&nonce.get_signing_message())
+    }
+
+    async fn wallet_login(
+        service: &TestService,
+        address: &str,
+        signature: String,
+    ) -> Result<LoginResult> {
+        service.login_with_wallet(address.to_string(), "pk".to_string(), signature).await
+    }
+
+    #[tokio::test]
+    async fn register_commits_user_provider_and_session() {
+        let db = MockDb::default();
+        let service = service(&db);
+
+        let login = register(&service, "ada@example.com", "ada").await.unwrap();
+
+        let tables = db.tables();
+        assert!(login.is_new_user);
+        assert_eq!(tables.users.len(), 1);
+        assert_eq!(tables.providers.len(), 1);
+        assert_eq!(tables.providers[0].user_id, login.user.user_id);
+        assert_eq!(tables.providers[0].provider_type, AuthProviderType::Email);
+        assert_eq!(tables.sessions.len(), 1);
+        assert!(tables.outbox.iter().any(|e| e.is::<UserLoggedInEvent>()));
+
+        let created = tables.outbox.iter().find_map(|e| e.decode::<UserCreatedEvent>().unwrap());
+        let created = created.unwrap();
+        assert_eq!(created.user_id, login.user.user_id);
+        assert_eq!(created.directory, UserDirectory::UnifiedAuth);
+        assert_eq!(created.email.as_deref(), Some("ada@example.com"));
+    }
+
+    #[tokio::test]
+    async fn register_rolls_back_user_when_provider_insert_fails() {
+        let db = MockDb::default();
+        let service = service(&db);
+        db.fail_provider_create();
+
+        let err = register(&service, "ada@example.com", "ada").await.unwrap_err();
+
+        let tables = db.tables();
+        assert_eq!(err.code, "DATABASE_ERROR");
+        assert!(tables.users.is_empty());
+        assert!(tables.providers.is_empty());
+        assert!(tables.sessions.is_empty());
+        assert!(tables.outbox.is_empty());
+    }
+
+    #[tokio::test]
+    async fn register_rejects_taken_email() {
+        let db = MockDb::default();
+        let service = service(&db);
+        register(&service, "ada@example.com", "ada").await.unwrap();
+
+        let err = register(&service, "ada@example.com", "ada2").await.unwrap_err();
+
+        assert_eq!(err.code, "EMAIL_ALREADY_EXISTS");
+        assert_eq!(db.tables().users.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn email_login_checks_password_and_provider_status() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let registered = register(&service, "ada@example.com", "ada").await.unwrap();
+
+        let login = service
+            .login_with_email("ada@example.com".to_string(), PASSWORD.to_string())
+            .await
+            .unwrap();
+        assert_eq!(login.user.user_id, registered.user.user_id);
+        assert_eq!(login.user.login_count, 1);
+
+        let err = service
+            .login_with_email("ada@example.com".to_string(), "Wrong-Horse-42".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, "INVALID_CREDENTIALS");
+
+        let provider_id = db.tables().providers[0].provider_id;
+        db.set_status(provider_id, ProviderStatus::Suspended).await.unwrap();
+        let err = service
+            .login_with_email("ada@example.com".to_string(), PASSWORD.to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, "ACCOUNT_DISABLED");
+    }
+
+    #[tokio::test]
+    async fn register_validates_password_before_lookups() {
+        let db = MockDb::default();
+        let service = service(&db);
+        register(&service, "ada@example.com", "ada").await.unwrap();
+
+        let err = service
+            .register_with_email(
+                "ada@example.com".to_string(),
+                "ada".to_string(),
+                "weak".to_string(),
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "WEAK_PASSWORD");
+    }
+
+    #[tokio::test]
+    async fn unknown_email_fails_like_a_wrong_password() {
+        let db = MockDb::default();
+        let service = service(&db);
+
+        let err = service
+            .login_with_email("nobody@example.com".to_string(), PASSWORD.to_string())
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "INVALID_CREDENTIALS");
+    }
+
+    #[tokio::test]
+    async fn last_provider_cannot_be_removed() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let login = register(&service, "ada@example.com", "ada").await.unwrap();
+        let provider_id = db.tables().providers[0].provider_id;
+
+        let err = service.remove_auth_provider(login.user.user_id, provider_id).await.unwrap_err();
+
+        assert_eq!(err.code, "LAST_AUTH_PROVIDER");
+        assert!(db.tables().providers[0].is_active());
+    }
+
+    #[tokio::test]
+    async fn removed_provider_is_revoked_and_hidden() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let login = register(&service, "ada@example.com", "ada").await.unwrap();
+        let user_id = login.user.user_id;
+        let wallet = format!("0x{}", "ab".repeat(32));
+        let linked = service
+            .add_auth_provider_to_user(UserAuthProvider::new_wallet_provider(
+                user_id,
+                wallet,
+                "pk".to_string(),
+            ))
+            .await
+            .unwrap();
+
+        service.remove_auth_provider(user_id, linked.provider.provider_id).await.unwrap();
+
+        let providers = service.get_user_providers(user_id).await.unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].provider_type, AuthProviderType::Email);
+    }
+
+    #[tokio::test]
+    async fn oauth_login_checks_provider_and_user_status() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let ada = || google_user("ada@example.com", Some(true));
+        let first = oauth_login(&service, ada()).await.unwrap();
+        let provider_id = db.tables().providers[0].provider_id;
+
+        db.set_status(provider_id, ProviderStatus::Suspended).await.unwrap();
+        let err = oauth_login(&service, ada()).await.unwrap_err();
+        assert_eq!(err.code, "ACCOUNT_DISABLED");
+
+        db.set_status(provider_id, ProviderStatus::Active).await.unwrap();
+        db.with_tables(|t| t.users[0].is_active = false);
+        let err = oauth_login(&service, ada()).await.unwrap_err();
+        assert_eq!(err.code, "ACCOUNT_DISABLED");
+
+        let tables = db.tables();
+        assert_eq!(tables.sessions.len(), 1);
+        assert_eq!(tables.sessions[0].user_id, first.user.user_id);
+    }
+
+    #[tokio::test]
+    async fn oauth_login_links_existing_email_only_when_verified() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let registered = register(&service, "ada@example.com", "ada").await.unwrap();
+
+        for verified_email in [None, Some(false)] {
+            let err = oauth_login(&service, google_user("ada@example.com", verified_email))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, "OAUTH_EMAIL_NOT_VERIFIED");
+        }
+        assert_eq!(db.tables().providers.len(), 1);
+
+        let verified = google_user("ada@example.com", Some(true));
+        let login = oauth_login(&service, verified).await.unwrap();
+        assert!(!login.is_new_user);
+        assert_eq!(login.user.user_id, registered.user.user_id);
+        assert_eq!(db.tables().providers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn unlinked_oauth_identity_no_longer_reaches_the_account() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let ada = register(&service, "ada@example.com", "ada").await.unwrap();
+        let ada_id = ada.user.user_id;
+        oauth_login(&service, google_user("ada@example.com", Some(true))).await.unwrap();
+        let google = service.get_user_providers(ada_id).await.unwrap();
+        let google = google.iter().find(|p| p.provider_type == AuthProviderType::Google).unwrap();
+        service.remove_auth_provider(ada_id, google.provider_id).await.unwrap();
+
+        // The same Google account now reports an address that is not ada's
+        let mut user_info = google_user("grace@example.com", Some(true));
+        user_info.id = google.provider_user_id.clone();
+        let login = oauth_login(&service, user_info).await.unwrap();
+
+        assert!(login.is_new_user);
+        assert_ne!(login.user.user_id, ada_id);
+        let ada_providers = service.get_user_providers(ada_id).await.unwrap();
+        assert_eq!(ada_providers.len(), 1);
+        assert_eq!(ada_providers[0].provider_type, AuthProviderType::Email);
+        // The unlinked row is reused rather than duplicated
+        assert_eq!(db.tables().providers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn wallet_login_with_a_valid_signature_creates_then_signs_in_the_user() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let wallet = format!("0x{}", "ab".repeat(32));
+
+        let signature = signed_nonce(&db, &wallet).await;
+        let first = wallet_login(&service, &wallet, signature).await.unwrap();
+        let signature = signed_nonce(&db, &wallet).await;
+        let second = wallet_login(&service, &wallet, signature).await.unwrap();
+
+        assert!(first.is_new_user);
+        assert!(!second.is_new_user);
+        let created: Vec<_> = db
+            .tables()
+            .outbox
+            .iter()
+            .filter_map(|e| e.decode::<UserCreatedEvent>().unwrap())
+            .collect();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].email, None);
+        assert_eq!(second.user.user_id, first.user.user_id);
+        let tables = db.tables();
+        assert_eq!(tables.providers.len(), 1);
+        assert_eq!(tables.providers[0].wallet_address.as_deref(), Some(wallet.as_str()));
+        assert_eq!(tables.sessions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn wallet_login_rejects_a_bad_signature() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let wallet = format!("0x{}", "ab".repeat(32));
+        signed_nonce(&db, &wallet).await;
+
+        let err = wallet_login(&service, &wallet, sign("another message")).await.unwrap_err();
+
+        assert_eq!(err.code, "INVALID_SIGNATURE");
+        let tables = db.tables();
+        assert!(tables.users.is_empty());
+        assert!(tables.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn wallet_login_rejects_a_replayed_nonce() {
+        let db = MockDb::default();
+        let service = service(&db);
+        let wallet = format!("0x{}", "ab".repeat(32));
+        let signature = signed_nonce(&db, &wallet).await;
+        wallet_login(&service, &wallet, signature.clone()).await.unwrap();
+
+        let err = wallet_login(&service, &wallet, signature).await.unwrap_err();
+
+        assert_eq!(err.code, "NONCE_NOT_FOUND");
+        assert_eq!(db.tables().sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn wallet_user_links_google_with_the_tokens_of_its_wallet_login() {
+        let server = StubOAuthServer::start().await;
+        let oauth_client = OAuthClient::from_settings(&jd_utils::config::OAuthSettings {
+            redirect_allowlist: String::new(),
+            google: Some(server.google_settings()),
+            github: None,
+        });
+        let db = MockDb::default();
+        let service = service_with_oauth(&db, oauth_client);
+        let wallet = format!("0x{}", "ab".repeat(32));
+
+        let signature = signed_nonce(&db, &wallet).await;
+        let login = wallet_login(&service, &wallet, signature).await.unwrap();
+
+        // What `mw_require_auth` resolves the caller from
+        let claims = JwtManager::new("test-secret".to_string())
+            .validate_token(&login.tokens.access_token)
+            .unwrap();
+        let user_id = claims.user_id().unwrap();
+        assert_eq!(user_id, login.user.user_id);
+        assert_eq!(claims.session_id(), Some(db.tables().sessions[0].session_id));
+
+        service.initiate_oauth_link(user_id, AuthProviderType::Google, None).await.unwrap();
+        // The stub provider only accepts its own PKCE verifier
+        let state = {
+            let mut inner = db.0.lock().unwrap();
+            let pending = inner.oauth_states.values_mut().next().unwrap();
+            pending.code_verifier = CODE_VERIFIER.to_string();
+            pending.state.clone()
+        };
+        let (result, _) = service
+            .complete_oauth_callback(AuthProviderType::Google, VALID_CODE.to_string(), state)
+            .await
+            .unwrap();
+
+        let OAuthCallbackResult::Linked(linked) = result else {
+            panic!("a link-mode callback must not log in");
+        };
+        assert_eq!(linked.provider.user_id, user_id);
+        let providers = service.get_user_providers(user_id).await.unwrap();
+        let types: Vec<_> = providers.iter().map(|p| p.provider_type).collect();
+        assert_eq!(types.len(), 2);
+        assert!(types.contains(&AuthProviderType::Wallet));
+        assert!(types.contains(&AuthProviderType::Google));
+    }
+}
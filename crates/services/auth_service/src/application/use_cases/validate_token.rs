@@ -9,8 +9,8 @@ pub struct ValidateTokenUseCase<R: UserRepository> {
 }
 
 impl<R: UserRepository> ValidateTokenUseCase<R> {
-  pub fn new(user_repo: R, jwt_secret: String) -> Self {
-    Self { user_repo, jwt_manager: JwtManager::new(jwt_secret) }
+  pub fn new(user_repo: R, jwt_manager: JwtManager) -> Self {
+    Self { user_repo, jwt_manager }
   }
 
   pub async fn execute(&self, token: &str) -> Result<AuthUser> {
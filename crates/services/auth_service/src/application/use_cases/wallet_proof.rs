@@ -0,0 +1,33 @@
+use crate::domain::{AuthUser, NonceRepository, SignatureVerifier};
+use crate::error::{Error, Result};
+
+/// Checks that the caller controls `address`: the wallet signed the nonce issued by
+/// `/auth/nonce` for it.
+///
+/// The nonce is consumed before the signature is checked, a proof is accepted at most once
+/// and a failed attempt needs a fresh nonce.
+pub(crate) async fn verify_wallet_proof<N: NonceRepository, V: SignatureVerifier>(
+  nonce_repo: &N,
+  signature_verifier: &V,
+  address: &str,
+  signature: &str,
+  public_key: &str,
+) -> Result<()> {
+  if !AuthUser::is_valid_address(address) {
+    return Err(Error::invalid_address());
+  }
+
+  let nonce = nonce_repo.take_nonce(address).await?.ok_or_else(Error::nonce_not_found)?;
+  if nonce.is_expired() {
+    return Err(Error::nonce_expired());
+  }
+
+  let is_valid = signature_verifier
+    .verify_signature(&nonce.get_signing_message(), signature, public_key, address)
+    .await?;
+  if !is_valid {
+    return Err(Error::invalid_signature());
+  }
+
+  Ok(())
+}
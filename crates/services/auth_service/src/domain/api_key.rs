@@ -0,0 +1,155 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::UserPermission;
+use crate::domain::oauth_state::random_token;
+
+/// Marks a string as one of our API keys, `jdk_<8 hex>_<secret>`
+pub const API_KEY_PREFIX: &str = "jdk_";
+
+/// `jdk_` and 8 hex characters, enough to tell the keys of one owner apart
+const API_KEY_PREFIX_LEN: usize = API_KEY_PREFIX.len() + 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApiKey {
+  pub key_id: Uuid,
+  pub owner_id: Uuid,
+  pub name: String,
+  pub prefix: String,
+  /// Permission names the key may use, at most those of the owner's role
+  pub scopes: Vec<String>,
+  /// Requests per rate limit window, replacing the limit of the route group
+  pub rate_limit: Option<i32>,
+  pub created_by: Option<Uuid>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub rotated_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub expires_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub last_used_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub revoked_at: Option<OffsetDateTime>,
+}
+
+/// Key requested by an administrator
+#[derive(Debug, Clone)]
+pub struct NewApiKey {
+  pub owner_id: Uuid,
+  pub name: String,
+  pub scopes: Vec<String>,
+  pub rate_limit: Option<i32>,
+  pub expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKeyForCreate {
+  pub owner_id: Uuid,
+  pub name: String,
+  pub secret: ApiKeySecret,
+  pub scopes: Vec<String>,
+  pub rate_limit: Option<i32>,
+  pub created_by: Uuid,
+  pub expires_at: Option<OffsetDateTime>,
+}
+
+/// Freshly generated key, `key` is handed out once and only `hash` is stored
+#[derive(Debug, Clone)]
+pub struct ApiKeySecret {
+  pub key: String,
+  pub prefix: String,
+  pub hash: String,
+}
+
+impl ApiKeySecret {
+  pub fn generate() -> Self {
+    let id: [u8; 4] = rand::thread_rng().r#gen();
+    let key = format!("{}{}_{}", API_KEY_PREFIX, hex::encode(id), random_token());
+    let prefix = key[..API_KEY_PREFIX_LEN].to_string();
+
+    Self { hash: hash_api_key(&key), prefix, key }
+  }
+}
+
+/// Stored key together with the plaintext, returned once when a key is created or rotated
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+  pub api_key: ApiKey,
+  pub key: String,
+}
+
+/// Keys carry 256 random bits, a plain digest is enough and allows lookups by hash
+pub fn hash_api_key(key: &str) -> String {
+  hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+impl ApiKey {
+  pub fn is_revoked(&self) -> bool {
+    self.revoked_at.is_some()
+  }
+
+  pub fn is_expired(&self) -> bool {
+    self
+      .expires_at
+      .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+  }
+
+  /// Narrows the owner's permissions down to the scopes of the key
+  pub fn grant(&self, permissions: Vec<UserPermission>) -> Vec<UserPermission> {
+    permissions
+      .into_iter()
+      .filter(|permission| self.scopes.contains(&permission.permission_name))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn generated_keys_start_with_their_prefix() {
+    let secret = ApiKeySecret::generate();
+
+    assert!(secret.key.starts_with(&secret.prefix));
+    assert!(secret.prefix.starts_with(API_KEY_PREFIX));
+    assert_eq!(secret.prefix.len(), API_KEY_PREFIX_LEN);
+    assert_eq!(secret.hash, hash_api_key(&secret.key));
+    assert_ne!(secret.key, ApiKeySecret::generate().key);
+  }
+
+  #[test]
+  fn keys_only_keep_scoped_permissions() {
+    let permission = |name: &str| UserPermission {
+      permission_name: name.to_string(),
+      resource: "content".to_string(),
+      action: "read".to_string(),
+    };
+    let key = ApiKey {
+      key_id: Uuid::new_v4(),
+      owner_id: Uuid::new_v4(),
+      name: "partner".to_string(),
+      prefix: "jdk_0a1b2c3d".to_string(),
+      scopes: vec!["content.read".to_string(), "system.admin".to_string()],
+      rate_limit: None,
+      created_by: None,
+      created_at: OffsetDateTime::now_utc(),
+      rotated_at: None,
+      expires_at: None,
+      last_used_at: None,
+      revoked_at: None,
+    };
+
+    let granted = key.grant(vec![permission("content.read"), permission("content.write.own")]);
+
+    // A scope the owner's role lacks is never granted
+    assert_eq!(granted.len(), 1);
+    assert_eq!(granted[0].permission_name, "content.read");
+    assert!(!key.is_expired());
+  }
+}
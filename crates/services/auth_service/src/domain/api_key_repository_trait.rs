@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{ApiKey, ApiKeyForCreate, ApiKeySecret};
+use crate::error::Result;
+
+/// Persistence for `unified_auth.api_keys`
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+  async fn create(&self, key: ApiKeyForCreate) -> Result<ApiKey>;
+  async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>>;
+  /// Every key, or the keys of one owner, revoked ones included
+  async fn list(&self, owner_id: Option<Uuid>) -> Result<Vec<ApiKey>>;
+  /// Names of `scopes` missing from `unified_auth.permissions`
+  async fn unknown_scopes(&self, scopes: &[String]) -> Result<Vec<String>>;
+  /// Replaces the secret of a key that is not revoked, the old one stops working at once
+  async fn rotate(&self, key_id: Uuid, secret: &ApiKeySecret) -> Result<Option<ApiKey>>;
+  /// Returns false when the key does not exist or is already revoked
+  async fn revoke(&self, key_id: Uuid) -> Result<bool>;
+  /// Refreshes `last_used_at`, implementations may throttle the write
+  async fn touch(&self, key_id: Uuid) -> Result<()>;
+}
@@ -109,6 +109,15 @@ impl AuthProviderType {
         matches!(self, AuthProviderType::Wallet)
     }
 
+    /// OAuth provider named in a route, e.g. `/oauth/google/start`
+    pub fn from_oauth_name(name: &str) -> Option<AuthProviderType> {
+        match name {
+            "google" => Some(AuthProviderType::Google),
+            "github" => Some(AuthProviderType::Github),
+            _ => None,
+        }
+    }
+
     pub fn oauth_scopes(&self) -> Vec<&'static str> {
         match self {
             AuthProviderType::Google => vec!["openid", "email", "profile"],
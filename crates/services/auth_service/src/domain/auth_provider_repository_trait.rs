@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{
+  AuthProviderType, OAuthTokenResponse, ProviderStatus, UserAuthProvider,
+  UserAuthProviderForCreate,
+};
+use crate::error::Result;
+
+/// Persistence for `unified_auth.user_auth_providers`
+#[async_trait]
+pub trait AuthProviderRepository: Send + Sync {
+  async fn find_by_external_id(
+    &self,
+    provider_type: AuthProviderType,
+    provider_user_id: &str,
+  ) -> Result<Option<UserAuthProvider>>;
+  async fn find_by_wallet_address(&self, wallet_address: &str) -> Result<Option<UserAuthProvider>>;
+  /// Prefers the active provider when the user unlinked and relinked this type
+  async fn find_for_user(
+    &self,
+    user_id: Uuid,
+    provider_type: AuthProviderType,
+  ) -> Result<Option<UserAuthProvider>>;
+  async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>>;
+  /// Row locks the user's active providers until the surrounding transaction ends
+  async fn lock_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>>;
+  async fn create(&self, provider: UserAuthProviderForCreate) -> Result<UserAuthProvider>;
+  /// Overwrites a revoked provider with fresh credentials, possibly for another user
+  async fn reactivate(
+    &self,
+    provider_id: Uuid,
+    provider: UserAuthProviderForCreate,
+  ) -> Result<UserAuthProvider>;
+  async fn update_oauth_tokens(
+    &self,
+    provider_id: Uuid,
+    token_response: &OAuthTokenResponse,
+  ) -> Result<UserAuthProvider>;
+  async fn update_password_hash(&self, provider_id: Uuid, password_hash: &str) -> Result<()>;
+  async fn set_status(&self, provider_id: Uuid, status: ProviderStatus) -> Result<()>;
+}
@@ -61,16 +61,26 @@ pub struct UnifiedAuthUserFilter {
     pub is_active: Option<OpValsValue>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
 pub struct UserPermission {
     pub permission_name: String,
     pub resource: String,
     pub action: String,
 }
 
-impl UnifiedAuthUser {
-    pub fn new(username: String, email: Option<String>) -> UnifiedAuthUserForCreate {
-        UnifiedAuthUserForCreate {
+/// Minimal user projection needed to authorize a request
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserAccess {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub is_active: bool,
+}
+
+impl UnifiedAuthUserForCreate {
+    pub fn new(username: String, email: Option<String>) -> Self {
+        Self {
             email,
             username,
             display_name: None,
@@ -79,7 +89,9 @@ impl UnifiedAuthUser {
             is_email_verified: Some(false),
         }
     }
+}
 
+impl UnifiedAuthUser {
     pub fn update_login(&self) -> UnifiedAuthUserForUpdate {
         UnifiedAuthUserForUpdate {
             email: None,
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::oauth_state::random_token;
+
+/// Lifetime of an email link verification token
+pub const EMAIL_LINK_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// Email/password provider waiting for its address to be confirmed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingEmailLink {
+  pub user_id: Uuid,
+  pub email: String,
+  /// Hashed when the link is requested, the plaintext is never stored
+  pub password_hash: String,
+  pub created_at: i64,
+}
+
+impl PendingEmailLink {
+  /// Returns the pending link together with the token mailed to `email`
+  pub fn new(user_id: Uuid, email: String, password_hash: String) -> (Self, String) {
+    let link = Self { user_id, email, password_hash, created_at: chrono::Utc::now().timestamp() };
+    (link, random_token())
+  }
+}
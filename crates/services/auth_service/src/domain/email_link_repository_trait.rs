@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::PendingEmailLink;
+use crate::error::Result;
+
+/// Pending email links keyed by their verification token
+#[async_trait]
+pub trait EmailLinkRepository: Send + Sync {
+  async fn store(&self, token: &str, link: &PendingEmailLink) -> Result<()>;
+  /// Returns and deletes the link in one step, a token can only be used once
+  async fn take(&self, token: &str) -> Result<Option<PendingEmailLink>>;
+}
@@ -1,7 +1,13 @@
+use std::sync::Arc;
+
+use crate::domain::JwtKeySet;
 use crate::error::{Error, Result};
-use chrono::{Duration, Utc};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use chrono::{DateTime, Duration, Utc};
+use jd_core::AppState;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -10,87 +16,171 @@ pub struct Claims {
   pub token_type: String, // "access" or "refresh"
   pub exp: usize,         // Expiration timestamp
   pub iat: usize,         // Issued at timestamp
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sub: Option<String>, // unified_auth.users.user_id
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub jti: Option<String>, // Unique token id
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sid: Option<String>, // Session id, shared by every pair rotated from the same login
 }
 
+impl Claims {
+  /// User id carried in `sub`, if the token was issued for a unified auth user
+  pub fn user_id(&self) -> Option<Uuid> {
+    self.sub.as_deref().and_then(|sub| Uuid::parse_str(sub).ok())
+  }
+
+  /// Session id carried in `sid`, which also identifies the refresh token family
+  pub fn session_id(&self) -> Option<Uuid> {
+    self.sid.as_deref().and_then(|sid| Uuid::parse_str(sid).ok())
+  }
+}
+
+/// Access tokens are short lived, the session outlives them through refresh
+pub const ACCESS_TOKEN_TTL_SECS: i64 = 60 * 60;
+/// Refresh token lifetime, which is also the absolute lifetime of a session. Rotating the
+/// refresh token does not extend the session, a new login is needed once it ends.
+pub const REFRESH_TOKEN_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
 #[derive(Debug, Clone)]
 pub struct JwtManager {
-  secret: String,
+  keys: Arc<JwtKeySet>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPair {
   pub access_token: String,
   pub refresh_token: String,
 }
 
 impl JwtManager {
+  /// HS256 manager signing with a shared secret
   pub fn new(secret: String) -> Self {
-    Self { secret }
+    Self::with_keys(Arc::new(JwtKeySet::hmac(&secret)))
   }
 
-  /// Generate access and refresh tokens for a user
-  pub fn generate_tokens(&self, address: &str, public_key: &str) -> Result<TokenPair> {
-    let access_token = self.generate_access_token(address, public_key)?;
-    let refresh_token = self.generate_refresh_token(address, public_key)?;
+  pub fn with_keys(keys: Arc<JwtKeySet>) -> Self {
+    Self { keys }
+  }
 
-    Ok(TokenPair { access_token, refresh_token })
+  /// Manager for the keys currently loaded in `state`. Key reloads reach managers created
+  /// afterwards, so build one per request rather than holding it.
+  pub fn from_state(state: &AppState) -> Self {
+    Self::with_keys(state.jwt_keys.current())
   }
 
-  /// Generate an access token (1 hour expiry)
-  fn generate_access_token(&self, address: &str, public_key: &str) -> Result<String> {
-    let now = Utc::now();
-    let exp = (now + Duration::hours(1)).timestamp() as usize;
-    let iat = now.timestamp() as usize;
+  /// Public keys accepted for verification, served as `/.well-known/jwks.json`
+  pub fn jwks(&self) -> JwkSet {
+    self.keys.jwks()
+  }
 
-    let claims = Claims {
-      address: address.to_string(),
-      public_key: public_key.to_string(),
-      token_type: "access".to_string(),
-      exp,
-      iat,
-    };
+  /// Generate access and refresh tokens for a wallet user
+  pub fn generate_tokens(
+    &self,
+    address: &str,
+    public_key: &str,
+    session_id: Uuid,
+  ) -> Result<TokenPair> {
+    self.generate_pair(address, public_key, None, &session_id.to_string())
+  }
 
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(self.secret.as_ref()))
-      .map_err(|e| Error::internal_error(&format!("Failed to generate access token: {}", e)))
+  /// Generate access and refresh tokens bound to a unified auth user.
+  /// Both tokens carry `session_id` as their `sid`.
+  pub fn generate_tokens_for_user(
+    &self,
+    user_id: Uuid,
+    session_id: Uuid,
+    address: &str,
+    public_key: &str,
+  ) -> Result<TokenPair> {
+    self.generate_pair(address, public_key, Some(&user_id.to_string()), &session_id.to_string())
   }
 
-  /// Generate a refresh token (7 days expiry)
-  fn generate_refresh_token(&self, address: &str, public_key: &str) -> Result<String> {
+  /// Issue the next pair of a refresh token family. The caller is responsible for
+  /// invalidating the presented refresh token.
+  pub fn rotate_tokens(&self, refresh_claims: &Claims) -> Result<TokenPair> {
+    if refresh_claims.token_type != "refresh" {
+      return Err(Error::invalid_token());
+    }
+
+    let sid = refresh_claims.sid.as_deref().ok_or_else(Error::invalid_token)?;
+
+    self.generate_pair(
+      &refresh_claims.address,
+      &refresh_claims.public_key,
+      refresh_claims.sub.as_deref(),
+      sid,
+    )
+  }
+
+  fn generate_pair(
+    &self,
+    address: &str,
+    public_key: &str,
+    sub: Option<&str>,
+    sid: &str,
+  ) -> Result<TokenPair> {
+    let access_token =
+      self.generate_token(address, public_key, sub, sid, "access", ACCESS_TOKEN_TTL_SECS)?;
+    let refresh_token =
+      self.generate_token(address, public_key, sub, sid, "refresh", REFRESH_TOKEN_TTL_SECS)?;
+
+    Ok(TokenPair { access_token, refresh_token })
+  }
+
+  /// Generate a token of the given type with a fresh `jti`
+  fn generate_token(
+    &self,
+    address: &str,
+    public_key: &str,
+    sub: Option<&str>,
+    sid: &str,
+    token_type: &str,
+    ttl: i64,
+  ) -> Result<String> {
     let now = Utc::now();
-    let exp = (now + Duration::days(7)).timestamp() as usize;
+    let exp = (now + Duration::seconds(ttl)).timestamp() as usize;
     let iat = now.timestamp() as usize;
 
     let claims = Claims {
       address: address.to_string(),
       public_key: public_key.to_string(),
-      token_type: "refresh".to_string(),
+      token_type: token_type.to_string(),
       exp,
       iat,
+      sub: sub.map(str::to_string),
+      jti: Some(Uuid::new_v4().to_string()),
+      sid: Some(sid.to_string()),
     };
 
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(self.secret.as_ref()))
-      .map_err(|e| Error::internal_error(&format!("Failed to generate refresh token: {}", e)))
-  }
-
-  /// Validate and decode a token
-  pub fn validate_token(&self, token: &str) -> Result<Claims> {
-    let validation = Validation::new(Algorithm::HS256);
+    let (kid, key) = self
+      .keys
+      .signing_key_at(now)
+      .ok_or_else(|| Error::internal_error("No JWT key can sign now"))?;
+    let mut header = Header::new(self.keys.algorithm());
+    header.kid = kid.map(str::to_string);
 
-    decode::<Claims>(token, &DecodingKey::from_secret(self.secret.as_ref()), &validation)
-      .map(|data| data.claims)
-      .map_err(|e| e.into())
+    encode(&header, &claims, key).map_err(|e| {
+      Error::internal_error(&format!("Failed to generate {} token: {}", token_type, e))
+    })
   }
 
-  /// Generate a new access token from a valid refresh token
-  pub fn refresh_access_token(&self, refresh_token: &str) -> Result<String> {
-    let claims = self.validate_token(refresh_token)?;
+  /// Validate and decode a token, picking the verification key by the header `kid`
+  pub fn validate_token(&self, token: &str) -> Result<Claims> {
+    let header = decode_header(token)?;
+    let (key, window) =
+      self.keys.decoding_key(header.kid.as_deref()).ok_or_else(Error::invalid_token)?;
+    // Pinned to the configured algorithm, the header `alg` is never trusted
+    let validation = Validation::new(self.keys.algorithm());
 
-    // Ensure it's a refresh token
-    if claims.token_type != "refresh" {
+    let claims = decode::<Claims>(token, key, &validation)?.claims;
+    // A retired key signs nothing, anything it claims to have issued since is forged
+    let issued_at = DateTime::from_timestamp(claims.iat as i64, 0);
+    if !issued_at.is_some_and(|issued_at| window.accepts_issued_at(issued_at)) {
       return Err(Error::invalid_token());
     }
 
-    self.generate_access_token(&claims.address, &claims.public_key)
+    Ok(claims)
   }
 
   /// Extract token from Authorization header
@@ -102,3 +192,123 @@ impl JwtManager {
     Ok(&auth_header[7..]) // Skip "Bearer "
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use ed25519_dalek::SigningKey;
+  use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+  use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+  use jsonwebtoken::Algorithm;
+
+  use super::*;
+  use crate::domain::{KeyMaterial, KeyWindow};
+
+  fn ed_key(kid: &str, seed: u8, is_private: bool, window: KeyWindow) -> KeyMaterial {
+    let signing = SigningKey::from_bytes(&[seed; 32]);
+    let pem = if is_private {
+      signing.to_pkcs8_pem(LineEnding::LF).unwrap().to_string()
+    } else {
+      signing.verifying_key().to_public_key_pem(LineEnding::LF).unwrap()    };
+
+    KeyMaterial { kid: kid.to_string(), pem, is_private, window }
+  }
+
+  fn manager(keys: Vec<KeyMaterial>) -> JwtManager {
+    JwtManager::with_keys(Arc::new(JwtKeySet::asymmetric(Algorithm::EdDSA, None, keys).unwrap()))
+  }
+
+  #[test]
+  fn user_tokens_carry_subject_and_session() {
+    let manager = JwtManager::new("test-secret".to_string());
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let pair = manager.generate_tokens_for_user(user_id, session_id, "0xabc", "pk").unwrap();
+    let access = manager.validate_token(&pair.access_token).unwrap();
+    let refresh = manager.validate_token(&pair.refresh_token).unwrap();
+
+    assert_eq!(access.token_type, "access");
+    assert_eq!(access.user_id(), Some(user_id));
+    assert_eq!(access.session_id(), Some(session_id));
+    assert_eq!(refresh.session_id(), Some(session_id));
+  }
+
+  #[test]
+  fn legacy_tokens_have_no_subject() {
+    let manager = JwtManager::new("test-secret".to_string());
+
+    let pair = manager.generate_tokens("0xabc", "pk", Uuid::new_v4()).unwrap();
+    let access = manager.validate_token(&pair.access_token).unwrap();
+
+    assert_eq!(access.user_id(), None);
+  }
+
+  #[test]
+  fn rotated_pair_keeps_subject_and_session() {
+    let manager = JwtManager::new("test-secret".to_string());
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+
+    let pair = manager.generate_tokens_for_user(user_id, session_id, "0xabc", "pk").unwrap();
+    let refresh = manager.validate_token(&pair.refresh_token).unwrap();
+    let rotated = manager.rotate_tokens(&refresh).unwrap();
+    let next = manager.validate_token(&rotated.refresh_token).unwrap();
+
+    assert_ne!(rotated.refresh_token, pair.refresh_token);
+    assert_ne!(next.jti, refresh.jti);
+    assert_eq!(next.user_id(), Some(user_id));
+    assert_eq!(next.session_id(), Some(session_id));
+  }
+
+  #[test]
+  fn access_token_cannot_be_rotated() {
+    let manager = JwtManager::new("test-secret".to_string());
+
+    let pair = manager.generate_tokens("0xabc", "pk", Uuid::new_v4()).unwrap();
+    let access = manager.validate_token(&pair.access_token).unwrap();
+
+    assert!(manager.rotate_tokens(&access).is_err());
+  }
+
+  #[test]
+  fn rotated_out_key_still_verifies() {
+    let old = manager(vec![ed_key("k1", 1, true, KeyWindow::default())]);
+    let retired = KeyWindow { not_before: None, retire_at: Some(Utc::now()) };
+    let rotated = manager(vec![
+      ed_key("k1", 1, true, retired),
+      ed_key("k2", 2, true, KeyWindow { not_before: Some(Utc::now()), retire_at: None }),
+    ]);
+
+    let old_pair = old.generate_tokens("0xabc", "pk", Uuid::new_v4()).unwrap();
+    let new_pair = rotated.generate_tokens("0xabc", "pk", Uuid::new_v4()).unwrap();
+
+    assert!(rotated.validate_token(&old_pair.access_token).is_ok());
+    assert!(rotated.validate_token(&new_pair.access_token).is_ok());
+    let header = decode_header(&new_pair.access_token).unwrap();
+    assert_eq!(header.kid.as_deref(), Some("k2"));
+    assert_eq!(rotated.jwks().keys.len(), 2);
+  }
+
+  #[test]
+  fn retired_key_rejects_tokens_issued_after_retirement() {
+    // Still holds the private key, e.g. a replica that missed the reload
+    let stale = manager(vec![ed_key("k1", 1, true, KeyWindow::default())]);
+    let retired = KeyWindow { not_before: None, retire_at: Some(Utc::now() - Duration::hours(1)) };
+    let rotated =
+      manager(vec![ed_key("k1", 1, false, retired), ed_key("k2", 2, true, KeyWindow::default())]);
+
+    let pair = stale.generate_tokens("0xabc", "pk", Uuid::new_v4()).unwrap();
+
+    assert!(rotated.validate_token(&pair.access_token).is_err());
+  }
+
+  #[test]
+  fn removed_key_no_longer_verifies() {
+    let old = manager(vec![ed_key("k1", 1, true, KeyWindow::default())]);
+    let rotated = manager(vec![ed_key("k2", 2, true, KeyWindow::default())]);
+
+    let pair = old.generate_tokens("0xabc", "pk", Uuid::new_v4()).unwrap();
+
+    assert!(rotated.validate_token(&pair.access_token).is_err());
+  }
+}
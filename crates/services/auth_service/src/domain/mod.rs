@@ -1,17 +1,48 @@
+pub mod api_key;
 pub mod auth_user;
 pub mod auth_provider;
+pub mod email_link;
 pub mod user_role;
 pub mod jwt;
 pub mod nonce;
+pub mod oauth_state;
+pub mod password;
+pub mod session;
+pub(crate) mod api_key_repository_trait;
+pub(crate) mod auth_provider_repository_trait;
+pub(crate) mod email_link_repository_trait;
 pub(crate) mod nonce_repository_trait;
+pub(crate) mod oauth_state_repository_trait;
+pub(crate) mod permission_repository_trait;
+pub(crate) mod refresh_token_repository_trait;
+pub(crate) mod session_repository_trait;
 pub(crate) mod signature_verifier_trait;
+pub(crate) mod unified_user_repository_trait;
+pub(crate) mod unit_of_work_trait;
 pub(crate) mod user_repository_trait;
+pub(crate) mod verification_mailer_trait;
 
+pub use api_key::*;
 pub use auth_user::*;
 pub use auth_provider::*;
+pub use email_link::*;
 pub use user_role::*;
 pub use jwt::*;
+pub use jd_core::jwt_keys::{JwtKeySet, KeyMaterial, KeyWindow};
 pub use nonce::*;
+pub use oauth_state::*;
+pub use password::*;
+pub use session::*;
+pub(crate) use api_key_repository_trait::ApiKeyRepository;
+pub(crate) use auth_provider_repository_trait::AuthProviderRepository;
+pub(crate) use email_link_repository_trait::EmailLinkRepository;
 pub(crate) use nonce_repository_trait::NonceRepository;
+pub(crate) use oauth_state_repository_trait::OAuthStateRepository;
+pub(crate) use permission_repository_trait::PermissionRepository;
+pub(crate) use refresh_token_repository_trait::{RefreshRotation, RefreshTokenRepository};
+pub(crate) use session_repository_trait::SessionRepository;
 pub(crate) use signature_verifier_trait::SignatureVerifier;
+pub(crate) use unified_user_repository_trait::UnifiedUserRepository;
+pub(crate) use unit_of_work_trait::UnitOfWork;
 pub(crate) use user_repository_trait::UserRepository;
+pub(crate) use verification_mailer_trait::VerificationMailer;
@@ -8,4 +8,6 @@ pub trait NonceRepository: Send + Sync {
   async fn store_nonce(&self, nonce: &Nonce) -> Result<()>;
   async fn get_nonce(&self, address: &str) -> Result<Option<Nonce>>;
   async fn remove_nonce(&self, address: &str) -> Result<()>;
+  /// Returns and deletes the nonce in one step, a nonce can only be consumed once
+  async fn take_nonce(&self, address: &str) -> Result<Option<Nonce>>;
 }
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::OAuthState;
+use crate::error::Result;
+
+/// Server side storage of pending OAuth authorizations
+#[async_trait]
+pub trait OAuthStateRepository: Send + Sync {
+  async fn store(&self, state: &OAuthState) -> Result<()>;
+  /// Returns and deletes the state in one step, a state can only be consumed once
+  async fn take(&self, state: &str) -> Result<Option<OAuthState>>;
+}
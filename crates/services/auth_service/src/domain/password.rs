@@ -0,0 +1,235 @@
+use argon2::password_hash::{SaltString, rand_core::OsRng};
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
+use jd_utils::config::PasswordConfig;
+
+use crate::error::{Error, Result};
+
+/// Upper bound keeps hashing cost predictable for hostile inputs
+const MAX_PASSWORD_LENGTH: usize = 128;
+/// Lower, upper, digit and symbol; a password must mix at least this many
+const MIN_CHARACTER_CLASSES: usize = 3;
+/// Salt and output of the hash verified when no account matches, only its cost matters
+const DUMMY_SALT: &str = "ZHVtbXlkdW1teWR1bW15";
+const DUMMY_OUTPUT: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordVerification {
+  Invalid,
+  /// `needs_rehash` is set when the stored hash was produced with other parameters
+  Valid { needs_rehash: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordManager {
+  params: Params,
+  min_length: usize,
+  // Same cost as a real hash, so lookups of unknown accounts take as long as wrong passwords
+  dummy_hash: String,
+}
+
+impl PasswordManager {
+  pub fn new(config: &PasswordConfig) -> Result<Self> {
+    let params = Params::new(config.memory_kib, config.iterations, config.parallelism, None)
+      .map_err(|e| Error::internal_error(&format!("Invalid Argon2 parameters: {}", e)))?;
+
+    let dummy_hash = format!(
+      "$argon2id$v=19$m={},t={},p={}${DUMMY_SALT}${DUMMY_OUTPUT}",
+      params.m_cost(),
+      params.t_cost(),
+      params.p_cost(),
+    );
+
+    Ok(Self { params, min_length: config.min_length, dummy_hash })
+  }
+
+  fn argon2(&self) -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+  }
+
+  /// Hash a password into a PHC string with a random salt, on the blocking thread pool
+  pub async fn hash(&self, password: &str) -> Result<String> {
+    let (manager, password) = (self.clone(), password.to_string());
+    run_blocking(move || manager.hash_now(&password)).await
+  }
+
+  /// Verify a password against a stored PHC string, on the blocking thread pool
+  pub async fn verify(&self, password: &str, stored_hash: &str) -> Result<PasswordVerification> {
+    let (manager, password, stored_hash) =
+      (self.clone(), password.to_string(), stored_hash.to_string());
+    run_blocking(move || manager.verify_now(&password, &stored_hash)).await
+  }
+
+  /// Spends the time of a verification when there is no hash to check the password against
+  pub async fn verify_dummy(&self, password: &str) {
+    let _ = self.verify(password, &self.dummy_hash).await;
+  }
+
+  fn hash_now(&self, password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    self
+      .argon2()
+      .hash_password(password.as_bytes(), &salt)
+      .map(|hash| hash.to_string())
+      .map_err(|e| Error::internal_error(&format!("Failed to hash password: {}", e)))
+  }
+
+  fn verify_now(&self, password: &str, stored_hash: &str) -> Result<PasswordVerification> {
+    let parsed = PasswordHash::new(stored_hash)
+      .map_err(|e| Error::internal_error(&format!("Malformed password hash: {}", e)))?;
+
+    // Verification uses the parameters embedded in the stored hash
+    match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+      Ok(()) => Ok(PasswordVerification::Valid { needs_rehash: self.needs_rehash(&parsed) }),
+      Err(argon2::password_hash::Error::Password) => Ok(PasswordVerification::Invalid),
+      Err(e) => Err(Error::internal_error(&format!("Failed to verify password: {}", e))),
+    }
+  }
+
+  fn needs_rehash(&self, parsed: &PasswordHash<'_>) -> bool {
+    if parsed.algorithm != Algorithm::Argon2id.ident()
+      || parsed.version != Some(Version::V0x13.into())
+    {
+      return true;
+    }
+
+    match Params::try_from(parsed) {
+      Ok(params) => {
+        params.m_cost() != self.params.m_cost()
+          || params.t_cost() != self.params.t_cost()
+          || params.p_cost() != self.params.p_cost()
+      }
+      Err(_) => true,
+    }
+  }
+
+  /// Enforce the password strength policy. `identifiers` are values the password
+  /// must not contain, such as the username or the local part of the email.
+  pub fn validate_strength(&self, password: &str, identifiers: &[&str]) -> Result<()> {
+    let length = password.chars().count();
+
+    if length < self.min_length {
+      return Err(Error::weak_password(&format!(
+        "must be at least {} characters long",
+        self.min_length
+      )));
+    }
+
+    if length > MAX_PASSWORD_LENGTH {
+      return Err(Error::weak_password(&format!(
+        "must be at most {} characters long",
+        MAX_PASSWORD_LENGTH
+      )));
+    }
+
+    let classes = [
+      password.chars().any(|c| c.is_lowercase()),
+      password.chars().any(|c| c.is_uppercase()),
+      password.chars().any(|c| c.is_ascii_digit()),
+      password.chars().any(|c| !c.is_alphanumeric()),
+    ];
+
+    if classes.iter().filter(|present| **present).count() < MIN_CHARACTER_CLASSES {
+      return Err(Error::weak_password(
+        "must mix at least three of lowercase, uppercase, digits and symbols",
+      ));
+    }
+
+    let lowered = password.to_lowercase();
+    let contains_identifier = identifiers
+      .iter()
+      .map(|id| id.trim().to_lowercase())
+      .any(|id| id.chars().count() >= 3 && lowered.contains(&id));
+
+    if contains_identifier {
+      return Err(Error::weak_password("must not contain your username or email"));
+    }
+
+    Ok(())
+  }
+}
+
+/// Argon2 takes tens of milliseconds of CPU, too long to run on an async worker
+async fn run_blocking<T: Send + 'static>(
+  f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+  tokio::task::spawn_blocking(f)
+    .await
+    .map_err(|e| Error::internal_error(&format!("Password task failed: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn manager() -> PasswordManager {
+    // Cheap parameters to keep the tests fast
+    let config = PasswordConfig { memory_kib: 1024, iterations: 1, parallelism: 1, min_length: 10 };
+    PasswordManager::new(&config).unwrap()
+  }
+
+  #[tokio::test]
+  async fn hash_and_verify_roundtrip() {
+    let manager = manager();
+    let hash = manager.hash("Correct-Horse-42").await.unwrap();
+
+    assert!(hash.starts_with("$argon2id$"));
+    assert_eq!(
+      manager.verify("Correct-Horse-42", &hash).await.unwrap(),
+      PasswordVerification::Valid { needs_rehash: false }
+    );
+    assert_eq!(
+      manager.verify("wrong-password", &hash).await.unwrap(),
+      PasswordVerification::Invalid
+    );
+  }
+
+  #[tokio::test]
+  async fn changed_parameters_request_rehash() {
+    let hash = manager().hash("Correct-Horse-42").await.unwrap();
+
+    let stronger = PasswordConfig { memory_kib: 2048, iterations: 2, parallelism: 1, min_length: 10 };
+    let stronger = PasswordManager::new(&stronger).unwrap();
+
+    assert_eq!(
+      stronger.verify("Correct-Horse-42", &hash).await.unwrap(),
+      PasswordVerification::Valid { needs_rehash: true }
+    );
+  }
+
+  #[test]
+  fn dummy_hash_uses_configured_cost() {
+    let manager = manager();
+    let parsed = PasswordHash::new(&manager.dummy_hash).unwrap();
+
+    assert!(!manager.needs_rehash(&parsed));
+    assert_eq!(
+      manager
+        .verify_now("Correct-Horse-42", &manager.dummy_hash)
+        .unwrap(),
+      PasswordVerification::Invalid
+    );
+  }
+
+  #[test]
+  fn strength_policy() {
+    let manager = manager();
+
+    assert!(
+      manager
+        .validate_strength("Correct-Horse-42", &["jayden"])
+        .is_ok()
+    );
+    assert!(manager.validate_strength("Sh0rt!", &[]).is_err());
+    assert!(
+      manager
+        .validate_strength("alllowercaseletters", &[])
+        .is_err()
+    );
+    assert!(
+      manager
+        .validate_strength("Jayden-Password-1", &["jayden"])
+        .is_err()
+    );
+  }
+}
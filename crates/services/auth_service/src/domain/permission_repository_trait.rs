@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{UserAccess, UserPermission, UserRole};
+use crate::error::Result;
+
+#[async_trait]
+pub trait PermissionRepository: Send + Sync {
+  async fn get_user_access(&self, user_id: Uuid) -> Result<Option<UserAccess>>;
+  async fn get_role_permissions(&self, role: UserRole) -> Result<Vec<UserPermission>>;
+  async fn update_user_role(&self, user_id: Uuid, role: UserRole) -> Result<()>;
+
+  // -- Permission cache
+  async fn get_cached_permissions(
+    &self,
+    user_id: Uuid,
+    role: UserRole,
+  ) -> Result<Option<Vec<UserPermission>>>;
+  async fn cache_permissions(
+    &self,
+    user_id: Uuid,
+    role: UserRole,
+    permissions: &[UserPermission],
+  ) -> Result<()>;
+  async fn invalidate_permissions(&self, user_id: Uuid) -> Result<()>;
+}
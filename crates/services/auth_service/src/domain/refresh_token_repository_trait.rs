@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::error::Result;
+
+/// Outcome of presenting a refresh token for rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshRotation {
+  /// The token was the current one of its family and has been replaced
+  Rotated,
+  /// The token was already rotated out, the family must be treated as compromised
+  Reused,
+  /// The family is unknown, either expired or revoked
+  Unknown,
+}
+
+/// Tracks the single valid refresh token of each token family (one family per session)
+#[async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+  async fn register(&self, family_id: Uuid, refresh_token: &str) -> Result<()>;
+  /// Atomically swaps `presented` for `next` if `presented` is the current token
+  async fn rotate(&self, family_id: Uuid, presented: &str, next: &str) -> Result<RefreshRotation>;
+  async fn revoke_family(&self, family_id: Uuid) -> Result<()>;
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Where a session was started from, captured from the login request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_info: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserSession {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub provider_id: Option<Uuid>,
+    pub jwt_token_id: String,
+    pub device_info: Option<serde_json::Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_activity: OffsetDateTime,
+    pub is_revoked: bool,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    pub revoked_reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UserSessionForCreate {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub provider_id: Option<Uuid>,
+    pub jwt_token_id: String,
+    pub client: ClientInfo,
+    pub expires_at: OffsetDateTime,
+}
+
+/// Reasons recorded in `revoked_reason`
+pub mod revoke_reason {
+    pub const USER_REVOKED: &str = "user_revoked";
+    pub const LOGOUT: &str = "logout";
+    pub const LOGOUT_ALL: &str = "logout_all";
+    pub const REFRESH_TOKEN_REUSE: &str = "refresh_token_reuse";
+}
+
+/// `last_activity` is only rewritten once it is older than this, not on every request
+pub const SESSION_TOUCH_INTERVAL: time::Duration = time::Duration::minutes(5);
+
+impl UserSession {
+    pub fn is_active(&self) -> bool {
+        !self.is_revoked && self.expires_at > OffsetDateTime::now_utc()
+    }
+
+    pub fn needs_touch(&self) -> bool {
+        self.last_activity < OffsetDateTime::now_utc() - SESSION_TOUCH_INTERVAL
+    }
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{UserSession, UserSessionForCreate};
+use crate::error::Result;
+
+/// Persistence for `unified_auth.user_sessions`
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+  async fn create(&self, session: UserSessionForCreate) -> Result<UserSession>;
+  async fn find_by_id(&self, session_id: Uuid) -> Result<Option<UserSession>>;
+  async fn list_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserSession>>;
+  /// True when the user has logged in before but never with this user agent
+  async fn is_unknown_client(&self, user_id: Uuid, user_agent: &str) -> Result<bool>;
+  /// Refreshes `last_activity`, implementations may throttle the write
+  async fn touch(&self, session_id: Uuid) -> Result<()>;
+  /// Returns false when the session does not belong to the user or is already revoked
+  async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str) -> Result<bool>;
+  async fn revoke_all_for_user(&self, user_id: Uuid, reason: &str) -> Result<u64>;
+}
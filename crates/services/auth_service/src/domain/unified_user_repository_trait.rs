@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::domain::{UnifiedAuthUser, UnifiedAuthUserForCreate};
+use crate::error::Result;
+
+/// Persistence for `unified_auth.users`
+#[async_trait]
+pub trait UnifiedUserRepository: Send + Sync {
+  async fn find_by_id(&self, user_id: Uuid) -> Result<Option<UnifiedAuthUser>>;
+  async fn find_by_email(&self, email: &str) -> Result<Option<UnifiedAuthUser>>;
+  async fn find_by_username(&self, username: &str) -> Result<Option<UnifiedAuthUser>>;
+  async fn create(&self, user: UnifiedAuthUserForCreate) -> Result<UnifiedAuthUser>;
+  /// Bumps `last_login` and `login_count` and returns the updated user
+  async fn record_login(&self, user_id: Uuid) -> Result<UnifiedAuthUser>;
+  /// Sets the primary email and marks it verified
+  async fn set_verified_email(&self, user_id: Uuid, email: &str) -> Result<UnifiedAuthUser>;
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use jd_messaging::EventEnvelope;
+
+use crate::error::Result;
+
+/// Transaction shared by the repositories of one use case, and the outbox written inside it.
+/// Begin/commit/rollback nest, so `with_transaction!` blocks can call each other.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+  async fn begin_txn(&self) -> Result<()>;
+  async fn commit_txn(&self) -> Result<()>;
+  async fn rollback_txn(&self) -> Result<()>;
+  /// Records the event in the current transaction, it is only relayed once that commits
+  async fn enqueue(&self, envelope: EventEnvelope) -> Result<()>;
+}
@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+    sqlx::Type,
+)]
 #[sqlx(type_name = "user_role", rename_all = "lowercase")]
 pub enum UserRole {
+    #[default]
     Normal,
     Member,
     Vip,
@@ -62,9 +67,15 @@ impl Display for UserRole {
     }
 }
 
-impl Default for UserRole {
-    fn default() -> Self {
-        UserRole::Normal
+/// Parses the names `Display` writes
+impl FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        UserRole::all()
+            .into_iter()
+            .find(|candidate| candidate.to_string() == role)
+            .ok_or_else(|| format!("Unknown user role `{}`", role))
     }
 }
 
@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+
+use crate::error::Result;
+
+/// Delivers verification tokens to email addresses
+#[async_trait]
+pub trait VerificationMailer: Send + Sync {
+  async fn send_email_link_token(&self, email: &str, token: &str) -> Result<()>;
+}
@@ -53,6 +53,40 @@ impl Error {
     Self::new("Invalid token format", "INVALID_TOKEN_FORMAT")
   }
 
+  // Session related errors
+  pub fn session_revoked() -> Self {
+    Self::new("Session has been revoked or has expired", "SESSION_REVOKED")
+  }
+
+  pub fn session_not_found() -> Self {
+    Self::new("Session not found", "SESSION_NOT_FOUND")
+  }
+
+  pub fn refresh_token_reused() -> Self {
+    Self::new("Refresh token was already used, session revoked", "REFRESH_TOKEN_REUSED")
+  }
+
+  // API key related errors
+  pub fn invalid_api_key() -> Self {
+    Self::new("Invalid API key", "INVALID_API_KEY")
+  }
+
+  pub fn api_key_revoked() -> Self {
+    Self::new("API key has been revoked", "API_KEY_REVOKED")
+  }
+
+  pub fn api_key_expired() -> Self {
+    Self::new("API key has expired", "API_KEY_EXPIRED")
+  }
+
+  pub fn api_key_not_found() -> Self {
+    Self::new("API key not found", "API_KEY_NOT_FOUND")
+  }
+
+  pub fn invalid_api_key_scopes(reason: &str) -> Self {
+    Self::new(&format!("Invalid API key scopes: {}", reason), "INVALID_API_KEY_SCOPES")
+  }
+
   // Database related errors
   pub fn database_error(msg: &str) -> Self {
     Self::new(&format!("Database error: {}", msg), "DATABASE_ERROR")
@@ -86,6 +120,10 @@ impl Error {
     Self::new("OAuth state has expired", "EXPIRED_OAUTH_STATE")
   }
 
+  pub fn redirect_url_not_allowed() -> Self {
+    Self::new("Redirect URL is not allowed", "REDIRECT_URL_NOT_ALLOWED")
+  }
+
   pub fn unsupported_oauth_provider() -> Self {
     Self::new("Unsupported OAuth provider", "UNSUPPORTED_OAUTH_PROVIDER")
   }
@@ -111,6 +149,10 @@ impl Error {
     Self::new("Username already exists", "USERNAME_ALREADY_EXISTS")
   }
 
+  pub fn weak_password(reason: &str) -> Self {
+    Self::new(&format!("Password {}", reason), "WEAK_PASSWORD")
+  }
+
   pub fn invalid_credentials() -> Self {
     Self::new("Invalid credentials", "INVALID_CREDENTIALS")
   }
@@ -119,10 +161,40 @@ impl Error {
     Self::new("Account is disabled", "ACCOUNT_DISABLED")
   }
 
+  // Provider linking errors
+  pub fn provider_not_found() -> Self {
+    Self::new("Auth provider not found", "PROVIDER_NOT_FOUND")
+  }
+
+  pub fn provider_already_linked() -> Self {
+    Self::new("Auth provider is already linked to an account", "PROVIDER_ALREADY_LINKED")
+  }
+
+  pub fn oauth_email_not_verified() -> Self {
+    Self::new(
+      "Email belongs to an existing account and the provider has not verified it, sign in and \
+       link the provider instead",
+      "OAUTH_EMAIL_NOT_VERIFIED",
+    )
+  }
+
+  pub fn last_auth_provider() -> Self {
+    Self::new("Cannot remove the last authentication provider", "LAST_AUTH_PROVIDER")
+  }
+
+  pub fn invalid_verification_token() -> Self {
+    Self::new("Invalid or expired verification token", "INVALID_VERIFICATION_TOKEN")
+  }
+
   // Internal errors
   pub fn internal_error(msg: &str) -> Self {
     Self::new(&format!("Internal error: {}", msg), "INTERNAL_ERROR")
   }
+
+  /// Storage or internal failure, as opposed to a problem with the caller's credentials
+  pub fn is_infrastructure(&self) -> bool {
+    matches!(self.code.as_str(), "DATABASE_ERROR" | "REDIS_ERROR" | "INTERNAL_ERROR")
+  }
 }
 
 impl fmt::Display for Error {
@@ -140,6 +212,18 @@ impl From<sqlx::Error> for Error {
   }
 }
 
+impl From<jd_storage::dbx::Error> for Error {
+  fn from(err: jd_storage::dbx::Error) -> Self {
+    Error::database_error(&err.to_string())
+  }
+}
+
+impl From<jd_messaging::Error> for Error {
+  fn from(err: jd_messaging::Error) -> Self {
+    Error::internal_error(&err.to_string())
+  }
+}
+
 impl From<redis::RedisError> for Error {
   fn from(err: redis::RedisError) -> Self {
     Error::redis_error(&err.to_string())
@@ -168,18 +252,29 @@ impl axum::response::IntoResponse for Error {
       "NONCE_NOT_FOUND" | "NONCE_EXPIRED" | "INVALID_SIGNATURE" | "INVALID_PUBLIC_KEY" => {
         axum::http::StatusCode::UNAUTHORIZED
       }
-      "INVALID_TOKEN" | "TOKEN_EXPIRED" | "MISSING_AUTH_HEADER" | "INVALID_TOKEN_FORMAT" => {
+      "INVALID_TOKEN" | "TOKEN_EXPIRED" | "MISSING_AUTH_HEADER" | "INVALID_TOKEN_FORMAT"
+      | "SESSION_REVOKED" | "REFRESH_TOKEN_REUSED" => {
         axum::http::StatusCode::UNAUTHORIZED
       }
       "INVALID_CREDENTIALS" | "ACCOUNT_DISABLED" => axum::http::StatusCode::UNAUTHORIZED,
+      "INVALID_API_KEY" | "API_KEY_REVOKED" | "API_KEY_EXPIRED" => {
+        axum::http::StatusCode::UNAUTHORIZED
+      }
       "INSUFFICIENT_PERMISSIONS" => axum::http::StatusCode::FORBIDDEN,
-      "USER_NOT_FOUND" => axum::http::StatusCode::NOT_FOUND,
-      "EMAIL_ALREADY_EXISTS" | "USERNAME_ALREADY_EXISTS" => axum::http::StatusCode::CONFLICT,
+      "USER_NOT_FOUND" | "SESSION_NOT_FOUND" | "PROVIDER_NOT_FOUND" | "API_KEY_NOT_FOUND" => {
+        axum::http::StatusCode::NOT_FOUND
+      }
+      "EMAIL_ALREADY_EXISTS" | "USERNAME_ALREADY_EXISTS" | "PROVIDER_ALREADY_LINKED"
+      | "LAST_AUTH_PROVIDER" | "OAUTH_EMAIL_NOT_VERIFIED" => axum::http::StatusCode::CONFLICT,
       "RATE_LIMIT_EXCEEDED" => axum::http::StatusCode::TOO_MANY_REQUESTS,
-      "INVALID_ADDRESS" | "INVALID_REQUEST_DATA" | "INVALID_OAUTH_STATE" | "EXPIRED_OAUTH_STATE" | "UNSUPPORTED_OAUTH_PROVIDER" => {
+      "INVALID_ADDRESS" | "INVALID_REQUEST_DATA" | "INVALID_OAUTH_STATE" | "EXPIRED_OAUTH_STATE" | "UNSUPPORTED_OAUTH_PROVIDER"
+      | "REDIRECT_URL_NOT_ALLOWED" => {
+        axum::http::StatusCode::BAD_REQUEST
+      }
+      "OAUTH_ERROR" | "WEAK_PASSWORD" | "INVALID_VERIFICATION_TOKEN"
+      | "INVALID_API_KEY_SCOPES" => {
         axum::http::StatusCode::BAD_REQUEST
       }
-      "OAUTH_ERROR" => axum::http::StatusCode::BAD_REQUEST,
       _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
     };
 
@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use uuid::Uuid;
+
+use crate::domain::{ApiKey, ApiKeyForCreate, ApiKeyRepository, ApiKeySecret};
+use crate::error::Result;
+
+const API_KEY_COLUMNS: &str = "key_id, owner_id, name, prefix, scopes, rate_limit, created_by, \
+  created_at, rotated_at, expires_at, last_used_at, revoked_at";
+
+/// `last_used_at` is written at most once per this many seconds per key
+const TOUCH_INTERVAL_SECS: i32 = 60;
+
+pub struct ApiKeyRepositoryImpl {
+  state: AppState,
+}
+
+impl ApiKeyRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepositoryImpl {
+  async fn create(&self, key: ApiKeyForCreate) -> Result<ApiKey> {
+    let sql = format!(
+      "INSERT INTO unified_auth.api_keys \
+       (owner_id, name, prefix, key_hash, scopes, rate_limit, created_by, expires_at) \
+       VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING {API_KEY_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, ApiKey>(&sql)
+      .bind(key.owner_id)
+      .bind(key.name)
+      .bind(key.secret.prefix)
+      .bind(key.secret.hash)
+      .bind(key.scopes)
+      .bind(key.rate_limit)
+      .bind(key.created_by)
+      .bind(key.expires_at);
+
+    Ok(self.state.mm.dbx().fetch_one(query).await?)
+  }
+
+  async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+    let sql = format!("SELECT {API_KEY_COLUMNS} FROM unified_auth.api_keys WHERE key_hash = $1");
+    let query = sqlx::query_as::<_, ApiKey>(&sql).bind(key_hash);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn list(&self, owner_id: Option<Uuid>) -> Result<Vec<ApiKey>> {
+    let sql = format!(
+      "SELECT {API_KEY_COLUMNS} FROM unified_auth.api_keys \
+       WHERE $1::uuid IS NULL OR owner_id = $1 ORDER BY created_at DESC"
+    );
+    let query = sqlx::query_as::<_, ApiKey>(&sql).bind(owner_id);
+
+    Ok(self.state.mm.dbx().fetch_all(query).await?)
+  }
+
+  async fn unknown_scopes(&self, scopes: &[String]) -> Result<Vec<String>> {
+    let query = sqlx::query_as::<_, (String,)>(
+      "SELECT scope FROM UNNEST($1::text[]) AS scope \
+       WHERE NOT EXISTS \
+       (SELECT 1 FROM unified_auth.permissions p WHERE p.permission_name = scope)",
+    )
+    .bind(scopes);
+
+    let unknown = self.state.mm.dbx().fetch_all(query).await?;
+    Ok(unknown.into_iter().map(|(scope,)| scope).collect())
+  }
+
+  async fn rotate(&self, key_id: Uuid, secret: &ApiKeySecret) -> Result<Option<ApiKey>> {
+    let sql = format!(
+      "UPDATE unified_auth.api_keys \
+       SET prefix = $2, key_hash = $3, rotated_at = CURRENT_TIMESTAMP \
+       WHERE key_id = $1 AND revoked_at IS NULL RETURNING {API_KEY_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, ApiKey>(&sql)
+      .bind(key_id)
+      .bind(&secret.prefix)
+      .bind(&secret.hash);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn revoke(&self, key_id: Uuid) -> Result<bool> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.api_keys SET revoked_at = CURRENT_TIMESTAMP \
+       WHERE key_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(key_id);
+
+    Ok(self.state.mm.dbx().execute(query).await? > 0)
+  }
+
+  async fn touch(&self, key_id: Uuid) -> Result<()> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.api_keys SET last_used_at = CURRENT_TIMESTAMP \
+       WHERE key_id = $1 \
+       AND (last_used_at IS NULL OR last_used_at < CURRENT_TIMESTAMP - $2 * INTERVAL '1 second')",
+    )
+    .bind(key_id)
+    .bind(TOUCH_INTERVAL_SECS);
+
+    self.state.mm.dbx().execute(query).await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,202 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::{
+  AuthProviderRepository, AuthProviderType, OAuthTokenResponse, ProviderStatus, UserAuthProvider,
+  UserAuthProviderForCreate,
+};
+use crate::error::{Error, Result};
+
+const PROVIDER_COLUMNS: &str = "provider_id, user_id, provider_type, provider_user_id, \
+  provider_email, password_hash, wallet_address, public_key, oauth_access_token, \
+  oauth_refresh_token, oauth_token_expires_at, provider_metadata, status, created_at, \
+  updated_at, last_used_at";
+
+pub struct AuthProviderRepositoryImpl {
+  state: AppState,
+}
+
+impl AuthProviderRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+}
+
+#[async_trait]
+impl AuthProviderRepository for AuthProviderRepositoryImpl {
+  async fn find_by_external_id(
+    &self,
+    provider_type: AuthProviderType,
+    provider_user_id: &str,
+  ) -> Result<Option<UserAuthProvider>> {
+    let sql = format!(
+      "SELECT {PROVIDER_COLUMNS} FROM unified_auth.user_auth_providers \
+       WHERE provider_type = $1 AND provider_user_id = $2"
+    );
+    let query =
+      sqlx::query_as::<_, UserAuthProvider>(&sql).bind(provider_type).bind(provider_user_id);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn find_by_wallet_address(&self, wallet_address: &str) -> Result<Option<UserAuthProvider>> {
+    let sql = format!(
+      "SELECT {PROVIDER_COLUMNS} FROM unified_auth.user_auth_providers WHERE wallet_address = $1"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql).bind(wallet_address);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn find_for_user(
+    &self,
+    user_id: Uuid,
+    provider_type: AuthProviderType,
+  ) -> Result<Option<UserAuthProvider>> {
+    let sql = format!(
+      "SELECT {PROVIDER_COLUMNS} FROM unified_auth.user_auth_providers \
+       WHERE user_id = $1 AND provider_type = $2 \
+       ORDER BY status = 'active' DESC, created_at LIMIT 1"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql).bind(user_id).bind(provider_type);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
+    let sql = format!(
+      "SELECT {PROVIDER_COLUMNS} FROM unified_auth.user_auth_providers \
+       WHERE user_id = $1 ORDER BY created_at"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql).bind(user_id);
+
+    Ok(self.state.mm.dbx().fetch_all(query).await?)
+  }
+
+  async fn lock_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserAuthProvider>> {
+    let sql = format!(
+      "SELECT {PROVIDER_COLUMNS} FROM unified_auth.user_auth_providers \
+       WHERE user_id = $1 AND status = 'active' ORDER BY created_at FOR UPDATE"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql).bind(user_id);
+
+    Ok(self.state.mm.dbx().fetch_all(query).await?)
+  }
+
+  async fn create(&self, provider: UserAuthProviderForCreate) -> Result<UserAuthProvider> {
+    let sql = format!(
+      "INSERT INTO unified_auth.user_auth_providers \
+       (user_id, provider_type, provider_user_id, provider_email, password_hash, wallet_address, \
+        public_key, oauth_access_token, oauth_refresh_token, oauth_token_expires_at, provider_metadata) \
+       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING {PROVIDER_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql)
+      .bind(provider.user_id)
+      .bind(provider.provider_type)
+      .bind(provider.provider_user_id)
+      .bind(provider.provider_email)
+      .bind(provider.password_hash)
+      .bind(provider.wallet_address)
+      .bind(provider.public_key)
+      .bind(provider.oauth_access_token)
+      .bind(provider.oauth_refresh_token)
+      .bind(provider.oauth_token_expires_at)
+      .bind(provider.provider_metadata);
+
+    self.state.mm.dbx().fetch_one(query).await.map_err(|e| {
+      if e.is_unique_violation() {
+        Error::provider_already_linked()
+      } else {
+        e.into()
+      }
+    })
+  }
+
+  async fn reactivate(
+    &self,
+    provider_id: Uuid,
+    provider: UserAuthProviderForCreate,
+  ) -> Result<UserAuthProvider> {
+    // Only revoked rows are taken over, a concurrent relink finds nothing to update
+    let sql = format!(
+      "UPDATE unified_auth.user_auth_providers \
+       SET user_id = $2, provider_email = $3, password_hash = $4, public_key = $5, \
+           oauth_access_token = $6, oauth_refresh_token = $7, oauth_token_expires_at = $8, \
+           provider_metadata = $9, status = 'active', last_used_at = CURRENT_TIMESTAMP \
+       WHERE provider_id = $1 AND status = 'revoked' RETURNING {PROVIDER_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql)
+      .bind(provider_id)
+      .bind(provider.user_id)
+      .bind(provider.provider_email)
+      .bind(provider.password_hash)
+      .bind(provider.public_key)
+      .bind(provider.oauth_access_token)
+      .bind(provider.oauth_refresh_token)
+      .bind(provider.oauth_token_expires_at)
+      .bind(provider.provider_metadata);
+
+    self.state.mm.dbx().fetch_optional(query).await?.ok_or_else(Error::provider_already_linked)
+  }
+
+  async fn update_oauth_tokens(
+    &self,
+    provider_id: Uuid,
+    token_response: &OAuthTokenResponse,
+  ) -> Result<UserAuthProvider> {
+    let expires_at = token_response
+      .expires_in
+      .map(|expires_in| OffsetDateTime::now_utc() + time::Duration::seconds(expires_in));
+
+    // Providers do not always rotate refresh tokens, keep the stored one in that case
+    let sql = format!(
+      "UPDATE unified_auth.user_auth_providers \
+       SET oauth_access_token = $2, \
+           oauth_refresh_token = COALESCE($3, oauth_refresh_token), \
+           oauth_token_expires_at = $4, \
+           last_used_at = CURRENT_TIMESTAMP \
+       WHERE provider_id = $1 RETURNING {PROVIDER_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UserAuthProvider>(&sql)
+      .bind(provider_id)
+      .bind(&token_response.access_token)
+      .bind(&token_response.refresh_token)
+      .bind(expires_at);
+
+    self
+      .state
+      .mm
+      .dbx()
+      .fetch_optional(query)
+      .await?
+      .ok_or_else(|| Error::invalid_request_data("Unknown auth provider"))
+  }
+
+  async fn update_password_hash(&self, provider_id: Uuid, password_hash: &str) -> Result<()> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.user_auth_providers SET password_hash = $2 WHERE provider_id = $1",
+    )
+    .bind(provider_id)
+    .bind(password_hash);
+
+    self.state.mm.dbx().execute(query).await?;
+    Ok(())
+  }
+
+  async fn set_status(&self, provider_id: Uuid, status: ProviderStatus) -> Result<()> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.user_auth_providers SET status = $2 WHERE provider_id = $1",
+    )
+    .bind(provider_id)
+    .bind(status);
+
+    let rows = self.state.mm.dbx().execute(query).await?;
+    if rows == 0 {
+      return Err(Error::invalid_request_data("Unknown auth provider"));
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+
+use crate::domain::{EMAIL_LINK_TTL_SECS, EmailLinkRepository, PendingEmailLink};
+use crate::error::{Error, Result};
+
+pub struct EmailLinkRepositoryImpl {
+  state: AppState,
+}
+
+impl EmailLinkRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+
+  /// Keyed by digest, a Redis dump must not yield usable tokens
+  fn link_key(token: &str) -> String {
+    format!("auth:email_link:{}", hex::encode(Sha256::digest(token.as_bytes())))
+  }
+
+  async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+    self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))
+  }
+}
+
+#[async_trait]
+impl EmailLinkRepository for EmailLinkRepositoryImpl {
+  async fn store(&self, token: &str, link: &PendingEmailLink) -> Result<()> {
+    let mut conn = self.connection().await?;
+
+    let value = serde_json::to_string(link)
+      .map_err(|e| Error::internal_error(&format!("Failed to serialize email link: {}", e)))?;
+
+    let _: () = conn.set_ex(Self::link_key(token), value, EMAIL_LINK_TTL_SECS as u64).await?;
+
+    Ok(())
+  }
+
+  async fn take(&self, token: &str) -> Result<Option<PendingEmailLink>> {
+    let mut conn = self.connection().await?;
+
+    let value: Option<String> = conn.get_del(Self::link_key(token)).await?;
+
+    value
+      .map(|json| {
+        serde_json::from_str(&json).map_err(|e| {
+          Error::internal_error(&format!("Failed to deserialize email link: {}", e))
+        })
+      })
+      .transpose()
+  }
+}
@@ -1,7 +1,25 @@
+pub mod api_key_repository_impl;
+pub mod auth_provider_repository_impl;
+pub mod email_link_repository_impl;
 pub mod nonce_repository_impl;
+pub mod oauth_state_repository_impl;
+pub mod permission_repository_impl;
+pub mod refresh_token_repository_impl;
+pub mod session_repository_impl;
 pub mod signature_verifier_impl;
+pub mod unified_user_repository_impl;
+pub mod unit_of_work_impl;
 pub mod user_repository_impl;
 
+pub use api_key_repository_impl::ApiKeyRepositoryImpl;
+pub use auth_provider_repository_impl::AuthProviderRepositoryImpl;
+pub use email_link_repository_impl::EmailLinkRepositoryImpl;
 pub use nonce_repository_impl::NonceRepositoryImpl;
+pub use oauth_state_repository_impl::OAuthStateRepositoryImpl;
+pub use permission_repository_impl::PermissionRepositoryImpl;
+pub use refresh_token_repository_impl::RefreshTokenRepositoryImpl;
+pub use session_repository_impl::SessionRepositoryImpl;
 pub use signature_verifier_impl::SignatureVerifierImpl;
+pub use unified_user_repository_impl::UnifiedUserRepositoryImpl;
+pub use unit_of_work_impl::UnitOfWorkImpl;
 pub use user_repository_impl::UserRepositoryImpl;
@@ -85,4 +85,26 @@ impl NonceRepository for NonceRepositoryImpl {
 
     Ok(())
   }
+
+  async fn take_nonce(&self, address: &str) -> Result<Option<Nonce>> {
+    let mut conn = self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::internal_error(&format!("Failed to get Redis connection: {}", e)))?;
+
+    // GETDEL makes a replayed signature find nothing
+    let value: Option<String> = conn
+      .get_del(Self::nonce_key(address))
+      .await
+      .map_err(|e| Error::internal_error(&format!("Failed to take nonce: {}", e)))?;
+
+    value
+      .map(|json| {
+        serde_json::from_str(&json)
+          .map_err(|e| Error::internal_error(&format!("Failed to deserialize nonce: {}", e)))
+      })
+      .transpose()
+  }
 }
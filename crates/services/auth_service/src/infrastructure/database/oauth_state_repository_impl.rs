@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use redis::AsyncCommands;
+
+use crate::domain::{OAUTH_STATE_TTL_SECS, OAuthState, OAuthStateRepository};
+use crate::error::{Error, Result};
+
+pub struct OAuthStateRepositoryImpl {
+  state: AppState,
+}
+
+impl OAuthStateRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+
+  fn state_key(state: &str) -> String {
+    format!("auth:oauth_state:{}", state)
+  }
+}
+
+#[async_trait]
+impl OAuthStateRepository for OAuthStateRepositoryImpl {
+  async fn store(&self, oauth_state: &OAuthState) -> Result<()> {
+    let mut conn = self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))?;
+
+    let value = serde_json::to_string(oauth_state)
+      .map_err(|e| Error::internal_error(&format!("Failed to serialize OAuth state: {}", e)))?;
+
+    let _: () = conn
+      .set_ex(Self::state_key(&oauth_state.state), value, OAUTH_STATE_TTL_SECS as u64)
+      .await?;
+
+    Ok(())
+  }
+
+  async fn take(&self, state: &str) -> Result<Option<OAuthState>> {
+    let mut conn = self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))?;
+
+    // GETDEL makes a replayed callback find nothing
+    let value: Option<String> = conn.get_del(Self::state_key(state)).await?;
+
+    value
+      .map(|json| {
+        serde_json::from_str(&json).map_err(|e| {
+          Error::internal_error(&format!("Failed to deserialize OAuth state: {}", e))
+        })
+      })
+      .transpose()
+  }
+}
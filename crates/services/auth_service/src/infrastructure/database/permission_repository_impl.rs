@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::{PermissionRepository, UserAccess, UserPermission, UserRole};
+use crate::error::{Error, Result};
+
+/// Cached permissions expire on their own even without an explicit invalidation
+const PERMISSION_CACHE_TTL_SECS: u64 = 600;
+
+/// Permission set cached together with the role it was resolved for, so a role
+/// change that skipped the explicit invalidation is still detected on read.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedPermissions {
+  role: UserRole,
+  permissions: Vec<UserPermission>,
+}
+
+pub struct PermissionRepositoryImpl {
+  state: AppState,
+}
+
+impl PermissionRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+
+  fn permissions_key(user_id: Uuid) -> String {
+    format!("auth:perms:{}", user_id)
+  }
+}
+
+#[async_trait]
+impl PermissionRepository for PermissionRepositoryImpl {
+  async fn get_user_access(&self, user_id: Uuid) -> Result<Option<UserAccess>> {
+    let query = sqlx::query_as::<_, UserAccess>(
+      "SELECT user_id, username, email, COALESCE(role, 'normal') AS role, \
+       COALESCE(is_active, false) AS is_active \
+       FROM unified_auth.users WHERE user_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(user_id);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn get_role_permissions(&self, role: UserRole) -> Result<Vec<UserPermission>> {
+    let query = sqlx::query_as::<_, UserPermission>(
+      "SELECT p.permission_name, p.resource, p.action \
+       FROM unified_auth.role_permissions rp \
+       JOIN unified_auth.permissions p ON rp.permission_id = p.permission_id \
+       WHERE rp.role = $1 ORDER BY p.permission_name",
+    )
+    .bind(role);
+
+    Ok(self.state.mm.dbx().fetch_all(query).await?)
+  }
+
+  async fn update_user_role(&self, user_id: Uuid, role: UserRole) -> Result<()> {
+    let query = sqlx::query("UPDATE unified_auth.users SET role = $1 WHERE user_id = $2")
+      .bind(role)
+      .bind(user_id);
+
+    let rows = self.state.mm.dbx().execute(query).await?;
+    if rows == 0 {
+      return Err(Error::user_not_found());
+    }
+
+    Ok(())
+  }
+
+  async fn get_cached_permissions(
+    &self,
+    user_id: Uuid,
+    role: UserRole,
+  ) -> Result<Option<Vec<UserPermission>>> {
+    let mut conn = self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))?;
+
+    let value: Option<String> = conn.get(Self::permissions_key(user_id)).await?;
+
+    let Some(json) = value else {
+      return Ok(None);
+    };
+
+    let cached: CachedPermissions = serde_json::from_str(&json)
+      .map_err(|e| Error::internal_error(&format!("Failed to deserialize permissions: {}", e)))?;
+
+    // Resolved for a different role, treat as a miss
+    if cached.role != role {
+      return Ok(None);
+    }
+
+    Ok(Some(cached.permissions))
+  }
+
+  async fn cache_permissions(
+    &self,
+    user_id: Uuid,
+    role: UserRole,
+    permissions: &[UserPermission],
+  ) -> Result<()> {
+    let mut conn = self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))?;
+
+    let value = serde_json::to_string(&CachedPermissions { role, permissions: permissions.to_vec() })
+      .map_err(|e| Error::internal_error(&format!("Failed to serialize permissions: {}", e)))?;
+
+    let _: () = conn.set_ex(Self::permissions_key(user_id), value, PERMISSION_CACHE_TTL_SECS).await?;
+
+    Ok(())
+  }
+
+  async fn invalidate_permissions(&self, user_id: Uuid) -> Result<()> {
+    let mut conn = self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))?;
+
+    let _: () = conn.del(Self::permissions_key(user_id)).await?;
+
+    Ok(())
+  }
+}
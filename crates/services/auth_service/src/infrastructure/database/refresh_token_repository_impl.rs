@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::domain::{REFRESH_TOKEN_TTL_SECS, RefreshRotation, RefreshTokenRepository};
+use crate::error::{Error, Result};
+
+/// Compare-and-swap of the current token digest.
+/// Returns 1 when rotated, -1 when a stale token was presented and 0 when the family is gone.
+const ROTATE_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if not current then
+  return 0
+end
+if current ~= ARGV[1] then
+  return -1
+end
+redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+return 1
+"#;
+
+pub struct RefreshTokenRepositoryImpl {
+  state: AppState,
+}
+
+impl RefreshTokenRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+
+  fn family_key(family_id: Uuid) -> String {
+    format!("auth:refresh:{}", family_id)
+  }
+
+  /// Only a digest is stored, a Redis dump must not yield usable tokens
+  fn digest(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+  }
+
+  async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+    self
+      .state
+      .redis
+      .get_multiplexed_async_connection()
+      .await
+      .map_err(|e| Error::redis_error(&format!("Failed to get Redis connection: {}", e)))
+  }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
+  async fn register(&self, family_id: Uuid, refresh_token: &str) -> Result<()> {
+    let mut conn = self.connection().await?;
+
+    let _: () = conn
+      .set_ex(
+        Self::family_key(family_id),
+        Self::digest(refresh_token),
+        REFRESH_TOKEN_TTL_SECS as u64,
+      )
+      .await?;
+
+    Ok(())
+  }
+
+  async fn rotate(&self, family_id: Uuid, presented: &str, next: &str) -> Result<RefreshRotation> {
+    let mut conn = self.connection().await?;
+
+    let outcome: i64 = redis::Script::new(ROTATE_SCRIPT)
+      .key(Self::family_key(family_id))
+      .arg(Self::digest(presented))
+      .arg(Self::digest(next))
+      .arg(REFRESH_TOKEN_TTL_SECS)
+      .invoke_async(&mut conn)
+      .await?;
+
+    Ok(match outcome {
+      1 => RefreshRotation::Rotated,
+      -1 => RefreshRotation::Reused,
+      _ => RefreshRotation::Unknown,
+    })
+  }
+
+  async fn revoke_family(&self, family_id: Uuid) -> Result<()> {
+    let mut conn = self.connection().await?;
+
+    let _: () = conn.del(Self::family_key(family_id)).await?;
+
+    Ok(())
+  }
+}
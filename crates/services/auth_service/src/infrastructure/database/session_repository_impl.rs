@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use uuid::Uuid;
+
+use crate::domain::{
+  SESSION_TOUCH_INTERVAL, SessionRepository, UserSession, UserSessionForCreate,
+};
+use crate::error::Result;
+
+const SESSION_COLUMNS: &str = "session_id, user_id, provider_id, jwt_token_id, device_info, \
+  host(ip_address) AS ip_address, user_agent, created_at, expires_at, \
+  COALESCE(last_activity, created_at) AS last_activity, COALESCE(is_revoked, false) AS is_revoked, \
+  revoked_at, revoked_reason";
+
+pub struct SessionRepositoryImpl {
+  state: AppState,
+}
+
+impl SessionRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+}
+
+#[async_trait]
+impl SessionRepository for SessionRepositoryImpl {
+  async fn create(&self, session: UserSessionForCreate) -> Result<UserSession> {
+    let sql = format!(
+      "INSERT INTO unified_auth.user_sessions \
+       (session_id, user_id, provider_id, jwt_token_id, device_info, ip_address, user_agent, expires_at) \
+       VALUES ($1, $2, $3, $4, $5, $6::inet, $7, $8) RETURNING {SESSION_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UserSession>(&sql)
+      .bind(session.session_id)
+      .bind(session.user_id)
+      .bind(session.provider_id)
+      .bind(session.jwt_token_id)
+      .bind(session.client.device_info)
+      .bind(session.client.ip_address)
+      .bind(session.client.user_agent)
+      .bind(session.expires_at);
+
+    Ok(self.state.mm.dbx().fetch_one(query).await?)
+  }
+
+  async fn find_by_id(&self, session_id: Uuid) -> Result<Option<UserSession>> {
+    let sql =
+      format!("SELECT {SESSION_COLUMNS} FROM unified_auth.user_sessions WHERE session_id = $1");
+    let query = sqlx::query_as::<_, UserSession>(&sql).bind(session_id);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn list_active_for_user(&self, user_id: Uuid) -> Result<Vec<UserSession>> {
+    let sql = format!(
+      "SELECT {SESSION_COLUMNS} FROM unified_auth.user_sessions \
+       WHERE user_id = $1 AND is_revoked = false AND expires_at > CURRENT_TIMESTAMP \
+       ORDER BY last_activity DESC"
+    );
+    let query = sqlx::query_as::<_, UserSession>(&sql).bind(user_id);
+
+    Ok(self.state.mm.dbx().fetch_all(query).await?)
+  }
+
+  async fn is_unknown_client(&self, user_id: Uuid, user_agent: &str) -> Result<bool> {
+    let query = sqlx::query_as::<_, (bool,)>(
+      "SELECT EXISTS (SELECT 1 FROM unified_auth.user_sessions WHERE user_id = $1) \
+       AND NOT EXISTS (SELECT 1 FROM unified_auth.user_sessions WHERE user_id = $1 AND user_agent = $2)",
+    )
+    .bind(user_id)
+    .bind(user_agent);
+
+    let (unknown,) = self.state.mm.dbx().fetch_one(query).await?;
+    Ok(unknown)
+  }
+
+  async fn touch(&self, session_id: Uuid) -> Result<()> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.user_sessions SET last_activity = CURRENT_TIMESTAMP \
+       WHERE session_id = $1 \
+       AND (last_activity IS NULL OR last_activity < CURRENT_TIMESTAMP - $2 * INTERVAL '1 second')",
+    )
+    .bind(session_id)
+    .bind(SESSION_TOUCH_INTERVAL.whole_seconds() as i32);
+
+    self.state.mm.dbx().execute(query).await?;
+    Ok(())
+  }
+
+  async fn revoke(&self, user_id: Uuid, session_id: Uuid, reason: &str) -> Result<bool> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.user_sessions \
+       SET is_revoked = true, revoked_at = CURRENT_TIMESTAMP, revoked_reason = $3 \
+       WHERE session_id = $1 AND user_id = $2 AND is_revoked = false",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .bind(reason);
+
+    Ok(self.state.mm.dbx().execute(query).await? > 0)
+  }
+
+  async fn revoke_all_for_user(&self, user_id: Uuid, reason: &str) -> Result<u64> {
+    let query = sqlx::query(
+      "UPDATE unified_auth.user_sessions \
+       SET is_revoked = true, revoked_at = CURRENT_TIMESTAMP, revoked_reason = $2 \
+       WHERE user_id = $1 AND is_revoked = false AND expires_at > CURRENT_TIMESTAMP",
+    )
+    .bind(user_id)
+    .bind(reason);
+
+    Ok(self.state.mm.dbx().execute(query).await?)
+  }
+}
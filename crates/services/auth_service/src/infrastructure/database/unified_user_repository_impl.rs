@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use jd_storage::dbx::Error as DbxError;
+use uuid::Uuid;
+
+use crate::domain::{UnifiedAuthUser, UnifiedAuthUserForCreate, UnifiedUserRepository, UserRole};
+use crate::error::{Error, Result};
+
+const USER_COLUMNS: &str = "user_id, email, username, display_name, role, is_active, \
+  is_email_verified, is_profile_complete, created_at, updated_at, last_login, login_count, \
+  deleted_at";
+
+pub struct UnifiedUserRepositoryImpl {
+  state: AppState,
+}
+
+impl UnifiedUserRepositoryImpl {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+
+  async fn find_one_by(&self, column: &str, value: &str) -> Result<Option<UnifiedAuthUser>> {
+    let sql = format!(
+      "SELECT {USER_COLUMNS} FROM unified_auth.users WHERE {column} = $1 AND deleted_at IS NULL"
+    );
+    let query = sqlx::query_as::<_, UnifiedAuthUser>(&sql).bind(value);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+}
+
+/// Email and username are both unique, report which one collided
+fn map_unique_violation(err: DbxError) -> Error {
+  let DbxError::Sqlx(sqlx_err) = &err else {
+    return err.into();
+  };
+
+  let constraint = sqlx_err
+    .as_database_error()
+    .filter(|_| err.is_unique_violation())
+    .and_then(|db_err| db_err.constraint());
+
+  match constraint {
+    Some(name) if name.contains("email") => Error::email_already_exists(),
+    Some(_) => Error::username_already_exists(),
+    None => err.into(),
+  }
+}
+
+#[async_trait]
+impl UnifiedUserRepository for UnifiedUserRepositoryImpl {
+  async fn find_by_id(&self, user_id: Uuid) -> Result<Option<UnifiedAuthUser>> {
+    let sql = format!(
+      "SELECT {USER_COLUMNS} FROM unified_auth.users WHERE user_id = $1 AND deleted_at IS NULL"
+    );
+    let query = sqlx::query_as::<_, UnifiedAuthUser>(&sql).bind(user_id);
+
+    Ok(self.state.mm.dbx().fetch_optional(query).await?)
+  }
+
+  async fn find_by_email(&self, email: &str) -> Result<Option<UnifiedAuthUser>> {
+    self.find_one_by("email", email).await
+  }
+
+  async fn find_by_username(&self, username: &str) -> Result<Option<UnifiedAuthUser>> {
+    self.find_one_by("username", username).await
+  }
+
+  async fn create(&self, user: UnifiedAuthUserForCreate) -> Result<UnifiedAuthUser> {
+    let sql = format!(
+      "INSERT INTO unified_auth.users (email, username, display_name, role, is_active, is_email_verified) \
+       VALUES ($1, $2, $3, $4, $5, $6) RETURNING {USER_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UnifiedAuthUser>(&sql)
+      .bind(user.email)
+      .bind(user.username)
+      .bind(user.display_name)
+      .bind(user.role.unwrap_or(UserRole::Normal))
+      .bind(user.is_active.unwrap_or(true))
+      .bind(user.is_email_verified.unwrap_or(false));
+
+    self.state.mm.dbx().fetch_one(query).await.map_err(map_unique_violation)
+  }
+
+  async fn record_login(&self, user_id: Uuid) -> Result<UnifiedAuthUser> {
+    let sql = format!(
+      "UPDATE unified_auth.users \
+       SET last_login = CURRENT_TIMESTAMP, login_count = COALESCE(login_count, 0) + 1 \
+       WHERE user_id = $1 AND deleted_at IS NULL RETURNING {USER_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UnifiedAuthUser>(&sql).bind(user_id);
+
+    self.state.mm.dbx().fetch_optional(query).await?.ok_or_else(Error::user_not_found)
+  }
+
+  async fn set_verified_email(&self, user_id: Uuid, email: &str) -> Result<UnifiedAuthUser> {
+    let sql = format!(
+      "UPDATE unified_auth.users SET email = $2, is_email_verified = true \
+       WHERE user_id = $1 AND deleted_at IS NULL RETURNING {USER_COLUMNS}"
+    );
+    let query = sqlx::query_as::<_, UnifiedAuthUser>(&sql).bind(user_id).bind(email);
+
+    self
+      .state
+      .mm
+      .dbx()
+      .fetch_optional(query)
+      .await
+      .map_err(map_unique_violation)?
+      .ok_or_else(Error::user_not_found)
+  }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use jd_core::ModelManager;
+use jd_messaging::{EventEnvelope, Outbox};
+
+use crate::domain::UnitOfWork;
+use crate::error::Result;
+
+/// Transaction of a `ModelManager` created with `new_with_txn`, hand the same one to the
+/// repositories so their queries run inside it
+pub struct UnitOfWorkImpl {
+  mm: Arc<ModelManager>,
+}
+
+impl UnitOfWorkImpl {
+  pub fn new(mm: Arc<ModelManager>) -> Self {
+    Self { mm }
+  }
+}
+
+#[async_trait]
+impl UnitOfWork for UnitOfWorkImpl {
+  async fn begin_txn(&self) -> Result<()> {
+    Ok(self.mm.dbx().begin_txn().await?)
+  }
+
+  async fn commit_txn(&self) -> Result<()> {
+    Ok(self.mm.dbx().commit_txn().await?)
+  }
+
+  async fn rollback_txn(&self) -> Result<()> {
+    Ok(self.mm.dbx().rollback_txn().await?)
+  }
+
+  async fn enqueue(&self, envelope: EventEnvelope) -> Result<()> {
+    Ok(Outbox::enqueue_envelope(self.mm.dbx(), &envelope).await?)
+  }
+}
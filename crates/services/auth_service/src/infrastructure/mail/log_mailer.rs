@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use crate::domain::VerificationMailer;
+use crate::error::Result;
+
+/// Writes verification tokens to the log, stands in until an email delivery service is wired up
+#[derive(Debug, Default, Clone)]
+pub struct LogMailer;
+
+impl LogMailer {
+  pub fn new() -> Self {
+    Self
+  }
+}
+
+#[async_trait]
+impl VerificationMailer for LogMailer {
+  async fn send_email_link_token(&self, email: &str, token: &str) -> Result<()> {
+    info!("📧 Email link verification for {}: token={}", email, token);
+    Ok(())
+  }
+}
@@ -0,0 +1,3 @@
+pub mod log_mailer;
+
+pub use log_mailer::LogMailer;
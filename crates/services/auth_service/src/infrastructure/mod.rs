@@ -1,5 +1,7 @@
 pub mod database;
+pub mod mail;
 pub mod oauth;
 
 pub use database::*;
+pub use mail::*;
 pub use oauth::*;
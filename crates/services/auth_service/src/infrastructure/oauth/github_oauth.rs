@@ -1,10 +1,11 @@
 use async_trait::async_trait;
+use jd_utils::config::OAuthProviderSettings;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::domain::{AuthProviderType, OAuthTokenResponse};
 use crate::error::Result;
-use super::oauth_client::{OAuthConfig, OAuthProvider, OAuthUserInfo, OAuthState};
+use super::oauth_client::{OAuthConfig, OAuthProvider, OAuthUserInfo};
 
 #[derive(Debug, Clone)]
 pub struct GitHubOAuthProvider {
@@ -18,6 +19,7 @@ struct GitHubTokenRequest {
     client_secret: String,
     code: String,
     redirect_uri: String,
+    code_verifier: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,10 +79,20 @@ impl GitHubOAuthProvider {
         }
     }
 
+    pub fn from_settings(settings: &OAuthProviderSettings) -> Self {
+        let mut provider = Self::new(
+            settings.client_id.clone(),
+            settings.client_secret.clone(),
+            settings.redirect_uri.clone(),
+        );
+        provider.config.apply_endpoint_overrides(settings);
+        provider
+    }
+
     async fn get_user_emails(&self, access_token: &str) -> Result<Vec<GitHubEmail>> {
         let response = self
             .http_client
-            .get("https://api.github.com/user/emails")
+            .get(format!("{}/emails", self.config.user_info_url))
             .bearer_auth(access_token)
             .header("User-Agent", "JD-Blog-Auth-Service")
             .send()
@@ -106,17 +118,21 @@ impl OAuthProvider for GitHubOAuthProvider {
         AuthProviderType::Github
     }
 
-    fn get_authorization_url(&self, state: &str) -> String {
+    fn get_authorization_url(&self, state: &str, code_challenge: &str) -> String {
         let scope_str = self.config.scope.join(" ");
         let state_str = state.to_string();
         let allow_signup_str = "true".to_string();
-        
+        let code_challenge_str = code_challenge.to_string();
+        let challenge_method_str = "S256".to_string();
+
         let mut params = HashMap::new();
         params.insert("client_id", &self.config.client_id);
         params.insert("redirect_uri", &self.config.redirect_uri);
         params.insert("scope", &scope_str);
         params.insert("state", &state_str);
         params.insert("allow_signup", &allow_signup_str);
+        params.insert("code_challenge", &code_challenge_str);
+        params.insert("code_challenge_method", &challenge_method_str);
 
         let query_string = params
             .iter()
@@ -127,12 +143,17 @@ impl OAuthProvider for GitHubOAuthProvider {
         format!("{}?{}", self.config.auth_url, query_string)
     }
 
-    async fn exchange_code_for_token(&self, code: &str) -> Result<OAuthTokenResponse> {
+    async fn exchange_code_for_token(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuthTokenResponse> {
         let token_request = GitHubTokenRequest {
             client_id: self.config.client_id.clone(),
             client_secret: self.config.client_secret.clone(),
             code: code.to_string(),
             redirect_uri: self.config.redirect_uri.clone(),
+            code_verifier: code_verifier.to_string(),
         };
 
         let response = self
@@ -229,8 +250,4 @@ impl OAuthProvider for GitHubOAuthProvider {
             "GitHub tokens do not support refresh".to_string(),
         ))
     }
-
-    fn validate_state(&self, state: &str) -> Result<OAuthState> {
-        super::oauth_client::OAuthClient::validate_state(state)
-    }
 }
\ No newline at end of file
@@ -1,10 +1,11 @@
 use async_trait::async_trait;
+use jd_utils::config::OAuthProviderSettings;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::domain::{AuthProviderType, OAuthTokenResponse};
 use crate::error::Result;
-use super::oauth_client::{OAuthConfig, OAuthProvider, OAuthUserInfo, OAuthState};
+use super::oauth_client::{OAuthConfig, OAuthProvider, OAuthUserInfo};
 
 #[derive(Debug, Clone)]
 pub struct GoogleOAuthProvider {
@@ -19,6 +20,7 @@ struct GoogleTokenRequest {
     code: String,
     grant_type: String,
     redirect_uri: String,
+    code_verifier: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -64,6 +66,16 @@ impl GoogleOAuthProvider {
             http_client: reqwest::Client::new(),
         }
     }
+
+    pub fn from_settings(settings: &OAuthProviderSettings) -> Self {
+        let mut provider = Self::new(
+            settings.client_id.clone(),
+            settings.client_secret.clone(),
+            settings.redirect_uri.clone(),
+        );
+        provider.config.apply_endpoint_overrides(settings);
+        provider
+    }
 }
 
 #[async_trait]
@@ -72,13 +84,15 @@ impl OAuthProvider for GoogleOAuthProvider {
         AuthProviderType::Google
     }
 
-    fn get_authorization_url(&self, state: &str) -> String {
+    fn get_authorization_url(&self, state: &str, code_challenge: &str) -> String {
         let scope_str = self.config.scope.join(" ");
         let response_type_str = "code".to_string();
         let state_str = state.to_string();
         let access_type_str = "offline".to_string();
         let prompt_str = "consent".to_string();
-        
+        let code_challenge_str = code_challenge.to_string();
+        let challenge_method_str = "S256".to_string();
+
         let mut params = HashMap::new();
         params.insert("client_id", &self.config.client_id);
         params.insert("redirect_uri", &self.config.redirect_uri);
@@ -87,6 +101,8 @@ impl OAuthProvider for GoogleOAuthProvider {
         params.insert("state", &state_str);
         params.insert("access_type", &access_type_str); // For refresh token
         params.insert("prompt", &prompt_str); // Force consent for refresh token
+        params.insert("code_challenge", &code_challenge_str);
+        params.insert("code_challenge_method", &challenge_method_str);
 
         let query_string = params
             .iter()
@@ -97,13 +113,18 @@ impl OAuthProvider for GoogleOAuthProvider {
         format!("{}?{}", self.config.auth_url, query_string)
     }
 
-    async fn exchange_code_for_token(&self, code: &str) -> Result<OAuthTokenResponse> {
+    async fn exchange_code_for_token(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuthTokenResponse> {
         let token_request = GoogleTokenRequest {
             client_id: self.config.client_id.clone(),
             client_secret: self.config.client_secret.clone(),
             code: code.to_string(),
             grant_type: "authorization_code".to_string(),
             redirect_uri: self.config.redirect_uri.clone(),
+            code_verifier: code_verifier.to_string(),
         };
 
         let response = self
@@ -211,8 +232,4 @@ impl OAuthProvider for GoogleOAuthProvider {
             scope: google_response.scope,
         })
     }
-
-    fn validate_state(&self, state: &str) -> Result<OAuthState> {
-        super::oauth_client::OAuthClient::validate_state(state)
-    }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 pub mod google_oauth;
 pub mod github_oauth;
 pub mod oauth_client;
+#[cfg(test)]
+pub(crate) mod stub_server;
 
 pub use google_oauth::*;
 pub use github_oauth::*;
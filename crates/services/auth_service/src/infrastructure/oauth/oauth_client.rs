@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 use async_trait::async_trait;
+use jd_utils::config::{OAuthProviderSettings, OAuthSettings};
+use reqwest::Url;
 
-use crate::domain::{AuthProviderType, OAuthTokenResponse};
-use crate::error::Result;
+use super::{GitHubOAuthProvider, GoogleOAuthProvider};
+
+use crate::domain::{AuthProviderType, OAuthState, OAuthTokenResponse};
+use crate::error::{Error, Result};
 
 #[derive(Debug, Clone)]
 pub struct OAuthConfig {
@@ -17,12 +20,18 @@ pub struct OAuthConfig {
     pub user_info_url: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OAuthState {
-    pub state: String,
-    pub provider: AuthProviderType,
-    pub redirect_url: Option<String>,
-    pub created_at: i64,
+impl OAuthConfig {
+    pub(crate) fn apply_endpoint_overrides(&mut self, settings: &OAuthProviderSettings) {
+        if let Some(auth_url) = &settings.auth_url {
+            self.auth_url = auth_url.clone();
+        }
+        if let Some(token_url) = &settings.token_url {
+            self.token_url = token_url.clone();
+        }
+        if let Some(user_info_url) = &settings.user_info_url {
+            self.user_info_url = user_info_url.clone();
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,24 +47,54 @@ pub struct OAuthUserInfo {
 #[async_trait]
 pub trait OAuthProvider: Send + Sync {
     fn provider_type(&self) -> AuthProviderType;
-    fn get_authorization_url(&self, state: &str) -> String;
-    async fn exchange_code_for_token(&self, code: &str) -> Result<OAuthTokenResponse>;
+    /// Authorization URL carrying `state` and the S256 PKCE `code_challenge`
+    fn get_authorization_url(&self, state: &str, code_challenge: &str) -> String;
+    async fn exchange_code_for_token(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuthTokenResponse>;
     async fn get_user_info(&self, access_token: &str) -> Result<OAuthUserInfo>;
     async fn refresh_token(&self, refresh_token: &str) -> Result<OAuthTokenResponse>;
-    fn validate_state(&self, state: &str) -> Result<OAuthState>;
 }
 
 pub struct OAuthClient {
     providers: HashMap<AuthProviderType, Box<dyn OAuthProvider>>,
+    redirect_allowlist: Vec<Url>,
 }
 
 impl OAuthClient {
     pub fn new() -> Self {
         Self {
             providers: HashMap::new(),
+            redirect_allowlist: Vec::new(),
         }
     }
 
+    /// Client with every provider that has credentials configured
+    pub fn from_settings(settings: &OAuthSettings) -> Self {
+        let mut client = Self::new().with_redirect_allowlist(settings.redirect_allowlist());
+
+        if let Some(google) = &settings.google {
+            client.add_provider(Box::new(GoogleOAuthProvider::from_settings(google)));
+        }
+        if let Some(github) = &settings.github {
+            client.add_provider(Box::new(GitHubOAuthProvider::from_settings(github)));
+        }
+
+        client
+    }
+
+    /// Entries that are not absolute http(s) URLs are ignored
+    pub fn with_redirect_allowlist(mut self, allowlist: Vec<String>) -> Self {
+        self.redirect_allowlist = allowlist
+            .iter()
+            .filter_map(|entry| Url::parse(entry).ok())
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .collect();
+        self
+    }
+
     pub fn add_provider(&mut self, provider: Box<dyn OAuthProvider>) {
         let provider_type = provider.provider_type();
         self.providers.insert(provider_type, provider);
@@ -65,50 +104,55 @@ impl OAuthClient {
         self.providers.get(&provider_type).map(|p| p.as_ref())
     }
 
-    pub fn generate_state(provider: AuthProviderType, redirect_url: Option<String>) -> String {
-        let oauth_state = OAuthState {
-            state: Uuid::new_v4().to_string(),
-            provider,
-            redirect_url,
-            created_at: chrono::Utc::now().timestamp(),
-        };
+    /// Relative paths stay on our own origin and are always allowed. Absolute URLs must share
+    /// the origin of an allowlist entry, and its path too unless the entry is a bare origin.
+    pub fn validate_redirect_url(&self, redirect_url: &str) -> Result<()> {
+        let is_relative = redirect_url.starts_with('/')
+            && !redirect_url.starts_with("//")
+            && !redirect_url.contains('\\');
+        if is_relative {
+            return Ok(());
+        }
 
-        // In production, you should encrypt this or store it in Redis
-        use base64::{Engine as _, engine::general_purpose};
-        general_purpose::STANDARD.encode(serde_json::to_string(&oauth_state).unwrap())
-    }
+        let url = Url::parse(redirect_url).map_err(|_| Error::redirect_url_not_allowed())?;
 
-    pub fn validate_state(state: &str) -> Result<OAuthState> {
-        use base64::{Engine as _, engine::general_purpose};
-        let decoded = general_purpose::STANDARD.decode(state)
-            .map_err(|_| crate::error::Error::invalid_oauth_state())?;
-        
-        let oauth_state: OAuthState = serde_json::from_slice(&decoded)
-            .map_err(|_| crate::error::Error::invalid_oauth_state())?;
+        let allowed = self.redirect_allowlist.iter().any(|entry| {
+            entry.origin() == url.origin()
+                && (entry.path() == "/" || entry.path() == url.path())
+        });
 
-        // Check if state is not older than 10 minutes
-        let current_time = chrono::Utc::now().timestamp();
-        if current_time - oauth_state.created_at > 600 {
-            return Err(crate::error::Error::expired_oauth_state());
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::redirect_url_not_allowed())
         }
-
-        Ok(oauth_state)
     }
 
+    /// Exchanges the code of a callback whose state was already consumed from the store
     pub async fn handle_oauth_callback(
         &self,
         provider_type: AuthProviderType,
         code: String,
-        state: String,
-    ) -> Result<(OAuthTokenResponse, OAuthUserInfo, OAuthState)> {
+        oauth_state: &OAuthState,
+    ) -> Result<(OAuthTokenResponse, OAuthUserInfo)> {
         let provider = self.get_provider(provider_type)
-            .ok_or(crate::error::Error::unsupported_oauth_provider())?;
+            .ok_or(Error::unsupported_oauth_provider())?;
+
+        // A state issued for another provider must not be redeemed here
+        if oauth_state.provider != provider_type {
+            return Err(Error::invalid_oauth_state());
+        }
+
+        if oauth_state.is_expired() {
+            return Err(Error::expired_oauth_state());
+        }
 
-        let oauth_state = Self::validate_state(&state)?;
-        let token_response = provider.exchange_code_for_token(&code).await?;
+        let token_response = provider
+            .exchange_code_for_token(&code, &oauth_state.code_verifier)
+            .await?;
         let user_info = provider.get_user_info(&token_response.access_token).await?;
 
-        Ok((token_response, user_info, oauth_state))
+        Ok((token_response, user_info))
     }
 }
 
@@ -116,4 +160,128 @@ impl Default for OAuthClient {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::oauth::stub_server::{CODE_VERIFIER, StubOAuthServer, VALID_CODE};
+
+    fn stub_client(server: &StubOAuthServer) -> OAuthClient {
+        OAuthClient::from_settings(&OAuthSettings {
+            redirect_allowlist: String::new(),
+            google: Some(server.google_settings()),
+            github: Some(server.github_settings()),
+        })
+    }
+
+    fn pending_state(provider: AuthProviderType) -> OAuthState {
+        let mut state = OAuthState::new(provider, None);
+        state.code_verifier = CODE_VERIFIER.to_string();
+        state
+    }
+
+    fn client() -> OAuthClient {
+        OAuthClient::new().with_redirect_allowlist(vec![
+            "https://app.example.com".to_string(),
+            "https://admin.example.com/oauth/done".to_string(),
+        ])
+    }
+
+    #[test]
+    fn allows_listed_origins_and_paths() {
+        let client = client();
+
+        assert!(client.validate_redirect_url("https://app.example.com/dashboard?tab=1").is_ok());
+        assert!(client.validate_redirect_url("https://admin.example.com/oauth/done").is_ok());
+        assert!(client.validate_redirect_url("/settings/accounts").is_ok());
+    }
+
+    #[test]
+    fn rejects_unlisted_redirects() {
+        let client = client();
+
+        assert!(client.validate_redirect_url("https://evil.example.com").is_err());
+        assert!(client.validate_redirect_url("http://app.example.com").is_err());
+        assert!(client.validate_redirect_url("https://app.example.com.evil.io").is_err());
+        assert!(client.validate_redirect_url("https://admin.example.com/other").is_err());
+        assert!(client.validate_redirect_url("//evil.example.com").is_err());
+        assert!(client.validate_redirect_url("javascript:alert(1)").is_err());
+    }
+
+    #[tokio::test]
+    async fn google_callback_exchanges_code_with_pkce() {
+        let server = StubOAuthServer::start().await;
+        let client = stub_client(&server);
+        let state = pending_state(AuthProviderType::Google);
+
+        let (tokens, user) = client
+            .handle_oauth_callback(AuthProviderType::Google, VALID_CODE.to_string(), &state)
+            .await
+            .unwrap();
+
+        assert_eq!(tokens.refresh_token.as_deref(), Some("stub-refresh-token"));
+        assert_eq!(user.id, "google-123");
+        assert_eq!(user.email, "jane@example.com");
+    }
+
+    #[tokio::test]
+    async fn github_callback_uses_primary_verified_email() {
+        let server = StubOAuthServer::start().await;
+        let client = stub_client(&server);
+        let state = pending_state(AuthProviderType::Github);
+
+        let (_, user) = client
+            .handle_oauth_callback(AuthProviderType::Github, VALID_CODE.to_string(), &state)
+            .await
+            .unwrap();
+
+        assert_eq!(user.id, "42");
+        assert_eq!(user.email, "jane@github.example");
+    }
+
+    #[tokio::test]
+    async fn exchange_fails_without_matching_verifier() {
+        let server = StubOAuthServer::start().await;
+        let client = stub_client(&server);
+        let state = OAuthState::new(AuthProviderType::Google, None);
+
+        let err = client
+            .handle_oauth_callback(AuthProviderType::Google, VALID_CODE.to_string(), &state)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "OAUTH_ERROR");
+    }
+
+    #[tokio::test]
+    async fn state_is_bound_to_its_provider() {
+        let server = StubOAuthServer::start().await;
+        let client = stub_client(&server);
+        let state = pending_state(AuthProviderType::Google);
+
+        let err = client
+            .handle_oauth_callback(AuthProviderType::Github, VALID_CODE.to_string(), &state)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "INVALID_OAUTH_STATE");
+    }
+
+    #[tokio::test]
+    async fn authorization_url_carries_pkce_challenge() {
+        let server = StubOAuthServer::start().await;
+        let client = stub_client(&server);
+        let state = pending_state(AuthProviderType::Google);
+
+        let provider = client.get_provider(AuthProviderType::Google).unwrap();
+        let url = Url::parse(&provider.get_authorization_url(&state.state, &state.code_challenge()))
+            .unwrap();
+        let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert!(url.as_str().starts_with(&server.google_settings().auth_url.unwrap()));
+        assert_eq!(params.get("code_challenge"), Some(&state.code_challenge()));
+        assert_eq!(params.get("code_challenge_method").map(String::as_str), Some("S256"));
+        assert_eq!(params.get("state"), Some(&state.state));
+    }
+}
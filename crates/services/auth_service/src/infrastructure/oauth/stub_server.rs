@@ -0,0 +1,131 @@
+//! Local stand-in for the Google and GitHub OAuth endpoints, used by the provider tests
+
+use std::collections::HashMap;
+
+use axum::{
+    Form, Json, Router,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use jd_utils::config::OAuthProviderSettings;
+use serde_json::{Value, json};
+
+pub const VALID_CODE: &str = "stub-code";
+pub const CODE_VERIFIER: &str = "stub-code-verifier-0123456789-abcdefghijklmnopq";
+const ACCESS_TOKEN: &str = "stub-access-token";
+
+pub struct StubOAuthServer {
+    base_url: String,
+}
+
+impl StubOAuthServer {
+    pub async fn start() -> Self {
+        let app = Router::new()
+            .route("/google/token", post(google_token))
+            .route("/google/userinfo", get(google_user))
+            .route("/github/token", post(github_token))
+            .route("/github/user", get(github_user))
+            .route("/github/user/emails", get(github_emails));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        Self { base_url }
+    }
+
+    pub fn google_settings(&self) -> OAuthProviderSettings {
+        self.settings(
+            &format!("{}/google/token", self.base_url),
+            &format!("{}/google/userinfo", self.base_url),
+        )
+    }
+
+    pub fn github_settings(&self) -> OAuthProviderSettings {
+        self.settings(
+            &format!("{}/github/token", self.base_url),
+            &format!("{}/github/user", self.base_url),
+        )
+    }
+
+    fn settings(&self, token_url: &str, user_info_url: &str) -> OAuthProviderSettings {
+        OAuthProviderSettings {
+            client_id: "stub-client".to_string(),
+            client_secret: "stub-secret".to_string(),
+            redirect_uri: "http://localhost:3000/auth/callback".to_string(),
+            auth_url: Some(format!("{}/authorize", self.base_url)),
+            token_url: Some(token_url.to_string()),
+            user_info_url: Some(user_info_url.to_string()),
+        }
+    }
+}
+
+fn exchange(code: Option<&String>, verifier: Option<&String>) -> (StatusCode, Json<Value>) {
+    if code.map(String::as_str) != Some(VALID_CODE)
+        || verifier.map(String::as_str) != Some(CODE_VERIFIER)
+    {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_grant" })));
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "access_token": ACCESS_TOKEN,
+            "refresh_token": "stub-refresh-token",
+            "expires_in": 3600,
+            "scope": "openid email profile",
+            "token_type": "Bearer",
+        })),
+    )
+}
+
+fn authorized(headers: &HeaderMap) -> bool {
+    headers.get("authorization").and_then(|v| v.to_str().ok())
+        == Some(format!("Bearer {}", ACCESS_TOKEN).as_str())
+}
+
+async fn google_token(Form(form): Form<HashMap<String, String>>) -> (StatusCode, Json<Value>) {
+    exchange(form.get("code"), form.get("code_verifier"))
+}
+
+async fn github_token(Json(body): Json<HashMap<String, String>>) -> (StatusCode, Json<Value>) {
+    exchange(body.get("code"), body.get("code_verifier"))
+}
+
+async fn google_user(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "error": "invalid_token" })));
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "id": "google-123",
+            "email": "jane@example.com",
+            "verified_email": true,
+            "name": "Jane Doe",
+        })),
+    )
+}
+
+async fn github_user(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "message": "Bad credentials" })));
+    }
+
+    (StatusCode::OK, Json(json!({ "id": 42, "login": "jane", "name": "Jane Doe", "email": null })))
+}
+
+async fn github_emails(headers: HeaderMap) -> (StatusCode, Json<Value>) {
+    if !authorized(&headers) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "message": "Bad credentials" })));
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!([
+            { "email": "old@example.com", "verified": false, "primary": false, "visibility": null },
+            { "email": "jane@github.example", "verified": true, "primary": true, "visibility": "public" },
+        ])),
+    )
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
 use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -27,6 +29,111 @@ pub struct RefreshRequest {
   pub refresh_token: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RegisterRequest {
+  #[validate(email(message = "Invalid email address"))]
+  pub email: String,
+
+  #[validate(length(min = 3, max = 50, message = "Username must be 3-50 characters"))]
+  #[validate(custom(function = "validate_username"))]
+  pub username: String,
+
+  // Strength is checked by the password policy, this only rejects empty input
+  #[validate(length(min = 1, message = "Password cannot be empty"))]
+  pub password: String,
+
+  #[validate(length(max = 150, message = "Display name must be at most 150 characters"))]
+  pub display_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct LoginRequest {
+  #[validate(email(message = "Invalid email address"))]
+  pub email: String,
+
+  #[validate(length(min = 1, message = "Password cannot be empty"))]
+  pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct OAuthStartQuery {
+  /// Where the frontend sends the user after login, checked against the allowlist
+  #[validate(length(max = 2048, message = "Redirect URL is too long"))]
+  pub redirect_url: Option<String>,
+}
+
+/// Query string the provider appends to the callback, `error` is set when consent was denied
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OAuthCallbackQuery {
+  pub code: Option<String>,
+  pub state: Option<String>,
+  pub error: Option<String>,
+  pub error_description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct LinkWalletRequest {
+  #[validate(length(min = 66, max = 66, message = "Address must be 66 characters"))]
+  #[validate(custom(function = "validate_sui_address"))]
+  pub address: String,
+
+  /// Signature over the message returned by `/auth/nonce` for this address
+  #[validate(length(min = 1, message = "Signature cannot be empty"))]
+  pub signature: String,
+
+  #[validate(length(min = 1, message = "Public key cannot be empty"))]
+  pub public_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct LinkEmailRequest {
+  #[validate(email(message = "Invalid email address"))]
+  pub email: String,
+
+  // Strength is checked by the password policy, this only rejects empty input
+  #[validate(length(min = 1, message = "Password cannot be empty"))]
+  pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ConfirmEmailLinkRequest {
+  #[validate(length(min = 1, message = "Token cannot be empty"))]
+  pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+  /// User the key acts as, usually a service account of the partner
+  pub owner_id: Uuid,
+
+  #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+  pub name: String,
+
+  /// Permission names from `unified_auth.permissions`
+  #[validate(length(min = 1, message = "At least one scope is required"))]
+  pub scopes: Vec<String>,
+
+  /// Requests per rate limit window, the route group limit when left out
+  #[validate(range(min = 1, message = "Rate limit must be positive"))]
+  pub rate_limit: Option<i32>,
+
+  #[serde(default, with = "time::serde::rfc3339::option")]
+  pub expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiKeyListQuery {
+  pub owner_id: Option<Uuid>,
+}
+
+fn validate_username(username: &str) -> Result<(), validator::ValidationError> {
+  if username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+    Ok(())
+  } else {
+    Err(validator::ValidationError::new("invalid_username"))
+  }
+}
+
 fn validate_sui_address(address: &str) -> Result<(), validator::ValidationError> {
   if crate::domain::AuthUser::is_valid_address(address) {
     Ok(())
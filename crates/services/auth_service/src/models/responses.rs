@@ -1,6 +1,11 @@
-use crate::domain::{AuthUser, TokenPair};
+use crate::application::use_cases::unified_auth::{AuthProviderResult, LoginResult};
+use crate::domain::{
+  ApiKey, AuthProviderType, AuthUser, IssuedApiKey, ProviderStatus, TokenPair, UnifiedAuthUser,
+  UserAuthProvider, UserRole, UserSession,
+};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NonceResponse {
@@ -32,13 +37,236 @@ impl From<AuthUser> for UserInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct VerifyResponse {
+pub struct RefreshResponse {
+  pub access_token: String,
+  /// Replaces the presented refresh token, which is no longer accepted
+  pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountInfo {
+  pub user_id: Uuid,
+  pub email: Option<String>,
+  pub username: String,
+  pub display_name: Option<String>,
+  pub role: UserRole,
+  pub is_email_verified: bool,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub last_login: Option<OffsetDateTime>,
+  pub login_count: i32,
+}
+
+impl From<UnifiedAuthUser> for AccountInfo {
+  fn from(user: UnifiedAuthUser) -> Self {
+    Self {
+      user_id: user.user_id,
+      email: user.email,
+      username: user.username,
+      display_name: user.display_name,
+      role: user.role,
+      is_email_verified: user.is_email_verified,
+      created_at: user.created_at,
+      last_login: user.last_login,
+      login_count: user.login_count,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginResponse {
   pub success: bool,
-  pub user: UserInfo,
+  pub user: AccountInfo,
   pub tokens: TokenPair,
+  pub is_new_user: bool,
+}
+
+impl From<LoginResult> for LoginResponse {
+  fn from(result: LoginResult) -> Self {
+    Self {
+      success: true,
+      user: AccountInfo::from(result.user),
+      tokens: result.tokens,
+      is_new_user: result.is_new_user,
+    }
+  }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct RefreshResponse {
-  pub access_token: String,
+pub struct OAuthStartResponse {
+  pub authorization_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OAuthLoginResponse {
+  #[serde(flatten)]
+  pub login: LoginResponse,
+  pub redirect_url: Option<String>,
+}
+
+/// Callback outcome, a login unless the authorization was started from `/auth/providers`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OAuthCallbackResponse {
+  Login(OAuthLoginResponse),
+  Linked(OAuthLinkResponse),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OAuthLinkResponse {
+  #[serde(flatten)]
+  pub link: LinkProviderResponse,
+  pub redirect_url: Option<String>,
+}
+
+/// Linked provider without its credentials
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderInfo {
+  pub provider_id: Uuid,
+  pub provider_type: AuthProviderType,
+  pub provider_email: Option<String>,
+  pub wallet_address: Option<String>,
+  pub status: ProviderStatus,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339")]
+  pub last_used_at: OffsetDateTime,
+}
+
+impl From<UserAuthProvider> for ProviderInfo {
+  fn from(provider: UserAuthProvider) -> Self {
+    Self {
+      provider_id: provider.provider_id,
+      provider_type: provider.provider_type,
+      provider_email: provider.provider_email,
+      wallet_address: provider.wallet_address,
+      status: provider.status,
+      created_at: provider.created_at,
+      last_used_at: provider.last_used_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkProviderResponse {
+  pub success: bool,
+  pub provider: ProviderInfo,
+  /// False when a previously unlinked identity was reattached
+  pub is_new_provider: bool,
+}
+
+impl From<AuthProviderResult> for LinkProviderResponse {
+  fn from(result: AuthProviderResult) -> Self {
+    Self {
+      success: true,
+      provider: ProviderInfo::from(result.provider),
+      is_new_provider: result.is_new_provider,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkEmailResponse {
+  pub success: bool,
+  /// Seconds the mailed verification token stays valid
+  pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnlinkProviderResponse {
+  pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionInfo {
+  pub session_id: Uuid,
+  pub ip_address: Option<String>,
+  pub user_agent: Option<String>,
+  pub device_info: Option<serde_json::Value>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339")]
+  pub last_activity: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339")]
+  pub expires_at: OffsetDateTime,
+  pub is_current: bool,
+}
+
+impl SessionInfo {
+  pub fn from_session(session: UserSession, is_current: bool) -> Self {
+    Self {
+      session_id: session.session_id,
+      ip_address: session.ip_address,
+      user_agent: session.user_agent,
+      device_info: session.device_info,
+      created_at: session.created_at,
+      last_activity: session.last_activity,
+      expires_at: session.expires_at,
+      is_current,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevokeSessionsResponse {
+  pub success: bool,
+  pub revoked: u64,
+}
+
+/// API key without its secret
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiKeyInfo {
+  pub key_id: Uuid,
+  pub owner_id: Uuid,
+  pub name: String,
+  pub prefix: String,
+  pub scopes: Vec<String>,
+  pub rate_limit: Option<i32>,
+  #[serde(with = "time::serde::rfc3339")]
+  pub created_at: OffsetDateTime,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub rotated_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub expires_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub last_used_at: Option<OffsetDateTime>,
+  #[serde(with = "time::serde::rfc3339::option")]
+  pub revoked_at: Option<OffsetDateTime>,
+}
+
+impl From<ApiKey> for ApiKeyInfo {
+  fn from(api_key: ApiKey) -> Self {
+    Self {
+      key_id: api_key.key_id,
+      owner_id: api_key.owner_id,
+      name: api_key.name,
+      prefix: api_key.prefix,
+      scopes: api_key.scopes,
+      rate_limit: api_key.rate_limit,
+      created_at: api_key.created_at,
+      rotated_at: api_key.rotated_at,
+      expires_at: api_key.expires_at,
+      last_used_at: api_key.last_used_at,
+      revoked_at: api_key.revoked_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssuedApiKeyResponse {
+  pub api_key: ApiKeyInfo,
+  /// Shown only in this response, it cannot be recovered later
+  pub key: String,
+}
+
+impl From<IssuedApiKey> for IssuedApiKeyResponse {
+  fn from(issued: IssuedApiKey) -> Self {
+    Self { api_key: ApiKeyInfo::from(issued.api_key), key: issued.key }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevokeApiKeyResponse {
+  pub success: bool,
 }
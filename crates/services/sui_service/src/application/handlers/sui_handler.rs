@@ -1,5 +1,5 @@
 use crate::Result;
-use crate::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use crate::infrastructure::resilient_sui_repository::ResilientSuiRepository;
 use axum::{
   Json,
   extract::State,
@@ -26,7 +26,7 @@ impl<R: SuiRepository> SuiHandler<R> {
     State(state): State<AppState>,
     Json(req): Json<String>,
   ) -> Result<Json<Coin>> {
-    let repository = EnhancedSuiRepository::new(state);
+    let repository = ResilientSuiRepository::new(state);
     let use_cases = SuiUseCases::new(repository);
     let object = use_cases.fetch_coin(req).await?;
     Ok(Json(object))
@@ -39,7 +39,7 @@ impl<R: SuiRepository> SuiHandler<R> {
 
   // Simple endpoint for testing
   pub async fn test_connection(State(state): State<AppState>) -> Result<Json<Value>> {
-    let repository = EnhancedSuiRepository::new(state);
+    let repository = ResilientSuiRepository::new(state);
     
     match repository.get_latest_checkpoint_sequence_number().await {
       Ok(checkpoint) => Ok(Json(json!({
@@ -19,13 +19,14 @@ impl<R: SuiRepository> CoinUseCases<R> {
   pub async fn get_sui_coins(
     &self,
     address: &str,
+    cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<Coin, String>> {
     let sui_address = SuiAddress::from_str(address)
       .map_err(|_| Error::InvalidRequest("Invalid address format".to_string()))?;
     
     self.repository
-      .get_coins(sui_address, Some("0x2::sui::SUI".to_string()), None, limit)
+      .get_coins(sui_address, Some("0x2::sui::SUI".to_string()), cursor, limit)
       .await
   }
 
@@ -33,12 +34,13 @@ impl<R: SuiRepository> CoinUseCases<R> {
   pub async fn get_all_coins(
     &self,
     address: &str,
+    cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<Coin, String>> {
     let sui_address = SuiAddress::from_str(address)
       .map_err(|_| Error::InvalidRequest("Invalid address format".to_string()))?;
     
-    self.repository.get_all_coins(sui_address, None, limit).await
+    self.repository.get_all_coins(sui_address, cursor, limit).await
   }
 
   /// Get coins for a specific coin type
@@ -46,13 +48,14 @@ impl<R: SuiRepository> CoinUseCases<R> {
     &self,
     address: &str,
     coin_type: &str,
+    cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<Coin, String>> {
     let sui_address = SuiAddress::from_str(address)
       .map_err(|_| Error::InvalidRequest("Invalid address format".to_string()))?;
     
     self.repository
-      .get_coins(sui_address, Some(coin_type.to_string()), None, limit)
+      .get_coins(sui_address, Some(coin_type.to_string()), cursor, limit)
       .await
   }
 
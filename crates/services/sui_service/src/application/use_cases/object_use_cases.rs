@@ -84,13 +84,15 @@ impl<R: SuiRepository> ObjectUseCases<R> {
     &self,
     address: &str,
     object_type: Option<String>,
+    cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<SuiObjectResponse, ObjectID>> {
     let sui_address = SuiAddress::from_str(address)
       .map_err(|_| Error::InvalidRequest("Invalid address format".to_string()))?;
+    let cursor = parse_cursor(cursor)?;
 
     self.repository
-      .get_owned_objects(sui_address, object_type, None, limit)
+      .get_owned_objects(sui_address, object_type, cursor, limit)
       .await
   }
 
@@ -99,21 +101,24 @@ impl<R: SuiRepository> ObjectUseCases<R> {
     &self,
     address: &str,
     object_type: &str,
+    cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<SuiObjectResponse, ObjectID>> {
-    self.get_owned_objects(address, Some(object_type.to_string()), limit).await
+    self.get_owned_objects(address, Some(object_type.to_string()), cursor, limit).await
   }
 
   /// Get dynamic fields of an object
   pub async fn get_dynamic_fields(
     &self,
     parent_object_id: &str,
+    cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<DynamicFieldInfo, ObjectID>> {
     let obj_id = ObjectID::from_str(parent_object_id)
       .map_err(|_| Error::InvalidRequest("Invalid parent object ID format".to_string()))?;
+    let cursor = parse_cursor(cursor)?;
 
-    self.repository.get_dynamic_fields(obj_id, None, limit).await
+    self.repository.get_dynamic_fields(obj_id, cursor, limit).await
   }
 
   /// Check if an object exists and is accessible
@@ -140,6 +145,16 @@ impl<R: SuiRepository> ObjectUseCases<R> {
 
 }
 
+/// Object pages are keyed by the last object ID returned
+fn parse_cursor(cursor: Option<String>) -> Result<Option<ObjectID>> {
+  cursor
+    .map(|cursor| {
+      ObjectID::from_str(&cursor)
+        .map_err(|_| Error::InvalidRequest("Invalid cursor format".to_string()))
+    })
+    .transpose()
+}
+
 /// Object owner information
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum ObjectOwner {
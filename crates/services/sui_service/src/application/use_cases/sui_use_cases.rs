@@ -1,10 +1,15 @@
 use crate::Result;
+use crate::domain::sponsor_policy::PolicyRule;
 use crate::error::Error;
-use crate::models::{GasPoolStatus, SponsorRequest, SponsorResponse, UserStats};
+use crate::models::{
+  ExecuteSponsorRequest, ExecutedSponsorship, GasPoolStatus, ReserveSponsorRequest,
+  ReserveSponsorResponse, SponsorCaller, SponsorResponse, UserStats,
+};
+use base64::{Engine as _, engine::general_purpose};
 use std::str::FromStr;
 use sui_sdk::rpc_types::Coin;
 use sui_types::base_types::{ObjectID, SuiAddress};
-use uuid::Uuid;
+use sui_types::transaction::TransactionKind;
 
 use crate::domain::sui_repository_trait::SuiRepository;
 
@@ -27,35 +32,72 @@ impl<R: SuiRepository> SuiUseCases<R> {
   }
 
   // Gas Station Use Cases
-  pub async fn sponsor_transaction(&self, request: SponsorRequest) -> Result<SponsorResponse> {
-    // Parse user address
-    let user_address = SuiAddress::from_str(&request.user_address)
-      .map_err(|_| Error::InvalidRequest("Invalid user address".to_string()))?;
+
+  /// First step of a sponsorship: checks the sponsorship policy for `caller`, locks a gas coin
+  /// and returns the transaction to sign
+  pub async fn reserve_sponsorship(
+    &self,
+    request: ReserveSponsorRequest,
+    caller: SponsorCaller,
+  ) -> Result<ReserveSponsorResponse> {
+    let sender = SuiAddress::from_str(&request.sender)
+      .map_err(|_| Error::InvalidRequest("Invalid sender address".to_string()))?;
+    let kind = general_purpose::STANDARD
+      .decode(&request.transaction_kind)
+      .map_err(|e| Error::InvalidRequest(format!("Invalid transaction kind encoding: {}", e)))
+      .and_then(|bytes| {
+        bcs::from_bytes::<TransactionKind>(&bytes)
+          .map_err(|e| Error::InvalidRequest(format!("Failed to parse transaction kind: {}", e)))
+      })?;
 
     // Check rate limiting
-    if !self.repository.check_rate_limit(&user_address).await? {
-      return Err(Error::InvalidRequest("Rate limit exceeded".to_string()));
+    if !self.repository.check_rate_limit(&sender).await? {
+      return Err(PolicyRule::RateLimit.reject("Too many sponsorships for this sender"));
     }
 
-    // Sponsor the transaction using tx_bytes
-    match self
+    let reservation = self
+      .repository
+      .reserve_sponsorship(kind, sender, &caller, request.app_id, request.gas_budget)
+      .await?;
+    let tx_bytes =
+      bcs::to_bytes(&reservation.tx_data).map_err(|e| Error::Internal(e.to_string()))?;
+
+    Ok(ReserveSponsorResponse {
+      reservation_id: reservation.id,
+      tx_bytes: general_purpose::STANDARD.encode(tx_bytes),
+      gas_budget: reservation.gas_budget,
+      expires_at: reservation.expires_at,
+    })
+  }
+
+  /// Second step of a sponsorship: executes a reserved transaction signed by its sender
+  pub async fn execute_sponsorship(
+    &self,
+    request: ExecuteSponsorRequest,
+  ) -> Result<SponsorResponse> {
+    let user_signature = general_purpose::STANDARD
+      .decode(&request.user_signature)
+      .map_err(|e| Error::InvalidRequest(format!("Invalid user signature encoding: {}", e)))?;
+
+    let reservation = self
       .repository
-      .sponsor_transaction(request.transaction_data.tx_bytes, &request.user_signature)
-      .await
-    {
-      Ok((transaction, digest)) => {
-        // Get gas budget from request for logging
-        let gas_budget = request
-          .transaction_data
-          .gas_data
-          .budget
-          .parse::<u64>()
-          .unwrap_or(10000000); // Default gas budget
+      .take_reservation(request.reservation_id)
+      .await?
+      .ok_or_else(|| Error::InvalidRequest("Reservation not found or expired".to_string()))?;
+    let app_id = reservation.app_id.as_deref();
 
+    match self.repository.execute_sponsorship(&reservation, &user_signature).await {
+      Ok(ExecutedSponsorship { transaction, digest, gas_used }) => {
         // Log the sponsored transaction
         if let Err(e) = self
           .repository
-          .log_sponsored_transaction(&user_address, gas_budget)
+          .log_sponsored_transaction(
+            &reservation.sender,
+            app_id,
+            reservation.gas_budget,
+            gas_used,
+            &digest,
+          )
           .await
         {
           tracing::warn!("Failed to log sponsored transaction: {}", e);
@@ -65,7 +107,7 @@ impl<R: SuiRepository> SuiUseCases<R> {
           sponsored_transaction: Some(transaction),
           sponsored_tx_bytes: None,
           sponsored_tx_digest: Some(digest.clone()),
-          transaction_id: Uuid::new_v4(),
+          transaction_id: reservation.id,
           status: "success".to_string(),
           message: Some(format!(
             "Transaction executed successfully on Sui network. Digest: {}",
@@ -73,30 +115,25 @@ impl<R: SuiRepository> SuiUseCases<R> {
           )),
         })
       }
-      Err(Error::ImplementationPending(msg)) => {
-        // Return success response for development phase without creating a mock transaction
-        Ok(SponsorResponse {
-          sponsored_transaction: None,
-          sponsored_tx_bytes: None,
-          sponsored_tx_digest: None,
-          transaction_id: Uuid::new_v4(),
-          status: "development_success".to_string(),
-          message: Some(format!("Development Phase: {}", msg)),
-        })
-      }
       Err(e) => {
-        // Provide helpful error messages for common issues
-        let error_message = match e {
-                Error::Internal(ref msg) if msg.contains("User signature is required") => {
-                    "User signature is required for sponsored transactions. Please sign the transaction with your wallet first, then submit both the transaction data and your signature.".to_string()
-                },
-                Error::Internal(ref msg) if msg.contains("Invalid user signature") => {
-                    "Invalid user signature format. Please ensure the signature is properly formatted.".to_string()
-                },
-                _ => format!("Failed to sponsor transaction: {}", e)
-            };
-
-        Err(Error::Internal(error_message))
+        if let Err(event_err) = self
+          .repository
+          .record_sponsorship_failure(
+            &reservation.sender,
+            app_id,
+            reservation.gas_budget,
+            &e.to_string(),
+          )
+          .await
+        {
+          tracing::warn!("Failed to record sponsorship failure: {}", event_err);
+        }
+
+        match e {
+          Error::InvalidRequest(msg) => Err(Error::InvalidRequest(msg)),
+          e @ Error::PolicyViolation { .. } => Err(e),
+          e => Err(Error::Internal(format!("Failed to sponsor transaction: {}", e))),
+        }
       }
     }
   }
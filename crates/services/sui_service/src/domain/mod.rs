@@ -1,2 +1,3 @@
 // Domain layer module
+pub(crate) mod sponsor_policy;
 pub(crate) mod sui_repository_trait;
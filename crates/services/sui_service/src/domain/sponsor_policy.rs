@@ -0,0 +1,370 @@
+use auth_service::domain::UserRole;
+use serde::{Deserialize, Deserializer, de::Error as _};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::transaction::{Command, TransactionKind};
+
+use crate::{Result, error::Error};
+
+/// Rules a transaction has to pass before it is sponsored, loaded from the JSON file at
+/// `SUI.POLICY_FILE`. Every rule is optional, the default policy only keeps the rate limit.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SponsorPolicy {
+  /// `0xpackage`, `0xpackage::module` or `0xpackage::module::function`. When set, every Move
+  /// call has to match an entry and publishing or upgrading packages is refused.
+  pub allowed_calls: Vec<MoveTarget>,
+  pub denied_addresses: HashSet<SuiAddress>,
+  /// Gas a user may reserve per UTC day, whichever sender they sign with
+  pub user_daily_gas_budget: Option<u64>,
+  /// Applications sponsorships are accounted to. When set, every reservation has to name one of
+  /// them, so app budgets cannot be dodged by naming no app or a made up one.
+  pub apps: HashSet<String>,
+  /// Gas an application may reserve per UTC day, needs `apps`
+  pub app_daily_gas_budget: Option<u64>,
+  /// Overrides `app_daily_gas_budget` for single applications
+  pub app_gas_budgets: HashMap<String, u64>,
+  /// Quotas by user role name, `normal`, `member`, `vip`, `moderator` or `admin`
+  #[serde(deserialize_with = "role_quotas")]
+  pub roles: HashMap<UserRole, RoleQuota>,
+  /// Highest gas, computation plus storage, a dry run of the transaction may use
+  pub max_simulated_gas: Option<u64>,
+  pub rate_limit: RateLimit,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RoleQuota {
+  /// Sponsored transactions per UTC day
+  pub daily_transactions: Option<u64>,
+  /// Replaces `user_daily_gas_budget` for the role
+  pub daily_gas_budget: Option<u64>,
+}
+
+/// Sponsorships per sender address within a fixed window
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+  pub max_transactions: u64,
+  pub window_secs: u64,
+}
+
+impl Default for RateLimit {
+  fn default() -> Self {
+    Self { max_transactions: 10, window_secs: 60 }
+  }
+}
+
+/// Keys of `roles`, a misspelled role fails the policy instead of never applying
+fn role_quotas<'de, D: Deserializer<'de>>(
+  deserializer: D,
+) -> std::result::Result<HashMap<UserRole, RoleQuota>, D::Error> {
+  HashMap::<String, RoleQuota>::deserialize(deserializer)?
+    .into_iter()
+    .map(|(role, quota)| Ok((role.parse().map_err(D::Error::custom)?, quota)))
+    .collect()
+}
+
+/// Move call pattern of `allowed_calls`, module and function left out match any
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct MoveTarget {
+  pub package: ObjectID,
+  pub module: Option<String>,
+  pub function: Option<String>,
+}
+
+impl MoveTarget {
+  pub fn matches(&self, package: &ObjectID, module: &str, function: &str) -> bool {
+    self.package == *package
+      && self.module.as_deref().is_none_or(|m| m == module)
+      && self.function.as_deref().is_none_or(|f| f == function)
+  }
+}
+
+impl TryFrom<String> for MoveTarget {
+  type Error = String;
+
+  fn try_from(target: String) -> std::result::Result<Self, Self::Error> {
+    let mut parts = target.split("::");
+    let package = parts
+      .next()
+      .and_then(|package| ObjectID::from_str(package).ok())
+      .ok_or_else(|| format!("Invalid package in Move call target `{}`", target))?;
+    let module = parts.next().map(str::to_string);
+    let function = parts.next().map(str::to_string);
+    if parts.next().is_some() {
+      return Err(format!("Invalid Move call target `{}`", target));
+    }
+
+    Ok(Self { package, module, function })
+  }
+}
+
+/// Rule named in a rejection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyRule {
+  DeniedAddress,
+  AllowedCalls,
+  RateLimit,
+  RoleQuota,
+  Apps,
+  UserGasBudget,
+  AppGasBudget,
+  SimulatedGas,
+}
+
+impl PolicyRule {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PolicyRule::DeniedAddress => "denied_addresses",
+      PolicyRule::AllowedCalls => "allowed_calls",
+      PolicyRule::RateLimit => "rate_limit",
+      PolicyRule::RoleQuota => "roles.daily_transactions",
+      PolicyRule::Apps => "apps",
+      PolicyRule::UserGasBudget => "user_daily_gas_budget",
+      PolicyRule::AppGasBudget => "app_daily_gas_budget",
+      PolicyRule::SimulatedGas => "max_simulated_gas",
+    }
+  }
+
+  pub fn reject(self, reason: impl Into<String>) -> Error {
+    Error::PolicyViolation { rule: self.as_str(), reason: reason.into() }
+  }
+}
+
+impl fmt::Display for PolicyRule {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+/// Daily limits that apply to one caller, `None` is unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyLimits {
+  pub user_gas: Option<u64>,
+  pub app_gas: Option<u64>,
+  pub transactions: Option<u64>,
+}
+
+impl SponsorPolicy {
+  /// Reads the policy file, the default policy when none is configured
+  pub fn load(path: Option<&str>) -> Result<Self> {
+    let Some(path) = path else {
+      return Ok(Self::default());
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|e| {
+      Error::Internal(format!("Failed to read sponsorship policy {}: {}", path, e))
+    })?;
+    let policy: Self = serde_json::from_str(&content)
+      .map_err(|e| Error::Internal(format!("Invalid sponsorship policy {}: {}", path, e)))?;
+    policy
+      .validate()
+      .map_err(|e| Error::Internal(format!("Invalid sponsorship policy {}: {}", path, e)))?;
+    Ok(policy)
+  }
+
+  /// App budgets only hold when reservations are limited to known apps
+  fn validate(&self) -> std::result::Result<(), String> {
+    let app_budgets = self.app_daily_gas_budget.is_some() || !self.app_gas_budgets.is_empty();
+    if app_budgets && self.apps.is_empty() {
+      return Err("app budgets need the sponsored `apps`".to_string());
+    }
+    match self.app_gas_budgets.keys().find(|app_id| !self.apps.contains(*app_id)) {
+      Some(app_id) => Err(format!("`app_gas_budgets` names `{}`, which is not in `apps`", app_id)),
+      None => Ok(()),
+    }
+  }
+
+  /// Accepts the app a reservation names, any or none while `apps` is empty
+  pub fn check_app(&self, app_id: Option<&str>) -> Result<()> {
+    if self.apps.is_empty() {
+      return Ok(());
+    }
+
+    match app_id {
+      Some(app_id) if self.apps.contains(app_id) => Ok(()),
+      Some(app_id) => Err(PolicyRule::Apps.reject(format!("App {} is not sponsored", app_id))),
+      None => Err(PolicyRule::Apps.reject("Sponsorships have to name their app")),
+    }
+  }
+
+  /// Rules that only depend on the request, checked before a gas coin is reserved
+  pub fn check_transaction(&self, sender: &SuiAddress, kind: &TransactionKind) -> Result<()> {
+    if self.denied_addresses.contains(sender) {
+      return Err(PolicyRule::DeniedAddress.reject(format!("Sender {} is denied", sender)));
+    }
+
+    let TransactionKind::ProgrammableTransaction(programmable) = kind else {
+      return Ok(());
+    };
+    if self.allowed_calls.is_empty() {
+      return Ok(());
+    }
+
+    for command in &programmable.commands {
+      match command {
+        Command::MoveCall(call) => {
+          let (module, function) = (call.module.as_str(), call.function.as_str());
+          let allowed =
+            self.allowed_calls.iter().any(|target| target.matches(&call.package, module, function));
+          if !allowed {
+            return Err(PolicyRule::AllowedCalls.reject(format!(
+              "{}::{}::{} is not allowed",
+              call.package, module, function
+            )));
+          }
+        }
+        Command::Publish(..) | Command::Upgrade(..) => {
+          return Err(
+            PolicyRule::AllowedCalls.reject("Publishing or upgrading packages is not allowed"),
+          );
+        }
+        _ => {}
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn check_simulated_gas(&self, gas_used: u64) -> Result<()> {
+    match self.max_simulated_gas {
+      Some(max) if gas_used > max => Err(PolicyRule::SimulatedGas.reject(format!(
+        "Simulated gas {} MIST exceeds {} MIST",
+        gas_used, max
+      ))),
+      _ => Ok(()),
+    }
+  }
+
+  pub fn daily_limits(&self, role: UserRole, app_id: Option<&str>) -> DailyLimits {
+    let quota = self.roles.get(&role);
+    let app_gas = app_id.and_then(|app_id| self.app_gas_budgets.get(app_id).copied());
+
+    DailyLimits {
+      user_gas: quota.and_then(|quota| quota.daily_gas_budget).or(self.user_daily_gas_budget),
+      app_gas: app_id.and(app_gas.or(self.app_daily_gas_budget)),
+      transactions: quota.and_then(|quota| quota.daily_transactions),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use sui_types::transaction::ProgrammableTransaction;
+
+  const POLICY: &str = r#"{
+    "allowed_calls": ["0x2::coin", "0x3::staking::request_add_stake"],
+    "denied_addresses": ["0x0000000000000000000000000000000000000000000000000000000000000bad"],
+    "user_daily_gas_budget": 1000,
+    "apps": ["game", "wallet"],
+    "app_daily_gas_budget": 5000,
+    "app_gas_budgets": { "game": 9000 },
+    "roles": { "vip": { "daily_transactions": 50, "daily_gas_budget": 3000 } },
+    "max_simulated_gas": 700
+  }"#;
+
+  fn policy() -> SponsorPolicy {
+    serde_json::from_str(POLICY).unwrap()
+  }
+
+  #[test]
+  fn targets_match_package_module_and_function() {
+    let policy = policy();
+    let allows = |package: u8, module: &str, function: &str| {
+      let package = ObjectID::from_single_byte(package);
+      policy.allowed_calls.iter().any(|target| target.matches(&package, module, function))
+    };
+
+    assert!(allows(2, "coin", "split"));
+    assert!(!allows(2, "pay", "split"));
+    assert!(allows(3, "staking", "request_add_stake"));
+    assert!(!allows(3, "staking", "request_withdraw_stake"));
+    assert!(!allows(4, "coin", "split"));
+  }
+
+  #[test]
+  fn malformed_targets_are_rejected() {
+    assert!(MoveTarget::try_from("coin::split".to_string()).is_err());
+    assert!(MoveTarget::try_from("0x2::coin::split::extra".to_string()).is_err());
+  }
+
+  #[test]
+  fn denied_sender_names_the_rule() {
+    let sender = SuiAddress::from_str(
+      "0x0000000000000000000000000000000000000000000000000000000000000bad",
+    )
+    .unwrap();
+    let kind = TransactionKind::ProgrammableTransaction(ProgrammableTransaction {
+      inputs: vec![],
+      commands: vec![],
+    });
+
+    match policy().check_transaction(&sender, &kind) {
+      Err(Error::PolicyViolation { rule, .. }) => assert_eq!(rule, "denied_addresses"),
+      other => panic!("expected a policy violation, got {:?}", other),
+    }
+    assert!(policy().check_transaction(&SuiAddress::ZERO, &kind).is_ok());
+  }
+
+  #[test]
+  fn role_and_app_overrides_win_over_defaults() {
+    let policy = policy();
+
+    assert_eq!(
+      policy.daily_limits(UserRole::Vip, Some("game")),
+      DailyLimits { user_gas: Some(3000), app_gas: Some(9000), transactions: Some(50) }
+    );
+    assert_eq!(
+      policy.daily_limits(UserRole::Normal, Some("wallet")),
+      DailyLimits { user_gas: Some(1000), app_gas: Some(5000), transactions: None }
+    );
+    assert_eq!(policy.daily_limits(UserRole::Normal, None).app_gas, None);
+  }
+
+  #[test]
+  fn only_listed_apps_are_sponsored() {
+    let policy = policy();
+
+    assert!(policy.check_app(Some("game")).is_ok());
+    for app_id in [None, Some("other")] {
+      match policy.check_app(app_id) {
+        Err(Error::PolicyViolation { rule, .. }) => assert_eq!(rule, "apps"),
+        other => panic!("expected a policy violation, got {:?}", other),
+      }
+    }
+    assert!(SponsorPolicy::default().check_app(None).is_ok());
+  }
+
+  #[test]
+  fn app_budgets_need_listed_apps() {
+    assert!(policy().validate().is_ok());
+
+    let unlisted: SponsorPolicy =
+      serde_json::from_str(r#"{ "apps": ["game"], "app_gas_budgets": { "other": 10 } }"#).unwrap();
+    assert!(unlisted.validate().is_err());
+
+    let no_apps: SponsorPolicy = serde_json::from_str(r#"{ "app_daily_gas_budget": 10 }"#).unwrap();
+    assert!(no_apps.validate().is_err());
+  }
+
+  #[test]
+  fn unknown_role_fails_the_policy() {
+    let policy = r#"{ "roles": { "gold": { "daily_transactions": 5 } } }"#;
+
+    let err = serde_json::from_str::<SponsorPolicy>(policy).unwrap_err();
+
+    assert!(err.to_string().contains("Unknown user role `gold`"));
+  }
+
+  #[test]
+  fn simulated_gas_is_capped() {
+    assert!(policy().check_simulated_gas(700).is_ok());
+    assert!(policy().check_simulated_gas(701).is_err());
+    assert!(SponsorPolicy::default().check_simulated_gas(u64::MAX).is_ok());
+  }
+}
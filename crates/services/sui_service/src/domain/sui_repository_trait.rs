@@ -1,5 +1,7 @@
 use crate::Result;
-use crate::models::{GasPoolStatus, UserStats};
+use crate::models::{
+  ExecutedSponsorship, GasPoolStatus, SponsorCaller, SponsorReservation, UserStats,
+};
 use async_trait::async_trait;
 use sui_sdk::rpc_types::{
   Coin, SuiObjectResponse, SuiTransactionBlockResponse, SuiEvent, Page,
@@ -7,7 +9,8 @@ use sui_sdk::rpc_types::{
   SuiTransactionBlockResponseOptions, DynamicFieldInfo
 };
 use sui_sdk::types::base_types::{SuiAddress, TransactionDigest};
-use sui_types::{base_types::ObjectID, transaction::Transaction};
+use sui_types::{base_types::ObjectID, transaction::TransactionKind};
+use uuid::Uuid;
 
 #[async_trait]
 pub trait SuiRepository: Send + Sync {
@@ -121,17 +124,42 @@ pub trait SuiRepository: Send + Sync {
   // ============== GAS STATION OPERATIONS ==============
   async fn get_available_gas(&self, required_budget: u64) -> Result<ObjectID>;
   async fn release_gas(&self, object_id: ObjectID) -> Result<()>;
-  async fn sponsor_transaction(
+  /// Locks a gas coin and builds the sponsored `TransactionData` the sender has to sign,
+  /// once the transaction passed the sponsorship policy
+  async fn reserve_sponsorship(
+    &self,
+    kind: TransactionKind,
+    sender: SuiAddress,
+    caller: &SponsorCaller,
+    app_id: Option<String>,
+    gas_budget: Option<u64>,
+  ) -> Result<SponsorReservation>;
+  /// Hands out a reservation once, `None` when unknown, already executed or expired
+  async fn take_reservation(&self, reservation_id: Uuid) -> Result<Option<SponsorReservation>>;
+  /// Countersigns and executes a taken reservation, its gas coin goes back to the pool after
+  async fn execute_sponsorship(
     &self,
-    tx_bytes: Vec<u8>,
+    reservation: &SponsorReservation,
     user_signature: &[u8],
-  ) -> Result<(Transaction, String)>;
+  ) -> Result<ExecutedSponsorship>;
   async fn get_pool_stats(&self) -> Result<GasPoolStatus>;
   async fn refresh_gas_pool(&self) -> Result<()>;
+  /// Logs the sponsorship and emits `SponsoredTransactionExecutedEvent` through the outbox
   async fn log_sponsored_transaction(
     &self,
     user_address: &SuiAddress,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    gas_used: u64,
+    tx_digest: &str,
+  ) -> Result<()>;
+  /// Emits `SponsoredTransactionFailedEvent` through the outbox
+  async fn record_sponsorship_failure(
+    &self,
+    user_address: &SuiAddress,
+    app_id: Option<&str>,
     gas_budget: u64,
+    reason: &str,
   ) -> Result<()>;
   async fn get_user_stats(&self, address: &str) -> Result<Option<UserStats>>;
   async fn check_rate_limit(&self, user_address: &SuiAddress) -> Result<bool>;
@@ -17,6 +17,15 @@ pub enum Error {
 
   #[error("Implementation pending: {0}")]
   ImplementationPending(String),
+
+  #[error("Rejected by sponsorship policy `{rule}`: {reason}")]
+  PolicyViolation { rule: &'static str, reason: String },
+
+  #[error("Sui RPC `{endpoint}` timed out after {timeout_ms}ms")]
+  Timeout { endpoint: &'static str, timeout_ms: u64 },
+
+  #[error("Circuit breaker open for Sui RPC `{endpoint}`")]
+  CircuitOpen { endpoint: &'static str },
 }
 
 impl IntoResponse for Error {
@@ -26,8 +35,30 @@ impl IntoResponse for Error {
       Error::InvalidRequest(msg) => (StatusCode::BAD_REQUEST, msg),
       Error::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
       Error::ImplementationPending(msg) => (StatusCode::OK, msg),
+      e @ Error::PolicyViolation { .. } => (StatusCode::FORBIDDEN, e.to_string()),
+      e @ Error::Timeout { .. } => (StatusCode::GATEWAY_TIMEOUT, e.to_string()),
+      e @ Error::CircuitOpen { .. } => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()),
     };
 
     (status, error_message).into_response()
   }
 }
+
+impl Error {
+  /// The node failed or did not answer, as opposed to rejecting the request
+  pub fn is_node_failure(&self) -> bool {
+    matches!(self, Error::SuiClient(_) | Error::Timeout { .. })
+  }
+}
+
+impl From<jd_storage::dbx::Error> for Error {
+  fn from(err: jd_storage::dbx::Error) -> Self {
+    Error::Internal(err.to_string())
+  }
+}
+
+impl From<jd_messaging::Error> for Error {
+  fn from(err: jd_messaging::Error) -> Self {
+    Error::Internal(err.to_string())
+  }
+}
@@ -0,0 +1,367 @@
+use async_trait::async_trait;
+use jd_core::AppState;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_sdk::rpc_types::{
+  Balance, Coin, DynamicFieldInfo, Page, SuiCoinMetadata, SuiEvent, SuiObjectDataOptions,
+  SuiObjectResponse, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+};
+use sui_sdk::types::base_types::{SuiAddress, TransactionDigest};
+use sui_types::base_types::ObjectID;
+use sui_types::object::Owner;
+use sui_types::transaction::TransactionKind;
+use uuid::Uuid;
+
+use crate::Result;
+use crate::domain::sui_repository_trait::SuiRepository;
+use crate::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use crate::infrastructure::resilient_sui_repository::ResilientSuiRepository;
+use crate::infrastructure::sui_cache::{Lifetime, SuiCache, options_digest};
+use crate::models::{
+  ExecutedSponsorship, GasPoolStatus, SponsorCaller, SponsorReservation, UserStats,
+};
+
+/// Serves reads of a `SuiRepository` from `SuiCache`.
+/// Coin metadata, the chain id, immutable objects and checkpointed transactions never change
+/// and are kept until evicted. Other objects are cached per version, with the latest version
+/// of an object trusted for `object_version_ttl_secs`. Balances live `balance_ttl_secs`.
+/// Pages, coin selection and everything the gas station does always go to the node.
+#[derive(Clone)]
+pub struct CachedSuiRepository<R: SuiRepository> {
+  inner: R,
+  cache: Arc<SuiCache>,
+}
+
+impl<R: SuiRepository> CachedSuiRepository<R> {
+  pub fn wrap(inner: R, cache: Arc<SuiCache>) -> Self {
+    Self { inner, cache }
+  }
+
+  fn ttl(secs: u64) -> Lifetime {
+    Lifetime::For(Duration::from_secs(secs))
+  }
+}
+
+impl CachedSuiRepository<ResilientSuiRepository<EnhancedSuiRepository>> {
+  pub fn new(app_state: AppState) -> Self {
+    let cache = SuiCache::shared(&app_state);
+    Self::wrap(ResilientSuiRepository::new(app_state), cache)
+  }
+}
+
+#[async_trait]
+impl<R: SuiRepository> SuiRepository for CachedSuiRepository<R> {
+  // ============== COIN OPERATIONS ==============
+
+  async fn get_coins(
+    &self,
+    address: SuiAddress,
+    coin_type: Option<String>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<Page<Coin, String>> {
+    self
+      .inner
+      .get_coins(address, coin_type, cursor, limit)
+      .await
+  }
+
+  async fn get_all_coins(
+    &self,
+    address: SuiAddress,
+    cursor: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<Page<Coin, String>> {
+    self.inner.get_all_coins(address, cursor, limit).await
+  }
+
+  async fn get_balance(&self, address: SuiAddress, coin_type: Option<String>) -> Result<Balance> {
+    let address_key = address.to_string();
+    let key = self
+      .cache
+      .key(&["balance", &address_key, coin_type.as_deref().unwrap_or("-")]);
+    let lifetime = Self::ttl(self.cache.config().balance_ttl_secs);
+    self
+      .cache
+      .read_through("balance", &key, |_| Some(lifetime), self.inner.get_balance(address, coin_type))
+      .await
+  }
+
+  async fn get_all_balances(&self, address: SuiAddress) -> Result<Vec<Balance>> {
+    let key = self.cache.key(&["balances", &address.to_string()]);
+    let lifetime = Self::ttl(self.cache.config().balance_ttl_secs);
+    self
+      .cache
+      .read_through("balance", &key, |_| Some(lifetime), self.inner.get_all_balances(address))
+      .await
+  }
+
+  async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>> {
+    // Unknown coin types may still be published, only found metadata is kept
+    let key = self.cache.key(&["coin_metadata", &coin_type]);
+    self
+      .cache
+      .read_through(
+        "coin_metadata",
+        &key,
+        |metadata| metadata.as_ref().map(|_| Lifetime::Forever),
+        self.inner.get_coin_metadata(coin_type),
+      )
+      .await
+  }
+
+  async fn get_total_supply(&self, coin_type: String) -> Result<Option<u64>> {
+    self.inner.get_total_supply(coin_type).await
+  }
+
+  async fn select_coins(
+    &self,
+    address: SuiAddress,
+    coin_type: Option<String>,
+    amount: u64,
+    exclude: Vec<ObjectID>,
+  ) -> Result<Vec<Coin>> {
+    self
+      .inner
+      .select_coins(address, coin_type, amount, exclude)
+      .await
+  }
+
+  // ============== OBJECT OPERATIONS ==============
+
+  async fn get_object(
+    &self,
+    object_id: ObjectID,
+    options: Option<SuiObjectDataOptions>,
+  ) -> Result<SuiObjectResponse> {
+    let id = object_id.to_string();
+    let digest = options_digest(&options);
+    let latest_key = self.cache.key(&["object", &id, "latest"]);
+
+    if let Some(version) = self
+      .cache
+      .lookup::<u64>("object_version", &latest_key)
+      .await
+    {
+      let key = self
+        .cache
+        .key(&["object", &id, &version.to_string(), &digest]);
+      if let Some(object) = self.cache.lookup("object", &key).await {
+        return Ok(object);
+      }
+    }
+
+    let object = self.inner.get_object(object_id, options).await?;
+    // Deleted or missing objects carry no version and are not cached
+    if let Some(data) = object.data.as_ref() {
+      let config = self.cache.config();
+      let version = data.version.value();
+      let (content, latest) = if matches!(data.owner, Some(Owner::Immutable)) {
+        (Lifetime::Forever, Lifetime::Forever)
+      } else {
+        (Self::ttl(config.versioned_ttl_secs), Self::ttl(config.object_version_ttl_secs))
+      };
+
+      let key = self
+        .cache
+        .key(&["object", &id, &version.to_string(), &digest]);
+      self.cache.store(&key, &object, content).await;
+      self.cache.store(&latest_key, &version, latest).await;
+    }
+    Ok(object)
+  }
+
+  async fn get_objects(
+    &self,
+    object_ids: Vec<ObjectID>,
+    options: Option<SuiObjectDataOptions>,
+  ) -> Result<Vec<SuiObjectResponse>> {
+    self.inner.get_objects(object_ids, options).await
+  }
+
+  async fn get_owned_objects(
+    &self,
+    address: SuiAddress,
+    query: Option<String>,
+    cursor: Option<ObjectID>,
+    limit: Option<usize>,
+  ) -> Result<Page<SuiObjectResponse, ObjectID>> {
+    self
+      .inner
+      .get_owned_objects(address, query, cursor, limit)
+      .await
+  }
+
+  async fn get_dynamic_fields(
+    &self,
+    parent_object_id: ObjectID,
+    cursor: Option<ObjectID>,
+    limit: Option<usize>,
+  ) -> Result<Page<DynamicFieldInfo, ObjectID>> {
+    self
+      .inner
+      .get_dynamic_fields(parent_object_id, cursor, limit)
+      .await
+  }
+
+  // ============== TRANSACTION OPERATIONS ==============
+
+  async fn get_transaction_block(
+    &self,
+    digest: TransactionDigest,
+    options: Option<SuiTransactionBlockResponseOptions>,
+  ) -> Result<SuiTransactionBlockResponse> {
+    // Checkpoint and timestamp are filled in once the transaction is checkpointed
+    let key = self
+      .cache
+      .key(&["transaction", &digest.to_string(), &options_digest(&options)]);
+    self
+      .cache
+      .read_through(
+        "transaction",
+        &key,
+        |response| response.checkpoint.map(|_| Lifetime::Forever),
+        self.inner.get_transaction_block(digest, options),
+      )
+      .await
+  }
+
+  async fn get_transaction_blocks(
+    &self,
+    digests: Vec<TransactionDigest>,
+    options: Option<SuiTransactionBlockResponseOptions>,
+  ) -> Result<Vec<SuiTransactionBlockResponse>> {
+    self.inner.get_transaction_blocks(digests, options).await
+  }
+
+  // ============== EVENT OPERATIONS ==============
+
+  async fn get_events(&self, digest: TransactionDigest) -> Result<Vec<SuiEvent>> {
+    // No events yet may also mean the transaction is not indexed yet
+    let key = self.cache.key(&["events", &digest.to_string()]);
+    self
+      .cache
+      .read_through(
+        "events",
+        &key,
+        |events| (!events.is_empty()).then_some(Lifetime::Forever),
+        self.inner.get_events(digest),
+      )
+      .await
+  }
+
+  // ============== NETWORK INFO ==============
+
+  async fn get_latest_checkpoint_sequence_number(&self) -> Result<u64> {
+    self.inner.get_latest_checkpoint_sequence_number().await
+  }
+
+  async fn get_total_transaction_blocks(&self) -> Result<u64> {
+    self.inner.get_total_transaction_blocks().await
+  }
+
+  async fn get_reference_gas_price(&self) -> Result<u64> {
+    self.inner.get_reference_gas_price().await
+  }
+
+  async fn get_chain_identifier(&self) -> Result<String> {
+    let key = self.cache.key(&["chain_identifier"]);
+    self
+      .cache
+      .read_through(
+        "chain_identifier",
+        &key,
+        |_| Some(Lifetime::Forever),
+        self.inner.get_chain_identifier(),
+      )
+      .await
+  }
+
+  // ============== LEGACY/DEPRECATED - Keep for backward compatibility ==============
+
+  async fn fetch_coin(&self, sender: String) -> Result<Option<Coin>> {
+    self.inner.fetch_coin(sender).await
+  }
+
+  // ============== GAS STATION OPERATIONS ==============
+
+  async fn get_available_gas(&self, required_budget: u64) -> Result<ObjectID> {
+    self.inner.get_available_gas(required_budget).await
+  }
+
+  async fn release_gas(&self, object_id: ObjectID) -> Result<()> {
+    self.inner.release_gas(object_id).await
+  }
+
+  async fn reserve_sponsorship(
+    &self,
+    kind: TransactionKind,
+    sender: SuiAddress,
+    caller: &SponsorCaller,
+    app_id: Option<String>,
+    gas_budget: Option<u64>,
+  ) -> Result<SponsorReservation> {
+    self
+      .inner
+      .reserve_sponsorship(kind, sender, caller, app_id, gas_budget)
+      .await
+  }
+
+  async fn take_reservation(&self, reservation_id: Uuid) -> Result<Option<SponsorReservation>> {
+    self.inner.take_reservation(reservation_id).await
+  }
+
+  async fn execute_sponsorship(
+    &self,
+    reservation: &SponsorReservation,
+    user_signature: &[u8],
+  ) -> Result<ExecutedSponsorship> {
+    self
+      .inner
+      .execute_sponsorship(reservation, user_signature)
+      .await
+  }
+
+  async fn get_pool_stats(&self) -> Result<GasPoolStatus> {
+    self.inner.get_pool_stats().await
+  }
+
+  async fn refresh_gas_pool(&self) -> Result<()> {
+    self.inner.refresh_gas_pool().await
+  }
+
+  async fn log_sponsored_transaction(
+    &self,
+    user_address: &SuiAddress,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    gas_used: u64,
+    tx_digest: &str,
+  ) -> Result<()> {
+    self
+      .inner
+      .log_sponsored_transaction(user_address, app_id, gas_budget, gas_used, tx_digest)
+      .await
+  }
+
+  async fn record_sponsorship_failure(
+    &self,
+    user_address: &SuiAddress,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    reason: &str,
+  ) -> Result<()> {
+    self
+      .inner
+      .record_sponsorship_failure(user_address, app_id, gas_budget, reason)
+      .await
+  }
+
+  async fn get_user_stats(&self, address: &str) -> Result<Option<UserStats>> {
+    self.inner.get_user_stats(address).await
+  }
+
+  async fn check_rate_limit(&self, user_address: &SuiAddress) -> Result<bool> {
+    self.inner.check_rate_limit(user_address).await
+  }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Breakers of this process, shared by every repository built per request
+static BREAKERS: LazyLock<Arc<CircuitBreakers>> = LazyLock::new(Arc::default);
+
+#[derive(Default)]
+struct Breaker {
+  consecutive_failures: u32,
+  /// Set while open, calls are rejected until then
+  open_until: Option<Instant>,
+  /// Start of the single call let through once `open_until` passed
+  probe_started: Option<Instant>,
+}
+
+impl Breaker {
+  /// Whether a call may go out, see `CircuitBreakers::try_acquire`
+  fn admits(&self, open_for: Duration, now: Instant) -> bool {
+    match self.open_until {
+      None => true,
+      Some(open_until) if now < open_until => false,
+      Some(_) => self.probe_started.is_none_or(|started| now.duration_since(started) >= open_for),
+    }
+  }
+}
+
+/// Fullnode URL and RPC method
+type Key = (String, &'static str);
+
+/// Consecutive failure breakers keyed by fullnode and RPC method, so a method failing on one
+/// node keeps going to the others. Every replica judges the nodes on its own, a node that fails
+/// one replica's calls is usually failing them all anyway.
+#[derive(Default)]
+pub struct CircuitBreakers {
+  breakers: Mutex<HashMap<Key, Breaker>>,
+}
+
+impl CircuitBreakers {
+  pub fn shared() -> Arc<Self> {
+    BREAKERS.clone()
+  }
+
+  /// Whether `try_acquire` would let a call to `method` on `node` go out, without taking the
+  /// probe. Used to pick the node.
+  pub fn is_closed(&self, node: &str, method: &'static str, open_for: Duration) -> bool {
+    let breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+    breakers
+      .get(&(node.to_string(), method))
+      .is_none_or(|breaker| breaker.admits(open_for, Instant::now()))
+  }
+
+  /// Whether a call to `method` on `node` may go out. Once the open period is over one probe
+  /// is let through; if it never reports back, another one is allowed after `open_for`.
+  pub fn try_acquire(&self, node: &str, method: &'static str, open_for: Duration) -> bool {
+    self.try_acquire_at(node, method, open_for, Instant::now())
+  }
+
+  pub fn record_success(&self, node: &str, method: &'static str) {
+    let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+    breakers.remove(&(node.to_string(), method));
+  }
+
+  /// Opens the circuit after `threshold` failures in a row, or right away when a probe failed.
+  /// Returns true when this failure opened it.
+  pub fn record_failure(
+    &self,
+    node: &str,
+    method: &'static str,
+    threshold: u32,
+    open_for: Duration,
+  ) -> bool {
+    self.record_failure_at(node, method, threshold, open_for, Instant::now())
+  }
+
+  fn try_acquire_at(
+    &self,
+    node: &str,
+    method: &'static str,
+    open_for: Duration,
+    now: Instant,
+  ) -> bool {
+    let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(breaker) = breakers.get_mut(&(node.to_string(), method)) else {
+      return true;
+    };
+    if !breaker.admits(open_for, now) {
+      return false;
+    }
+    if breaker.open_until.is_some() {
+      breaker.probe_started = Some(now);
+    }
+    true
+  }
+
+  fn record_failure_at(
+    &self,
+    node: &str,
+    method: &'static str,
+    threshold: u32,
+    open_for: Duration,
+    now: Instant,
+  ) -> bool {
+    let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+    let breaker = breakers.entry((node.to_string(), method)).or_default();
+    breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+
+    let probe_failed = breaker.probe_started.take().is_some();
+    let opens = probe_failed
+      || (breaker.open_until.is_none() && breaker.consecutive_failures >= threshold.max(1));
+    if opens {
+      breaker.open_until = Some(now + open_for);
+    }
+    opens
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const OPEN_FOR: Duration = Duration::from_secs(30);
+  const NODE: &str = "https://fullnode-a.example.com";
+  const OTHER_NODE: &str = "https://fullnode-b.example.com";
+
+  #[test]
+  fn opens_after_consecutive_failures_and_probes_once() {
+    let breakers = CircuitBreakers::default();
+    let now = Instant::now();
+
+    assert!(!breakers.record_failure_at(NODE, "sui_getObject", 3, OPEN_FOR, now));
+    assert!(!breakers.record_failure_at(NODE, "sui_getObject", 3, OPEN_FOR, now));
+    assert!(breakers.record_failure_at(NODE, "sui_getObject", 3, OPEN_FOR, now));
+    assert!(!breakers.try_acquire_at(NODE, "sui_getObject", OPEN_FOR, now));
+    // Other methods and other nodes keep their own circuit
+    assert!(breakers.try_acquire_at(NODE, "sui_getEvents", OPEN_FOR, now));
+    assert!(breakers.try_acquire_at(OTHER_NODE, "sui_getObject", OPEN_FOR, now));
+    assert!(!breakers.is_closed(NODE, "sui_getObject", OPEN_FOR));
+
+    let later = now + OPEN_FOR;
+    assert!(breakers.try_acquire_at(NODE, "sui_getObject", OPEN_FOR, later));
+    assert!(!breakers.try_acquire_at(NODE, "sui_getObject", OPEN_FOR, later));
+
+    // A failed probe opens the circuit for another period
+    assert!(breakers.record_failure_at(NODE, "sui_getObject", 3, OPEN_FOR, later));
+    assert!(!breakers.try_acquire_at(NODE, "sui_getObject", OPEN_FOR, later));
+
+    let much_later = later + OPEN_FOR;
+    assert!(breakers.try_acquire_at(NODE, "sui_getObject", OPEN_FOR, much_later));
+    breakers.record_success(NODE, "sui_getObject");
+    assert!(breakers.try_acquire_at(NODE, "sui_getObject", OPEN_FOR, much_later));
+  }
+
+  #[test]
+  fn success_resets_the_failure_count() {
+    let breakers = CircuitBreakers::default();
+    let now = Instant::now();
+
+    breakers.record_failure_at(NODE, "suix_getBalance", 2, OPEN_FOR, now);
+    breakers.record_success(NODE, "suix_getBalance");
+    assert!(!breakers.record_failure_at(NODE, "suix_getBalance", 2, OPEN_FOR, now));
+    assert!(breakers.try_acquire_at(NODE, "suix_getBalance", OPEN_FOR, now));
+  }
+
+  #[test]
+  fn abandoned_probe_is_replaced_after_the_open_period() {
+    let breakers = CircuitBreakers::default();
+    let now = Instant::now();
+    breakers.record_failure_at(NODE, "sui_getEvents", 1, OPEN_FOR, now);
+
+    let probe = now + OPEN_FOR;
+    assert!(breakers.try_acquire_at(NODE, "sui_getEvents", OPEN_FOR, probe));
+    assert!(!breakers.try_acquire_at(NODE, "sui_getEvents", OPEN_FOR, probe + OPEN_FOR / 2));
+    assert!(breakers.try_acquire_at(NODE, "sui_getEvents", OPEN_FOR, probe + OPEN_FOR));
+  }
+}
@@ -1,11 +1,13 @@
 use crate::domain::sui_repository_trait::SuiRepository;
+use crate::infrastructure::resilient_sui_repository::NodeBound;
 use crate::{Result, error::Error};
 use async_trait::async_trait;
 use jd_core::AppState;
 use sui_sdk::rpc_types::{
   Coin, SuiObjectResponse, SuiTransactionBlockResponse, SuiEvent, Page,
   Balance, SuiCoinMetadata, SuiObjectDataOptions,
-  SuiTransactionBlockResponseOptions, DynamicFieldInfo
+  SuiTransactionBlockResponseOptions, DynamicFieldInfo, SuiObjectDataFilter,
+  SuiObjectResponseQuery,
 };
 use sui_sdk::types::base_types::{SuiAddress, TransactionDigest};
 use sui_types::base_types::ObjectID;
@@ -15,11 +17,23 @@ use sui_types::base_types::ObjectID;
 #[derive(Clone)]
 pub struct EnhancedSuiRepository {
   app_state: AppState,
+  /// Node every read goes to, otherwise the `SuiClient` picks one per read
+  node: Option<sui_sdk::SuiClient>,
 }
 
 impl EnhancedSuiRepository {
   pub fn new(app_state: AppState) -> Self {
-    Self { app_state }
+    Self { app_state, node: None }
+  }
+
+  fn client(&self) -> sui_sdk::SuiClient {
+    self.node.clone().unwrap_or_else(|| self.app_state.sui_client.client())
+  }
+}
+
+impl NodeBound for EnhancedSuiRepository {
+  fn on_node(&self, client: sui_sdk::SuiClient) -> Self {
+    Self { app_state: self.app_state.clone(), node: Some(client) }
   }
 }
 
@@ -34,12 +48,12 @@ impl SuiRepository for EnhancedSuiRepository {
     cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<Coin, String>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .coin_read_api()
       .get_coins(address, coin_type, cursor, limit)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get coins: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get coins: {}", e)))
   }
 
   async fn get_all_coins(
@@ -48,48 +62,48 @@ impl SuiRepository for EnhancedSuiRepository {
     cursor: Option<String>,
     limit: Option<usize>,
   ) -> Result<Page<Coin, String>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .coin_read_api()
       .get_all_coins(address, cursor, limit)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get all coins: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get all coins: {}", e)))
   }
 
   async fn get_balance(&self, address: SuiAddress, coin_type: Option<String>) -> Result<Balance> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .coin_read_api()
       .get_balance(address, coin_type)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get balance: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get balance: {}", e)))
   }
 
   async fn get_all_balances(&self, address: SuiAddress) -> Result<Vec<Balance>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .coin_read_api()
       .get_all_balances(address)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get all balances: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get all balances: {}", e)))
   }
 
   async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .coin_read_api()
       .get_coin_metadata(coin_type)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get coin metadata: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get coin metadata: {}", e)))
   }
 
   async fn get_total_supply(&self, coin_type: String) -> Result<Option<u64>> {
-    let supply = self.app_state
-      .sui_client.client
+    let supply = self
+      .client()
       .coin_read_api()
       .get_total_supply(coin_type)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get total supply: {}", e)))?;
+      .map_err(|e| Error::SuiClient(format!("Failed to get total supply: {}", e)))?;
     Ok(Some(supply.value))
   }
 
@@ -100,12 +114,12 @@ impl SuiRepository for EnhancedSuiRepository {
     amount: u64,
     exclude: Vec<ObjectID>,
   ) -> Result<Vec<Coin>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .coin_read_api()
       .select_coins(address, coin_type, amount.into(), exclude)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to select coins: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to select coins: {}", e)))
   }
 
   // ============== OBJECT OPERATIONS ==============
@@ -115,12 +129,12 @@ impl SuiRepository for EnhancedSuiRepository {
     object_id: ObjectID,
     options: Option<SuiObjectDataOptions>,
   ) -> Result<SuiObjectResponse> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .get_object_with_options(object_id, options.unwrap_or_default())
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get object: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get object: {}", e)))
   }
 
   async fn get_objects(
@@ -128,12 +142,12 @@ impl SuiRepository for EnhancedSuiRepository {
     object_ids: Vec<ObjectID>,
     options: Option<SuiObjectDataOptions>,
   ) -> Result<Vec<SuiObjectResponse>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .multi_get_object_with_options(object_ids, options.unwrap_or_default())
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get objects: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get objects: {}", e)))
   }
 
   async fn get_owned_objects(
@@ -143,17 +157,22 @@ impl SuiRepository for EnhancedSuiRepository {
     cursor: Option<ObjectID>,
     limit: Option<usize>,
   ) -> Result<Page<SuiObjectResponse, ObjectID>> {
-    // Convert string query to proper query type if needed
-    let query = query.map(|_| sui_sdk::rpc_types::SuiObjectResponseQuery::new_with_options(
-      SuiObjectDataOptions::full_content(),
-    ));
-    
-    self.app_state
-      .sui_client.client
+    // The query string is a Move struct type, e.g. `0x2::coin::Coin<0x2::sui::SUI>`
+    let filter = query
+      .map(|object_type| {
+        sui_types::parse_sui_struct_tag(&object_type)
+          .map(SuiObjectDataFilter::StructType)
+          .map_err(|_| Error::InvalidRequest(format!("Invalid object type: {}", object_type)))
+      })
+      .transpose()?;
+    let query = SuiObjectResponseQuery::new(filter, Some(SuiObjectDataOptions::full_content()));
+
+    self
+      .client()
       .read_api()
-      .get_owned_objects(address, query, cursor, limit)
+      .get_owned_objects(address, Some(query), cursor, limit)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get owned objects: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get owned objects: {}", e)))
   }
 
   async fn get_dynamic_fields(
@@ -162,12 +181,12 @@ impl SuiRepository for EnhancedSuiRepository {
     cursor: Option<ObjectID>,
     limit: Option<usize>,
   ) -> Result<Page<DynamicFieldInfo, ObjectID>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .get_dynamic_fields(parent_object_id, cursor, limit)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get dynamic fields: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get dynamic fields: {}", e)))
   }
 
   // ============== TRANSACTION OPERATIONS ==============
@@ -177,12 +196,12 @@ impl SuiRepository for EnhancedSuiRepository {
     digest: TransactionDigest,
     options: Option<SuiTransactionBlockResponseOptions>,
   ) -> Result<SuiTransactionBlockResponse> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .get_transaction_with_options(digest, options.unwrap_or_default())
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get transaction: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get transaction: {}", e)))
   }
 
   async fn get_transaction_blocks(
@@ -190,62 +209,62 @@ impl SuiRepository for EnhancedSuiRepository {
     digests: Vec<TransactionDigest>,
     options: Option<SuiTransactionBlockResponseOptions>,
   ) -> Result<Vec<SuiTransactionBlockResponse>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .multi_get_transactions_with_options(digests, options.unwrap_or_default())
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get transactions: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get transactions: {}", e)))
   }
 
   // ============== EVENT OPERATIONS ==============
 
   async fn get_events(&self, digest: TransactionDigest) -> Result<Vec<SuiEvent>> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .event_api()
       .get_events(digest)
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get events: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get events: {}", e)))
   }
 
 
   // ============== NETWORK INFO ==============
 
   async fn get_latest_checkpoint_sequence_number(&self) -> Result<u64> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .get_latest_checkpoint_sequence_number()
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get latest checkpoint: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get latest checkpoint: {}", e)))
   }
 
   async fn get_total_transaction_blocks(&self) -> Result<u64> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .get_total_transaction_blocks()
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get total transactions: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get total transactions: {}", e)))
   }
 
   async fn get_reference_gas_price(&self) -> Result<u64> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .governance_api()
       .get_reference_gas_price()
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get gas price: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get gas price: {}", e)))
   }
 
   async fn get_chain_identifier(&self) -> Result<String> {
-    self.app_state
-      .sui_client.client
+    self
+      .client()
       .read_api()
       .get_chain_identifier()
       .await
-      .map_err(|e| Error::Internal(format!("Failed to get chain identifier: {}", e)))
+      .map_err(|e| Error::SuiClient(format!("Failed to get chain identifier: {}", e)))
   }
 
   // ============== LEGACY/DEPRECATED - Keep for backward compatibility ==============
@@ -271,11 +290,29 @@ impl SuiRepository for EnhancedSuiRepository {
     Err(Error::ImplementationPending("Gas station operations not implemented in enhanced repository".to_string()))
   }
 
-  async fn sponsor_transaction(
+  async fn reserve_sponsorship(
+    &self,
+    _kind: sui_types::transaction::TransactionKind,
+    _sender: SuiAddress,
+    _caller: &crate::models::SponsorCaller,
+    _app_id: Option<String>,
+    _gas_budget: Option<u64>,
+  ) -> Result<crate::models::SponsorReservation> {
+    Err(Error::ImplementationPending("Sponsored transactions not implemented in enhanced repository".to_string()))
+  }
+
+  async fn take_reservation(
     &self,
-    _tx_bytes: Vec<u8>,
+    _reservation_id: uuid::Uuid,
+  ) -> Result<Option<crate::models::SponsorReservation>> {
+    Ok(None)
+  }
+
+  async fn execute_sponsorship(
+    &self,
+    _reservation: &crate::models::SponsorReservation,
     _user_signature: &[u8],
-  ) -> Result<(sui_types::transaction::Transaction, String)> {
+  ) -> Result<crate::models::ExecutedSponsorship> {
     Err(Error::ImplementationPending("Sponsored transactions not implemented in enhanced repository".to_string()))
   }
 
@@ -290,11 +327,24 @@ impl SuiRepository for EnhancedSuiRepository {
   async fn log_sponsored_transaction(
     &self,
     _user_address: &SuiAddress,
+    _app_id: Option<&str>,
     _gas_budget: u64,
+    _gas_used: u64,
+    _tx_digest: &str,
   ) -> Result<()> {
     Err(Error::ImplementationPending("Transaction logging not implemented in enhanced repository".to_string()))
   }
 
+  async fn record_sponsorship_failure(
+    &self,
+    _user_address: &SuiAddress,
+    _app_id: Option<&str>,
+    _gas_budget: u64,
+    _reason: &str,
+  ) -> Result<()> {
+    Err(Error::ImplementationPending("Failure events not implemented in enhanced repository".to_string()))
+  }
+
   async fn get_user_stats(&self, _address: &str) -> Result<Option<crate::models::UserStats>> {
     Err(Error::ImplementationPending("User stats not implemented in enhanced repository".to_string()))
   }
@@ -0,0 +1,230 @@
+use anyhow::Result;
+use redis::AsyncCommands;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use uuid::Uuid;
+
+/// How long an instance counts as alive after its last heartbeat
+pub const INSTANCE_TTL: Duration = Duration::from_secs(30);
+
+/// Heartbeats are sent well within `INSTANCE_TTL`
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+// Lease expiries are scored in milliseconds of Redis `TIME` so instance clocks don't matter.
+const NOW_MS: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+"#;
+
+/// Leases the first candidate without a live lease.
+/// KEYS: leases, holders. ARGV: ttl ms, instance, candidates...
+const ACQUIRE_FIRST: &str = r#"
+for i = 3, #ARGV do
+  local expires = redis.call('ZSCORE', KEYS[1], ARGV[i])
+  if not expires or tonumber(expires) <= now then
+    redis.call('ZADD', KEYS[1], now + tonumber(ARGV[1]), ARGV[i])
+    redis.call('HSET', KEYS[2], ARGV[i], ARGV[2])
+    return ARGV[i]
+  end
+end
+return false
+"#;
+
+/// Leases every candidate or none of them. Returns 1 when leased.
+const ACQUIRE_ALL: &str = r#"
+for i = 3, #ARGV do
+  local expires = redis.call('ZSCORE', KEYS[1], ARGV[i])
+  if expires and tonumber(expires) > now then
+    return 0
+  end
+end
+for i = 3, #ARGV do
+  redis.call('ZADD', KEYS[1], now + tonumber(ARGV[1]), ARGV[i])
+  redis.call('HSET', KEYS[2], ARGV[i], ARGV[2])
+end
+return 1
+"#;
+
+/// Drops a lease held by this instance. ARGV: coin, instance.
+const RELEASE: &str = r#"
+if redis.call('HGET', KEYS[2], ARGV[1]) == ARGV[2] then
+  redis.call('ZREM', KEYS[1], ARGV[1])
+  redis.call('HDEL', KEYS[2], ARGV[1])
+  return 1
+end
+return 0
+"#;
+
+/// Hands a live lease over to another instance. ARGV: coin, holder, instance. Returns 1 when
+/// the lease was still held by `holder`.
+const TAKE_OVER: &str = r#"
+if redis.call('HGET', KEYS[2], ARGV[1]) ~= ARGV[2] then
+  return 0
+end
+local expires = redis.call('ZSCORE', KEYS[1], ARGV[1])
+if not expires or tonumber(expires) <= now then
+  return 0
+end
+redis.call('HSET', KEYS[2], ARGV[1], ARGV[3])
+return 1
+"#;
+
+/// Coins with a live lease
+const LEASED: &str = r#"
+return redis.call('ZRANGEBYSCORE', KEYS[1], '(' .. now, '+inf')
+"#;
+
+/// Drops expired leases and those of instances without a heartbeat. ARGV: instance key prefix.
+const RECLAIM: &str = r#"
+local reclaimed = 0
+local holders = redis.call('HGETALL', KEYS[2])
+for i = 1, #holders, 2 do
+  local coin, instance = holders[i], holders[i + 1]
+  local expires = redis.call('ZSCORE', KEYS[1], coin)
+  local expired = not expires or tonumber(expires) <= now
+  if expired or redis.call('EXISTS', ARGV[1] .. instance) == 0 then
+    redis.call('ZREM', KEYS[1], coin)
+    redis.call('HDEL', KEYS[2], coin)
+    reclaimed = reclaimed + 1
+  end
+end
+return reclaimed
+"#;
+
+/// Gas coin leases shared by every instance sponsoring from the same address, so no two
+/// instances pay with the same coin. A lease ends when released, when its TTL runs out or when
+/// the instance holding it stops sending heartbeats.
+pub struct GasLeases {
+  redis: Arc<redis::Client>,
+  instance_id: String,
+  lease_ttl: Duration,
+  leases_key: String,
+  holders_key: String,
+  instance_prefix: String,
+}
+
+impl GasLeases {
+  pub fn new(redis: Arc<redis::Client>, sponsor_address: SuiAddress, lease_ttl: Duration) -> Self {
+    // Hash tag keeps every key of a sponsor in one cluster slot
+    let prefix = format!("sui:gas:{{{}}}", sponsor_address);
+    Self {
+      redis,
+      instance_id: Uuid::new_v4().to_string(),
+      lease_ttl,
+      leases_key: format!("{}:leases", prefix),
+      holders_key: format!("{}:holders", prefix),
+      instance_prefix: format!("{}:instance:", prefix),
+    }
+  }
+
+  /// Leases the first free coin of `candidates`, in the given order of preference
+  pub async fn acquire_first(&self, candidates: &[ObjectID]) -> Result<Option<ObjectID>> {
+    if candidates.is_empty() {
+      return Ok(None);
+    }
+
+    let leased: Option<String> = self.acquire_script(ACQUIRE_FIRST, candidates).await?;
+    Ok(leased.map(|coin| ObjectID::from_str(&coin)).transpose()?)
+  }
+
+  /// Leases all `coins` at once, `false` when any of them is taken
+  pub async fn acquire_all(&self, coins: &[ObjectID]) -> Result<bool> {
+    let leased: i64 = self.acquire_script(ACQUIRE_ALL, coins).await?;
+    Ok(leased == 1)
+  }
+
+  pub async fn release(&self, coin: ObjectID) -> Result<()> {
+    let mut conn = self.connection().await?;
+    let _: i64 = script(RELEASE)
+      .key(&self.leases_key)
+      .key(&self.holders_key)
+      .arg(coin.to_string())
+      .arg(&self.instance_id)
+      .invoke_async(&mut conn)
+      .await?;
+    Ok(())
+  }
+
+  /// Takes over a coin leased by `holder`, `false` once that lease ran out or was released.
+  /// The lease keeps its expiry.
+  pub async fn take_over(&self, coin: ObjectID, holder: &str) -> Result<bool> {
+    let mut conn = self.connection().await?;
+    let taken: i64 = script(TAKE_OVER)
+      .key(&self.leases_key)
+      .key(&self.holders_key)
+      .arg(coin.to_string())
+      .arg(holder)
+      .arg(&self.instance_id)
+      .invoke_async(&mut conn)
+      .await?;
+    Ok(taken == 1)
+  }
+
+  /// Identifies this instance's leases
+  pub fn lease_id(&self) -> &str {
+    &self.instance_id
+  }
+
+  pub fn lease_ttl(&self) -> Duration {
+    self.lease_ttl
+  }
+
+  /// Coins leased by any instance
+  pub async fn leased(&self) -> Result<HashSet<ObjectID>> {
+    let mut conn = self.connection().await?;
+    let coins: Vec<String> = script(LEASED).key(&self.leases_key).invoke_async(&mut conn).await?;
+
+    Ok(coins.iter().filter_map(|coin| ObjectID::from_str(coin).ok()).collect())
+  }
+
+  /// Marks this instance alive, its leases survive as long as heartbeats keep coming
+  pub async fn heartbeat(&self) -> Result<()> {
+    let mut conn = self.connection().await?;
+    let _: () = conn
+      .set_ex(format!("{}{}", self.instance_prefix, self.instance_id), 1, INSTANCE_TTL.as_secs())
+      .await?;
+    Ok(())
+  }
+
+  /// Frees leases that expired or belong to instances that went away, returns how many
+  pub async fn reclaim(&self) -> Result<u64> {
+    let mut conn = self.connection().await?;
+    let reclaimed: u64 = script(RECLAIM)
+      .key(&self.leases_key)
+      .key(&self.holders_key)
+      .arg(&self.instance_prefix)
+      .invoke_async(&mut conn)
+      .await?;
+    Ok(reclaimed)
+  }
+
+  async fn acquire_script<T: redis::FromRedisValue>(
+    &self,
+    body: &str,
+    coins: &[ObjectID],
+  ) -> Result<T> {
+    let mut conn = self.connection().await?;
+    let script = script(body);
+    let mut invocation = script.key(&self.leases_key);
+    invocation
+      .key(&self.holders_key)
+      .arg(self.lease_ttl.as_millis() as u64)
+      .arg(&self.instance_id);
+    for coin in coins {
+      invocation.arg(coin.to_string());
+    }
+
+    Ok(invocation.invoke_async(&mut conn).await?)
+  }
+
+  async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+    Ok(self.redis.get_multiplexed_async_connection().await?)
+  }
+}
+
+fn script(body: &str) -> redis::Script {
+  redis::Script::new(&format!("{}{}", NOW_MS, body))
+}
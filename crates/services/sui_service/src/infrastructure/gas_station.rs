@@ -1,93 +1,212 @@
 use anyhow::Result;
+use jd_core::sui::sui_client::SuiClient;
+use jd_utils::config::GasPoolConfig;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{error, info, warn};
 
-use sui_sdk::rpc_types::{SuiObjectDataOptions, SuiObjectResponseQuery};
-use sui_sdk::{SuiClient, SuiClientBuilder};
+use sui_sdk::rpc_types::{
+  SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponseQuery,
+};
 use sui_types::{
-  base_types::{ObjectID, SuiAddress},
+  base_types::{ObjectID, ObjectRef, SuiAddress},
   gas_coin::GasCoin,
+  object::Owner,
 };
 
+use crate::infrastructure::gas_leases::GasLeases;
 use crate::models::GasPoolStatus;
 
-#[derive(Debug)]
+/// Gas budget of the sponsor's own split and merge transactions, 0.05 SUI
+pub const MAINTENANCE_GAS_BUDGET: u64 = 50_000_000;
+
+/// Upper bound on coins created or merged by one maintenance transaction
+const MAX_COINS_PER_REBALANCE: usize = 100;
+
+/// Candidates tried when reserving gas, leased coins that turn out stale are skipped
+const MAX_RESERVE_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone)]
 pub struct GasObject {
   pub object_id: ObjectID,
-  pub version: u64,
+  /// Reference at the last known version, used as gas payment
+  pub object_ref: ObjectRef,
   pub balance: u64,
-  pub in_use: bool,
 }
 
+/// Total pool balance measured against the configured watermarks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PoolHealth {
+  Healthy,
+  Low,
+  Critical,
+}
+
+impl PoolHealth {
+  pub fn assess(total_balance: u64, config: &GasPoolConfig) -> Self {
+    if total_balance < config.critical_balance_watermark {
+      PoolHealth::Critical
+    } else if total_balance < config.low_balance_watermark {
+      PoolHealth::Low
+    } else {
+      PoolHealth::Healthy
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      PoolHealth::Healthy => "healthy",
+      PoolHealth::Low => "low",
+      PoolHealth::Critical => "critical",
+    }
+  }
+}
+
+/// Raised when the pool balance crosses a watermark downwards
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceAlert {
+  pub level: PoolHealth,
+  pub total_balance: u64,
+  pub watermark: u64,
+}
+
+/// Coin movement bringing the pool closer to its target shape. Every coin involved is
+/// leased until the maintenance transaction settled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rebalance {
+  /// Split coins of `amounts` off `coin`, which pays the gas, back to the sponsor
+  Split { coin: ObjectRef, amounts: Vec<u64> },
+  /// Merge `dust` into `target`, which pays the gas
+  Merge { target: ObjectRef, dust: Vec<ObjectRef> },
+}
+
+impl Rebalance {
+  pub fn coins(&self) -> Vec<ObjectID> {
+    match self {
+      Rebalance::Split { coin, .. } => vec![coin.0],
+      Rebalance::Merge { target, dust } => {
+        std::iter::once(target).chain(dust).map(|coin| coin.0).collect()
+      }
+    }
+  }
+}
+
+/// Sponsor gas coins. Every instance keeps its own view of the coins loaded from the chain,
+/// which coin is in use lives in the shared `GasLeases`.
 pub struct GasStation {
-  pub sui_client: SuiClient,
+  pub sui_client: Arc<SuiClient>,
   pub sponsor_address: SuiAddress,
   pub gas_pool: RwLock<HashMap<ObjectID, GasObject>>,
   pub max_gas_budget: u64,
+  pub pool_config: GasPoolConfig,
+  leases: GasLeases,
+  /// Level of the last balance check, alerts only fire when it gets worse
+  health: Mutex<PoolHealth>,
 }
 
 impl GasStation {
+  /// Gas station on the application's Sui client, its calls stay on the pinned node
   pub async fn new(
-    sui_rpc_url: &str,
+    sui_client: Arc<SuiClient>,
     sponsor_address: SuiAddress,
     max_gas_budget: u64,
+    pool_config: GasPoolConfig,
+    redis: Arc<redis::Client>,
   ) -> Result<Self> {
-    // Initialize SUI client
-    let sui_client = SuiClientBuilder::default().build(sui_rpc_url).await?;
+    let lease_ttl = Duration::from_secs(pool_config.lease_ttl_secs);
 
     // Initialize gas pool
-    let gas_station =
-      Self { sui_client, sponsor_address, gas_pool: RwLock::new(HashMap::new()), max_gas_budget };
+    let gas_station = Self {
+      sui_client,
+      sponsor_address,
+      gas_pool: RwLock::new(HashMap::new()),
+      max_gas_budget,
+      pool_config,
+      leases: GasLeases::new(redis, sponsor_address, lease_ttl),
+      health: Mutex::new(PoolHealth::Healthy),
+    };
 
-    // Load gas objects
+    // Announce the instance before it takes any lease, then load gas objects
+    gas_station.leases.heartbeat().await?;
     gas_station.refresh_gas_pool().await?;
 
     Ok(gas_station)
   }
 
+  /// Reloads every gas coin the sponsor owns, page by page
   pub async fn refresh_gas_pool(&self) -> Result<()> {
-    let gas_objects = self
-      .sui_client
-      .read_api()
-      .get_owned_objects(
-        self.sponsor_address,
-        Some(SuiObjectResponseQuery::new_with_options(
-          SuiObjectDataOptions::new().with_type().with_content(),
-        )),
-        None,
-        None,
-      )
-      .await?;
+    let mut coins = HashMap::new();
+    let mut cursor = None;
 
-    let mut pool = self.gas_pool.write().await;
-    pool.clear();
-
-    for obj_response in gas_objects.data {
-      if let Some(obj) = obj_response.data {
-        if let Ok(gas_coin) = GasCoin::try_from(&obj) {
-          let gas_object = GasObject {
-            object_id: obj.object_id,
-            version: obj.version.value(),
-            balance: gas_coin.value(),
-            in_use: false,
-          };
-          pool.insert(obj.object_id, gas_object);
-        }
+    loop {
+      let query = SuiObjectResponseQuery::new(
+        Some(SuiObjectDataFilter::StructType(GasCoin::type_())),
+        Some(SuiObjectDataOptions::new().with_type().with_content().with_owner()),
+      );
+      let page = self
+        .client()
+        .read_api()
+        .get_owned_objects(self.sponsor_address, Some(query), cursor, None)
+        .await?;
+
+      coins.extend(
+        page
+          .data
+          .into_iter()
+          .filter_map(|response| response.data)
+          .filter_map(|obj| self.sponsor_gas_object(&obj))
+          .map(|gas_object| (gas_object.object_id, gas_object)),
+      );
+
+      if !page.has_next_page || page.next_cursor.is_none() {
+        break;
       }
+      cursor = page.next_cursor;
     }
 
+    let mut pool = self.gas_pool.write().await;
+    *pool = coins;
+
     info!("Refreshed gas pool with {} objects", pool.len());
     Ok(())
   }
 
   pub async fn get_available_gas(&self, required_budget: u64) -> Result<ObjectID> {
-    let mut pool = self.gas_pool.write().await;
+    Ok(self.reserve_gas(required_budget).await?.0)
+  }
+
+  /// Leases the smallest free coin covering `required_budget` and returns its current
+  /// reference, large coins stay available for large budgets and splitting.
+  /// The coin is read back from the chain since another instance may have used it last.
+  pub async fn reserve_gas(&self, required_budget: u64) -> Result<ObjectRef> {
+    for _ in 0..MAX_RESERVE_ATTEMPTS {
+      let mut candidates: Vec<(u64, ObjectID)> = self
+        .gas_pool
+        .read()
+        .await
+        .values()
+        .filter(|gas_obj| gas_obj.balance >= required_budget)
+        .map(|gas_obj| (gas_obj.balance, gas_obj.object_id))
+        .collect();
+      candidates.sort();
+      let candidates: Vec<ObjectID> = candidates.into_iter().map(|(_, id)| id).collect();
+
+      let Some(object_id) = self.leases.acquire_first(&candidates).await? else {
+        break;
+      };
 
-    for (object_id, gas_obj) in pool.iter_mut() {
-      if !gas_obj.in_use && gas_obj.balance >= required_budget {
-        gas_obj.in_use = true;
-        return Ok(*object_id);
+      match self.load_gas_object(object_id).await {
+        Some(gas_obj) if gas_obj.balance >= required_budget => {
+          let object_ref = gas_obj.object_ref;
+          self.gas_pool.write().await.insert(object_id, gas_obj);
+          return Ok(object_ref);
+        }
+        reloaded => {
+          self.update_local(object_id, reloaded).await;
+          self.release_gas(object_id).await;
+        }
       }
     }
 
@@ -95,19 +214,143 @@ impl GasStation {
   }
 
   pub async fn release_gas(&self, object_id: ObjectID) {
+    if let Err(e) = self.leases.release(object_id).await {
+      // The lease runs out on its own
+      warn!("Failed to release lease on gas object {}: {}", object_id, e);
+    }
+  }
+
+  /// Takes over the lease `lease_id` holds on a reserved coin, which may have been reserved by
+  /// another instance. `false` when the lease already ran out.
+  pub async fn take_over_gas(&self, object_id: ObjectID, lease_id: &str) -> Result<bool> {
+    self.leases.take_over(object_id, lease_id).await
+  }
+
+  /// Client of the node the sponsor's transactions and the coin reads after them go to,
+  /// see `SuiClient::pinned_client`
+  pub fn client(&self) -> sui_sdk::SuiClient {
+    self.sui_client.pinned_client()
+  }
+
+  /// Leases taken by this instance carry this id
+  pub fn lease_id(&self) -> &str {
+    self.leases.lease_id()
+  }
+
+  /// How long a coin stays leased unless released
+  pub fn lease_ttl(&self) -> Duration {
+    self.leases.lease_ttl()
+  }
+
+  /// Reloads a coin after it paid for a transaction and releases its lease.
+  /// The coin is dropped when it is gone, no longer a sponsor-owned gas coin or cannot be read,
+  /// the next `refresh_gas_pool` picks it up again if it still exists.
+  pub async fn refresh_gas_object(&self, object_id: ObjectID) {
+    let reloaded = self.load_gas_object(object_id).await;
+    self.update_local(object_id, reloaded).await;
+    self.release_gas(object_id).await;
+  }
+
+  /// Keeps this instance's leases alive, see `GasLeases::heartbeat`
+  pub async fn heartbeat(&self) -> Result<()> {
+    self.leases.heartbeat().await
+  }
+
+  /// Frees leases of instances that stopped or crashed
+  pub async fn reclaim_leases(&self) -> Result<()> {
+    let reclaimed = self.leases.reclaim().await?;
+    if reclaimed > 0 {
+      info!("Reclaimed {} gas coin leases", reclaimed);
+    }
+    Ok(())
+  }
+
+  async fn load_gas_object(&self, object_id: ObjectID) -> Option<GasObject> {
+    let response = self
+      .client()
+      .read_api()
+      .get_object_with_options(
+        object_id,
+        SuiObjectDataOptions::new().with_type().with_content().with_owner(),
+      )
+      .await;
+
+    match response {
+      Ok(response) => response.data.and_then(|obj| self.sponsor_gas_object(&obj)),
+      Err(e) => {
+        warn!("Failed to reload gas object {}: {}", object_id, e);
+        None
+      }
+    }
+  }
+
+  async fn update_local(&self, object_id: ObjectID, reloaded: Option<GasObject>) {
     let mut pool = self.gas_pool.write().await;
-    if let Some(gas_obj) = pool.get_mut(&object_id) {
-      gas_obj.in_use = false;
+    match reloaded {
+      Some(gas_object) => {
+        pool.insert(object_id, gas_object);
+      }
+      None => {
+        warn!("Removing gas object {} from the pool", object_id);
+        pool.remove(&object_id);
+      }
     }
   }
 
-  pub async fn get_pool_stats(&self) -> GasPoolStatus {
+  /// Compares the pool balance with the watermarks, logging while it stays low.
+  /// Returns an alert when the level got worse since the previous check.
+  pub async fn check_balance(&self) -> Option<BalanceAlert> {
+    let total_balance: u64 = self.gas_pool.read().await.values().map(|obj| obj.balance).sum();
+    let level = PoolHealth::assess(total_balance, &self.pool_config);
+
+    match level {
+      PoolHealth::Critical => error!(
+        "Gas pool of {} is critically low: {} MIST",
+        self.sponsor_address, total_balance
+      ),
+      PoolHealth::Low => {
+        warn!("Gas pool of {} is running low: {} MIST", self.sponsor_address, total_balance)
+      }
+      PoolHealth::Healthy => {}
+    }
+
+    let previous = std::mem::replace(&mut *self.health.lock().unwrap(), level);
+    if level <= previous {
+      return None;
+    }
+
+    let watermark = match level {
+      PoolHealth::Critical => self.pool_config.critical_balance_watermark,
+      _ => self.pool_config.low_balance_watermark,
+    };
+    Some(BalanceAlert { level, total_balance, watermark })
+  }
+
+  /// Picks the next split or merge and leases its coins, `None` when the pool is in shape
+  pub async fn take_rebalance(&self) -> Result<Option<Rebalance>> {
+    let leased = self.leases.leased().await?;
+    let rebalance = {
+      let pool = self.gas_pool.read().await;
+      let free: Vec<&GasObject> =
+        pool.values().filter(|gas_obj| !leased.contains(&gas_obj.object_id)).collect();
+      plan_rebalance(&free, pool.len(), &self.pool_config)
+    };
+
+    match rebalance {
+      Some(rebalance) if self.leases.acquire_all(&rebalance.coins()).await? => Ok(Some(rebalance)),
+      _ => Ok(None),
+    }
+  }
+
+  /// Pool utilization across every instance sharing the sponsor
+  pub async fn get_pool_stats(&self) -> Result<GasPoolStatus> {
+    let leased = self.leases.leased().await?;
     let pool = self.gas_pool.read().await;
     let total_objects = pool.len();
     let total_balance: u64 = pool.values().map(|obj| obj.balance).sum();
-    let available_objects = pool.values().filter(|obj| !obj.in_use).count();
+    let available_objects = pool.keys().filter(|object_id| !leased.contains(object_id)).count();
 
-    GasPoolStatus {
+    Ok(GasPoolStatus {
       total_objects,
       total_balance,
       available_objects,
@@ -116,6 +359,141 @@ impl GasStation {
       } else {
         0.0
       },
+    })
+  }
+
+  fn sponsor_gas_object(&self, obj: &SuiObjectData) -> Option<GasObject> {
+    if obj.owner != Some(Owner::AddressOwner(self.sponsor_address)) {
+      return None;
+    }
+
+    let gas_coin = GasCoin::try_from(obj).ok()?;
+    Some(GasObject {
+      object_id: obj.object_id,
+      object_ref: obj.object_ref(),
+      balance: gas_coin.value(),
+    })
+  }
+}
+
+/// Merging dust comes first, then the largest free coin is split until the pool holds
+/// `target_coin_count` coins. `pool_size` counts leased coins as well.
+fn plan_rebalance(
+  free: &[&GasObject],
+  pool_size: usize,
+  config: &GasPoolConfig,
+) -> Option<Rebalance> {
+  let mut dust: Vec<&GasObject> =
+    free.iter().copied().filter(|obj| obj.balance < config.dust_threshold).collect();
+  dust.sort_by_key(|obj| obj.balance);
+
+  let largest = free.iter().copied().max_by_key(|obj| obj.balance)?;
+  if largest.balance < MAINTENANCE_GAS_BUDGET {
+    return None;
+  }
+
+  let merge: Vec<ObjectRef> = dust
+    .iter()
+    .filter(|obj| obj.object_id != largest.object_id)
+    .take(MAX_COINS_PER_REBALANCE)
+    .map(|obj| obj.object_ref)
+    .collect();
+  if !merge.is_empty() {
+    return Some(Rebalance::Merge { target: largest.object_ref, dust: merge });
+  }
+
+  let missing = config.target_coin_count.saturating_sub(pool_size);
+  if missing == 0 || config.target_coin_balance == 0 {
+    return None;
+  }
+
+  // The source keeps at least one target balance on top of the gas it pays
+  let spare = largest.balance.saturating_sub(MAINTENANCE_GAS_BUDGET) / config.target_coin_balance;
+  let splits = missing.min(spare.saturating_sub(1) as usize).min(MAX_COINS_PER_REBALANCE);
+  if splits == 0 {
+    return None;
+  }
+
+  Some(Rebalance::Split {
+    coin: largest.object_ref,
+    amounts: vec![config.target_coin_balance; splits],
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use sui_types::base_types::SequenceNumber;
+  use sui_types::digests::ObjectDigest;
+
+  const SUI: u64 = 1_000_000_000;
+
+  fn coin(balance: u64) -> GasObject {
+    let object_id = ObjectID::random();
+    GasObject {
+      object_id,
+      object_ref: (object_id, SequenceNumber::from_u64(1), ObjectDigest::random()),
+      balance,
     }
   }
+
+  fn config() -> GasPoolConfig {
+    GasPoolConfig {
+      target_coin_count: 5,
+      target_coin_balance: SUI,
+      dust_threshold: SUI / 100,
+      low_balance_watermark: 10 * SUI,
+      critical_balance_watermark: 2 * SUI,
+      maintenance_interval_secs: 60,
+      lease_ttl_secs: 180,
+    }
+  }
+
+  #[test]
+  fn dust_is_merged_into_largest_coin() {
+    let (big, dust_a, dust_b) = (coin(3 * SUI), coin(1_000), coin(2_000));
+    let free = [&big, &dust_a, &dust_b];
+
+    let plan = plan_rebalance(&free, 3, &config()).unwrap();
+
+    assert_eq!(
+      plan,
+      Rebalance::Merge { target: big.object_ref, dust: vec![dust_a.object_ref, dust_b.object_ref] }
+    );
+  }
+
+  #[test]
+  fn largest_coin_is_split_up_to_target_count() {
+    let big = coin(100 * SUI);
+
+    let plan = plan_rebalance(&[&big], 2, &config()).unwrap();
+
+    assert_eq!(plan, Rebalance::Split { coin: big.object_ref, amounts: vec![SUI; 3] });
+  }
+
+  #[test]
+  fn split_leaves_source_a_target_balance() {
+    let source = coin(2 * SUI + MAINTENANCE_GAS_BUDGET);
+
+    let plan = plan_rebalance(&[&source], 1, &config()).unwrap();
+
+    assert_eq!(plan, Rebalance::Split { coin: source.object_ref, amounts: vec![SUI] });
+  }
+
+  #[test]
+  fn full_pool_without_dust_needs_nothing() {
+    let coins: Vec<GasObject> = (0..5).map(|_| coin(SUI)).collect();
+    let free: Vec<&GasObject> = coins.iter().collect();
+
+    assert_eq!(plan_rebalance(&free, 5, &config()), None);
+  }
+
+  #[test]
+  fn health_follows_watermarks() {
+    let config = config();
+
+    assert_eq!(PoolHealth::assess(20 * SUI, &config), PoolHealth::Healthy);
+    assert_eq!(PoolHealth::assess(5 * SUI, &config), PoolHealth::Low);
+    assert_eq!(PoolHealth::assess(SUI, &config), PoolHealth::Critical);
+  }
 }
@@ -1,3 +1,11 @@
 // Infrastructure layer module
+pub mod cached_sui_repository;
+pub mod circuit_breaker;
 pub mod enhanced_sui_repository;
+pub mod gas_leases;
 pub mod gas_station;
+pub mod reservations;
+pub mod resilient_sui_repository;
+pub mod sponsor_budgets;
+pub mod sui_cache;
+pub mod sui_repository_impl;
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use redis::AsyncCommands;
+use uuid::Uuid;
+
+use crate::models::SponsorReservation;
+use crate::{Result, error::Error};
+
+/// Sponsorships waiting for the sender's signature, shared by every instance so any of them
+/// can execute it. Each entry is handed out once, whoever takes it owns the reserved gas coin.
+/// An entry nobody takes expires on its own and its coin goes back once the lease runs out.
+#[derive(Clone)]
+pub struct Reservations {
+  redis: Arc<redis::Client>,
+}
+
+impl Reservations {
+  pub fn new(redis: Arc<redis::Client>) -> Self {
+    Self { redis }
+  }
+
+  /// Stores a reservation until its `expires_at`
+  pub async fn insert(&self, reservation: &SponsorReservation) -> Result<()> {
+    let ttl_ms = (reservation.expires_at - Utc::now()).num_milliseconds();
+    let ttl_ms = u64::try_from(ttl_ms)
+      .ok()
+      .filter(|ttl_ms| *ttl_ms > 0)
+      .ok_or_else(|| Error::Internal("Reservation expired before it was stored".to_string()))?;
+    let bytes = bcs::to_bytes(reservation).map_err(|e| Error::Internal(e.to_string()))?;
+
+    let mut conn = self.connection().await?;
+    conn
+      .pset_ex::<_, _, ()>(key(reservation.id), bytes, ttl_ms)
+      .await
+      .map_err(|e| Error::Internal(e.to_string()))
+  }
+
+  /// Removes a reservation and returns it, `None` once taken or expired
+  pub async fn take(&self, id: Uuid) -> Result<Option<SponsorReservation>> {
+    let mut conn = self.connection().await?;
+    let bytes: Option<Vec<u8>> =
+      conn.get_del(key(id)).await.map_err(|e| Error::Internal(e.to_string()))?;
+
+    bytes
+      .map(|bytes| bcs::from_bytes(&bytes).map_err(|e| Error::Internal(e.to_string())))
+      .transpose()
+  }
+
+  async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+    self.redis.get_multiplexed_async_connection().await.map_err(|e| Error::Internal(e.to_string()))
+  }
+}
+
+fn key(id: Uuid) -> String {
+  format!("sui:reservation:{}", id)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::{BudgetCharge, SponsorCaller};
+  use auth_service::domain::UserRole;
+  use std::time::Duration;
+  use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress};
+  use sui_types::digests::ObjectDigest;
+  use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+  use sui_types::transaction::{TransactionData, TransactionKind};
+
+  fn object_ref() -> ObjectRef {
+    (ObjectID::random(), SequenceNumber::from_u64(1), ObjectDigest::random())
+  }
+
+  fn reservation(ttl: Duration) -> SponsorReservation {
+    let sender = SuiAddress::random_for_testing_only();
+    let gas_coin = object_ref();
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(SuiAddress::random_for_testing_only(), object_ref()).unwrap();
+    let tx_data = TransactionData::new_with_gas_coins_allow_sponsor(
+      TransactionKind::ProgrammableTransaction(builder.finish()),
+      sender,
+      vec![gas_coin],
+      10_000_000,
+      1_000,
+      SuiAddress::random_for_testing_only(),
+    );
+
+    let caller = SponsorCaller { user_id: Uuid::new_v4(), role: UserRole::Normal };
+    let charge = BudgetCharge {
+      id: Uuid::new_v4(),
+      day: Utc::now().format("%Y-%m-%d").to_string(),
+      user_id: caller.user_id,
+      app_id: Some("wallet".to_string()),
+      gas: 10_000_000,
+    };
+
+    SponsorReservation {
+      id: Uuid::new_v4(),
+      sender,
+      caller,
+      app_id: Some("wallet".to_string()),
+      gas_budget: 10_000_000,
+      gas_coin,
+      lease_id: Uuid::new_v4().to_string(),
+      charge,
+      tx_data,
+      expires_at: Utc::now() + ttl,
+    }
+  }
+
+  /// Live Redis tests run against `REDIS_URL`
+  fn reservations() -> Reservations {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    Reservations::new(Arc::new(redis::Client::open(url).unwrap()))
+  }
+
+  #[test]
+  fn stored_reservation_keeps_every_field() {
+    let reservation = reservation(Duration::from_secs(60));
+
+    let bytes = bcs::to_bytes(&reservation).unwrap();
+    let stored: SponsorReservation = bcs::from_bytes(&bytes).unwrap();
+
+    assert_eq!(stored.id, reservation.id);
+    assert_eq!(stored.caller.user_id, reservation.caller.user_id);
+    assert_eq!(stored.lease_id, reservation.lease_id);
+    assert_eq!(stored.charge, reservation.charge);
+    assert_eq!(stored.gas_coin, reservation.gas_coin);
+    assert_eq!(stored.tx_data, reservation.tx_data);
+    assert_eq!(stored.expires_at, reservation.expires_at);
+  }
+
+  #[tokio::test]
+  #[ignore = "needs a Redis server at REDIS_URL"]
+  async fn reservation_is_taken_once() {
+    let reservations = reservations();
+    let reservation = reservation(Duration::from_secs(60));
+
+    reservations.insert(&reservation).await.unwrap();
+
+    let taken = reservations.take(reservation.id).await.unwrap().unwrap();
+    assert_eq!(taken.tx_data, reservation.tx_data);
+    assert!(reservations.take(reservation.id).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  #[ignore = "needs a Redis server at REDIS_URL"]
+  async fn reservation_expires_with_its_ttl() {
+    let reservations = reservations();
+    let reservation = reservation(Duration::from_millis(200));
+
+    reservations.insert(&reservation).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    assert!(reservations.take(reservation.id).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn expired_reservation_is_not_stored() {
+    let reservation = reservation(Duration::ZERO);
+
+    assert!(reservations().insert(&reservation).await.is_err());
+  }
+}
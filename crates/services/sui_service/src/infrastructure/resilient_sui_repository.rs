@@ -0,0 +1,528 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use jd_core::AppState;
+use jd_core::sui::sui_client::SuiClient;
+use jd_utils::config::SuiResilienceConfig;
+use rand::Rng;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use sui_sdk::rpc_types::{
+  Balance, Coin, DynamicFieldInfo, Page, SuiCoinMetadata, SuiEvent, SuiObjectDataOptions,
+  SuiObjectResponse, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+};
+use sui_sdk::types::base_types::{SuiAddress, TransactionDigest};
+use sui_types::base_types::ObjectID;
+use sui_types::transaction::TransactionKind;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::domain::sui_repository_trait::SuiRepository;
+use crate::infrastructure::circuit_breaker::CircuitBreakers;
+use crate::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use crate::models::{
+  ExecutedSponsorship, GasPoolStatus, SponsorCaller, SponsorReservation, UserStats,
+};
+use crate::{Result, error::Error};
+
+/// Timeouts, retries and circuit breakers for fullnode reads
+#[derive(Clone)]
+pub struct Resilience {
+  config: SuiResilienceConfig,
+  breakers: Arc<CircuitBreakers>,
+}
+
+impl Resilience {
+  pub fn new(config: SuiResilienceConfig) -> Self {
+    Self { config, breakers: CircuitBreakers::shared() }
+  }
+
+  /// Runs an idempotent read of `endpoint`, retrying node failures. Every attempt goes to the
+  /// node `pick` chooses among those whose circuit for `endpoint` is closed, a retry to another
+  /// node when there is one. Answers the node gave, including rejections of the request, are
+  /// returned as they are.
+  pub async fn read<T, N, P, F, Fut>(&self, endpoint: &'static str, pick: P, call: F) -> Result<T>
+  where
+    P: Fn(&dyn Fn(&str) -> bool) -> Option<(String, N)>,
+    F: Fn(N) -> Fut,
+    Fut: Future<Output = Result<T>>,
+  {
+    let mut tried: Vec<String> = Vec::new();
+    let mut attempt = 0;
+    loop {
+      let Some((url, node)) = self.pick_node(endpoint, &pick, &tried) else {
+        return Err(Error::CircuitOpen { endpoint });
+      };
+      match self.attempt(&url, endpoint, call(node)).await {
+        Err(e) if e.is_node_failure() && attempt < self.config.read_retries => {
+          attempt += 1;
+          let delay = self.backoff(attempt);
+          debug!("Retrying Sui RPC {} in {:?} after {} failed: {}", endpoint, delay, url, e);
+          tried.push(url);
+          tokio::time::sleep(delay).await;
+        }
+        result => return result,
+      }
+    }
+  }
+
+  /// Node with a closed circuit for `endpoint`, one not tried yet when possible
+  fn pick_node<N>(
+    &self,
+    endpoint: &'static str,
+    pick: &impl Fn(&dyn Fn(&str) -> bool) -> Option<(String, N)>,
+    tried: &[String],
+  ) -> Option<(String, N)> {
+    let open_for = Duration::from_secs(self.config.open_secs);
+    let closed = |url: &str| self.breakers.is_closed(url, endpoint, open_for);
+    let untried = |url: &str| closed(url) && !tried.iter().any(|tried| tried == url);
+
+    pick(&untried).or_else(|| pick(&closed))
+  }
+
+  async fn attempt<T>(
+    &self,
+    node: &str,
+    endpoint: &'static str,
+    call: impl Future<Output = Result<T>>,
+  ) -> Result<T> {
+    let open_for = Duration::from_secs(self.config.open_secs);
+    if !self.breakers.try_acquire(node, endpoint, open_for) {
+      return Err(Error::CircuitOpen { endpoint });
+    }
+
+    let timeout_ms = self.config.timeout_ms;
+    let result = tokio::time::timeout(Duration::from_millis(timeout_ms), call)
+      .await
+      .unwrap_or(Err(Error::Timeout { endpoint, timeout_ms }));
+
+    match &result {
+      Ok(_) => self.breakers.record_success(node, endpoint),
+      Err(e) if e.is_node_failure() => {
+        if self
+          .breakers
+          .record_failure(node, endpoint, self.config.failure_threshold, open_for)
+        {
+          warn!(
+            "⚠️ Circuit for Sui RPC {} on {} open for {:?} after: {}",
+            endpoint, node, open_for, e
+          );
+        }
+      }
+      Err(_) => {}
+    }
+    result
+  }
+
+  /// Full jitter, a random delay up to the exponential backoff so retries don't line up
+  fn backoff(&self, attempt: u32) -> Duration {
+    let ceiling = self
+      .config
+      .retry_base_delay_ms
+      .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+      .min(self.config.retry_max_delay_ms);
+    Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling))
+  }
+}
+
+/// Repository whose reads can be sent to one fullnode of the `SuiClient`
+pub trait NodeBound: SuiRepository {
+  /// Copy of the repository reading from `client`
+  fn on_node(&self, client: sui_sdk::SuiClient) -> Self;
+}
+
+/// Guards the reads of a `SuiRepository` with `Resilience`. Gas station and sponsorship calls
+/// pass through untouched, cutting off an execution would release its gas coin while the
+/// transaction may still land.
+#[derive(Clone)]
+pub struct ResilientSuiRepository<R: NodeBound> {
+  inner: R,
+  sui_client: Arc<SuiClient>,
+  resilience: Resilience,
+}
+
+impl<R: NodeBound> ResilientSuiRepository<R> {
+  pub fn wrap(inner: R, sui_client: Arc<SuiClient>, config: SuiResilienceConfig) -> Self {
+    Self { inner, sui_client, resilience: Resilience::new(config) }
+  }
+
+  /// Runs `call` on the inner repository bound to the node `Resilience` picked
+  async fn read<T, F>(&self, endpoint: &'static str, call: F) -> Result<T>
+  where
+    F: for<'a> Fn(&'a R) -> BoxFuture<'a, Result<T>>,
+  {
+    let call = &call;
+    self
+      .resilience
+      .read(
+        endpoint,
+        |usable| self.sui_client.pick(usable).map(|(url, client)| (url.to_string(), client)),
+        |client| {
+          let inner = self.inner.on_node(client);
+          async move { call(&inner).await }
+        },
+      )
+      .await
+  }
+}
+
+impl ResilientSuiRepository<EnhancedSuiRepository> {
+  pub fn new(app_state: AppState) -> Self {
+    let config = app_state.config.sui.resilience.clone();
+    let sui_client = app_state.sui_client.clone();
+    Self::wrap(EnhancedSuiRepository::new(app_state), sui_client, config)
+  }
+}
+
+#[async_trait]
+impl<R: NodeBound> SuiRepository for ResilientSuiRepository<R> {
+  // ============== COIN OPERATIONS ==============
+
+  async fn get_coins(
+    &self,
+    address: SuiAddress,
+    coin_type: Option<String>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<Page<Coin, String>> {
+    self
+      .read("suix_getCoins", |inner| {
+        inner.get_coins(address, coin_type.clone(), cursor.clone(), limit)
+      })
+      .await
+  }
+
+  async fn get_all_coins(
+    &self,
+    address: SuiAddress,
+    cursor: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<Page<Coin, String>> {
+    self.read("suix_getAllCoins", |inner| inner.get_all_coins(address, cursor.clone(), limit)).await
+  }
+
+  async fn get_balance(&self, address: SuiAddress, coin_type: Option<String>) -> Result<Balance> {
+    self.read("suix_getBalance", |inner| inner.get_balance(address, coin_type.clone())).await
+  }
+
+  async fn get_all_balances(&self, address: SuiAddress) -> Result<Vec<Balance>> {
+    self.read("suix_getAllBalances", |inner| inner.get_all_balances(address)).await
+  }
+
+  async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>> {
+    self.read("suix_getCoinMetadata", |inner| inner.get_coin_metadata(coin_type.clone())).await
+  }
+
+  async fn get_total_supply(&self, coin_type: String) -> Result<Option<u64>> {
+    self.read("suix_getTotalSupply", |inner| inner.get_total_supply(coin_type.clone())).await
+  }
+
+  async fn select_coins(
+    &self,
+    address: SuiAddress,
+    coin_type: Option<String>,
+    amount: u64,
+    exclude: Vec<ObjectID>,
+  ) -> Result<Vec<Coin>> {
+    // Selection pages through `suix_getCoins`
+    self
+      .read("suix_getCoins", |inner| {
+        inner.select_coins(address, coin_type.clone(), amount, exclude.clone())
+      })
+      .await
+  }
+
+  // ============== OBJECT OPERATIONS ==============
+
+  async fn get_object(
+    &self,
+    object_id: ObjectID,
+    options: Option<SuiObjectDataOptions>,
+  ) -> Result<SuiObjectResponse> {
+    self.read("sui_getObject", |inner| inner.get_object(object_id, options.clone())).await
+  }
+
+  async fn get_objects(
+    &self,
+    object_ids: Vec<ObjectID>,
+    options: Option<SuiObjectDataOptions>,
+  ) -> Result<Vec<SuiObjectResponse>> {
+    self
+      .read("sui_multiGetObjects", |inner| inner.get_objects(object_ids.clone(), options.clone()))
+      .await
+  }
+
+  async fn get_owned_objects(
+    &self,
+    address: SuiAddress,
+    query: Option<String>,
+    cursor: Option<ObjectID>,
+    limit: Option<usize>,
+  ) -> Result<Page<SuiObjectResponse, ObjectID>> {
+    self
+      .read("suix_getOwnedObjects", |inner| {
+        inner.get_owned_objects(address, query.clone(), cursor, limit)
+      })
+      .await
+  }
+
+  async fn get_dynamic_fields(
+    &self,
+    parent_object_id: ObjectID,
+    cursor: Option<ObjectID>,
+    limit: Option<usize>,
+  ) -> Result<Page<DynamicFieldInfo, ObjectID>> {
+    self
+      .read("suix_getDynamicFields", |inner| {
+        inner.get_dynamic_fields(parent_object_id, cursor, limit)
+      })
+      .await
+  }
+
+  // ============== TRANSACTION OPERATIONS ==============
+
+  async fn get_transaction_block(
+    &self,
+    digest: TransactionDigest,
+    options: Option<SuiTransactionBlockResponseOptions>,
+  ) -> Result<SuiTransactionBlockResponse> {
+    self
+      .read("sui_getTransactionBlock", |inner| inner.get_transaction_block(digest, options.clone()))
+      .await
+  }
+
+  async fn get_transaction_blocks(
+    &self,
+    digests: Vec<TransactionDigest>,
+    options: Option<SuiTransactionBlockResponseOptions>,
+  ) -> Result<Vec<SuiTransactionBlockResponse>> {
+    self
+      .read("sui_multiGetTransactionBlocks", |inner| {
+        inner.get_transaction_blocks(digests.clone(), options.clone())
+      })
+      .await
+  }
+
+  // ============== EVENT OPERATIONS ==============
+
+  async fn get_events(&self, digest: TransactionDigest) -> Result<Vec<SuiEvent>> {
+    self.read("sui_getEvents", |inner| inner.get_events(digest)).await
+  }
+
+  // ============== NETWORK INFO ==============
+
+  async fn get_latest_checkpoint_sequence_number(&self) -> Result<u64> {
+    self
+      .read("sui_getLatestCheckpointSequenceNumber", |inner| {
+        inner.get_latest_checkpoint_sequence_number()
+      })
+      .await
+  }
+
+  async fn get_total_transaction_blocks(&self) -> Result<u64> {
+    self.read("sui_getTotalTransactionBlocks", |inner| inner.get_total_transaction_blocks()).await
+  }
+
+  async fn get_reference_gas_price(&self) -> Result<u64> {
+    self.read("suix_getReferenceGasPrice", |inner| inner.get_reference_gas_price()).await
+  }
+
+  async fn get_chain_identifier(&self) -> Result<String> {
+    self.read("sui_getChainIdentifier", |inner| inner.get_chain_identifier()).await
+  }
+
+  // ============== LEGACY/DEPRECATED - Keep for backward compatibility ==============
+
+  async fn fetch_coin(&self, sender: String) -> Result<Option<Coin>> {
+    self.read("suix_getCoins", |inner| inner.fetch_coin(sender.clone())).await
+  }
+
+  // ============== GAS STATION OPERATIONS ==============
+
+  async fn get_available_gas(&self, required_budget: u64) -> Result<ObjectID> {
+    self.inner.get_available_gas(required_budget).await
+  }
+
+  async fn release_gas(&self, object_id: ObjectID) -> Result<()> {
+    self.inner.release_gas(object_id).await
+  }
+
+  async fn reserve_sponsorship(
+    &self,
+    kind: TransactionKind,
+    sender: SuiAddress,
+    caller: &SponsorCaller,
+    app_id: Option<String>,
+    gas_budget: Option<u64>,
+  ) -> Result<SponsorReservation> {
+    self
+      .inner
+      .reserve_sponsorship(kind, sender, caller, app_id, gas_budget)
+      .await
+  }
+
+  async fn take_reservation(&self, reservation_id: Uuid) -> Result<Option<SponsorReservation>> {
+    self.inner.take_reservation(reservation_id).await
+  }
+
+  async fn execute_sponsorship(
+    &self,
+    reservation: &SponsorReservation,
+    user_signature: &[u8],
+  ) -> Result<ExecutedSponsorship> {
+    self
+      .inner
+      .execute_sponsorship(reservation, user_signature)
+      .await
+  }
+
+  async fn get_pool_stats(&self) -> Result<GasPoolStatus> {
+    self.inner.get_pool_stats().await
+  }
+
+  async fn refresh_gas_pool(&self) -> Result<()> {
+    self.inner.refresh_gas_pool().await
+  }
+
+  async fn log_sponsored_transaction(
+    &self,
+    user_address: &SuiAddress,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    gas_used: u64,
+    tx_digest: &str,
+  ) -> Result<()> {
+    self
+      .inner
+      .log_sponsored_transaction(user_address, app_id, gas_budget, gas_used, tx_digest)
+      .await
+  }
+
+  async fn record_sponsorship_failure(
+    &self,
+    user_address: &SuiAddress,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    reason: &str,
+  ) -> Result<()> {
+    self
+      .inner
+      .record_sponsorship_failure(user_address, app_id, gas_budget, reason)
+      .await
+  }
+
+  async fn get_user_stats(&self, address: &str) -> Result<Option<UserStats>> {
+    self.inner.get_user_stats(address).await
+  }
+
+  async fn check_rate_limit(&self, user_address: &SuiAddress) -> Result<bool> {
+    self.inner.check_rate_limit(user_address).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicU32, Ordering};
+
+  const NODE_A: &str = "https://fullnode-a.example.com";
+  const NODE_B: &str = "https://fullnode-b.example.com";
+
+  fn resilience(failure_threshold: u32) -> Resilience {
+    let config = SuiResilienceConfig {
+      timeout_ms: 20,
+      read_retries: 2,
+      retry_base_delay_ms: 1,
+      retry_max_delay_ms: 2,
+      failure_threshold,
+      open_secs: 60,
+    };
+    Resilience { config, breakers: Arc::default() }
+  }
+
+  /// Picks the first usable of two nodes, the call gets the node's URL
+  fn both_nodes(usable: &dyn Fn(&str) -> bool) -> Option<(String, &'static str)> {
+    [NODE_A, NODE_B].into_iter().find(|url| usable(url)).map(|url| (url.to_string(), url))
+  }
+
+  fn node_a(usable: &dyn Fn(&str) -> bool) -> Option<(String, &'static str)> {
+    usable(NODE_A).then(|| (NODE_A.to_string(), NODE_A))
+  }
+
+  #[tokio::test]
+  async fn retries_node_failures_on_another_node() {
+    let resilience = resilience(5);
+    let calls = AtomicU32::new(0);
+
+    let result = resilience
+      .read("sui_getObject", both_nodes, |node| {
+        calls.fetch_add(1, Ordering::SeqCst);
+        async move {
+          match node {
+            NODE_A => Err(Error::SuiClient("connection reset".to_string())),
+            _ => Ok(7),
+          }
+        }
+      })
+      .await;
+
+    assert_eq!(result.unwrap(), 7);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+  }
+
+  #[tokio::test]
+  async fn rejected_requests_are_not_retried() {
+    let resilience = resilience(1);
+    let calls = AtomicU32::new(0);
+
+    let result: Result<()> = resilience
+      .read("sui_getObject", node_a, |_| async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Err(Error::InvalidRequest("bad digest".to_string()))
+      })
+      .await;
+
+    assert!(matches!(result, Err(Error::InvalidRequest(_))));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    // and don't count against the node
+    assert!(resilience.read("sui_getObject", node_a, |_| async { Ok(()) }).await.is_ok());
+  }
+
+  #[tokio::test]
+  async fn slow_calls_time_out_and_open_the_circuit() {
+    let resilience = resilience(3);
+    let calls = AtomicU32::new(0);
+
+    let result: Result<()> = resilience
+      .read("sui_getEvents", node_a, |_| async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(())
+      })
+      .await;
+
+    assert!(matches!(result, Err(Error::Timeout { endpoint: "sui_getEvents", timeout_ms: 20 })));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let result = resilience.read("sui_getEvents", node_a, |_| async { Ok(()) }).await;
+    assert!(matches!(result, Err(Error::CircuitOpen { endpoint: "sui_getEvents" })));
+    assert!(resilience.read("sui_getObject", node_a, |_| async { Ok(()) }).await.is_ok());
+  }
+
+  #[tokio::test]
+  async fn method_moves_off_the_node_its_circuit_opened_on() {
+    let resilience = resilience(1);
+
+    let failed: Result<()> = resilience
+      .read("sui_getEvents", node_a, |_| async {
+        Err(Error::SuiClient("connection reset".to_string()))
+      })
+      .await;
+    assert!(failed.is_err());
+
+    let node = resilience.read("sui_getEvents", both_nodes, |node| async move { Ok(node) }).await;
+    assert_eq!(node.unwrap(), NODE_B);
+    let node = resilience.read("sui_getObject", both_nodes, |node| async move { Ok(node) }).await;
+    assert_eq!(node.unwrap(), NODE_A);
+  }
+}
@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::domain::sponsor_policy::{DailyLimits, PolicyRule};
+use crate::models::BudgetCharge;
+use crate::{Result, error::Error};
+
+/// Counters outlive their day a little so late reservations still see them
+const COUNTER_TTL_SECS: u64 = 2 * 24 * 60 * 60;
+
+/// Charges still waiting for their execution, scored by when they are refunded
+const HOLDS_KEY: &str = "sui:policy:holds";
+
+/// A hold is refunded this long after its reservation expired, so an execution that took the
+/// reservation just in time still settles it
+const HOLD_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// Checks every daily limit and only charges when all of them hold, returns the index of the
+/// first exceeded limit or 0. KEYS: user gas, app gas, user transactions.
+/// ARGV: gas, user gas limit, app gas limit, transaction limit, ttl. A limit of -1 is unlimited.
+const CHARGE: &str = r#"
+local gas = tonumber(ARGV[1])
+local increments = { gas, gas, 1 }
+for i = 1, 3 do
+  local limit = tonumber(ARGV[i + 1])
+  local used = tonumber(redis.call('GET', KEYS[i]) or '0')
+  if limit >= 0 and used + increments[i] > limit then
+    return i
+  end
+end
+for i = 1, 3 do
+  redis.call('INCRBY', KEYS[i], increments[i])
+  redis.call('EXPIRE', KEYS[i], ARGV[5])
+end
+return 0
+"#;
+
+/// Moves the counters of a charge, never below zero. KEYS: as `CHARGE`.
+/// ARGV: gas delta, transaction delta, ttl.
+const ADJUST: &str = r#"
+local deltas = { ARGV[1], ARGV[1], ARGV[2] }
+for i = 1, 3 do
+  if redis.call('INCRBY', KEYS[i], deltas[i]) < 0 then
+    redis.call('SET', KEYS[i], 0)
+  end
+  redis.call('EXPIRE', KEYS[i], ARGV[3])
+end
+return 0
+"#;
+
+/// Daily gas and transaction counters per user and application, shared by every instance.
+/// A reservation charges its gas budget up front, an upper bound of what the sponsor pays, and
+/// holds the charge until execution settles it at the gas actually used. Charges of failed or
+/// expired reservations are refunded.
+#[derive(Clone)]
+pub struct SponsorBudgets {
+  redis: Arc<redis::Client>,
+}
+
+impl SponsorBudgets {
+  pub fn new(redis: Arc<redis::Client>) -> Self {
+    Self { redis }
+  }
+
+  /// Charges `gas_budget` and one transaction, or fails naming the exceeded rule. The charge is
+  /// refunded by `refund_expired` unless settled before `expires_at`.
+  pub async fn charge(
+    &self,
+    user_id: Uuid,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    limits: &DailyLimits,
+    expires_at: DateTime<Utc>,
+  ) -> Result<BudgetCharge> {
+    let mut conn = self.connection().await?;
+
+    let charge = BudgetCharge {
+      id: Uuid::new_v4(),
+      day: Utc::now().format("%Y-%m-%d").to_string(),
+      user_id,
+      app_id: app_id.map(str::to_string),
+      gas: gas_budget,
+    };
+    let limit =
+      |limit: Option<u64>| limit.map_or(-1, |limit| i64::try_from(limit).unwrap_or(i64::MAX));
+    let script = redis::Script::new(CHARGE);
+    let mut invocation = script.prepare_invoke();
+    for key in counter_keys(&charge) {
+      invocation.key(key);
+    }
+    let exceeded: u8 = invocation
+      .arg(gas_budget)
+      .arg(limit(limits.user_gas))
+      .arg(limit(limits.app_gas))
+      .arg(limit(limits.transactions))
+      .arg(COUNTER_TTL_SECS)
+      .invoke_async(&mut conn)
+      .await
+      .map_err(|e| Error::Internal(e.to_string()))?;
+
+    let rejection = match exceeded {
+      0 => None,
+      1 => Some(PolicyRule::UserGasBudget.reject("Daily gas budget of the user is used up")),
+      2 => Some(PolicyRule::AppGasBudget.reject("Daily gas budget of the application is used up")),
+      _ => Some(PolicyRule::RoleQuota.reject("Daily sponsored transactions of the role used up")),
+    };
+    if let Some(rejection) = rejection {
+      return Err(rejection);
+    }
+
+    let refund_at = (expires_at + HOLD_GRACE).timestamp_millis();
+    let held = conn.zadd::<_, _, _, ()>(HOLDS_KEY, hold_member(&charge)?, refund_at).await;
+    if let Err(e) = held {
+      // Without a hold nothing would refund it
+      self.adjust(&charge, -gas(charge.gas), -1).await?;
+      return Err(Error::Internal(e.to_string()));
+    }
+    Ok(charge)
+  }
+
+  /// Corrects a charge to the gas the transaction actually used, `false` when it was already
+  /// settled or refunded
+  pub async fn settle(&self, charge: &BudgetCharge, gas_used: u64) -> Result<bool> {
+    if !self.release_hold(charge).await? {
+      return Ok(false);
+    }
+    self.adjust(charge, gas(gas_used) - gas(charge.gas), 0).await?;
+    Ok(true)
+  }
+
+  /// Gives back the gas and the transaction of a charge that never executed, `false` when it
+  /// was already settled or refunded
+  pub async fn refund(&self, charge: &BudgetCharge) -> Result<bool> {
+    if !self.release_hold(charge).await? {
+      return Ok(false);
+    }
+    self.adjust(charge, -gas(charge.gas), -1).await?;
+    Ok(true)
+  }
+
+  /// Refunds the charges of reservations that expired without an execution, returns how many
+  pub async fn refund_expired(&self) -> Result<u64> {
+    let mut conn = self.connection().await?;
+    let expired: Vec<String> = conn
+      .zrangebyscore(HOLDS_KEY, "-inf", Utc::now().timestamp_millis())
+      .await
+      .map_err(|e| Error::Internal(e.to_string()))?;
+
+    let mut refunded = 0;
+    for member in expired {
+      let charge: BudgetCharge =
+        serde_json::from_str(&member).map_err(|e| Error::Internal(e.to_string()))?;
+      if self.refund(&charge).await? {
+        refunded += 1;
+      }
+    }
+    Ok(refunded)
+  }
+
+  /// Whoever removes the hold owns the charge, so it is settled or refunded only once
+  async fn release_hold(&self, charge: &BudgetCharge) -> Result<bool> {
+    let mut conn = self.connection().await?;
+    let removed: u64 = conn
+      .zrem(HOLDS_KEY, hold_member(charge)?)
+      .await
+      .map_err(|e| Error::Internal(e.to_string()))?;
+    Ok(removed == 1)
+  }
+
+  async fn adjust(&self, charge: &BudgetCharge, gas_delta: i64, transactions: i64) -> Result<()> {
+    let mut conn = self.connection().await?;
+    let script = redis::Script::new(ADJUST);
+    let mut invocation = script.prepare_invoke();
+    for key in counter_keys(charge) {
+      invocation.key(key);
+    }
+    let _: i64 = invocation
+      .arg(gas_delta)
+      .arg(transactions)
+      .arg(COUNTER_TTL_SECS)
+      .invoke_async(&mut conn)
+      .await
+      .map_err(|e| Error::Internal(e.to_string()))?;
+    Ok(())
+  }
+
+  async fn connection(&self) -> Result<redis::aio::MultiplexedConnection> {
+    self.redis.get_multiplexed_async_connection().await.map_err(|e| Error::Internal(e.to_string()))
+  }
+}
+
+/// Counters of a charge: user gas, app gas, user transactions. The day is their hash tag, so
+/// the scripts touching them stay in one Redis Cluster slot.
+fn counter_keys(charge: &BudgetCharge) -> [String; 3] {
+  let prefix = format!("sui:policy:{{{}}}", charge.day);
+  [
+    format!("{}:user:{}:gas", prefix, charge.user_id),
+    format!("{}:app:{}:gas", prefix, charge.app_id.as_deref().unwrap_or("-")),
+    format!("{}:user:{}:transactions", prefix, charge.user_id),
+  ]
+}
+
+fn hold_member(charge: &BudgetCharge) -> Result<String> {
+  serde_json::to_string(charge).map_err(|e| Error::Internal(e.to_string()))
+}
+
+fn gas(amount: u64) -> i64 {
+  i64::try_from(amount).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn charge(gas: u64) -> BudgetCharge {
+    BudgetCharge {
+      id: Uuid::new_v4(),
+      day: Utc::now().format("%Y-%m-%d").to_string(),
+      user_id: Uuid::new_v4(),
+      app_id: Some(format!("app-{}", Uuid::new_v4())),
+      gas,
+    }
+  }
+
+  /// Live Redis tests run against `REDIS_URL`
+  fn budgets() -> SponsorBudgets {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    SponsorBudgets::new(Arc::new(redis::Client::open(url).unwrap()))
+  }
+
+  async fn counters(budgets: &SponsorBudgets, charge: &BudgetCharge) -> Vec<Option<i64>> {
+    let mut conn = budgets.connection().await.unwrap();
+    let keys = counter_keys(charge);
+    conn.mget(&keys).await.unwrap()
+  }
+
+  fn unlimited() -> DailyLimits {
+    DailyLimits { user_gas: None, app_gas: None, transactions: None }
+  }
+
+  #[test]
+  fn counters_of_a_charge_share_one_hash_tag() {
+    let keys = counter_keys(&charge(10));
+
+    let tag = format!("{{{}}}", Utc::now().format("%Y-%m-%d"));
+    assert!(keys.iter().all(|key| key.contains(&tag)));
+  }
+
+  #[tokio::test]
+  #[ignore = "needs a Redis server at REDIS_URL"]
+  async fn execution_settles_the_charge_at_the_gas_used() {
+    let budgets = budgets();
+    let template = charge(0);
+    let expires_at = Utc::now() + Duration::from_secs(60);
+
+    let charge = budgets
+      .charge(template.user_id, template.app_id.as_deref(), 1_000, &unlimited(), expires_at)
+      .await
+      .unwrap();
+    assert_eq!(counters(&budgets, &charge).await, vec![Some(1_000), Some(1_000), Some(1)]);
+
+    assert!(budgets.settle(&charge, 400).await.unwrap());
+    assert_eq!(counters(&budgets, &charge).await, vec![Some(400), Some(400), Some(1)]);
+
+    // Settled once, a late refund leaves it alone
+    assert!(!budgets.refund(&charge).await.unwrap());
+    assert_eq!(counters(&budgets, &charge).await, vec![Some(400), Some(400), Some(1)]);
+  }
+
+  #[tokio::test]
+  #[ignore = "needs a Redis server at REDIS_URL"]
+  async fn failed_execution_is_refunded() {
+    let budgets = budgets();
+    let template = charge(0);
+    let expires_at = Utc::now() + Duration::from_secs(60);
+
+    let charge = budgets
+      .charge(template.user_id, template.app_id.as_deref(), 1_000, &unlimited(), expires_at)
+      .await
+      .unwrap();
+
+    assert!(budgets.refund(&charge).await.unwrap());
+    assert_eq!(counters(&budgets, &charge).await, vec![Some(0), Some(0), Some(0)]);
+  }
+
+  #[tokio::test]
+  #[ignore = "needs a Redis server at REDIS_URL"]
+  async fn expired_hold_is_refunded() {
+    let budgets = budgets();
+    let template = charge(0);
+    let expires_at = Utc::now() - HOLD_GRACE - Duration::from_secs(1);
+
+    let charge = budgets
+      .charge(template.user_id, template.app_id.as_deref(), 1_000, &unlimited(), expires_at)
+      .await
+      .unwrap();
+
+    assert!(budgets.refund_expired().await.unwrap() >= 1);
+    assert_eq!(counters(&budgets, &charge).await, vec![Some(0), Some(0), Some(0)]);
+    assert!(!budgets.settle(&charge, 400).await.unwrap());
+  }
+}
@@ -0,0 +1,456 @@
+use blake2::{Blake2b, Digest};
+use jd_core::AppState;
+use jd_utils::config::SuiCacheConfig;
+use lru::LruCache;
+use redis::AsyncCommands;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::Result;
+
+/// Cache of this process, shared by every repository built per request
+static CACHE: OnceLock<Arc<SuiCache>> = OnceLock::new();
+
+/// How long a cached answer stays valid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lifetime {
+  /// Immutable on chain, held in process until evicted and in Redis for
+  /// `SuiCacheConfig::immutable_ttl_secs`
+  Forever,
+  /// A zero duration is not cached at all
+  For(Duration),
+}
+
+struct LocalEntry {
+  json: Arc<str>,
+  expires_at: Option<Instant>,
+}
+
+/// Hits and misses of one kind of read
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheCounters {
+  pub local_hits: u64,
+  pub redis_hits: u64,
+  pub misses: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+  pub enabled: bool,
+  pub namespace: String,
+  pub local_entries: usize,
+  pub redis_errors: u64,
+  /// Redis is skipped after repeated failures, see `SuiCacheConfig::redis_failure_threshold`
+  pub redis_paused: bool,
+  pub kinds: BTreeMap<&'static str, CacheCounters>,
+}
+
+/// Read-through cache of fullnode answers stored as JSON, in an LRU of this process in
+/// front of Redis. Keys are namespaced by chain so networks sharing a Redis never mix.
+/// Redis failures are counted and read through to the node, the cache never fails a read.
+/// After repeated failures Redis is left alone for a while so a dead server costs nothing.
+pub struct SuiCache {
+  config: SuiCacheConfig,
+  namespace: String,
+  redis: Arc<redis::Client>,
+  /// Shared by every read, dropped on failure so the next call reconnects
+  conn: tokio::sync::Mutex<Option<redis::aio::MultiplexedConnection>>,
+  local: Option<Mutex<LruCache<String, LocalEntry>>>,
+  counters: Mutex<BTreeMap<&'static str, CacheCounters>>,
+  redis_errors: AtomicU64,
+  consecutive_failures: AtomicU32,
+  paused_until: Mutex<Option<Instant>>,
+}
+
+impl SuiCache {
+  /// The process wide cache, namespaced by the chain the fullnodes agreed on
+  pub fn shared(app_state: &AppState) -> Arc<Self> {
+    CACHE
+      .get_or_init(|| {
+        let namespace = app_state
+          .sui_client
+          .chain_id()
+          .unwrap_or(&app_state.config.sui.env)
+          .to_string();
+        Arc::new(Self::new(app_state.config.sui.cache.clone(), namespace, app_state.redis.clone()))
+      })
+      .clone()
+  }
+
+  pub fn new(config: SuiCacheConfig, namespace: String, redis: Arc<redis::Client>) -> Self {
+    let local = NonZeroUsize::new(config.local_capacity).map(|cap| Mutex::new(LruCache::new(cap)));
+    Self {
+      config,
+      namespace,
+      redis,
+      conn: tokio::sync::Mutex::new(None),
+      local,
+      counters: Mutex::default(),
+      redis_errors: AtomicU64::new(0),
+      consecutive_failures: AtomicU32::new(0),
+      paused_until: Mutex::new(None),
+    }
+  }
+
+  pub fn config(&self) -> &SuiCacheConfig {
+    &self.config
+  }
+
+  /// `sui:cache:<namespace>:<parts>`
+  pub fn key(&self, parts: &[&str]) -> String {
+    format!("sui:cache:{}:{}", self.namespace, parts.join(":"))
+  }
+
+  /// Returns the cached answer of `key`, or fetches it and caches it for as long as
+  /// `lifetime` says, `None` leaves the answer uncached
+  pub async fn read_through<T, Fut>(
+    &self,
+    kind: &'static str,
+    key: &str,
+    lifetime: impl FnOnce(&T) -> Option<Lifetime>,
+    fetch: Fut,
+  ) -> Result<T>
+  where
+    T: Serialize + DeserializeOwned,
+    Fut: Future<Output = Result<T>>,
+  {
+    if let Some(value) = self.lookup(kind, key).await {
+      return Ok(value);
+    }
+    let value = fetch.await?;
+    if let Some(lifetime) = lifetime(&value) {
+      self.store(key, &value, lifetime).await;
+    }
+    Ok(value)
+  }
+
+  pub async fn lookup<T: DeserializeOwned>(&self, kind: &'static str, key: &str) -> Option<T> {
+    if !self.config.enabled {
+      return None;
+    }
+
+    if let Some(value) = self.local_get(key).and_then(|json| decode(key, &json)) {
+      self.count(kind, |counters| counters.local_hits += 1);
+      return Some(value);
+    }
+
+    match self.redis_get(key).await {
+      Ok(Some((json, lifetime))) => {
+        if let Some(value) = decode(key, &json) {
+          if let Some(lifetime) = lifetime {
+            self.local_put(key, json.into(), lifetime);
+          }
+          self.count(kind, |counters| counters.redis_hits += 1);
+          return Some(value);
+        }
+      }
+      Ok(None) => {}
+      Err(e) => self.redis_failed("read", key, e),
+    }
+
+    self.count(kind, |counters| counters.misses += 1);
+    None
+  }
+
+  pub async fn store<T: Serialize>(&self, key: &str, value: &T, lifetime: Lifetime) {
+    if !self.config.enabled || lifetime == Lifetime::For(Duration::ZERO) {
+      return;
+    }
+    let json = match serde_json::to_string(value) {
+      Ok(json) => json,
+      Err(e) => {
+        warn!("⚠️ Failed to encode Sui cache entry {}: {}", key, e);
+        return;
+      }
+    };
+
+    if let Err(e) = self.redis_set(key, &json, lifetime).await {
+      self.redis_failed("write", key, e);
+    }
+    self.local_put(key, json.into(), lifetime);
+  }
+
+  pub fn stats(&self) -> CacheStats {
+    let local_entries = self
+      .local
+      .as_ref()
+      .map_or(0, |local| local.lock().unwrap_or_else(|e| e.into_inner()).len());
+    CacheStats {
+      enabled: self.config.enabled,
+      namespace: self.namespace.clone(),
+      local_entries,
+      redis_errors: self.redis_errors.load(Ordering::Relaxed),
+      redis_paused: self.redis_paused(),
+      kinds: self
+        .counters
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone(),
+    }
+  }
+
+  fn local_get(&self, key: &str) -> Option<Arc<str>> {
+    let mut local = self
+      .local
+      .as_ref()?
+      .lock()
+      .unwrap_or_else(|e| e.into_inner());
+    let entry = local.get(key)?;
+    if entry
+      .expires_at
+      .is_some_and(|expires_at| expires_at <= Instant::now())
+    {
+      local.pop(key);
+      return None;
+    }
+    Some(entry.json.clone())
+  }
+
+  fn local_put(&self, key: &str, json: Arc<str>, lifetime: Lifetime) {
+    let Some(local) = self.local.as_ref() else {
+      return;
+    };
+    let expires_at = match lifetime {
+      Lifetime::Forever => None,
+      Lifetime::For(ttl) => Some(Instant::now() + ttl),
+    };
+    local
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .put(key.to_string(), LocalEntry { json, expires_at });
+  }
+
+  /// The entry and what is left of its lifetime, `None` when it expires right now or Redis
+  /// is paused
+  async fn redis_get(&self, key: &str) -> redis::RedisResult<Option<(String, Option<Lifetime>)>> {
+    let Some(mut conn) = self.connection().await? else {
+      return Ok(None);
+    };
+    let (json, pttl): (Option<String>, i64) = redis::pipe()
+      .get(key)
+      .pttl(key)
+      .query_async(&mut conn)
+      .await?;
+    self.consecutive_failures.store(0, Ordering::Relaxed);
+    Ok(json.map(|json| (json, remaining(pttl))))
+  }
+
+  async fn redis_set(&self, key: &str, json: &str, lifetime: Lifetime) -> redis::RedisResult<()> {
+    let Some(mut conn) = self.connection().await? else {
+      return Ok(());
+    };
+    let ttl = match lifetime {
+      Lifetime::Forever => Duration::from_secs(self.config.immutable_ttl_secs),
+      Lifetime::For(ttl) => ttl,
+    };
+    conn.pset_ex::<_, _, ()>(key, json, ttl.as_millis() as u64).await?;
+    self.consecutive_failures.store(0, Ordering::Relaxed);
+    Ok(())
+  }
+
+  /// The shared connection, opened on first use. `None` while Redis is paused.
+  async fn connection(&self) -> redis::RedisResult<Option<redis::aio::MultiplexedConnection>> {
+    if self.redis_paused() {
+      return Ok(None);
+    }
+    let mut conn = self.conn.lock().await;
+    if let Some(conn) = conn.as_ref() {
+      return Ok(Some(conn.clone()));
+    }
+    let connected = self.redis.get_multiplexed_async_connection().await?;
+    *conn = Some(connected.clone());
+    Ok(Some(connected))
+  }
+
+  fn redis_paused(&self) -> bool {
+    self
+      .paused_until
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .is_some_and(|until| Instant::now() < until)
+  }
+
+  /// Counts the failure, drops the connection and pauses Redis once failures pile up
+  fn redis_failed(&self, action: &str, key: &str, e: redis::RedisError) {
+    self.redis_errors.fetch_add(1, Ordering::Relaxed);
+    debug!("Sui cache {} of {} skipped Redis: {}", action, key, e);
+
+    // Whoever holds the lock is reconnecting already
+    if let Ok(mut conn) = self.conn.try_lock() {
+      *conn = None;
+    }
+    let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+    if failures >= self.config.redis_failure_threshold.max(1) {
+      let pause = Duration::from_secs(self.config.redis_pause_secs);
+      *self.paused_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + pause);
+      warn!("⚠️ Sui cache skips Redis for {:?} after {} failures: {}", pause, failures, e);
+    }
+  }
+
+  fn count(&self, kind: &'static str, update: impl FnOnce(&mut CacheCounters)) {
+    let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+    update(counters.entry(kind).or_default());
+  }
+}
+
+/// Short stable digest of request options, so answers to different options get their own
+/// entries
+pub fn options_digest(options: &impl Serialize) -> String {
+  let json = serde_json::to_vec(options).unwrap_or_default();
+  let mut hasher = Blake2b::<blake2::digest::consts::U32>::new();
+  hasher.update(&json);
+  hex::encode(&hasher.finalize()[..8])
+}
+
+/// Maps a Redis `PTTL` reply, -1 means no expiry
+fn remaining(pttl: i64) -> Option<Lifetime> {
+  match pttl {
+    -1 => Some(Lifetime::Forever),
+    ms if ms > 0 => Some(Lifetime::For(Duration::from_millis(ms as u64))),
+    _ => None,
+  }
+}
+
+/// Entries written by an older build may no longer decode, they count as misses
+fn decode<T: DeserializeOwned>(key: &str, json: &str) -> Option<T> {
+  serde_json::from_str(json)
+    .map_err(|e| debug!("Ignoring undecodable Sui cache entry {}: {}", key, e))
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::AtomicU32;
+
+  /// Nothing listens there, so every Redis call fails and only the local tier answers
+  fn cache(local_capacity: usize) -> SuiCache {
+    let config = SuiCacheConfig { local_capacity, ..SuiCacheConfig::default() };
+    let redis = redis::Client::open("redis://127.0.0.1:1").unwrap();
+    SuiCache::new(config, "35834a8a".to_string(), Arc::new(redis))
+  }
+
+  /// Live Redis tests run against `REDIS_URL`
+  fn live_cache() -> SuiCache {
+    let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+    let redis = redis::Client::open(url).unwrap();
+    SuiCache::new(SuiCacheConfig::default(), uuid::Uuid::new_v4().to_string(), Arc::new(redis))
+  }
+
+  async fn fetch_counted(calls: &AtomicU32, value: u64) -> Result<u64> {
+    calls.fetch_add(1, Ordering::SeqCst);
+    Ok(value)
+  }
+
+  #[tokio::test]
+  async fn reads_through_once_and_counts_hits() {
+    let cache = cache(16);
+    let calls = AtomicU32::new(0);
+    let key = cache.key(&["balance", "0x1"]);
+    assert_eq!(key, "sui:cache:35834a8a:balance:0x1");
+
+    for _ in 0..3 {
+      let value = cache
+        .read_through("balance", &key, |_| Some(Lifetime::Forever), fetch_counted(&calls, 7))
+        .await
+        .unwrap();
+      assert_eq!(value, 7);
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    let stats = cache.stats();
+    assert_eq!(stats.kinds["balance"], CacheCounters { local_hits: 2, redis_hits: 0, misses: 1 });
+    assert_eq!(stats.local_entries, 1);
+    assert!(stats.redis_errors >= 2);
+  }
+
+  #[tokio::test]
+  async fn expired_and_uncached_answers_are_fetched_again() {
+    let cache = cache(16);
+    let calls = AtomicU32::new(0);
+    let short = Lifetime::For(Duration::from_millis(10));
+
+    cache
+      .read_through("balance", "expiring", |_| Some(short), fetch_counted(&calls, 1))
+      .await
+      .unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    cache
+      .read_through("balance", "expiring", |_| Some(short), fetch_counted(&calls, 1))
+      .await
+      .unwrap();
+
+    cache
+      .read_through("coin_metadata", "missing", |_| None, fetch_counted(&calls, 1))
+      .await
+      .unwrap();
+    cache
+      .read_through("coin_metadata", "missing", |_| None, fetch_counted(&calls, 1))
+      .await
+      .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+  }
+
+  #[tokio::test]
+  async fn least_recently_used_entries_are_evicted() {
+    let cache = cache(2);
+    for key in ["a", "b"] {
+      cache.store(key, &1u64, Lifetime::Forever).await;
+    }
+    assert_eq!(cache.lookup::<u64>("object", "a").await, Some(1));
+    cache.store("c", &1u64, Lifetime::Forever).await;
+
+    assert_eq!(cache.lookup::<u64>("object", "b").await, None);
+    assert_eq!(cache.lookup::<u64>("object", "a").await, Some(1));
+    assert_eq!(cache.lookup::<u64>("object", "c").await, Some(1));
+  }
+
+  #[tokio::test]
+  async fn repeated_redis_failures_pause_redis() {
+    let cache = cache(16);
+
+    for key in ["a", "b", "c", "d", "e"] {
+      assert_eq!(cache.lookup::<u64>("object", key).await, None);
+    }
+
+    let stats = cache.stats();
+    assert_eq!(stats.redis_errors, u64::from(SuiCacheConfig::default().redis_failure_threshold));
+    assert!(stats.redis_paused);
+    assert_eq!(stats.kinds["object"].misses, 5);
+  }
+
+  #[tokio::test]
+  #[ignore = "needs a Redis server at REDIS_URL"]
+  async fn immutable_entries_expire_from_redis_but_not_in_process() {
+    let cache = live_cache();
+    let key = cache.key(&["coin_metadata", "0x2::sui::SUI"]);
+
+    cache.store(&key, &9u64, Lifetime::Forever).await;
+
+    let (_, lifetime) = cache.redis_get(&key).await.unwrap().unwrap();
+    let immutable_ttl = Duration::from_secs(cache.config().immutable_ttl_secs);
+    assert!(matches!(lifetime, Some(Lifetime::For(ttl)) if ttl <= immutable_ttl));
+    assert_eq!(cache.local.as_ref().unwrap().lock().unwrap().get(&key).unwrap().expires_at, None);
+    assert_eq!(cache.stats().redis_errors, 0);
+  }
+
+  #[test]
+  fn options_get_distinct_digests_and_ttls_map_to_lifetimes() {
+    assert_eq!(options_digest(&Some(1)), options_digest(&Some(1)));
+    assert_ne!(options_digest(&Some(1)), options_digest(&None::<u8>));
+    assert_eq!(options_digest(&()).len(), 16);
+
+    assert_eq!(remaining(-1), Some(Lifetime::Forever));
+    assert_eq!(remaining(1500), Some(Lifetime::For(Duration::from_millis(1500))));
+    assert_eq!(remaining(-2), None);
+    assert_eq!(remaining(0), None);
+  }
+}
@@ -1,139 +1,694 @@
-use crate::infrastructure::gas_station::GasStation;
-use crate::models::{GasPoolStatus, UserStats};
+use crate::domain::sponsor_policy::SponsorPolicy;
+use crate::infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use crate::infrastructure::gas_leases::HEARTBEAT_INTERVAL;
+use crate::infrastructure::gas_station::{GasStation, MAINTENANCE_GAS_BUDGET, Rebalance};
+use crate::infrastructure::reservations::Reservations;
+use crate::infrastructure::resilient_sui_repository::ResilientSuiRepository;
+use crate::infrastructure::sponsor_budgets::SponsorBudgets;
+use crate::models::{
+  BudgetCharge, ExecutedSponsorship, GasPoolStatus, SponsorCaller, SponsorReservation, UserStats,
+};
 use crate::{Result, domain::sui_repository_trait::SuiRepository, error::Error};
 use async_trait::async_trait;
 use blake2::{Blake2b, Digest};
+use chrono::{DateTime, Utc};
 use fastcrypto::{
   ed25519::Ed25519KeyPair,
   traits::{KeyPair, ToFromBytes},
 };
 use futures::{StreamExt, future};
+use jd_contracts::sui::dtos::events::{
+  GasPoolLowBalanceEvent, SponsoredTransactionExecutedEvent, SponsoredTransactionFailedEvent,
+};
 use jd_core::AppState;
+use jd_messaging::Outbox;
+use jd_utils::config::GasPoolConfig;
 use jd_utils::time;
-use redis::AsyncCommands;
+use shared_crypto::intent::{Intent, IntentMessage};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use sui_keys::keystore::{AccountKeystore, InMemKeystore};
-use sui_sdk::{rpc_types::Coin, types::base_types::SuiAddress};
-use sui_types::crypto::SuiKeyPair;
+use sui_sdk::rpc_types::{
+  Balance, Coin, DynamicFieldInfo, Page, SuiCoinMetadata, SuiEvent, SuiExecutionStatus,
+  SuiObjectDataOptions, SuiObjectResponse, SuiTransactionBlockEffects,
+  SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+};
+use sui_sdk::types::base_types::{SuiAddress, TransactionDigest};
+use sui_types::crypto::{Signature, Signer, SuiKeyPair, SuiSignature};
+use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
+use sui_types::signature::GenericSignature;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::{
   base_types::ObjectID,
-  crypto::Signer,
-  transaction::{Transaction, TransactionData},
+  transaction::{
+    Argument, Command, ObjectArg, Transaction, TransactionData, TransactionDataAPI,
+    TransactionKind,
+  },
 };
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// Used when `SuiConfig::max_gas_budget` is not set, 1 SUI
+const DEFAULT_MAX_GAS_BUDGET: u64 = 1_000_000_000;
+
+/// Counts one sponsorship in the sender's window and returns the count, the first one opens the
+/// window. KEYS: counter. ARGV: window secs.
+const FIXED_WINDOW: &str = r#"
+local count = redis.call('INCR', KEYS[1])
+if count == 1 then
+  redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+return count
+"#;
 
 #[derive(Clone)]
 pub struct SuiRepositoryImpl {
   app_state: AppState,
+  reads: ResilientSuiRepository<EnhancedSuiRepository>,
   gas_station: Option<Arc<GasStation>>,
   sponsor_keystore: Option<Arc<InMemKeystore>>,
+  reservations: Reservations,
+  policy: Arc<SponsorPolicy>,
+  budgets: SponsorBudgets,
 }
 
 impl SuiRepositoryImpl {
   pub fn new(app_state: AppState) -> Self {
-    Self { app_state, gas_station: None, sponsor_keystore: None }
+    let reads = ResilientSuiRepository::new(app_state.clone());
+    let reservations = Reservations::new(app_state.redis.clone());
+    let budgets = SponsorBudgets::new(app_state.redis.clone());
+    Self {
+      app_state,
+      reads,
+      gas_station: None,
+      sponsor_keystore: None,
+      reservations,
+      policy: Arc::default(),
+      budgets,
+    }
   }
 
   pub async fn with_gas_station(
     app_state: AppState,
-    sui_rpc_url: &str,
     sponsor_address: SuiAddress,
     max_gas_budget: u64,
   ) -> Result<Self> {
-    let gas_station = GasStation::new(sui_rpc_url, sponsor_address, max_gas_budget)
-      .await
-      .map_err(|e| Error::Internal(e.to_string()))?;
+    let pool_config = app_state.config.sui.gas_pool.clone();
+    let redis = app_state.redis.clone();
+    let sui_client = app_state.sui_client.clone();
+    let gas_station =
+      GasStation::new(sui_client, sponsor_address, max_gas_budget, pool_config, redis)
+        .await
+        .map_err(|e| Error::Internal(e.to_string()))?;
 
-    Ok(Self { app_state, gas_station: Some(Arc::new(gas_station)), sponsor_keystore: None })
+    Ok(Self { gas_station: Some(Arc::new(gas_station)), ..Self::new(app_state) })
   }
 
   pub async fn with_gas_station_and_key(
     app_state: AppState,
-    sui_rpc_url: &str,
     sponsor_address: SuiAddress,
     sponsor_private_key: &str,
     max_gas_budget: u64,
   ) -> Result<Self> {
-    let gas_station = GasStation::new(sui_rpc_url, sponsor_address, max_gas_budget)
-      .await
-      .map_err(|e| Error::Internal(e.to_string()))?;
+    let pool_config = app_state.config.sui.gas_pool.clone();
+    let redis = app_state.redis.clone();
+    let sui_client = app_state.sui_client.clone();
+    let gas_station =
+      GasStation::new(sui_client, sponsor_address, max_gas_budget, pool_config, redis)
+        .await
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    let keystore = import_sponsor_key(sponsor_address, sponsor_private_key)?;
 
-    // Create keystore with sponsor private key
-    let mut keystore = InMemKeystore::default();
+    Ok(Self {
+      gas_station: Some(Arc::new(gas_station)),
+      sponsor_keystore: Some(Arc::new(keystore)),
+      ..Self::new(app_state)
+    })
+  }
+
+  /// Sponsor set up from `SuiConfig`, the gas station shares the application's Sui client
+  pub async fn from_config(app_state: AppState) -> Result<Self> {
+    let config = &app_state.config.sui;
+    let sponsor_address = config
+      .sponsor_address
+      .as_deref()
+      .ok_or_else(|| Error::Internal("Sponsor address is not configured".to_string()))?;
+    let sponsor_address = SuiAddress::from_str(sponsor_address)
+      .map_err(|e| Error::Internal(format!("Invalid sponsor address: {}", e)))?;
+    let sponsor_private_key = config
+      .sponsor_private_key
+      .as_deref()
+      .ok_or_else(|| Error::Internal("Sponsor private key is not configured".to_string()))?;
+    let max_gas_budget = config.max_gas_budget.unwrap_or(DEFAULT_MAX_GAS_BUDGET);
+
+    let keystore = import_sponsor_key(sponsor_address, sponsor_private_key)?;
+    let policy = SponsorPolicy::load(config.policy_file.as_deref())?;
+    let gas_station = GasStation::new(
+      app_state.sui_client.clone(),
+      sponsor_address,
+      max_gas_budget,
+      config.gas_pool.clone(),
+      app_state.redis.clone(),
+    )
+    .await
+    .map_err(|e| Error::Internal(e.to_string()))?;
+
+    let repository = Self {
+      gas_station: Some(Arc::new(gas_station)),
+      sponsor_keystore: Some(Arc::new(keystore)),
+      policy: Arc::new(policy),
+      ..Self::new(app_state)
+    };
+    repository.spawn_gas_pool_maintenance();
+    Ok(repository)
+  }
+
+  /// Runs `maintain_gas_pool` every `maintenance_interval_secs` in the background and keeps
+  /// this instance's gas coin leases alive in between
+  pub fn spawn_gas_pool_maintenance(&self) -> JoinHandle<()> {
+    let repository = self.clone();
+    let interval_secs = repository
+      .gas_station
+      .as_ref()
+      .map_or(60, |gas_station| gas_station.pool_config.maintenance_interval_secs);
+
+    tokio::spawn(async move {
+      let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+      let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+      loop {
+        tokio::select! {
+          _ = heartbeat.tick() => {
+            if let Some(gas_station) = &repository.gas_station {
+              if let Err(e) = gas_station.heartbeat().await {
+                tracing::warn!("Gas lease heartbeat failed: {}", e);
+              }
+            }
+          }
+          _ = ticker.tick() => {
+            if let Err(e) = repository.maintain_gas_pool().await {
+              tracing::warn!("Gas pool maintenance failed: {}", e);
+            }
+          }
+        }
+      }
+    })
+  }
+
+  /// Reclaims leases of dead instances, refunds budgets held by expired reservations, reloads
+  /// the pool, raises a `GasPoolLowBalanceEvent` when the balance crosses a watermark and runs
+  /// the next split or merge, one per round so each sees the previous one's coins
+  pub async fn maintain_gas_pool(&self) -> Result<()> {
+    let gas_station = self.gas_station()?;
+    gas_station.reclaim_leases().await.map_err(|e| Error::Internal(e.to_string()))?;
+    let refunded = self.budgets.refund_expired().await?;
+    if refunded > 0 {
+      tracing::info!("Refunded budgets of {} expired sponsorship reservations", refunded);
+    }
+    gas_station.refresh_gas_pool().await.map_err(|e| Error::Internal(e.to_string()))?;
+
+    if let Some(alert) = gas_station.check_balance().await {
+      let event = GasPoolLowBalanceEvent {
+        sponsor_address: gas_station.sponsor_address.to_string(),
+        level: alert.level.as_str().to_string(),
+        total_balance: alert.total_balance,
+        watermark: alert.watermark,
+        detected_at: time::now_utc(),
+      };
+      Outbox::enqueue(self.app_state.mm().dbx(), &event).await?;
+    }
 
-    // Parse private key (handle both with and without 0x prefix)
-    let key_str = sponsor_private_key
-      .strip_prefix("0x")
-      .unwrap_or(sponsor_private_key);
+    let rebalance =
+      gas_station.take_rebalance().await.map_err(|e| Error::Internal(e.to_string()))?;
+    let Some(rebalance) = rebalance else {
+      return Ok(());
+    };
 
-    let key_bytes = hex::decode(key_str)
-      .map_err(|e| Error::Internal(format!("Invalid private key format: {}", e)))?;
+    let executed = match self.rebalance_transaction(&rebalance).await {
+      Ok(transaction) => self.execute_transaction(transaction).await,
+      Err(e) => Err(e),
+    };
+    for object_id in rebalance.coins() {
+      gas_station.refresh_gas_object(object_id).await;
+    }
 
-    if key_bytes.len() != 32 {
-      return Err(Error::Internal("Private key must be 32 bytes".to_string()));
+    let digest = executed?;
+    tracing::info!("Gas pool rebalanced with {:?} in {}", rebalance, digest);
+    // Coins created by a split are picked up by a full reload
+    if matches!(rebalance, Rebalance::Split { .. }) {
+      gas_station.refresh_gas_pool().await.map_err(|e| Error::Internal(e.to_string()))?;
     }
+    Ok(())
+  }
 
-    let keypair = Ed25519KeyPair::from_bytes(&key_bytes)
-      .map_err(|e| Error::Internal(format!("Failed to create keypair: {}", e)))?;
-
-    // Add key to keystore manually since import_from_keypair doesn't exist
-    // Sui addresses are derived by hashing [scheme_flag || public_key_bytes] with BLAKE2b
-    let scheme_flag = 0u8; // Ed25519 flag is 0
-    let public_key_bytes = keypair.public().as_bytes();
-    let mut hasher_input = Vec::new();
-    hasher_input.push(scheme_flag);
-    hasher_input.extend_from_slice(public_key_bytes);
-
-    let mut hasher = Blake2b::<blake2::digest::consts::U32>::new();
-    hasher.update(&hasher_input);
-    let hash_result = hasher.finalize();
-    let sui_address = SuiAddress::from_bytes(hash_result.as_slice())
-      .map_err(|e| Error::Internal(format!("Failed to create SuiAddress: {}", e)))?;
-
-    // Create SuiKeyPair with proper format: [scheme_flag || private_key_bytes]
-    let private_key = keypair.private();
-    let private_key_bytes = private_key.as_bytes();
-    let mut keypair_bytes = Vec::new();
-    keypair_bytes.push(scheme_flag);
-    keypair_bytes.extend_from_slice(private_key_bytes);
-
-    let sui_keypair = SuiKeyPair::from_bytes(&keypair_bytes)
-      .map_err(|e| Error::Internal(format!("Failed to create SuiKeyPair: {}", e)))?;
+  /// Builds and signs the sponsor's own split or merge transaction
+  async fn rebalance_transaction(&self, rebalance: &Rebalance) -> Result<Transaction> {
+    let sponsor_address = self.gas_station()?.sponsor_address;
+    let gas_price = self.get_reference_gas_price().await?;
+    let mut builder = ProgrammableTransactionBuilder::new();
+
+    let gas_coin = match rebalance {
+      Rebalance::Split { coin, amounts } => {
+        builder
+          .pay_sui(vec![sponsor_address; amounts.len()], amounts.clone())
+          .map_err(|e| Error::Internal(e.to_string()))?;
+        *coin
+      }
+      Rebalance::Merge { target, dust } => {
+        let sources = dust
+          .iter()
+          .map(|coin| builder.obj(ObjectArg::ImmOrOwnedObject(*coin)))
+          .collect::<anyhow::Result<Vec<_>>>()
+          .map_err(|e| Error::Internal(e.to_string()))?;
+        builder.command(Command::MergeCoins(Argument::GasCoin, sources));
+        *target
+      }
+    };
+
+    let tx_data = TransactionData::new_programmable(
+      sponsor_address,
+      vec![gas_coin],
+      builder.finish(),
+      MAINTENANCE_GAS_BUDGET,
+      gas_price,
+    );
+    let keypair: &dyn Signer<Signature> = self.sponsor_keypair(&sponsor_address)?;
+    Ok(Transaction::from_data_and_signer(tx_data, vec![keypair]))
+  }
+
+  fn gas_station(&self) -> Result<&GasStation> {
+    self
+      .gas_station
+      .as_deref()
+      .ok_or_else(|| Error::Internal("Gas station not initialized".to_string()))
+  }
+
+  fn sponsor_keypair(&self, sponsor_address: &SuiAddress) -> Result<&SuiKeyPair> {
+    let keystore = self.sponsor_keystore.as_ref().ok_or_else(|| {
+      Error::Internal(
+        "Sponsor keystore not available. Private key required for transaction sponsoring."
+          .to_string(),
+      )
+    })?;
 
     keystore
-      .add_key(None, sui_keypair)
-      .map_err(|e| Error::Internal(format!("Failed to add key to keystore: {}", e)))?;
-
-    // Verify the address matches
-    if sui_address != sponsor_address {
-      return Err(Error::Internal(format!(
-        "Private key address {} doesn't match sponsor address {}",
-        sui_address, sponsor_address
-      )));
+      .get_key(sponsor_address)
+      .map_err(|e| Error::Internal(format!("Failed to get keypair: {}", e)))
+  }
+
+  /// Checks the sender's signature over the sponsored transaction and countersigns as sponsor
+  fn sign_sponsored(
+    &self,
+    tx_data: TransactionData,
+    user_signature: GenericSignature,
+  ) -> Result<Transaction> {
+    verify_user_signature(&tx_data, &user_signature)?;
+
+    let keypair = self.sponsor_keypair(&self.gas_station()?.sponsor_address)?;
+    let sponsor_signature = Signature::new_secure(
+      &IntentMessage::new(Intent::sui_transaction(), tx_data.clone()),
+      keypair,
+    );
+
+    Ok(Transaction::from_generic_sig_data(
+      tx_data,
+      vec![user_signature, GenericSignature::Signature(sponsor_signature)],
+    ))
+  }
+
+  /// Dry runs a reserved transaction and charges the caller's daily budgets until `expires_at`,
+  /// the last policy rules since both need the complete transaction
+  async fn vet_sponsored(
+    &self,
+    tx_data: &TransactionData,
+    caller: &SponsorCaller,
+    app_id: Option<&str>,
+    expires_at: DateTime<Utc>,
+  ) -> Result<BudgetCharge> {
+    let dry_run = self
+      .gas_station()?
+      .client()
+      .read_api()
+      .dry_run_transaction_block(tx_data.clone())
+      .await
+      .map_err(|e| Error::SuiClient(format!("Failed to dry run transaction: {}", e)))?;
+
+    if let SuiExecutionStatus::Failure { error } = dry_run.effects.status() {
+      return Err(Error::InvalidRequest(format!("Transaction fails in simulation: {}", error)));
     }
+    self.policy.check_simulated_gas(dry_run.effects.gas_cost_summary().gas_used())?;
 
-    tracing::info!("Successfully imported sponsor private key for address: {}", sponsor_address);
+    let limits = self.policy.daily_limits(caller.role, app_id);
+    self.budgets.charge(caller.user_id, app_id, tx_data.gas_budget(), &limits, expires_at).await
+  }
 
-    Ok(Self {
-      app_state,
-      gas_station: Some(Arc::new(gas_station)),
-      sponsor_keystore: Some(Arc::new(keystore)),
-    })
+  /// Gives the daily budgets back for a reservation that will not execute
+  async fn refund_budgets(&self, reservation: &SponsorReservation) {
+    if let Err(e) = self.budgets.refund(&reservation.charge).await {
+      tracing::warn!("Failed to refund budgets of reservation {}: {}", reservation.id, e);
+    }
+  }
+
+  /// Submits a signed transaction and returns its digest once it executed successfully
+  async fn execute_transaction(&self, transaction: Transaction) -> Result<String> {
+    executed_digest(&self.submit_transaction(transaction).await?)
+  }
+
+  /// Submits a signed transaction, its effects tell whether and how it executed
+  async fn submit_transaction(
+    &self,
+    transaction: Transaction,
+  ) -> std::result::Result<SuiTransactionBlockResponse, SubmitFailure> {
+    self
+      .gas_station()
+      .map_err(SubmitFailure::Rejected)?
+      .client()
+      .quorum_driver_api()
+      .execute_transaction_block(
+        transaction,
+        SuiTransactionBlockResponseOptions::new().with_effects(),
+        Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+      )
+      .await
+      .map_err(SubmitFailure::new)
+  }
+
+  /// Looks up a transaction whose submission failed without an answer, `None` while the
+  /// network does not know it executed
+  async fn find_executed(&self, digest: TransactionDigest) -> Option<SuiTransactionBlockResponse> {
+    let response = self
+      .gas_station()
+      .ok()?
+      .client()
+      .read_api()
+      .get_transaction_with_options(
+        digest,
+        SuiTransactionBlockResponseOptions::new().with_effects(),
+      )
+      .await;
+
+    match response {
+      Ok(response) if response.effects.is_some() => Some(response),
+      Ok(_) => None,
+      Err(e) => {
+        tracing::warn!("Failed to look up transaction {}: {}", digest, e);
+        None
+      }
+    }
+  }
+}
+
+/// Why a submission returned no response
+enum SubmitFailure {
+  /// The node refused the transaction, it never executed and never will
+  Rejected(Error),
+  /// No answer, e.g. a timeout, the transaction may have executed or still execute
+  Unknown(Error),
+}
+
+impl SubmitFailure {
+  fn new(error: sui_sdk::error::Error) -> Self {
+    let rejected = match &error {
+      sui_sdk::error::Error::JsonRpcError(e) => e.is_client_error(),
+      sui_sdk::error::Error::UserInputError(_) => true,
+      _ => false,
+    };
+
+    let error = Error::SuiClient(format!("Failed to submit transaction to network: {}", error));
+    if rejected { Self::Rejected(error) } else { Self::Unknown(error) }
+  }
+}
+
+impl From<SubmitFailure> for Error {
+  fn from(failure: SubmitFailure) -> Self {
+    match failure {
+      SubmitFailure::Rejected(e) | SubmitFailure::Unknown(e) => e,
+    }
+  }
+}
+
+/// MIST the sponsor paid for a transaction that ran, computation and storage minus the storage
+/// rebate. Budgets are settled and analytics count with it, so both agree.
+fn gas_spent(effects: &SuiTransactionBlockEffects) -> u64 {
+  u64::try_from(effects.gas_cost_summary().net_gas_usage()).unwrap_or(0)
+}
+
+/// Digest of a submitted transaction, an error when it failed on chain
+fn executed_digest(response: &SuiTransactionBlockResponse) -> Result<String> {
+  let digest = response.digest.to_string();
+  if let Some(SuiExecutionStatus::Failure { error }) =
+    response.effects.as_ref().map(|effects| effects.status())
+  {
+    return Err(Error::SuiClient(format!("Transaction {} failed: {}", digest, error)));
+  }
+
+  tracing::info!("Transaction executed with digest: {}", digest);
+  Ok(digest)
+}
+
+/// Builds the sponsor keystore from a hex Ed25519 private key
+fn import_sponsor_key(
+  sponsor_address: SuiAddress,
+  sponsor_private_key: &str,
+) -> Result<InMemKeystore> {
+  // Create keystore with sponsor private key
+  let mut keystore = InMemKeystore::default();
+
+  // Parse private key (handle both with and without 0x prefix)
+  let key_str = sponsor_private_key
+    .strip_prefix("0x")
+    .unwrap_or(sponsor_private_key);
+
+  let key_bytes = hex::decode(key_str)
+    .map_err(|e| Error::Internal(format!("Invalid private key format: {}", e)))?;
+
+  if key_bytes.len() != 32 {
+    return Err(Error::Internal("Private key must be 32 bytes".to_string()));
   }
+
+  let keypair = Ed25519KeyPair::from_bytes(&key_bytes)
+    .map_err(|e| Error::Internal(format!("Failed to create keypair: {}", e)))?;
+
+  // Add key to keystore manually since import_from_keypair doesn't exist
+  // Sui addresses are derived by hashing [scheme_flag || public_key_bytes] with BLAKE2b
+  let scheme_flag = 0u8; // Ed25519 flag is 0
+  let public_key_bytes = keypair.public().as_bytes();
+  let mut hasher_input = Vec::new();
+  hasher_input.push(scheme_flag);
+  hasher_input.extend_from_slice(public_key_bytes);
+
+  let mut hasher = Blake2b::<blake2::digest::consts::U32>::new();
+  hasher.update(&hasher_input);
+  let hash_result = hasher.finalize();
+  let sui_address = SuiAddress::from_bytes(hash_result.as_slice())
+    .map_err(|e| Error::Internal(format!("Failed to create SuiAddress: {}", e)))?;
+
+  // Create SuiKeyPair with proper format: [scheme_flag || private_key_bytes]
+  let private_key = keypair.private();
+  let private_key_bytes = private_key.as_bytes();
+  let mut keypair_bytes = Vec::new();
+  keypair_bytes.push(scheme_flag);
+  keypair_bytes.extend_from_slice(private_key_bytes);
+
+  let sui_keypair = SuiKeyPair::from_bytes(&keypair_bytes)
+    .map_err(|e| Error::Internal(format!("Failed to create SuiKeyPair: {}", e)))?;
+
+  keystore
+    .add_key(None, sui_keypair)
+    .map_err(|e| Error::Internal(format!("Failed to add key to keystore: {}", e)))?;
+
+  // Verify the address matches
+  if sui_address != sponsor_address {
+    return Err(Error::Internal(format!(
+      "Private key address {} doesn't match sponsor address {}",
+      sui_address, sponsor_address
+    )));
+  }
+
+  tracing::info!("Successfully imported sponsor private key for address: {}", sponsor_address);
+  Ok(keystore)
+}
+
+/// Checks a single-key user signature (Ed25519, Secp256k1, Secp256r1) over the sponsored data.
+/// MultiSig, zkLogin and passkey authenticators need chain state such as the zkLogin JWKs, the
+/// validators verify them on submission and reject the transaction when they do not hold.
+fn verify_user_signature(tx_data: &TransactionData, signature: &GenericSignature) -> Result<()> {
+  match signature {
+    GenericSignature::Signature(signature) => {
+      let message = IntentMessage::new(Intent::sui_transaction(), tx_data);
+      signature
+        .verify_secure(&message, tx_data.sender(), signature.scheme())
+        .map_err(|e| Error::InvalidRequest(format!("Invalid user signature: {}", e)))
+    }
+    // The committee alone tells whether the multisig can sign for the sender
+    GenericSignature::MultiSig(multisig)
+      if SuiAddress::from(multisig.get_pk()) != tx_data.sender() =>
+    {
+      Err(Error::InvalidRequest("Multisig does not belong to the sender".to_string()))
+    }
+    _ => Ok(()),
+  }
+}
+
+/// Only programmable transactions that leave the sponsor's gas coin alone can be sponsored,
+/// otherwise the sender could split or transfer it away
+fn ensure_sponsorable(kind: &TransactionKind) -> Result<()> {
+  let TransactionKind::ProgrammableTransaction(programmable) = kind else {
+    return Err(Error::InvalidRequest(
+      "Only programmable transactions can be sponsored".to_string(),
+    ));
+  };
+
+  let uses_gas_coin = programmable.commands.iter().any(|command| {
+    let arguments: Vec<&Argument> = match command {
+      Command::MoveCall(call) => call.arguments.iter().collect(),
+      Command::TransferObjects(objects, recipient) => {
+        objects.iter().chain(std::iter::once(recipient)).collect()
+      }
+      Command::SplitCoins(coin, amounts) => std::iter::once(coin).chain(amounts).collect(),
+      Command::MergeCoins(target, sources) => std::iter::once(target).chain(sources).collect(),
+      Command::MakeMoveVec(_, elements) => elements.iter().collect(),
+      Command::Publish(..) => Vec::new(),
+      Command::Upgrade(.., ticket) => vec![ticket],
+    };
+    arguments.into_iter().any(|argument| matches!(argument, Argument::GasCoin))
+  });
+
+  if uses_gas_coin {
+    return Err(Error::InvalidRequest(
+      "Sponsored transactions cannot use the gas coin as an argument".to_string(),
+    ));
+  }
+
+  Ok(())
 }
 
 #[async_trait]
 impl SuiRepository for SuiRepositoryImpl {
+  // Chain reads are the same as the read-only repository
+
+  async fn get_coins(
+    &self,
+    address: SuiAddress,
+    coin_type: Option<String>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<Page<Coin, String>> {
+    self.reads.get_coins(address, coin_type, cursor, limit).await
+  }
+
+  async fn get_all_coins(
+    &self,
+    address: SuiAddress,
+    cursor: Option<String>,
+    limit: Option<usize>,
+  ) -> Result<Page<Coin, String>> {
+    self.reads.get_all_coins(address, cursor, limit).await
+  }
+
+  async fn get_balance(&self, address: SuiAddress, coin_type: Option<String>) -> Result<Balance> {
+    self.reads.get_balance(address, coin_type).await
+  }
+
+  async fn get_all_balances(&self, address: SuiAddress) -> Result<Vec<Balance>> {
+    self.reads.get_all_balances(address).await
+  }
+
+  async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>> {
+    self.reads.get_coin_metadata(coin_type).await
+  }
+
+  async fn get_total_supply(&self, coin_type: String) -> Result<Option<u64>> {
+    self.reads.get_total_supply(coin_type).await
+  }
+
+  async fn select_coins(
+    &self,
+    address: SuiAddress,
+    coin_type: Option<String>,
+    amount: u64,
+    exclude: Vec<ObjectID>,
+  ) -> Result<Vec<Coin>> {
+    self.reads.select_coins(address, coin_type, amount, exclude).await
+  }
+
+  async fn get_object(
+    &self,
+    object_id: ObjectID,
+    options: Option<SuiObjectDataOptions>,
+  ) -> Result<SuiObjectResponse> {
+    self.reads.get_object(object_id, options).await
+  }
+
+  async fn get_objects(
+    &self,
+    object_ids: Vec<ObjectID>,
+    options: Option<SuiObjectDataOptions>,
+  ) -> Result<Vec<SuiObjectResponse>> {
+    self.reads.get_objects(object_ids, options).await
+  }
+
+  async fn get_owned_objects(
+    &self,
+    address: SuiAddress,
+    query: Option<String>,
+    cursor: Option<ObjectID>,
+    limit: Option<usize>,
+  ) -> Result<Page<SuiObjectResponse, ObjectID>> {
+    self.reads.get_owned_objects(address, query, cursor, limit).await
+  }
+
+  async fn get_dynamic_fields(
+    &self,
+    parent_object_id: ObjectID,
+    cursor: Option<ObjectID>,
+    limit: Option<usize>,
+  ) -> Result<Page<DynamicFieldInfo, ObjectID>> {
+    self.reads.get_dynamic_fields(parent_object_id, cursor, limit).await
+  }
+
+  async fn get_transaction_block(
+    &self,
+    digest: TransactionDigest,
+    options: Option<SuiTransactionBlockResponseOptions>,
+  ) -> Result<SuiTransactionBlockResponse> {
+    self.reads.get_transaction_block(digest, options).await
+  }
+
+  async fn get_transaction_blocks(
+    &self,
+    digests: Vec<TransactionDigest>,
+    options: Option<SuiTransactionBlockResponseOptions>,
+  ) -> Result<Vec<SuiTransactionBlockResponse>> {
+    self.reads.get_transaction_blocks(digests, options).await
+  }
+
+  async fn get_events(&self, digest: TransactionDigest) -> Result<Vec<SuiEvent>> {
+    self.reads.get_events(digest).await
+  }
+
+  async fn get_latest_checkpoint_sequence_number(&self) -> Result<u64> {
+    self.reads.get_latest_checkpoint_sequence_number().await
+  }
+
+  async fn get_total_transaction_blocks(&self) -> Result<u64> {
+    self.reads.get_total_transaction_blocks().await
+  }
+
+  async fn get_reference_gas_price(&self) -> Result<u64> {
+    self.reads.get_reference_gas_price().await
+  }
+
+  async fn get_chain_identifier(&self) -> Result<String> {
+    self.reads.get_chain_identifier().await
+  }
+
   async fn fetch_coin(&self, sender: String) -> Result<Option<Coin>> {
     let coin_type = "0x2::sui::SUI".to_string();
     let address =
       SuiAddress::from_str(&sender).map_err(|e| Error::InvalidRequest(e.to_string()))?;
-    let coins_stream = self
-      .app_state
-      .sui_client
-      .client
-      .coin_read_api()
-      .get_coins_stream(address, Some(coin_type));
+    let client = self.app_state.sui_client.client();
+    let coins_stream = client.coin_read_api().get_coins_stream(address, Some(coin_type));
 
     let mut coins = coins_stream
       .skip_while(|c| future::ready(c.balance < 5_000_000))
@@ -143,152 +698,180 @@ impl SuiRepository for SuiRepositoryImpl {
   }
 
   async fn get_available_gas(&self, required_budget: u64) -> Result<ObjectID> {
-    let gas_station = self
-      .gas_station
-      .as_ref()
-      .ok_or_else(|| Error::Internal("Gas station not initialized".to_string()))?;
-
-    gas_station
+    self
+      .gas_station()?
       .get_available_gas(required_budget)
       .await
       .map_err(|e| Error::Internal(e.to_string()))
   }
 
   async fn release_gas(&self, object_id: ObjectID) -> Result<()> {
-    let gas_station = self
-      .gas_station
-      .as_ref()
-      .ok_or_else(|| Error::Internal("Gas station not initialized".to_string()))?;
-
-    gas_station.release_gas(object_id).await;
+    self.gas_station()?.release_gas(object_id).await;
     Ok(())
   }
 
-  // TODO: Not really working, need to finish later
-  async fn sponsor_transaction(
+  async fn reserve_sponsorship(
     &self,
-    tx_bytes: Vec<u8>,
-    user_signature: &[u8],
-  ) -> Result<(Transaction, String)> {
-    // Check if we have gas station and keystore
-    let gas_station = self
-      .gas_station
-      .as_ref()
-      .ok_or_else(|| Error::Internal("Gas station not initialized".to_string()))?;
+    kind: TransactionKind,
+    sender: SuiAddress,
+    caller: &SponsorCaller,
+    app_id: Option<String>,
+    gas_budget: Option<u64>,
+  ) -> Result<SponsorReservation> {
+    let gas_station = self.gas_station()?;
 
-    let keystore = self.sponsor_keystore.as_ref().ok_or_else(|| {
-      Error::Internal(
-        "Sponsor keystore not available. Private key required for transaction sponsoring."
-          .to_string(),
-      )
-    })?;
+    let gas_budget = gas_budget.unwrap_or(gas_station.max_gas_budget);
+    if gas_budget == 0 || gas_budget > gas_station.max_gas_budget {
+      return Err(Error::InvalidRequest(format!(
+        "Gas budget must be between 1 and {} MIST",
+        gas_station.max_gas_budget
+      )));
+    }
+    ensure_sponsorable(&kind)?;
+    self.policy.check_transaction(&sender, &kind)?;
+    self.policy.check_app(app_id.as_deref())?;
 
-    // Parse transaction data from bytes
-    let tx_data = match bcs::from_bytes::<TransactionData>(&tx_bytes) {
-      Ok(data) => data,
+    let gas_price = self.get_reference_gas_price().await?;
+    let gas_coin = gas_station
+      .reserve_gas(gas_budget)
+      .await
+      .map_err(|e| Error::Internal(format!("Failed to get gas object: {}", e)))?;
+
+    let tx_data = TransactionData::new_with_gas_coins_allow_sponsor(
+      kind,
+      sender,
+      vec![gas_coin],
+      gas_budget,
+      gas_price,
+      gas_station.sponsor_address,
+    );
+    // The coin's lease outlasts the reservation and its execution, see `GasPoolConfig::validate`
+    let expires_at = Utc::now() + GasPoolConfig::RESERVATION_TTL;
+    let charge = match self.vet_sponsored(&tx_data, caller, app_id.as_deref(), expires_at).await {
+      Ok(charge) => charge,
       Err(e) => {
-        tracing::error!("Failed to parse transaction data: {}", e);
-        return Err(Error::Internal(format!("Failed to parse transaction data: {}", e)));
+        gas_station.release_gas(gas_coin.0).await;
+        return Err(e);
       }
     };
 
-    // Extract sender and gas data using pattern matching
-    let (_sender, gas_budget) = match &tx_data {
-      TransactionData::V1(tx) => (tx.sender, tx.gas_data.budget),
+    let reservation = SponsorReservation {
+      id: Uuid::new_v4(),
+      sender,
+      caller: caller.clone(),
+      app_id,
+      gas_budget,
+      gas_coin,
+      lease_id: gas_station.lease_id().to_string(),
+      charge,
+      tx_data,
+      expires_at,
     };
 
-    let sponsor_address = gas_station.sponsor_address;
+    if let Err(e) = self.reservations.insert(&reservation).await {
+      gas_station.release_gas(gas_coin.0).await;
+      self.refund_budgets(&reservation).await;
+      return Err(e);
+    }
+    Ok(reservation)
+  }
 
-    // Get an available gas object for the required budget
-    let _gas_object_id = match gas_station.get_available_gas(gas_budget).await {
-      Ok(id) => id,
-      Err(e) => {
-        tracing::error!("Failed to get gas object: {}", e);
-        return Err(Error::Internal(format!("Failed to get gas object: {}", e)));
-      }
+  async fn take_reservation(&self, reservation_id: Uuid) -> Result<Option<SponsorReservation>> {
+    let Some(reservation) = self.reservations.take(reservation_id).await? else {
+      return Ok(None);
     };
 
-    // Create a new transaction data with sponsor's gas object
-    let sponsored_tx_data = tx_data.clone();
+    // Another instance may have reserved it, its lease moves here unless it already ran out
+    let gas_station = self.gas_station()?;
+    let leased = gas_station
+      .take_over_gas(reservation.gas_coin.0, &reservation.lease_id)
+      .await
+      .map_err(|e| Error::Internal(e.to_string()))?;
+    if !leased {
+      self.refund_budgets(&reservation).await;
+      return Ok(None);
+    }
+
+    // Redis expiry may lag behind, an expired reservation is never executed
+    if reservation.expires_at <= Utc::now() {
+      gas_station.release_gas(reservation.gas_coin.0).await;
+      self.refund_budgets(&reservation).await;
+      return Ok(None);
+    }
+
+    Ok(Some(reservation))
+  }
 
-    // Create transaction bytes for signing
-    let tx_bytes_for_signing = match bcs::to_bytes(&sponsored_tx_data) {
-      Ok(bytes) => bytes,
+  async fn execute_sponsorship(
+    &self,
+    reservation: &SponsorReservation,
+    user_signature: &[u8],
+  ) -> Result<ExecutedSponsorship> {
+    let gas_station = self.gas_station()?;
+    let gas_coin = reservation.gas_coin.0;
+
+    // Serialized signature, `flag || signature || public key`, see `verify_user_signature`
+    let signed = GenericSignature::from_bytes(user_signature)
+      .map_err(|e| Error::InvalidRequest(format!("Invalid user signature: {}", e)))
+      .and_then(|signature| self.sign_sponsored(reservation.tx_data.clone(), signature));
+
+    // Nothing reached the network yet, the coin goes back unchanged
+    let transaction = match signed {
+      Ok(transaction) => transaction,
       Err(e) => {
-        tracing::error!("Failed to serialize transaction: {}", e);
-        return Err(Error::Internal(format!("Failed to serialize transaction: {}", e)));
+        gas_station.release_gas(gas_coin).await;
+        self.refund_budgets(reservation).await;
+        return Err(e);
       }
     };
 
-    // Sign the transaction with sponsor's key
-    let keypair = match keystore.get_key(&sponsor_address) {
-      Ok(kp) => kp,
-      Err(e) => {
-        tracing::error!("Failed to get keypair: {}", e);
-        return Err(Error::Internal(format!("Failed to get keypair: {}", e)));
+    let submitted = match self.submit_transaction(transaction.clone()).await {
+      Ok(response) => response,
+      Err(SubmitFailure::Rejected(e)) => {
+        gas_station.refresh_gas_object(gas_coin).await;
+        self.refund_budgets(reservation).await;
+        return Err(e);
       }
+      Err(SubmitFailure::Unknown(e)) => match self.find_executed(*transaction.digest()).await {
+        Some(response) => response,
+        None => {
+          // Releasing the coin now could hand it to a second transaction while this one still
+          // locks it. The lease runs out on its own and `refund_expired` gives the budgets back.
+          tracing::warn!(
+            "Outcome of sponsored transaction {} unknown, its coin and budgets stay held: {}",
+            transaction.digest(),
+            e
+          );
+          return Err(e);
+        }
+      },
     };
 
-    let signature = keypair.sign(&tx_bytes_for_signing);
-
-    // Parse the user signature from bytes
-    // For sponsored transactions, we typically receive raw signature bytes from the frontend
-    // We'll construct a proper signature or work with the bytes directly
-    if user_signature.len() != 64 {
-      tracing::error!(
-        "Invalid user signature length: {} bytes (expected 64)",
-        user_signature.len()
-      );
-      return Err(Error::Internal(
-        "Invalid user signature length. Expected 64 bytes for Ed25519 signature.".to_string(),
-      ));
-    }
+    // Executed or not, the coin may have paid gas, reload it at its new version
+    gas_station.refresh_gas_object(gas_coin).await;
 
-    // For now, we'll skip adding the user signature to the transaction
-    // In a proper implementation, you would need to:
-    // 1. Reconstruct the full signature with proper format (flag || sig || pk)
-    // 2. Or handle multi-sig scenarios properly
-    // 3. Ensure the transaction structure supports sponsored transactions
-
-    // Add sponsor signature
-    let all_signatures = vec![signature];
-
-    // Create the final transaction
-    let final_transaction = Transaction::from_data(sponsored_tx_data, all_signatures);
-
-    // Submit the transaction to the Sui network
-    let response = gas_station
-      .sui_client
-      .quorum_driver_api()
-      .execute_transaction_block(
-        final_transaction.clone(),
-        sui_sdk::rpc_types::SuiTransactionBlockResponseOptions::full_content(),
-        None,
-      )
-      .await
-      .map_err(|e| Error::Internal(format!("Failed to submit transaction to network: {}", e)))?;
-
-    tracing::info!("Transaction submitted successfully with digest: {}", response.digest);
+    // Budgets keep the gas the sponsor actually paid, nothing when the transaction never ran
+    let gas_used = submitted.effects.as_ref().map(gas_spent);
+    match gas_used {
+      Some(gas_used) => {
+        if let Err(e) = self.budgets.settle(&reservation.charge, gas_used).await {
+          tracing::warn!("Failed to settle budgets of reservation {}: {}", reservation.id, e);
+        }
+      }
+      None => self.refund_budgets(reservation).await,
+    }
 
-    Ok((final_transaction, response.digest.to_string()))
+    let digest = executed_digest(&submitted)?;
+    Ok(ExecutedSponsorship { transaction, digest, gas_used: gas_used.unwrap_or_default() })
   }
 
   async fn get_pool_stats(&self) -> Result<GasPoolStatus> {
-    let gas_station = self
-      .gas_station
-      .as_ref()
-      .ok_or_else(|| Error::Internal("Gas station not initialized".to_string()))?;
-
-    Ok(gas_station.get_pool_stats().await)
+    self.gas_station()?.get_pool_stats().await.map_err(|e| Error::Internal(e.to_string()))
   }
 
   async fn refresh_gas_pool(&self) -> Result<()> {
-    let gas_station = self
-      .gas_station
-      .as_ref()
-      .ok_or_else(|| Error::Internal("Gas station not initialized".to_string()))?;
-
-    gas_station
+    self
+      .gas_station()?
       .refresh_gas_pool()
       .await
       .map_err(|e| Error::Internal(e.to_string()))
@@ -297,24 +880,68 @@ impl SuiRepository for SuiRepositoryImpl {
   async fn log_sponsored_transaction(
     &self,
     user_address: &SuiAddress,
+    app_id: Option<&str>,
     gas_budget: u64,
+    gas_used: u64,
+    tx_digest: &str,
   ) -> Result<()> {
-    sqlx::query(
-      "INSERT INTO sponsored_transactions (user_address, gas_budget, timestamp) VALUES ($1, $2, $3)"
-    )
-    .bind(user_address.to_string())
-    .bind(gas_budget as i64)
-    .bind(time::now_utc())
-    .execute(self.app_state.mm().dbx().db())
-    .await
-    .map_err(|e| Error::Internal(e.to_string()))?;
+    let event = SponsoredTransactionExecutedEvent {
+      user_address: user_address.to_string(),
+      app_id: app_id.map(str::to_string),
+      gas_budget,
+      gas_used: Some(gas_used),
+      tx_digest: tx_digest.to_string(),
+      executed_at: time::now_utc(),
+    };
+
+    // The log row and its event commit together
+    let mm = self.app_state.mm().new_with_txn().map_err(|e| Error::Internal(e.to_string()))?;
+    let dbx = mm.dbx();
+    jd_utils::with_transaction!(dbx, {
+      let query = sqlx::query(
+        "INSERT INTO sponsored_transactions \
+         (user_address, app_id, gas_budget, gas_used, tx_digest, timestamp) \
+         VALUES ($1, $2, $3, $4, $5, $6)",
+      )
+      .bind(&event.user_address)
+      .bind(&event.app_id)
+      .bind(gas_budget as i64)
+      .bind(gas_used as i64)
+      .bind(&event.tx_digest)
+      .bind(event.executed_at);
+      dbx.execute(query).await?;
+
+      Outbox::enqueue(dbx, &event).await?;
+      Ok::<_, Error>(())
+    })
+  }
+
+  async fn record_sponsorship_failure(
+    &self,
+    user_address: &SuiAddress,
+    app_id: Option<&str>,
+    gas_budget: u64,
+    reason: &str,
+  ) -> Result<()> {
+    let event = SponsoredTransactionFailedEvent {
+      user_address: user_address.to_string(),
+      app_id: app_id.map(str::to_string),
+      gas_budget,
+      reason: reason.to_string(),
+      failed_at: time::now_utc(),
+    };
 
+    Outbox::enqueue(self.app_state.mm().dbx(), &event).await?;
     Ok(())
   }
 
   async fn get_user_stats(&self, address: &str) -> Result<Option<UserStats>> {
     let stats = sqlx::query_as::<_, UserStats>(
-      "SELECT user_address, COUNT(*) as transaction_count, SUM(gas_budget) as total_gas_sponsored, MAX(timestamp) as last_transaction FROM sponsored_transactions WHERE user_address = $1 GROUP BY user_address"
+      // Daily rollups maintained by the analytics processor, a few rows per user
+      "SELECT user_address, SUM(success_count)::BIGINT AS transaction_count, \
+       SUM(gas_sponsored)::BIGINT AS total_gas_sponsored, \
+       MAX(last_transaction_at) AS last_transaction \
+       FROM analytics.sponsorship_daily WHERE user_address = $1 GROUP BY user_address"
     )
     .bind(address)
     .fetch_optional(self.app_state.mm().dbx().db())
@@ -333,28 +960,120 @@ impl SuiRepository for SuiRepositoryImpl {
       .map_err(|e| Error::Internal(e.to_string()))?;
 
     let key = format!("rate_limit:{}", user_address);
+    let rate_limit = &self.policy.rate_limit;
 
-    let count: Option<i32> = conn
-      .get(&key)
+    let count: u64 = redis::Script::new(FIXED_WINDOW)
+      .key(key)
+      .arg(rate_limit.window_secs.max(1))
+      .invoke_async(&mut conn)
       .await
       .map_err(|e| Error::Internal(e.to_string()))?;
 
-    match count {
-      Some(c) if c >= 10 => Ok(false), // Rate limit exceeded
-      Some(_) => {
-        conn
-          .incr::<_, _, ()>(&key, 1)
-          .await
-          .map_err(|e| Error::Internal(e.to_string()))?;
-        Ok(true)
-      }
-      None => {
-        conn
-          .set_ex::<_, _, ()>(&key, 1, 60)
-          .await
-          .map_err(|e| Error::Internal(e.to_string()))?;
-        Ok(true)
-      }
-    }
+    Ok(count <= rate_limit.max_transactions)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use sui_types::base_types::{ObjectRef, SequenceNumber};
+  use sui_types::crypto::get_key_pair;
+  use sui_types::digests::ObjectDigest;
+  use sui_types::multisig::{MultiSig, MultiSigPublicKey};
+  use sui_types::transaction::GenesisTransaction;
+
+  fn object_ref() -> ObjectRef {
+    (ObjectID::random(), SequenceNumber::from_u64(1), ObjectDigest::random())
+  }
+
+  fn keypair() -> (SuiAddress, SuiKeyPair) {
+    let (address, keypair) = get_key_pair::<Ed25519KeyPair>();
+    (address, SuiKeyPair::Ed25519(keypair))
+  }
+
+  fn transfer(sender: SuiAddress) -> TransactionData {
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(SuiAddress::random_for_testing_only(), object_ref()).unwrap();
+    let gas_payment = vec![object_ref()];
+    TransactionData::new_programmable(sender, gas_payment, builder.finish(), 10_000_000, 1_000)
+  }
+
+  fn sign(tx_data: &TransactionData, keypair: &SuiKeyPair) -> Signature {
+    Signature::new_secure(&IntentMessage::new(Intent::sui_transaction(), tx_data.clone()), keypair)
+  }
+
+  #[test]
+  fn user_signature_from_the_sender_is_accepted() {
+    let (sender, keypair) = keypair();
+    let tx_data = transfer(sender);
+
+    let signature = GenericSignature::Signature(sign(&tx_data, &keypair));
+
+    assert!(verify_user_signature(&tx_data, &signature).is_ok());
+  }
+
+  #[test]
+  fn user_signature_from_another_key_is_rejected() {
+    let (sender, _) = keypair();
+    let (_, other) = keypair();
+    let tx_data = transfer(sender);
+
+    let signature = GenericSignature::Signature(sign(&tx_data, &other));
+
+    assert!(matches!(verify_user_signature(&tx_data, &signature), Err(Error::InvalidRequest(_))));
+  }
+
+  fn multisig(tx_data: &TransactionData, keypair: &SuiKeyPair) -> GenericSignature {
+    let multisig_pk = MultiSigPublicKey::new(vec![keypair.public()], vec![1], 1).unwrap();
+    let signature = GenericSignature::Signature(sign(tx_data, keypair));
+    GenericSignature::MultiSig(MultiSig::combine(vec![signature], multisig_pk).unwrap())
+  }
+
+  #[test]
+  fn multisig_user_signature_of_the_sender_is_accepted() {
+    let (_, keypair) = keypair();
+    let multisig_pk = MultiSigPublicKey::new(vec![keypair.public()], vec![1], 1).unwrap();
+    let tx_data = transfer(SuiAddress::from(&multisig_pk));
+
+    assert!(verify_user_signature(&tx_data, &multisig(&tx_data, &keypair)).is_ok());
+  }
+
+  #[test]
+  fn multisig_user_signature_of_another_committee_is_rejected() {
+    let (sender, _) = keypair();
+    let (_, other) = keypair();
+    let tx_data = transfer(sender);
+
+    assert!(matches!(
+      verify_user_signature(&tx_data, &multisig(&tx_data, &other)),
+      Err(Error::InvalidRequest(_))
+    ));
+  }
+
+  #[test]
+  fn programmable_transaction_without_the_gas_coin_is_sponsorable() {
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(SuiAddress::random_for_testing_only(), object_ref()).unwrap();
+
+    let kind = TransactionKind::ProgrammableTransaction(builder.finish());
+
+    assert!(ensure_sponsorable(&kind).is_ok());
+  }
+
+  #[test]
+  fn transaction_using_the_gas_coin_is_not_sponsorable() {
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_sui(SuiAddress::random_for_testing_only(), None);
+
+    let kind = TransactionKind::ProgrammableTransaction(builder.finish());
+
+    assert!(matches!(ensure_sponsorable(&kind), Err(Error::InvalidRequest(_))));
+  }
+
+  #[test]
+  fn system_transaction_is_not_sponsorable() {
+    let kind = TransactionKind::Genesis(GenesisTransaction { objects: Vec::new() });
+
+    assert!(matches!(ensure_sponsorable(&kind), Err(Error::InvalidRequest(_))));
   }
 }
@@ -8,25 +8,26 @@ mod domain;
 mod error;
 
 use application::{handlers::sui_handler::SuiHandler, use_cases::sui_use_cases::SuiUseCases};
-use error::Error;
+pub use error::Error;
 use infrastructure::enhanced_sui_repository::EnhancedSuiRepository;
+use infrastructure::resilient_sui_repository::ResilientSuiRepository;
 use jd_core::AppState;
-type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, Error>;
 
 pub struct SuiService {
-  handler: SuiHandler<EnhancedSuiRepository>,
+  handler: SuiHandler<ResilientSuiRepository<EnhancedSuiRepository>>,
 }
 
 impl SuiService {
   pub async fn new(state: AppState) -> Self {
-    let repository = EnhancedSuiRepository::new(state);
+    let repository = ResilientSuiRepository::new(state);
     let use_cases = SuiUseCases::new(repository);
     let handler = SuiHandler::new(use_cases);
 
     Self { handler }
   }
 
-  pub fn handler(&self) -> &SuiHandler<EnhancedSuiRepository> {
+  pub fn handler(&self) -> &SuiHandler<ResilientSuiRepository<EnhancedSuiRepository>> {
     &self.handler
   }
 }
@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use auth_service::domain::UserRole;
 use chrono::{DateTime, Utc};
-use sui_sdk::types::{base_types::ObjectID, dynamic_field::DynamicFieldInfo, object::Data};
-use sui_types::transaction::Transaction;
+use sui_sdk::types::{
+  base_types::{ObjectID, ObjectRef, SuiAddress},
+  dynamic_field::DynamicFieldInfo,
+  object::Data,
+};
+use sui_types::transaction::{Transaction, TransactionData};
 use uuid::Uuid;
 
 pub mod requests;
@@ -37,23 +42,76 @@ pub struct DynamicFieldPage {
 
 // Gas Station Models - Custom JSON-friendly structs
 #[derive(Debug, Serialize, Deserialize)]
-pub struct GasData {
-  pub budget: String, // JSON usually sends numbers as strings
-  pub price: String,  // JSON usually sends numbers as strings
+pub struct ReserveSponsorRequest {
+  pub sender: String,
+  /// Application the transaction is sponsored for, one of the policy's `apps` when it lists any.
+  /// Its daily budget applies and analytics group by it.
+  #[serde(default)]
+  pub app_id: Option<String>,
+  /// Base64 BCS `TransactionKind`, the programmable transaction without gas data
+  pub transaction_kind: String,
+  /// Defaults to the sponsor's maximum gas budget
+  #[serde(default)]
+  pub gas_budget: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct TransactionDataJson {
-  pub sender: String,
-  pub tx_bytes: Vec<u8>,
-  pub gas_data: GasData,
+pub struct ReserveSponsorResponse {
+  pub reservation_id: Uuid,
+  /// Base64 BCS `TransactionData` paid by the sponsor, to be signed by the sender
+  pub tx_bytes: String,
+  pub gas_budget: u64,
+  pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SponsorRequest {
-  pub user_address: String,
-  pub transaction_data: TransactionDataJson,
-  pub user_signature: Vec<u8>,
+pub struct ExecuteSponsorRequest {
+  pub reservation_id: Uuid,
+  /// Base64 serialized Sui signature of the sender over `tx_bytes`, any scheme the sender uses
+  pub user_signature: String,
+}
+
+/// Authenticated user asking for a sponsorship, daily budgets and role quotas apply to them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SponsorCaller {
+  pub user_id: Uuid,
+  pub role: UserRole,
+}
+
+/// Daily budgets charged for a reservation, settled once it executed or refunded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetCharge {
+  pub id: Uuid,
+  /// Day whose counters were charged, `YYYY-MM-DD` in UTC
+  pub day: String,
+  pub user_id: Uuid,
+  pub app_id: Option<String>,
+  pub gas: u64,
+}
+
+/// Gas coin locked for a sender and the transaction built around it, until executed or expired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SponsorReservation {
+  pub id: Uuid,
+  pub sender: SuiAddress,
+  pub caller: SponsorCaller,
+  pub app_id: Option<String>,
+  pub gas_budget: u64,
+  pub gas_coin: ObjectRef,
+  /// Holder of the gas coin's lease, the instance that reserved it
+  pub lease_id: String,
+  pub charge: BudgetCharge,
+  pub tx_data: TransactionData,
+  pub expires_at: DateTime<Utc>,
+}
+
+/// A sponsored transaction that reached the network and ran
+#[derive(Debug, Clone)]
+pub struct ExecutedSponsorship {
+  pub transaction: Transaction,
+  pub digest: String,
+  /// MIST the sponsor paid, computation and storage minus the storage rebate
+  pub gas_used: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
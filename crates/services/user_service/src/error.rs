@@ -484,6 +484,18 @@ impl From<serde_json::Error> for Error {
   }
 }
 
+impl From<jd_storage::dbx::Error> for Error {
+  fn from(err: jd_storage::dbx::Error) -> Self {
+    Self::Storage(Arc::new(err))
+  }
+}
+
+impl From<jd_messaging::Error> for Error {
+  fn from(err: jd_messaging::Error) -> Self {
+    Self::internal_with_source(err, "Failed to record domain event")
+  }
+}
+
 impl From<validator::ValidationErrors> for Error {
   fn from(err: validator::ValidationErrors) -> Self {
     let field_errors = err
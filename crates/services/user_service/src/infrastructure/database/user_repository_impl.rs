@@ -4,6 +4,7 @@ use crate::{
 };
 use async_trait::async_trait;
 use jd_contracts::user::dtos::{
+  events::{UserCreatedEvent, UserDirectory},
   records::user_record::UserRecord,
   requests::{
     create_profile_request::CreateUserProfileRequest, create_user_request::CreateUserRequest,
@@ -15,7 +16,8 @@ use jd_core::{
   AppState,
   base::{self},
 };
-use jd_utils::ensure;
+use jd_messaging::Outbox;
+use jd_utils::{ensure, with_transaction};
 
 pub struct UserRepositoryImpl {
   app_state: AppState,
@@ -31,13 +33,27 @@ impl UserRepositoryImpl {
 impl UserRepository for UserRepositoryImpl {
   async fn create(&self, req: CreateUserRequest) -> Result<UserRecord> {
     // Check if user exists with same username or email
-    let exists = self.exists(&req).await.unwrap();
+    let exists = self.exists(&req).await?;
 
     ensure!(!exists, Error::conflict("User with this username or email already exists"));
 
-    base::rest::create::<UsersDmc, _, _>(&self.app_state.mm, req)
-      .await
-      .map_error()
+    // The event is written in the same transaction, it is only published if the user exists
+    let txn_mm = self.app_state.mm.new_with_txn().map_error()?;
+    let (mm, dbx) = (&txn_mm, txn_mm.dbx());
+    with_transaction!(dbx, {
+      let user: UserRecord = base::rest::create::<UsersDmc, _, _>(mm, req).await.map_error()?;
+
+      let event = UserCreatedEvent {
+        user_id: *user.user_id.value(),
+        directory: UserDirectory::Profile,
+        email: Some(user.email.clone()),
+        username: user.username.clone(),
+        created_at: user.created_at,
+      };
+      Outbox::enqueue(dbx, &event).await?;
+
+      Ok::<_, Error>(user)
+    })
   }
 
   async fn create_profile(
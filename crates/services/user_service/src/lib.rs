@@ -7,7 +7,7 @@ pub mod models;
 mod domain;
 mod error;
 
-use error::Error;
+pub use error::Error;
 type Result<T> = std::result::Result<T, Error>;
 
 use jd_core::base::DMC;
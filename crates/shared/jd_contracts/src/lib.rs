@@ -1,2 +1,3 @@
 pub mod common;
+pub mod sui;
 pub mod user;
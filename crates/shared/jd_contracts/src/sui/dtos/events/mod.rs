@@ -0,0 +1,67 @@
+use jd_messaging::DomainEvent;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// The gas station sponsored and executed a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SponsoredTransactionExecutedEvent {
+  pub user_address: String,
+  /// Application the transaction was sponsored for, if the client named one
+  pub app_id: Option<String>,
+  pub gas_budget: u64,
+  /// MIST the sponsor paid, computation and storage minus the storage rebate. `None` for events
+  /// emitted before it was recorded
+  #[serde(default)]
+  pub gas_used: Option<u64>,
+  pub tx_digest: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub executed_at: OffsetDateTime,
+}
+
+impl DomainEvent for SponsoredTransactionExecutedEvent {
+  const EVENT_TYPE: &'static str = "sui.sponsored_transaction_executed";
+
+  fn aggregate_id(&self) -> String {
+    self.user_address.clone()
+  }
+}
+
+/// The gas station refused or failed to sponsor a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SponsoredTransactionFailedEvent {
+  pub user_address: String,
+  #[serde(default)]
+  pub app_id: Option<String>,
+  pub gas_budget: u64,
+  pub reason: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub failed_at: OffsetDateTime,
+}
+
+impl DomainEvent for SponsoredTransactionFailedEvent {
+  const EVENT_TYPE: &'static str = "sui.sponsored_transaction_failed";
+
+  fn aggregate_id(&self) -> String {
+    self.user_address.clone()
+  }
+}
+
+/// The sponsor's gas pool dropped below a balance watermark and needs topping up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasPoolLowBalanceEvent {
+  pub sponsor_address: String,
+  /// `low` or `critical`
+  pub level: String,
+  pub total_balance: u64,
+  pub watermark: u64,
+  #[serde(with = "time::serde::rfc3339")]
+  pub detected_at: OffsetDateTime,
+}
+
+impl DomainEvent for GasPoolLowBalanceEvent {
+  const EVENT_TYPE: &'static str = "sui.gas_pool_low_balance";
+
+  fn aggregate_id(&self) -> String {
+    self.sponsor_address.clone()
+  }
+}
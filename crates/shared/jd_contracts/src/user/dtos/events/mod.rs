@@ -1,10 +1,91 @@
+use jd_messaging::DomainEvent;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+/// Table a user id belongs to, user_service and auth_service keep separate users
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserDirectory {
+  /// `profile.users`, the only producer before the field existed
+  #[default]
+  Profile,
+  /// `unified_auth.users`
+  UnifiedAuth,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserCreatedEvent {
   pub user_id: Uuid,
-  pub email: String,
+  #[serde(default)]
+  pub directory: UserDirectory,
+  /// `None` for wallet sign-ups
+  pub email: Option<String>,
+  #[serde(default)]
+  pub username: String,
+  #[serde(with = "time::serde::rfc3339")]
   pub created_at: OffsetDateTime,
 }
+
+impl DomainEvent for UserCreatedEvent {
+  const EVENT_TYPE: &'static str = "user.created";
+
+  fn aggregate_id(&self) -> String {
+    self.user_id.to_string()
+  }
+}
+
+/// The primary email of a user was set or replaced with a verified address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserEmailChangedEvent {
+  pub user_id: Uuid,
+  pub old_email: Option<String>,
+  pub new_email: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub changed_at: OffsetDateTime,
+}
+
+impl DomainEvent for UserEmailChangedEvent {
+  const EVENT_TYPE: &'static str = "user.email_changed";
+
+  fn aggregate_id(&self) -> String {
+    self.user_id.to_string()
+  }
+}
+
+/// A session was started from a user agent the user has not logged in with before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewDeviceLoginEvent {
+  pub user_id: Uuid,
+  pub session_id: Uuid,
+  pub ip_address: Option<String>,
+  pub user_agent: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub logged_in_at: OffsetDateTime,
+}
+
+impl DomainEvent for NewDeviceLoginEvent {
+  const EVENT_TYPE: &'static str = "user.new_device_login";
+
+  fn aggregate_id(&self) -> String {
+    self.user_id.to_string()
+  }
+}
+
+/// A session was started, `provider_type` is how the user authenticated (`email`, `wallet`, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserLoggedInEvent {
+  pub user_id: Uuid,
+  pub session_id: Uuid,
+  pub provider_type: String,
+  #[serde(with = "time::serde::rfc3339")]
+  pub logged_in_at: OffsetDateTime,
+}
+
+impl DomainEvent for UserLoggedInEvent {
+  const EVENT_TYPE: &'static str = "user.logged_in";
+
+  fn aggregate_id(&self) -> String {
+    self.user_id.to_string()
+  }
+}
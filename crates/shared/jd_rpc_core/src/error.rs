@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::error;
+
+/// JSON-RPC 2.0 error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("{message} ({code})")]
+pub struct RpcError {
+  pub code: i64,
+  pub message: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub data: Option<Value>,
+}
+
+impl RpcError {
+  // -- Codes defined by the specification
+  pub const PARSE_ERROR: i64 = -32700;
+  pub const INVALID_REQUEST: i64 = -32600;
+  pub const METHOD_NOT_FOUND: i64 = -32601;
+  pub const INVALID_PARAMS: i64 = -32602;
+  pub const INTERNAL_ERROR: i64 = -32603;
+
+  // -- Codes from the implementation defined server error range
+  pub const SERVER_ERROR: i64 = -32000;
+  pub const NOT_FOUND: i64 = -32001;
+  pub const CONFLICT: i64 = -32002;
+  pub const FORBIDDEN: i64 = -32003;
+
+  pub fn new(code: i64, message: impl Into<String>) -> Self {
+    Self { code, message: message.into(), data: None }
+  }
+
+  pub fn with_data(mut self, data: Value) -> Self {
+    self.data = Some(data);
+    self
+  }
+
+  pub fn parse_error(reason: impl std::fmt::Display) -> Self {
+    Self::new(Self::PARSE_ERROR, "Parse error").with_data(json!(reason.to_string()))
+  }
+
+  pub fn invalid_request(reason: impl Into<String>) -> Self {
+    Self::new(Self::INVALID_REQUEST, "Invalid Request").with_data(json!(reason.into()))
+  }
+
+  pub fn method_not_found(method: &str) -> Self {
+    Self::new(Self::METHOD_NOT_FOUND, "Method not found").with_data(json!(method))
+  }
+
+  pub fn invalid_params(reason: impl std::fmt::Display) -> Self {
+    Self::new(Self::INVALID_PARAMS, "Invalid params").with_data(json!(reason.to_string()))
+  }
+
+  pub fn forbidden(method: &str) -> Self {
+    Self::new(Self::FORBIDDEN, "Forbidden").with_data(json!(method))
+  }
+
+  /// Internal failure, details are logged rather than sent to the caller
+  pub fn internal() -> Self {
+    Self::new(Self::INTERNAL_ERROR, "Internal error")
+  }
+}
+
+/// Caller mistakes keep their details, everything else becomes an opaque internal error
+impl From<jd_core::Error> for RpcError {
+  fn from(err: jd_core::Error) -> Self {
+    use jd_core::Error as E;
+
+    let data = serde_json::to_value(&err).ok();
+    let rpc_error = match &err {
+      E::ListLimitOverMax { .. } | E::InvalidEnumValue { .. } | E::RpcError(_) => {
+        Self::new(Self::INVALID_PARAMS, err.to_string())
+      }
+      E::EntityNotFound { .. } => Self::new(Self::NOT_FOUND, err.to_string()),
+      _ if err.is_unique_violation() => Self::new(Self::CONFLICT, "Resource already exists"),
+      _ => {
+        error!("RPC call failed: {err}");
+        return Self::internal();
+      }
+    };
+
+    Self { data, ..rpc_error }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn core_errors_map_to_rpc_codes() {
+    assert_eq!(
+      RpcError::from(jd_core::Error::list_limit_exceeded(100, 500)).code,
+      RpcError::INVALID_PARAMS
+    );
+    assert_eq!(
+      RpcError::from(jd_core::Error::entity_not_found("users", 7)).code,
+      RpcError::NOT_FOUND
+    );
+    assert_eq!(
+      RpcError::from(jd_core::Error::unique_violation("users", "users_email_key")).code,
+      RpcError::CONFLICT
+    );
+  }
+
+  #[test]
+  fn internal_errors_hide_details() {
+    let err = RpcError::from(jd_core::Error::count_fail());
+
+    assert_eq!(err, RpcError::internal());
+    assert!(err.data.is_none());
+  }
+}
@@ -1,14 +1,9 @@
-pub fn add(left: u64, right: u64) -> u64 {
-  left + right
-}
+// -->>> Region:: START  --->>>  Public Modules
+pub mod error;
+pub mod protocol;
+pub mod registry;
+// <<<-- Region:: END    <<<---  Public Modules
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-
-  #[test]
-  fn it_works() {
-    let result = add(2, 2);
-    assert_eq!(result, 4);
-  }
-}
+pub use error::RpcError;
+pub use protocol::{Request, Response, RpcPayload};
+pub use registry::RpcRegistry;
@@ -0,0 +1,145 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::RpcError;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Validated JSON-RPC 2.0 request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+  /// `None` for notifications, `Some(Value::Null)` for an explicit null id
+  pub id: Option<Value>,
+  pub method: String,
+  pub params: Option<Value>,
+}
+
+impl Request {
+  pub fn is_notification(&self) -> bool {
+    self.id.is_none()
+  }
+}
+
+impl TryFrom<Value> for Request {
+  /// Id to answer with, if it could be read, and the reason the request is invalid
+  type Error = (Value, RpcError);
+
+  fn try_from(value: Value) -> Result<Self, Self::Error> {
+    let Value::Object(mut object) = value else {
+      return Err((Value::Null, RpcError::invalid_request("request must be an object")));
+    };
+
+    let id = match object.remove("id") {
+      None => None,
+      Some(id @ (Value::Null | Value::String(_) | Value::Number(_))) => Some(id),
+      Some(_) => {
+        return Err((Value::Null, RpcError::invalid_request("id must be a string or number")));
+      }
+    };
+    let reply_id = id.clone().unwrap_or(Value::Null);
+
+    if object.get("jsonrpc").and_then(Value::as_str) != Some(JSONRPC_VERSION) {
+      return Err((reply_id, RpcError::invalid_request("jsonrpc must be \"2.0\"")));
+    }
+
+    let method = match object.remove("method") {
+      Some(Value::String(method)) => method,
+      _ => return Err((reply_id, RpcError::invalid_request("method must be a string"))),
+    };
+
+    let params = match object.remove("params") {
+      None => None,
+      Some(params @ (Value::Object(_) | Value::Array(_))) => Some(params),
+      Some(_) => {
+        return Err((reply_id, RpcError::invalid_request("params must be an object or array")));
+      }
+    };
+
+    Ok(Self { id, method, params })
+  }
+}
+
+/// JSON-RPC 2.0 response object
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Response {
+  jsonrpc: &'static str,
+  #[serde(flatten)]
+  pub outcome: Outcome,
+  pub id: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+  Result(Value),
+  Error(RpcError),
+}
+
+impl Response {
+  pub fn result(id: Value, result: Value) -> Self {
+    Self { jsonrpc: JSONRPC_VERSION, outcome: Outcome::Result(result), id }
+  }
+
+  pub fn error(id: Value, error: RpcError) -> Self {
+    Self { jsonrpc: JSONRPC_VERSION, outcome: Outcome::Error(error), id }
+  }
+}
+
+/// Body answering a single request or a batch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum RpcPayload {
+  Single(Response),
+  Batch(Vec<Response>),
+}
+
+/// Params to deserialize when the request omitted them, so handlers taking
+/// structs with only optional fields accept a bare call
+pub(crate) fn empty_params() -> Value {
+  Value::Object(Map::new())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn parses_calls_and_notifications() {
+    let call = json!({ "jsonrpc": "2.0", "method": "user.get", "params": [1], "id": 3 });
+    let notification = json!({ "jsonrpc": "2.0", "method": "user.touch" });
+
+    let call = Request::try_from(call).unwrap();
+    assert_eq!(call.id, Some(json!(3)));
+    assert!(!call.is_notification());
+    assert!(Request::try_from(notification).unwrap().is_notification());
+  }
+
+  #[test]
+  fn rejects_malformed_requests_keeping_the_id() {
+    let (id, err) = Request::try_from(json!({ "jsonrpc": "1.0", "method": "a", "id": "x" }))
+      .unwrap_err();
+    assert_eq!(id, json!("x"));
+    assert_eq!(err.code, RpcError::INVALID_REQUEST);
+
+    let (_, err) =
+      Request::try_from(json!({ "jsonrpc": "2.0", "method": "a", "params": 1 })).unwrap_err();
+    assert_eq!(err.code, RpcError::INVALID_REQUEST);
+
+    let (id, _) = Request::try_from(json!(1)).unwrap_err();
+    assert_eq!(id, Value::Null);
+  }
+
+  #[test]
+  fn responses_serialize_per_spec() {
+    let ok = serde_json::to_value(Response::result(json!(1), json!("done"))).unwrap();
+    let err = serde_json::to_value(Response::error(Value::Null, RpcError::internal())).unwrap();
+
+    assert_eq!(ok, json!({ "jsonrpc": "2.0", "result": "done", "id": 1 }));
+    assert_eq!(
+      err,
+      json!({ "jsonrpc": "2.0", "error": { "code": -32603, "message": "Internal error" },
+              "id": null })
+    );
+  }
+}
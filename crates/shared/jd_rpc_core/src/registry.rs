@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::FutureExt;
+use futures::future::{BoxFuture, join_all};
+use jd_core::ctx::Ctx;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::RpcError;
+use crate::protocol::{Request, Response, RpcPayload, empty_params};
+
+const DEFAULT_MAX_BATCH: usize = 50;
+
+type ErasedHandler<S> =
+  Arc<dyn Fn(S, Ctx, Option<Value>) -> BoxFuture<'static, Result<Value, RpcError>> + Send + Sync>;
+
+struct Method<S> {
+  handler: ErasedHandler<S>,
+  /// Scope a scoped caller needs, methods without one are closed to scoped callers
+  scope: Option<&'static str>,
+}
+
+/// Method table for the JSON-RPC endpoint.
+///
+/// Services register typed handlers taking the shared state, the caller `Ctx` and a params
+/// struct, and returning a serializable result. Params are decoded from the request before
+/// the handler runs, so handlers never see raw JSON.
+pub struct RpcRegistry<S> {
+  methods: HashMap<String, Method<S>>,
+  max_batch: usize,
+}
+
+impl<S: Clone + Send + Sync + 'static> RpcRegistry<S> {
+  pub fn new() -> Self {
+    Self { methods: HashMap::new(), max_batch: DEFAULT_MAX_BATCH }
+  }
+
+  /// Registers `handler` under `method`, panics if the name is already taken.
+  /// Callers limited to scopes, i.e. API keys, cannot call it.
+  pub fn register<P, R, E, F, Fut>(self, method: impl Into<String>, handler: F) -> Self
+  where
+    P: DeserializeOwned + Send + 'static,
+    R: Serialize + 'static,
+    E: Into<RpcError> + 'static,
+    F: Fn(S, Ctx, P) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, E>> + Send + 'static,
+  {
+    self.insert(method.into(), None, handler)
+  }
+
+  /// Registers `handler` under `method`, callable by scoped callers holding `scope`
+  pub fn register_scoped<P, R, E, F, Fut>(
+    self,
+    method: impl Into<String>,
+    scope: &'static str,
+    handler: F,
+  ) -> Self
+  where
+    P: DeserializeOwned + Send + 'static,
+    R: Serialize + 'static,
+    E: Into<RpcError> + 'static,
+    F: Fn(S, Ctx, P) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, E>> + Send + 'static,
+  {
+    self.insert(method.into(), Some(scope), handler)
+  }
+
+  fn insert<P, R, E, F, Fut>(
+    mut self,
+    method: String,
+    scope: Option<&'static str>,
+    handler: F,
+  ) -> Self
+  where
+    P: DeserializeOwned + Send + 'static,
+    R: Serialize + 'static,
+    E: Into<RpcError> + 'static,
+    F: Fn(S, Ctx, P) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R, E>> + Send + 'static,
+  {
+    let handler = Arc::new(handler);
+    let erased: ErasedHandler<S> = Arc::new(move |state: S, ctx: Ctx, params: Option<Value>| {
+      let handler = handler.clone();
+      async move {
+        let params = decode_params::<P>(params)?;
+        let result = handler(state, ctx, params).await.map_err(Into::into)?;
+        serde_json::to_value(result).map_err(|_| RpcError::internal())
+      }
+      .boxed()
+    });
+
+    let previous = self.methods.insert(method.clone(), Method { handler: erased, scope });
+    assert!(previous.is_none(), "RPC method '{method}' registered twice");
+    self
+  }
+
+  /// Moves every method of `other` into this registry, panics on name clashes
+  pub fn merge(mut self, other: Self) -> Self {
+    for (method, handler) in other.methods {
+      let previous = self.methods.insert(method.clone(), handler);
+      assert!(previous.is_none(), "RPC method '{method}' registered twice");
+    }
+    self
+  }
+
+  pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+    self.max_batch = max_batch.max(1);
+    self
+  }
+
+  pub fn methods(&self) -> impl Iterator<Item = &str> {
+    self.methods.keys().map(String::as_str)
+  }
+
+  /// Invokes one method directly
+  pub async fn call(
+    &self,
+    state: S,
+    ctx: Ctx,
+    method: &str,
+    params: Option<Value>,
+  ) -> Result<Value, RpcError> {
+    let Method { handler, scope } =
+      self.methods.get(method).ok_or_else(|| RpcError::method_not_found(method))?;
+
+    let allowed = match scope {
+      Some(scope) => ctx.has_scope(scope),
+      None => !ctx.is_scoped(),
+    };
+    if !allowed {
+      return Err(RpcError::forbidden(method));
+    }
+
+    handler(state, ctx, params).await
+  }
+
+  /// Handles a raw request body, single or batch.
+  /// Returns `None` when nothing must be sent back, i.e. the body held only notifications.
+  pub async fn handle(&self, state: S, ctx: Ctx, body: &[u8]) -> Option<RpcPayload> {
+    let body: Value = match serde_json::from_slice(body) {
+      Ok(body) => body,
+      Err(err) => {
+        return Some(RpcPayload::Single(Response::error(Value::Null, RpcError::parse_error(err))));
+      }
+    };
+
+    match body {
+      Value::Array(items) if items.is_empty() => Some(RpcPayload::Single(Response::error(
+        Value::Null,
+        RpcError::invalid_request("batch must not be empty"),
+      ))),
+      Value::Array(items) if items.len() > self.max_batch => {
+        Some(RpcPayload::Single(Response::error(
+          Value::Null,
+          RpcError::invalid_request(format!("batch exceeds {} requests", self.max_batch)),
+        )))
+      }
+      Value::Array(items) => {
+        let calls = items.into_iter().map(|item| self.handle_one(state.clone(), ctx.clone(), item));
+        let responses: Vec<Response> = join_all(calls).await.into_iter().flatten().collect();
+
+        (!responses.is_empty()).then_some(RpcPayload::Batch(responses))
+      }
+      item => self.handle_one(state, ctx, item).await.map(RpcPayload::Single),
+    }
+  }
+
+  async fn handle_one(&self, state: S, ctx: Ctx, item: Value) -> Option<Response> {
+    let request = match Request::try_from(item) {
+      Ok(request) => request,
+      Err((id, err)) => return Some(Response::error(id, err)),
+    };
+
+    debug!(method = %request.method, notification = request.is_notification(), "RPC call");
+    let outcome = self.call(state, ctx, &request.method, request.params).await;
+
+    // Notifications get no response, not even for errors
+    let id = request.id?;
+    Some(match outcome {
+      Ok(result) => Response::result(id, result),
+      Err(err) => Response::error(id, err),
+    })
+  }
+}
+
+/// Absent params decode as null first so `()` params work, then as an empty object
+fn decode_params<P: DeserializeOwned>(params: Option<Value>) -> Result<P, RpcError> {
+  match params {
+    Some(params) => serde_json::from_value(params),
+    None => serde_json::from_value(Value::Null)
+      .or_else(|_| serde_json::from_value(empty_params())),
+  }
+  .map_err(RpcError::invalid_params)
+}
+
+impl<S: Clone + Send + Sync + 'static> Default for RpcRegistry<S> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  use serde::Deserialize;
+  use serde_json::json;
+  use uuid::Uuid;
+
+  use super::*;
+  use crate::protocol::Outcome;
+
+  #[derive(Deserialize)]
+  struct AddParams {
+    a: i64,
+    b: i64,
+  }
+
+  #[derive(Deserialize)]
+  struct EchoParams {
+    text: Option<String>,
+  }
+
+  type State = Arc<AtomicUsize>;
+
+  fn registry() -> RpcRegistry<State> {
+    RpcRegistry::new()
+      .register("math.add", |_: State, _: Ctx, params: AddParams| async move {
+        Ok::<_, RpcError>(params.a + params.b)
+      })
+      .register("echo", |_: State, ctx: Ctx, params: EchoParams| async move {
+        Ok::<_, RpcError>(json!({ "text": params.text, "user_id": ctx.user_id() }))
+      })
+      .register("counter.bump", |state: State, _: Ctx, _: ()| async move {
+        Ok::<_, RpcError>(state.fetch_add(1, Ordering::SeqCst) + 1)
+      })
+      .register("entity.get", |_: State, _: Ctx, _: ()| async move {
+        Err::<(), _>(jd_core::Error::entity_not_found("users", 1))
+      })
+      .register_scoped("entity.list", "entities.read", |_: State, _: Ctx, _: ()| async move {
+        Ok::<_, RpcError>(json!([]))
+      })
+  }
+
+  fn ctx() -> Ctx {
+    Ctx::new(Uuid::new_v4(), "member", None).unwrap()
+  }
+
+  async fn handle(body: Value) -> (Option<RpcPayload>, State) {
+    let state = State::default();
+    let payload = registry().handle(state.clone(), ctx(), body.to_string().as_bytes()).await;
+    (payload, state)
+  }
+
+  fn single(payload: Option<RpcPayload>) -> Response {
+    match payload {
+      Some(RpcPayload::Single(response)) => response,
+      other => panic!("expected a single response, got {other:?}"),
+    }
+  }
+
+  fn error_code(response: &Response) -> i64 {
+    match &response.outcome {
+      Outcome::Error(err) => err.code,
+      Outcome::Result(result) => panic!("expected an error, got {result}"),
+    }
+  }
+
+  #[tokio::test]
+  async fn dispatches_typed_params() {
+    let request = json!({
+      "jsonrpc": "2.0", "method": "math.add", "params": { "a": 2, "b": 3 }, "id": 1
+    });
+    let (payload, _) = handle(request).await;
+
+    assert_eq!(single(payload), Response::result(json!(1), json!(5)));
+  }
+
+  #[tokio::test]
+  async fn injects_ctx_and_accepts_missing_params() {
+    let (payload, _) = handle(json!({ "jsonrpc": "2.0", "method": "echo", "id": "a" })).await;
+
+    let Outcome::Result(result) = single(payload).outcome else { panic!("expected a result") };
+    assert_eq!(result["text"], Value::Null);
+    assert!(result["user_id"].is_string());
+  }
+
+  #[tokio::test]
+  async fn reports_standard_error_codes() {
+    let unknown = json!({ "jsonrpc": "2.0", "method": "nope", "id": 1 });
+    let bad_params =
+      json!({ "jsonrpc": "2.0", "method": "math.add", "params": { "a": 1 }, "id": 2 });
+    let not_found = json!({ "jsonrpc": "2.0", "method": "entity.get", "id": 3 });
+
+    assert_eq!(error_code(&single(handle(unknown).await.0)), RpcError::METHOD_NOT_FOUND);
+    assert_eq!(error_code(&single(handle(bad_params).await.0)), RpcError::INVALID_PARAMS);
+    assert_eq!(error_code(&single(handle(not_found).await.0)), RpcError::NOT_FOUND);
+    assert_eq!(error_code(&single(handle(json!([])).await.0)), RpcError::INVALID_REQUEST);
+  }
+
+  #[tokio::test]
+  async fn reports_parse_errors() {
+    let payload = registry().handle(State::default(), ctx(), b"{\"jsonrpc\"").await;
+
+    assert_eq!(error_code(&single(payload)), RpcError::PARSE_ERROR);
+  }
+
+  #[tokio::test]
+  async fn batches_skip_notifications() {
+    let (payload, state) = handle(json!([
+      { "jsonrpc": "2.0", "method": "counter.bump" },
+      { "jsonrpc": "2.0", "method": "math.add", "params": [1, 1], "id": 1 },
+      { "foo": "bar" },
+    ]))
+    .await;
+
+    let Some(RpcPayload::Batch(responses)) = payload else { panic!("expected a batch") };
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0], Response::result(json!(1), json!(2)));
+    assert_eq!(error_code(&responses[1]), RpcError::INVALID_REQUEST);
+    assert_eq!(state.load(Ordering::SeqCst), 1);
+  }
+
+  #[tokio::test]
+  async fn notification_only_bodies_get_no_response() {
+    let (payload, state) = handle(json!([
+      { "jsonrpc": "2.0", "method": "counter.bump" },
+      { "jsonrpc": "2.0", "method": "counter.bump" },
+    ]))
+    .await;
+
+    assert!(payload.is_none());
+    assert_eq!(state.load(Ordering::SeqCst), 2);
+  }
+
+  #[tokio::test]
+  async fn scoped_callers_only_reach_methods_of_their_scopes() {
+    let registry = registry();
+    let key = |scopes: &[&str]| ctx().with_scopes(scopes.iter().map(|s| s.to_string()).collect());
+    let call = |ctx: Ctx, method: &'static str| registry.call(State::default(), ctx, method, None);
+
+    assert!(call(key(&["entities.read"]), "entity.list").await.is_ok());
+    assert!(call(ctx(), "entity.list").await.is_ok());
+
+    let denied = call(key(&["entities.write"]), "entity.list").await.unwrap_err();
+    assert_eq!(denied.code, RpcError::FORBIDDEN);
+    let unscoped_method = call(key(&["entities.read"]), "counter.bump").await.unwrap_err();
+    assert_eq!(unscoped_method.code, RpcError::FORBIDDEN);
+  }
+}
@@ -1,10 +1,16 @@
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::error::Error;
 
 #[derive(Deserialize)]
 pub struct WebConfig {
   pub addr: String,
+  /// Comma separated IPs or CIDRs of the reverse proxies in front of the services. Forwarded
+  /// client address headers are only believed on connections from these.
+  #[serde(default)]
+  pub trusted_proxies: String,
 }
 
 #[derive(Deserialize)]
@@ -20,10 +26,436 @@ pub struct Redis {
 
 #[derive(Deserialize)]
 pub struct SuiConfig {
+  /// Network whose public fullnode is used when `rpc_urls` is empty
   pub env: String,
+  /// Comma separated fullnodes as `url` or `url|weight`, see `SuiConfig::rpc_endpoints`
+  #[serde(default)]
+  pub rpc_urls: String,
+  #[serde(default)]
+  pub health_check: SuiHealthCheckConfig,
   pub sponsor_address: Option<String>,
   pub sponsor_private_key: Option<String>,
   pub max_gas_budget: Option<u64>,
+  /// JSON sponsorship policy, see `docs/SUI_GAS_STATION_SETUP.md`
+  pub policy_file: Option<String>,
+  #[serde(default)]
+  pub gas_pool: GasPoolConfig,
+  #[serde(default)]
+  pub resilience: SuiResilienceConfig,
+  #[serde(default)]
+  pub cache: SuiCacheConfig,
+}
+
+/// One fullnode of `SuiConfig::rpc_urls`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuiRpcEndpoint {
+  pub url: String,
+  /// Share of the reads among healthy nodes, 0 makes a standby only used when no weighted
+  /// node is healthy
+  pub weight: u32,
+}
+
+impl SuiConfig {
+  /// Parses `rpc_urls`, an entry without `|weight` weighs 1
+  pub fn rpc_endpoints(&self) -> crate::Result<Vec<SuiRpcEndpoint>> {
+    self
+      .rpc_urls
+      .split(',')
+      .map(str::trim)
+      .filter(|entry| !entry.is_empty())
+      .map(|entry| {
+        let (url, weight) = match entry.rsplit_once('|') {
+          Some((url, weight)) => {
+            let weight = weight.trim().parse().map_err(|_| {
+              config::ConfigError::Message(format!("Invalid weight in SUI.RPC_URLS: {entry}"))
+            })?;
+            (url.trim(), weight)
+          }
+          None => (entry, 1),
+        };
+        Ok(SuiRpcEndpoint { url: url.to_string(), weight })
+      })
+      .collect()
+  }
+}
+
+/// Fullnode probes. Every `interval_secs` each node reports its chain identifier and latest
+/// checkpoint; a node on another chain, unreachable, or more than `max_checkpoint_lag`
+/// checkpoints behind the best node gets no reads until it catches up.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SuiHealthCheckConfig {
+  pub interval_secs: u64,
+  pub timeout_ms: u64,
+  pub max_checkpoint_lag: u64,
+}
+
+impl Default for SuiHealthCheckConfig {
+  fn default() -> Self {
+    Self { interval_secs: 10, timeout_ms: 3_000, max_checkpoint_lag: 20 }
+  }
+}
+
+/// Sponsor gas coin pool upkeep. Amounts are in MIST, 1 SUI = 1_000_000_000 MIST.
+/// Large coins are split and dust is merged until the pool holds `target_coin_count` coins
+/// of about `target_coin_balance` each, so concurrent sponsorships don't wait on a coin.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct GasPoolConfig {
+  pub target_coin_count: usize,
+  pub target_coin_balance: u64,
+  /// Coins below this balance are merged into a larger one
+  pub dust_threshold: u64,
+  /// Total pool balance under which a low balance warning is logged and emitted
+  pub low_balance_watermark: u64,
+  /// Total pool balance under which the warning becomes critical
+  pub critical_balance_watermark: u64,
+  /// Pause between maintenance runs
+  pub maintenance_interval_secs: u64,
+  /// Longest a coin stays leased to one instance, must outlast a reservation and its execution,
+  /// see `GasPoolConfig::validate`
+  pub lease_ttl_secs: u64,
+}
+
+impl GasPoolConfig {
+  /// Time a sender has to sign a reserved transaction
+  pub const RESERVATION_TTL: Duration = Duration::from_secs(60);
+  /// Longest the execution of a transaction signed at the last moment may take, the Sui
+  /// client's request timeout
+  pub const EXECUTION_TIME: Duration = Duration::from_secs(60);
+
+  /// A lease that ran out before its transaction executed would hand the coin to a second one
+  pub fn validate(&self) -> crate::Result<()> {
+    let required = Self::RESERVATION_TTL + Self::EXECUTION_TIME;
+    if Duration::from_secs(self.lease_ttl_secs) <= required {
+      return Err(
+        config::ConfigError::Message(format!(
+          "SUI.GAS_POOL.LEASE_TTL_SECS must exceed {} seconds, a reservation and its execution",
+          required.as_secs()
+        ))
+        .into(),
+      );
+    }
+    Ok(())
+  }
+}
+
+impl Default for GasPoolConfig {
+  fn default() -> Self {
+    Self {
+      target_coin_count: 20,
+      target_coin_balance: 1_000_000_000,
+      dust_threshold: 10_000_000,
+      low_balance_watermark: 10_000_000_000,
+      critical_balance_watermark: 2_000_000_000,
+      maintenance_interval_secs: 60,
+      lease_ttl_secs: 180,
+    }
+  }
+}
+
+/// Guards around fullnode reads. Each attempt gets `timeout_ms`, failed reads are retried with
+/// jittered exponential backoff. After `failure_threshold` failures in a row an RPC method's
+/// circuit opens and its calls fail fast for `open_secs`, then a single probe decides whether
+/// it closes again.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SuiResilienceConfig {
+  pub timeout_ms: u64,
+  /// Extra attempts after the first one, writes are never retried
+  pub read_retries: u32,
+  pub retry_base_delay_ms: u64,
+  pub retry_max_delay_ms: u64,
+  pub failure_threshold: u32,
+  pub open_secs: u64,
+}
+
+impl Default for SuiResilienceConfig {
+  fn default() -> Self {
+    Self {
+      timeout_ms: 5_000,
+      read_retries: 2,
+      retry_base_delay_ms: 100,
+      retry_max_delay_ms: 1_000,
+      failure_threshold: 5,
+      open_secs: 30,
+    }
+  }
+}
+
+/// Read-through cache of fullnode reads, in process first and then in Redis. Coin metadata,
+/// immutable objects, transactions and events are kept until evicted. Other objects are kept
+/// per version, their latest version is trusted for `object_version_ttl_secs`.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SuiCacheConfig {
+  pub enabled: bool,
+  /// Entries held in process, least recently used go first
+  pub local_capacity: usize,
+  pub balance_ttl_secs: u64,
+  pub object_version_ttl_secs: u64,
+  /// How long Redis keeps an object version nobody asked for again
+  pub versioned_ttl_secs: u64,
+  /// How long Redis keeps immutable answers, only the in process LRU holds them until evicted
+  pub immutable_ttl_secs: u64,
+  /// Consecutive Redis failures after which the cache stops calling Redis for a while
+  pub redis_failure_threshold: u32,
+  pub redis_pause_secs: u64,
+}
+
+impl Default for SuiCacheConfig {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      local_capacity: 10_000,
+      balance_ttl_secs: 5,
+      object_version_ttl_secs: 5,
+      versioned_ttl_secs: 86_400,
+      immutable_ttl_secs: 7 * 86_400,
+      redis_failure_threshold: 3,
+      redis_pause_secs: 10,
+    }
+  }
+}
+
+/// Argon2id cost parameters and password policy.
+/// Raising the costs is safe at any time, existing hashes are upgraded on the next login.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct PasswordConfig {
+  /// Memory cost in KiB
+  pub memory_kib: u32,
+  /// Number of passes over memory
+  pub iterations: u32,
+  /// Degree of parallelism
+  pub parallelism: u32,
+  pub min_length: usize,
+}
+
+impl Default for PasswordConfig {
+  fn default() -> Self {
+    // OWASP recommended baseline for Argon2id
+    Self { memory_kib: 19_456, iterations: 2, parallelism: 1, min_length: 10 }
+  }
+}
+
+/// Token signing setup. HS256 signs with `auth_jwt_secret` and is meant for local development;
+/// RS256 and EdDSA load PKCS#8 PEM keys from `keys_dir`.
+///
+/// Each `<kid>.pem` file is a private key, each `<kid>.pub.pem` file a public key that only
+/// verifies. An optional `keys.json` in the same directory schedules keys by kid, e.g.
+/// `{"k2": {"not_before": "2026-01-01T00:00:00Z"}, "k1": {"retire_at": "2026-01-01T00:00:00Z"}}`.
+/// The private key with the newest `not_before` that has passed and no `retire_at` reached
+/// signs, `active_kid` only breaks ties. A retired key keeps verifying the tokens it signed
+/// before `retire_at` until they expire. The directory is re-read every `reload_secs`.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct JwtConfig {
+  /// `HS256`, `RS256` or `EdDSA`
+  pub algorithm: String,
+  pub keys_dir: Option<String>,
+  pub active_kid: Option<String>,
+  pub reload_secs: u64,
+}
+
+impl Default for JwtConfig {
+  fn default() -> Self {
+    Self { algorithm: "HS256".to_string(), keys_dir: None, active_kid: None, reload_secs: 300 }
+  }
+}
+
+/// Credentials of one OAuth provider. The endpoint overrides default to the provider's
+/// public endpoints and exist for self-hosted setups and tests.
+#[derive(Deserialize, Clone)]
+pub struct OAuthProviderSettings {
+  pub client_id: String,
+  pub client_secret: String,
+  /// Callback registered with the provider
+  pub redirect_uri: String,
+  pub auth_url: Option<String>,
+  pub token_url: Option<String>,
+  pub user_info_url: Option<String>,
+}
+
+/// OAuth login settings, a provider without credentials is not offered
+#[derive(Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct OAuthSettings {
+  /// Comma separated list of where a login may send the user back to. An entry without a
+  /// path (`https://app.example.com`) allows the whole origin, otherwise the path must match.
+  pub redirect_allowlist: String,
+  pub google: Option<OAuthProviderSettings>,
+  pub github: Option<OAuthProviderSettings>,
+}
+
+impl OAuthSettings {
+  pub fn redirect_allowlist(&self) -> Vec<String> {
+    self
+      .redirect_allowlist
+      .split(',')
+      .map(str::trim)
+      .filter(|entry| !entry.is_empty())
+      .map(str::to_string)
+      .collect()
+  }
+}
+
+/// Domain event delivery. Services write events to the outbox table, a relay task moves them
+/// to the Redis stream every subscriber reads from.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MessagingConfig {
+  pub stream_key: String,
+  /// Approximate number of entries the stream is trimmed to
+  pub stream_max_len: usize,
+  /// Outbox rows published per relay transaction
+  pub relay_batch_size: i64,
+  /// Pause between relay polls once the outbox is empty
+  pub relay_interval_ms: u64,
+  /// Failed publishes before an event is moved to the dead letter table
+  pub relay_max_attempts: i32,
+  /// Unacknowledged stream entries idle this long are handed to another consumer
+  pub claim_min_idle_ms: u64,
+}
+
+impl Default for MessagingConfig {
+  fn default() -> Self {
+    Self {
+      stream_key: "jd:events".to_string(),
+      stream_max_len: 100_000,
+      relay_batch_size: 100,
+      relay_interval_ms: 500,
+      relay_max_attempts: 10,
+      claim_min_idle_ms: 30_000,
+    }
+  }
+}
+
+/// SMTP relay used for email notifications, STARTTLS on `port`
+#[derive(Deserialize, Clone)]
+pub struct SmtpSettings {
+  pub host: String,
+  pub port: u16,
+  pub username: String,
+  pub password: String,
+  /// Sender mailbox, e.g. `JD <no-reply@example.com>`
+  pub from: String,
+}
+
+/// Notification worker settings. Email and webhook delivery are only enabled when configured,
+/// the in-app inbox is always written.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct NotificationConfig {
+  /// Redis consumer group, every worker instance shares it
+  pub consumer_group: String,
+  /// Unique per worker instance, derived from the group and host when unset
+  pub consumer_name: Option<String>,
+  pub batch_size: usize,
+  /// Delivery attempts per channel before the notification is dead-lettered
+  pub max_attempts: u32,
+  /// First retry delay, doubled on every further attempt
+  pub retry_base_ms: u64,
+  pub smtp: Option<SmtpSettings>,
+  pub webhook_url: Option<String>,
+}
+
+impl Default for NotificationConfig {
+  fn default() -> Self {
+    Self {
+      consumer_group: "notification_processor".to_string(),
+      consumer_name: None,
+      batch_size: 32,
+      max_attempts: 5,
+      retry_base_ms: 500,
+      smtp: None,
+      webhook_url: None,
+    }
+  }
+}
+
+/// Analytics worker settings
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AnalyticsConfig {
+  pub consumer_group: String,
+  /// Unique per worker instance, derived from the group and host when unset
+  pub consumer_name: Option<String>,
+  pub batch_size: usize,
+}
+
+impl Default for AnalyticsConfig {
+  fn default() -> Self {
+    Self {
+      consumer_group: "analytics_processor".to_string(),
+      consumer_name: None,
+      batch_size: 100,
+    }
+  }
+}
+
+/// Gateway request limits, counted in Redis over a sliding window. A client is counted by its
+/// API key, else its user, else its IP address.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+  pub enabled: bool,
+  /// Lets requests through uncounted while Redis is unreachable, otherwise they are rejected
+  /// with `503`
+  pub fail_open: bool,
+  /// Limit of routes outside every group
+  pub default: RateLimitRule,
+  /// Limits by route group, the first path segment after `/api/v1`, e.g. `auth`. JSON-RPC
+  /// calls under `/api/rpc` form the `rpc` group.
+  pub groups: HashMap<String, RateLimitRule>,
+}
+
+/// `requests` per `window_secs`, raised or lowered for signed in users by role name
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct RateLimitRule {
+  pub requests: u32,
+  pub window_secs: u64,
+  pub roles: HashMap<String, u32>,
+}
+
+impl Default for RateLimitConfig {
+  fn default() -> Self {
+    let auth = RateLimitRule { requests: 30, ..RateLimitRule::default() };
+    Self {
+      enabled: true,
+      fail_open: true,
+      default: RateLimitRule::default(),
+      groups: HashMap::from([("auth".to_string(), auth)]),
+    }
+  }
+}
+
+impl Default for RateLimitRule {
+  fn default() -> Self {
+    Self { requests: 300, window_secs: 60, roles: HashMap::new() }
+  }
+}
+
+impl RateLimitConfig {
+  /// Group a request of route group `group` is counted in and its rule. Routes outside every
+  /// configured group share the `default` group.
+  pub fn rule(&self, group: &str) -> (&str, &RateLimitRule) {
+    match self.groups.get_key_value(group) {
+      Some((group, rule)) => (group.as_str(), rule),
+      None => ("default", &self.default),
+    }
+  }
+}
+
+impl RateLimitRule {
+  /// Requests allowed in one window, `role` is `None` for anonymous clients
+  pub fn limit_for(&self, role: Option<&str>) -> u32 {
+    role
+      .and_then(|role| self.roles.get(role))
+      .copied()
+      .unwrap_or(self.requests)
+  }
 }
 
 #[derive(Deserialize)]
@@ -34,15 +466,32 @@ pub struct Config {
   pub sui: SuiConfig,
   #[serde(rename = "auth_jwt_secret")]
   pub auth_jwt_secret: String,
+  #[serde(default)]
+  pub jwt: JwtConfig,
+  #[serde(default)]
+  pub password: PasswordConfig,
+  #[serde(default)]
+  pub oauth: OAuthSettings,
+  #[serde(default)]
+  pub messaging: MessagingConfig,
+  #[serde(default)]
+  pub notifications: NotificationConfig,
+  #[serde(default)]
+  pub analytics: AnalyticsConfig,
+  #[serde(default)]
+  pub rate_limit: RateLimitConfig,
 }
 
 impl Config {
   pub fn from_env() -> crate::Result<Config> {
-    config::Config::builder()
+    let config = config::Config::builder()
       .add_source(config::Environment::default())
       .build()
       .map_err(Error::Config)?
       .try_deserialize::<Config>()
-      .map_err(Error::Config)
+      .map_err(Error::Config)?;
+
+    config.sui.gas_pool.validate()?;
+    Ok(config)
   }
 }
@@ -0,0 +1,43 @@
+-- ===================================================================================================
+-- TRANSACTIONAL OUTBOX
+-- Domain events are inserted in the same transaction as the change they describe and relayed
+-- to the event stream afterwards, so an event is published if and only if its change committed
+-- ===================================================================================================
+
+DROP SCHEMA IF EXISTS "messaging" CASCADE;
+CREATE SCHEMA "messaging";
+
+CREATE TABLE messaging.outbox (
+    event_id UUID PRIMARY KEY,
+
+    -- Event identity
+    event_type VARCHAR(100) NOT NULL, -- e.g. user.created
+    aggregate_id VARCHAR(255) NOT NULL, -- Entity the event is about
+    payload JSONB NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+
+    -- Relay bookkeeping, rows are deleted once published
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The relay drains in insertion order
+CREATE INDEX idx_outbox_created_at ON messaging.outbox(created_at);
+
+COMMENT ON SCHEMA messaging IS 'Infrastructure for reliable domain event delivery';
+COMMENT ON TABLE messaging.outbox IS 'Domain events waiting to be relayed to the event stream';
+
+-- Events that kept failing to publish, kept for inspection and manual replay
+CREATE TABLE messaging.outbox_dead_letter (
+    event_id UUID PRIMARY KEY,
+    event_type VARCHAR(100) NOT NULL,
+    aggregate_id VARCHAR(255) NOT NULL,
+    payload JSONB NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    attempts INT NOT NULL,
+    last_error TEXT,
+    dead_lettered_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE messaging.outbox_dead_letter IS 'Outbox events given up on after repeated publish failures';
@@ -0,0 +1,66 @@
+-- ===================================================================================================
+-- NOTIFICATIONS
+-- Written by the notification processor; preferences live next to the user profile
+-- ===================================================================================================
+
+-- ===================================================================================================
+-- 1. PER-USER PREFERENCES - Opt-out, a missing row means the channel is enabled
+-- ===================================================================================================
+CREATE TABLE unified_auth.notification_preferences (
+    user_id UUID NOT NULL REFERENCES unified_auth.users(user_id) ON DELETE CASCADE,
+    event_type VARCHAR(100) NOT NULL, -- e.g. user.new_device_login, '*' for every event
+    channel VARCHAR(20) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (user_id, event_type, channel),
+    CONSTRAINT valid_notification_channel CHECK (channel IN ('email', 'webhook', 'in_app'))
+);
+
+CREATE TRIGGER trigger_notification_preferences_updated_at BEFORE UPDATE ON unified_auth.notification_preferences
+    FOR EACH ROW EXECUTE FUNCTION unified_auth.update_updated_at_column();
+
+-- ===================================================================================================
+-- 2. IN-APP INBOX
+-- ===================================================================================================
+CREATE TABLE unified_auth.notification_inbox (
+    notification_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES unified_auth.users(user_id) ON DELETE CASCADE,
+
+    -- Source event, events are delivered at least once
+    event_id UUID NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+
+    UNIQUE(event_id, user_id)
+);
+
+CREATE INDEX idx_notification_inbox_user_unread ON unified_auth.notification_inbox(user_id, created_at DESC)
+    WHERE read_at IS NULL;
+
+-- ===================================================================================================
+-- 3. DEAD LETTERS - Deliveries that kept failing after every retry
+-- ===================================================================================================
+CREATE TABLE messaging.dead_letters (
+    dead_letter_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    consumer VARCHAR(100) NOT NULL, -- Consumer group that gave up
+    channel VARCHAR(20),
+
+    event_id UUID NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL, -- Full event envelope
+
+    attempts INT NOT NULL,
+    last_error TEXT NOT NULL,
+    failed_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_dead_letters_failed_at ON messaging.dead_letters(failed_at);
+
+COMMENT ON TABLE unified_auth.notification_preferences IS 'Per user and event opt-outs of notification channels';
+COMMENT ON TABLE unified_auth.notification_inbox IS 'In-app notifications shown to the user';
+COMMENT ON TABLE messaging.dead_letters IS 'Events a consumer failed to handle after all retries';
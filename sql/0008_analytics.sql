@@ -0,0 +1,79 @@
+-- ===================================================================================================
+-- ANALYTICS ROLLUPS
+-- Maintained by the analytics processor from domain events; admin reports and user stats read
+-- these instead of aggregating raw rows on every request
+-- ===================================================================================================
+
+DROP SCHEMA IF EXISTS "analytics" CASCADE;
+CREATE SCHEMA "analytics";
+
+-- Raw sponsorship log gains the fields the rollups are keyed by
+ALTER TABLE public.sponsored_transactions
+    ADD COLUMN IF NOT EXISTS app_id VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS tx_digest VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS gas_used BIGINT; -- MIST the sponsor paid, computation + storage - rebate
+
+-- ===================================================================================================
+-- 1. IDEMPOTENCY - Events are delivered at least once, each is applied once
+-- ===================================================================================================
+CREATE TABLE analytics.processed_events (
+    event_id UUID PRIMARY KEY,
+    processed_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- ===================================================================================================
+-- 2. SPONSORSHIP ROLLUPS - One row per bucket, user and app
+-- ===================================================================================================
+CREATE TABLE analytics.sponsorship_hourly (
+    bucket TIMESTAMPTZ NOT NULL, -- Start of the hour, UTC
+    user_address VARCHAR(66) NOT NULL,
+    app_id VARCHAR(100) NOT NULL, -- 'unspecified' when the client named none
+
+    success_count BIGINT NOT NULL DEFAULT 0,
+    failure_count BIGINT NOT NULL DEFAULT 0,
+    gas_sponsored BIGINT NOT NULL DEFAULT 0, -- Sum of gas used in MIST
+    last_transaction_at TIMESTAMPTZ,
+
+    PRIMARY KEY (bucket, user_address, app_id)
+);
+
+CREATE TABLE analytics.sponsorship_daily (
+    bucket DATE NOT NULL, -- UTC day
+    user_address VARCHAR(66) NOT NULL,
+    app_id VARCHAR(100) NOT NULL,
+
+    success_count BIGINT NOT NULL DEFAULT 0,
+    failure_count BIGINT NOT NULL DEFAULT 0,
+    gas_sponsored BIGINT NOT NULL DEFAULT 0,
+    last_transaction_at TIMESTAMPTZ,
+
+    PRIMARY KEY (bucket, user_address, app_id)
+);
+
+CREATE INDEX idx_sponsorship_daily_user ON analytics.sponsorship_daily(user_address, bucket);
+CREATE INDEX idx_sponsorship_daily_app ON analytics.sponsorship_daily(app_id, bucket);
+
+-- ===================================================================================================
+-- 3. LOGIN ROLLUPS - Logins and daily active users per auth provider
+-- ===================================================================================================
+CREATE TABLE analytics.login_daily (
+    bucket DATE NOT NULL,
+    provider_type VARCHAR(20) NOT NULL,
+    login_count BIGINT NOT NULL DEFAULT 0,
+    active_users BIGINT NOT NULL DEFAULT 0,
+
+    PRIMARY KEY (bucket, provider_type)
+);
+
+-- Users seen per day and provider, makes active_users a distinct count
+CREATE TABLE analytics.login_daily_users (
+    bucket DATE NOT NULL,
+    provider_type VARCHAR(20) NOT NULL,
+    user_id UUID NOT NULL,
+
+    PRIMARY KEY (bucket, provider_type, user_id)
+);
+
+COMMENT ON SCHEMA analytics IS 'Rollups built by the analytics processor';
+COMMENT ON TABLE analytics.sponsorship_daily IS 'Sponsored transactions per day, user and app';
+COMMENT ON TABLE analytics.login_daily IS 'Logins and distinct users per day and auth provider';
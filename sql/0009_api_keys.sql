@@ -0,0 +1,39 @@
+-- ===================================================================================================
+-- API KEYS
+-- Server-to-server credentials, each key acts as its owner limited to its scopes
+-- ===================================================================================================
+CREATE TABLE unified_auth.api_keys (
+    key_id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_id UUID NOT NULL REFERENCES unified_auth.users(user_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+
+    -- Credentials, only the SHA-256 of the full key is stored
+    prefix VARCHAR(16) UNIQUE NOT NULL, -- Leading characters of the key, shown to identify it
+    key_hash CHAR(64) UNIQUE NOT NULL,
+
+    -- Access
+    scopes TEXT[] NOT NULL, -- unified_auth.permissions names, capped by the owner's role
+    rate_limit INTEGER CHECK (rate_limit > 0), -- Requests per rate limit window, NULL keeps the route limit
+
+    -- Lifecycle
+    created_by UUID REFERENCES unified_auth.users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    rotated_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_api_keys_owner ON unified_auth.api_keys(owner_id);
+
+COMMENT ON TABLE unified_auth.api_keys IS 'API keys of backend partners, sent in the X-Api-Key header';
+
+-- Scopes of the Sui routes, every role may grant them to its keys
+INSERT INTO unified_auth.permissions (permission_name, description, resource, action) VALUES
+    ('sui.read', 'Read Sui chain data', 'sui', 'read'),
+    ('sui.sponsor', 'Request sponsored Sui transactions', 'sui', 'sponsor');
+
+INSERT INTO unified_auth.role_permissions (role, permission_id)
+SELECT role, permission_id
+FROM unified_auth.permissions, unnest(enum_range(NULL::user_role)) AS role
+WHERE permission_name IN ('sui.read', 'sui.sponsor');